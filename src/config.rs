@@ -3,14 +3,55 @@
 //! Handles loading, parsing, and providing access to configuration settings
 //! from TOML files, environment variables, and CLI arguments.
 
+use crate::models::{FileType, ListColumn, SizeFormat, TimestampSource, ViewMode};
+use crate::quota::Quota;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
+/// Accepted values for `debounce_ms`
+const DEBOUNCE_MS_RANGE: RangeInclusive<u64> = 10..=10_000;
+
+/// Accepted values for `max_batch_size`
+const MAX_BATCH_SIZE_RANGE: RangeInclusive<usize> = 1..=100_000;
+
+/// Accepted values for `ui_batch_delay_ms`
+const UI_BATCH_DELAY_MS_RANGE: RangeInclusive<u64> = 10..=5_000;
+
+/// Accepted values for `auto_refresh_secs` (besides 0, which turns it off)
+const AUTO_REFRESH_SECS_RANGE: RangeInclusive<u64> = 1..=3_600;
+
+/// Accepted values for `stability_check_ms`
+const STABILITY_CHECK_MS_RANGE: RangeInclusive<u64> = 10..=60_000;
+
+/// Accepted values for `health_check_ms` (besides 0, which turns it off)
+const HEALTH_CHECK_MS_RANGE: RangeInclusive<u64> = 100..=3_600_000;
+
+/// Accepted values for `busy_timeout_ms`
+const BUSY_TIMEOUT_MS_RANGE: RangeInclusive<u64> = 100..=600_000;
+
+/// Accepted values for `page_size` (also the bounds of `+`/`-` in the TUI)
+pub const PAGE_SIZE_RANGE: RangeInclusive<usize> = 10..=1_000;
+
+/// Environment variable overriding the config file path
+const CONFIG_ENV: &str = "FERRET_CONFIG";
+
+/// Environment variable overriding the database path
+const DB_ENV: &str = "FERRET_DB";
+
+/// Environment variable overriding the default data directory
+const DATA_DIR_ENV: &str = "FERRET_DATA_DIR";
+
+/// Environment variable overriding the directory for state files
+const STATE_DIR_ENV: &str = "FERRET_STATE_DIR";
+
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
     /// Directories to watch for new files
     pub watch_paths: Vec<PathBuf>,
@@ -18,9 +59,14 @@ pub struct Config {
     /// Glob patterns for paths to ignore
     pub ignore_patterns: Vec<String>,
 
-    /// Minimum file size in bytes to log (0 = log all)
+    /// Minimum file size in bytes to log (0 = log all); also accepts e.g. "10MB"
+    #[serde(deserialize_with = "deserialize_size")]
     pub min_size_bytes: u64,
 
+    /// Skip empty files, whatever `min_size_bytes` is; editors and apps
+    /// often create them just before writing the real file
+    pub ignore_zero_byte: bool,
+
     /// Days to retain events before cleanup (0 = never cleanup)
     pub retention_days: u32,
 
@@ -30,14 +76,343 @@ pub struct Config {
     /// Custom database path (optional, uses XDG default if not set)
     pub database_path: Option<PathBuf>,
 
+    /// How long to wait for another process to release the database (milliseconds)
+    pub busy_timeout_ms: u64,
+
     /// Custom log file path (optional)
     pub log_file: Option<PathBuf>,
 
     /// Whether to follow symlinks when watching
     pub follow_symlinks: bool,
 
-    /// Debounce delay in milliseconds for file events
+    /// How long a path must be quiet before its event is processed (milliseconds)
     pub debounce_ms: u64,
+
+    /// Most raw events the watcher collects before processing a batch
+    pub max_batch_size: usize,
+
+    /// How long the TUI gathers new files before refreshing the list (milliseconds)
+    pub ui_batch_delay_ms: u64,
+
+    /// Reload the TUI list this often, to pick up rows written by other
+    /// processes (seconds, 0 = only on watcher events)
+    pub auto_refresh_secs: u64,
+
+    /// How often `watch --headless --follow` prints a summary line
+    /// (seconds, 0 = never)
+    pub follow_summary_secs: u64,
+
+    /// Record a single summary event when more than this many files appear
+    /// in one new directory at once (0 = off)
+    pub extraction_threshold: usize,
+
+    /// Leave out the individual files of a summarized extraction
+    pub collapse_extractions: bool,
+
+    /// Only record a file once its size is unchanged over this interval, so
+    /// downloads still being written are skipped until done (milliseconds)
+    pub stability_check_ms: Option<u64>,
+
+    /// How often to check that watch paths still exist, re-watching them
+    /// when they come back, e.g. a remounted drive (milliseconds, 0 = off)
+    pub health_check_ms: u64,
+
+    /// Whether to compute a SHA-256 content hash for new files
+    pub compute_hashes: bool,
+
+    /// Files larger than this are not hashed (0 = no limit)
+    #[serde(deserialize_with = "deserialize_size")]
+    pub max_hash_size_bytes: u64,
+
+    /// How many directory levels below a watch path to track (None = unlimited)
+    pub max_depth: Option<usize>,
+
+    /// Detect the type of extensionless/unknown files from their magic bytes
+    pub content_sniffing: bool,
+
+    /// Track files already present in a watch path when watching starts
+    pub scan_existing: bool,
+
+    /// Time new files are recorded with: "discovered", "modified" or "created"
+    pub timestamp_source: String,
+
+    /// Skip files ignored by `.gitignore` files above them
+    pub respect_gitignore: bool,
+
+    /// Move files to the OS trash when deleting from the TUI
+    pub trash_instead_of_delete: bool,
+
+    /// Keep the ledger row of a file deleted through Ferret, hidden from
+    /// listings unless `--include-deleted` is given
+    pub retain_deleted_in_ledger: bool,
+
+    /// View the TUI starts in ("flat", "grouped", "extension" or "tree")
+    pub default_view_mode: String,
+
+    /// Columns shown in the flat list, in order (time, size, type, path, tags)
+    pub list_columns: Vec<String>,
+
+    /// Show list times as "5m ago" instead of absolute timestamps
+    pub relative_time: bool,
+
+    /// How sizes are written: "binary" (1 MiB), "decimal" (1.05 MB) or "short" (1.0M)
+    pub size_format: String,
+
+    /// Up/Down wrap from the last row to the first (and across pages)
+    pub wrap_navigation: bool,
+
+    /// Show a bar of each folder's size relative to its siblings in the tree view
+    pub tree_size_bars: bool,
+
+    /// Events loaded per TUI page
+    pub page_size: usize,
+
+    /// TUI colors: a preset name or a `[theme]` table of per-role colors
+    pub theme: ThemeConfig,
+
+    /// Desktop notifications for new files
+    pub notifications: NotificationConfig,
+
+    /// Where to POST new files as JSON
+    pub webhook: WebhookConfig,
+
+    /// Command templates for `o`, keyed by extension or file type name
+    pub openers: BTreeMap<String, String>,
+
+    /// User-defined categories (e.g. e-books, disk images) matched by extension
+    pub categories: Vec<CategoryConfig>,
+
+    /// File type overrides, checked in order before extension-based classification
+    pub rules: Vec<TypeRule>,
+
+    /// Watch paths with their own filters, in addition to `watch_paths`
+    pub watch: Vec<WatchConfig>,
+
+    /// Size and file count limits that raise a warning when exceeded
+    pub quota: Vec<QuotaConfig>,
+
+    /// Profile picked with `--profile`, naming the default config file,
+    /// database and state files (not read from the config file)
+    #[serde(skip)]
+    pub profile: Option<String>,
+}
+
+/// `[notifications]` section: when to show a desktop notification for a new file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationConfig {
+    /// Whether notifications are sent at all
+    pub enabled: bool,
+    /// Notify for any file at least this large (0 = don't notify by size)
+    #[serde(deserialize_with = "deserialize_size")]
+    pub min_size_bytes: u64,
+    /// Notify for any file of these types
+    pub types: Vec<FileType>,
+}
+
+/// `[webhook]` section: an HTTP endpoint told about each new file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// URL each new file is POSTed to as JSON (unset = no webhook)
+    pub url: Option<String>,
+}
+
+/// The `theme` setting: `theme = "light"` or a `[theme]` table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ThemeConfig {
+    /// A built-in preset, "dark" or "light"
+    Preset(String),
+    /// A preset with individual roles overridden
+    Custom(Box<ThemeColors>),
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self::Preset("dark".to_string())
+    }
+}
+
+/// `[theme]` section: color names (e.g. "cyan", "darkgray", "#336699") per role
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeColors {
+    /// Preset the overrides apply to (default "dark")
+    pub preset: Option<String>,
+    pub header: Option<String>,
+    pub label: Option<String>,
+    pub selection_fg: Option<String>,
+    pub selection_bg: Option<String>,
+    pub border: Option<String>,
+    pub status: Option<String>,
+    pub muted: Option<String>,
+    pub executable: Option<String>,
+    pub archive: Option<String>,
+    pub document: Option<String>,
+    pub media: Option<String>,
+    pub code: Option<String>,
+    pub other: Option<String>,
+}
+
+/// A `[[rules]]` entry that overrides the file type for matching paths
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypeRule {
+    /// Glob the full path must match (a leading `~/` is expanded)
+    pub path_glob: String,
+    /// Only apply to files with this extension (case-insensitive, no dot)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extension: Option<String>,
+    /// File type to assign, e.g. "document"
+    pub file_type: String,
+}
+
+/// A `[[categories]]` entry: a user-defined category on top of the file type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CategoryConfig {
+    /// Identifier used in filters, e.g. "ebook"
+    pub name: String,
+    /// Short text shown in the list's Type column, e.g. "Book"
+    pub label: String,
+    /// Extensions that belong to the category (case-insensitive, no dot)
+    pub extensions: Vec<String>,
+}
+
+/// A `[[watch]]` entry: a watch path whose filters differ from the top-level ones
+///
+/// Unset fields fall back to the top-level setting of the same name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchConfig {
+    /// Directory to watch (a leading `~/` is expanded)
+    pub path: PathBuf,
+    /// Glob patterns for paths to ignore under this directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_patterns: Option<Vec<String>>,
+    /// Minimum file size in bytes to log under this directory
+    #[serde(default, deserialize_with = "deserialize_optional_size", skip_serializing_if = "Option::is_none")]
+    pub min_size_bytes: Option<u64>,
+    /// How many directory levels below this directory to track
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+}
+
+/// A `[[quota]]` entry: warn when the files in a directory grow too large
+/// or too many
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuotaConfig {
+    /// Directory whose files count, subdirectories included, or `*` for all files
+    pub dir: String,
+    /// Only count files of this type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_type: Option<FileType>,
+    /// Largest total size before warning
+    #[serde(default, deserialize_with = "deserialize_optional_size", skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+    /// Most files before warning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<u64>,
+}
+
+/// Compiled filters for the files under one watch path
+#[derive(Debug, Clone)]
+pub struct WatchRules {
+    /// Paths matching these globs are ignored
+    pub ignore_matcher: globset::GlobSet,
+    /// Smaller files are ignored
+    pub min_size: u64,
+    /// Deepest level below the watch path to track (None = unlimited)
+    pub max_depth: Option<usize>,
+}
+
+/// Compiled `[[rules]]`, ready to classify paths
+#[derive(Debug, Clone, Default)]
+pub struct TypeRules {
+    rules: Vec<(globset::GlobMatcher, Option<String>, FileType)>,
+}
+
+impl TypeRules {
+    /// Return the file type of the first rule matching `path`, if any
+    pub fn classify(&self, path: &Path) -> Option<FileType> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+
+        self.rules
+            .iter()
+            .find(|(glob, extension, _)| {
+                glob.is_match(path)
+                    && extension
+                        .as_deref()
+                        .is_none_or(|want| ext.as_deref() == Some(want))
+            })
+            .map(|(_, _, file_type)| *file_type)
+    }
+}
+
+/// Compiled `[[categories]]`, ready to classify paths
+#[derive(Debug, Clone, Default)]
+pub struct Categories {
+    /// (name, label) of each category, in config order
+    entries: Vec<(String, String)>,
+    /// Lowercase extension -> index into `entries`
+    by_extension: HashMap<String, usize>,
+}
+
+impl Categories {
+    /// Name of the category `path`'s extension belongs to, if any
+    pub fn classify(&self, path: &Path) -> Option<&str> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        self.by_extension
+            .get(&ext)
+            .map(|&i| self.entries[i].0.as_str())
+    }
+
+    /// Label of the category called `name`
+    pub fn label(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, label)| label.as_str())
+    }
+
+    /// (name, label) of every category, in config order
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+}
+
+/// Compiled `[openers]`: the argument template each file is opened with
+#[derive(Debug, Clone, Default)]
+pub struct Openers {
+    /// Lowercase extension -> arguments, checked first
+    by_extension: HashMap<String, Vec<String>>,
+    /// File type -> arguments
+    by_type: HashMap<FileType, Vec<String>>,
+}
+
+impl Openers {
+    /// Command line to open `path` with, if an opener matches its extension or type
+    ///
+    /// `{path}` is replaced inside each argument, so a path with spaces stays
+    /// one argument; without a `{path}` the path is appended.
+    pub fn command_for(&self, path: &Path, file_type: FileType) -> Option<Vec<String>> {
+        let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+        let template = ext
+            .and_then(|ext| self.by_extension.get(&ext))
+            .or_else(|| self.by_type.get(&file_type))?;
+
+        let path = path.to_string_lossy();
+        let mut args: Vec<String> = template.iter().map(|arg| arg.replace("{path}", &path)).collect();
+        if !template.iter().any(|arg| arg.contains("{path}")) {
+            args.push(path.into_owned());
+        }
+        Some(args)
+    }
 }
 
 impl Default for Config {
@@ -46,16 +421,81 @@ impl Default for Config {
             watch_paths: default_watch_paths(),
             ignore_patterns: default_ignore_patterns(),
             min_size_bytes: 0,
+            ignore_zero_byte: true,
             retention_days: 90,
             log_level: "info".to_string(),
             database_path: None,
+            busy_timeout_ms: 5000,
             log_file: None,
             follow_symlinks: false,
-            debounce_ms: 500,
+            debounce_ms: 300,
+            max_batch_size: 500,
+            ui_batch_delay_ms: 200,
+            auto_refresh_secs: 0,
+            follow_summary_secs: 10,
+            extraction_threshold: 0,
+            collapse_extractions: false,
+            stability_check_ms: None,
+            health_check_ms: 5_000,
+            compute_hashes: true,
+            max_hash_size_bytes: 256 * 1024 * 1024,
+            max_depth: None,
+            content_sniffing: true,
+            scan_existing: false,
+            timestamp_source: "discovered".to_string(),
+            respect_gitignore: false,
+            trash_instead_of_delete: true,
+            retain_deleted_in_ledger: false,
+            default_view_mode: "flat".to_string(),
+            list_columns: default_list_columns(),
+            relative_time: false,
+            size_format: "binary".to_string(),
+            wrap_navigation: false,
+            tree_size_bars: true,
+            page_size: 100,
+            theme: ThemeConfig::default(),
+            notifications: NotificationConfig::default(),
+            webhook: WebhookConfig::default(),
+            openers: BTreeMap::new(),
+            categories: Vec::new(),
+            rules: Vec::new(),
+            watch: Vec::new(),
+            quota: Vec::new(),
+            profile: None,
         }
     }
 }
 
+/// Check a `--profile` name: letters, digits, `-` and `_`, so it can't
+/// point outside the config and data directories
+pub fn parse_profile(name: &str) -> std::result::Result<String, String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("invalid profile name '{}': use letters, digits, '-' and '_'", name));
+    }
+    if name == "config" {
+        return Err("'config' is the default config file; pick another profile name".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// A path from an environment variable, ignoring it when unset or empty
+fn env_path(var: &str) -> Option<PathBuf> {
+    env_var(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(not(test))]
+fn env_var(var: &str) -> Option<std::ffi::OsString> {
+    std::env::var_os(var)
+}
+
+/// Tests read variables from their own thread, never the process environment
+#[cfg(test)]
+fn env_var(var: &str) -> Option<std::ffi::OsString> {
+    tests::TEST_ENV.with(|env| env.borrow().get(var).cloned())
+}
+
 /// Returns default watch paths (~/Downloads, ~/Desktop)
 fn default_watch_paths() -> Vec<PathBuf> {
     let mut paths = Vec::new();
@@ -75,6 +515,14 @@ fn default_watch_paths() -> Vec<PathBuf> {
     paths
 }
 
+/// Returns the default list view columns
+fn default_list_columns() -> Vec<String> {
+    ["time", "size", "type", "path"]
+        .iter()
+        .map(|c| c.to_string())
+        .collect()
+}
+
 /// Returns default ignore patterns
 fn default_ignore_patterns() -> Vec<String> {
     vec![
@@ -89,6 +537,9 @@ fn default_ignore_patterns() -> Vec<String> {
         "**/*.part".to_string(),    // Partial downloads
         "**/*.crdownload".to_string(), // Chrome partial downloads
         "**/*.download".to_string(),
+        "**/.DS_Store".to_string(), // OS junk, kept even if hidden files aren't ignored
+        "**/Thumbs.db".to_string(),
+        "**/desktop.ini".to_string(),
         "**/venv/**".to_string(),
         "**/__pycache__/**".to_string(),
         "**/.cache/**".to_string(),
@@ -98,16 +549,23 @@ fn default_ignore_patterns() -> Vec<String> {
 }
 
 impl Config {
-    /// Load configuration from the default config file location
-    pub fn load() -> Result<Self> {
-        let config_path = Self::config_file_path();
+    /// Load configuration from the default config file location of `profile`
+    pub fn load(profile: Option<&str>) -> Result<Self> {
+        let config_path = Self::config_file_path(profile);
         
-        if config_path.exists() {
-            Self::load_from_file(&config_path)
+        let config = if config_path.exists() {
+            Self::load_from_file(&config_path)?
         } else {
             info!("No config file found, using defaults");
-            Ok(Self::default())
-        }
+            Self::default()
+        };
+        Ok(config.with_profile(profile))
+    }
+
+    /// Use `profile`'s default database and state files
+    pub fn with_profile(mut self, profile: Option<&str>) -> Self {
+        self.profile = profile.map(str::to_string);
+        self
     }
 
     /// Load configuration from a specific file
@@ -115,8 +573,7 @@ impl Config {
         let content = std::fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
         
-        let config: Config = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+        let config: Config = toml::from_str(&content).map_err(|e| parse_error(path, &content, &e))?;
         
         debug!("Loaded config from {}", path.display());
         Ok(config)
@@ -124,10 +581,29 @@ impl Config {
 
     /// Save configuration to the default config file
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_file_path();
+        let config_path = Self::config_file_path(self.profile.as_deref());
         self.save_to_file(&config_path)
     }
 
+    /// Add `pattern` to `ignore_patterns` in the config file at `path`
+    ///
+    /// The file is loaded on its own, so CLI and environment overrides
+    /// aren't written back; a missing file starts from the defaults.
+    /// Returns false if the pattern was already there.
+    pub fn append_ignore_pattern(path: &Path, pattern: &str) -> Result<bool> {
+        let mut config = if path.exists() {
+            Self::load_from_file(path)?
+        } else {
+            Self::default()
+        };
+        if config.ignore_patterns.iter().any(|p| p == pattern) {
+            return Ok(false);
+        }
+        config.ignore_patterns.push(pattern.to_string());
+        config.save_to_file(path)?;
+        Ok(true)
+    }
+
     /// Save configuration to a specific file
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
         // Ensure parent directory exists
@@ -146,9 +622,9 @@ impl Config {
         Ok(())
     }
 
-    /// Create default config file if it doesn't exist
-    pub fn ensure_default_config() -> Result<PathBuf> {
-        let config_path = Self::config_file_path();
+    /// Create default config file (of `profile`, if given) if it doesn't exist
+    pub fn ensure_default_config(profile: Option<&str>) -> Result<PathBuf> {
+        let config_path = Self::config_file_path(profile);
         
         if !config_path.exists() {
             let default_config = Self::default();
@@ -160,29 +636,102 @@ impl Config {
     }
 
     /// Get the path to the config file
-    pub fn config_file_path() -> PathBuf {
-        dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("ferret")
-            .join("config.toml")
+    ///
+    /// `<profile>.toml` in the XDG config directory for a profile, else
+    /// `$FERRET_CONFIG` if set, else `config.toml` there. A `--config` flag
+    /// takes precedence over all of these; it is handled by the caller,
+    /// which loads that file instead.
+    pub fn config_file_path(profile: Option<&str>) -> PathBuf {
+        let config_dir = || dirs::config_dir().unwrap_or_else(|| PathBuf::from(".")).join("ferret");
+        match profile {
+            Some(profile) => config_dir().join(format!("{}.toml", profile)),
+            None => env_path(CONFIG_ENV).unwrap_or_else(|| config_dir().join("config.toml")),
+        }
+    }
+
+    /// Directory for the database and log file when their paths aren't set:
+    /// `$FERRET_DATA_DIR` if set, else `ferret` in the XDG data directory
+    pub fn data_dir() -> Option<PathBuf> {
+        env_path(DATA_DIR_ENV).or_else(|| dirs::data_local_dir().map(|d| d.join("ferret")))
+    }
+
+    /// Directory for mutable state other than the database (saved TUI state,
+    /// the default log file)
+    ///
+    /// `$FERRET_STATE_DIR` if set, else `$FERRET_DATA_DIR`, else `ferret` in
+    /// `$XDG_STATE_HOME` (`~/.local/state`). Where there is no state directory
+    /// (macOS, and `%LOCALAPPDATA%` on Windows) it is [`Self::data_dir`].
+    pub fn state_dir() -> Option<PathBuf> {
+        env_path(STATE_DIR_ENV)
+            .or_else(|| env_path(DATA_DIR_ENV))
+            .or_else(|| dirs::state_dir().map(|d| d.join("ferret")))
+            .or_else(Self::data_dir)
     }
 
     /// Get the path to the database file
+    ///
+    /// Precedence: CLI override > `$FERRET_DB` > `database_path` setting >
+    /// `ledger.db` (`<profile>.db` for a profile) in [`Self::data_dir`]. The
+    /// first two are written into the setting by [`Self::with_cli_overrides`]
+    /// and [`Self::with_env_overrides`].
     pub fn database_path(&self) -> PathBuf {
         self.database_path.clone().unwrap_or_else(|| {
-            dirs::data_local_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("ferret")
-                .join("ledger.db")
+            let file_name = match &self.profile {
+                Some(profile) => format!("{}.db", profile),
+                None => "ledger.db".to_string(),
+            };
+            Self::data_dir()
+                .unwrap_or_else(|| PathBuf::from("ferret"))
+                .join(file_name)
         })
     }
 
+    /// How long to wait for a database locked by another process
+    pub fn busy_timeout(&self) -> Duration {
+        Duration::from_millis(self.busy_timeout_ms)
+    }
+
+    /// Get the path of the file that keeps the tree view's expanded
+    /// directories between sessions
+    pub fn tree_state_path(&self) -> PathBuf {
+        self.state_file_path("tree_state.json")
+    }
+
+    /// Get the path to the file the TUI's last filter is saved to
+    pub fn filter_state_path(&self) -> PathBuf {
+        self.state_file_path("filter_state.json")
+    }
+
     /// Get the path to the log file (if configured)
     pub fn log_file_path(&self) -> Option<PathBuf> {
-        self.log_file.clone().or_else(|| {
-            dirs::data_local_dir()
-                .map(|d| d.join("ferret").join("ferret.log"))
-        })
+        let file_name = match &self.profile {
+            Some(profile) => format!("{}.log", profile),
+            None => "ferret.log".to_string(),
+        };
+        self.log_file
+            .clone()
+            .or_else(|| Self::state_dir().map(|d| d.join(file_name)))
+    }
+
+    /// State file `name` in [`Self::state_dir`]
+    ///
+    /// A database at a custom path keeps its state files next to it, so
+    /// separate ledgers don't share expanded folders and filters.
+    fn state_file_path(&self, name: &str) -> PathBuf {
+        let name = self.profile_file_name(name);
+        match &self.database_path {
+            Some(db_path) => db_path.with_file_name(name),
+            None => Self::state_dir().unwrap_or_else(|| PathBuf::from("ferret")).join(name),
+        }
+    }
+
+    /// `name` prefixed with the profile, so profiles sharing a directory
+    /// keep their own state files
+    fn profile_file_name(&self, name: &str) -> String {
+        match &self.profile {
+            Some(profile) => format!("{}.{}", profile, name),
+            None => name.to_string(),
+        }
     }
 
     /// Expand a path, resolving ~ to home directory
@@ -198,11 +747,14 @@ impl Config {
         path.to_path_buf()
     }
 
-    /// Get expanded watch paths (with ~ resolved)
+    /// Get expanded watch paths (with ~ resolved), including `[[watch]]` entries
     pub fn expanded_watch_paths(&self) -> Vec<PathBuf> {
+        let mut seen = std::collections::HashSet::new();
         self.watch_paths
             .iter()
+            .chain(self.watch.iter().map(|w| &w.path))
             .map(|p| Self::expand_path(p))
+            .filter(|p| seen.insert(p.clone()))
             .filter(|p| {
                 if !p.exists() {
                     warn!("Watch path does not exist: {}", p.display());
@@ -214,11 +766,42 @@ impl Config {
             .collect()
     }
 
+    /// The config as Ferret will use it: `~` expanded, default database and
+    /// log paths filled in, and `watch_paths` replaced by every directory
+    /// that will actually be watched (see [`Self::expanded_watch_paths`])
+    pub fn resolved(&self) -> Config {
+        let mut resolved = self.clone();
+        resolved.watch_paths = self.expanded_watch_paths();
+        for watch in &mut resolved.watch {
+            watch.path = Self::expand_path(&watch.path);
+        }
+        for quota in &mut resolved.quota {
+            if quota.dir != "*" {
+                quota.dir = Self::expand_path(Path::new(&quota.dir)).display().to_string();
+            }
+        }
+        resolved.database_path = Some(Self::expand_path(&self.database_path()));
+        resolved.log_file = self.log_file_path().map(|p| Self::expand_path(&p));
+        resolved
+    }
+
+    /// Apply `$FERRET_DB`, which beats the config file but not the CLI
+    /// (apply CLI overrides afterwards)
+    ///
+    /// A profile keeps its own database, so the variable is ignored then.
+    pub fn with_env_overrides(mut self) -> Self {
+        if let (Some(db_path), None) = (env_path(DB_ENV), &self.profile) {
+            self.database_path = Some(db_path);
+        }
+        self
+    }
+
     /// Merge CLI overrides into config
     pub fn with_cli_overrides(mut self, overrides: CliOverrides) -> Self {
         if !overrides.watch_paths.is_empty() {
             if overrides.no_defaults {
                 self.watch_paths = overrides.watch_paths;
+                self.watch.clear();
             } else {
                 self.watch_paths.extend(overrides.watch_paths);
             }
@@ -237,15 +820,186 @@ impl Config {
 
     /// Build a GlobSet from ignore patterns
     pub fn build_ignore_matcher(&self) -> Result<globset::GlobSet> {
-        let mut builder = globset::GlobSetBuilder::new();
-        
-        for pattern in &self.ignore_patterns {
-            let glob = globset::Glob::new(pattern)
-                .with_context(|| format!("Invalid ignore pattern: {}", pattern))?;
-            builder.add(glob);
+        build_glob_set(&self.ignore_patterns)
+    }
+
+    /// Filters for watch paths without a `[[watch]]` entry
+    pub fn default_watch_rules(&self) -> Result<WatchRules> {
+        Ok(WatchRules {
+            ignore_matcher: self.build_ignore_matcher()?,
+            min_size: self.min_size_bytes,
+            max_depth: self.max_depth,
+        })
+    }
+
+    /// Compile each `[[watch]]` entry's filters, keyed by its canonical path
+    pub fn build_watch_rules(&self) -> Result<HashMap<PathBuf, WatchRules>> {
+        let mut rules = HashMap::new();
+
+        for entry in &self.watch {
+            let ignore_matcher = match &entry.ignore_patterns {
+                Some(patterns) => build_glob_set(patterns)?,
+                None => self.build_ignore_matcher()?,
+            };
+            let path = Self::expand_path(&entry.path);
+            let path = path.canonicalize().unwrap_or(path);
+
+            rules.insert(
+                path,
+                WatchRules {
+                    ignore_matcher,
+                    min_size: entry.min_size_bytes.unwrap_or(self.min_size_bytes),
+                    max_depth: entry.max_depth.or(self.max_depth),
+                },
+            );
+        }
+
+        Ok(rules)
+    }
+
+    /// Parse `default_view_mode`
+    pub fn view_mode(&self) -> Result<ViewMode> {
+        self.default_view_mode
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid default_view_mode: {}", e))
+    }
+
+    /// Parse `timestamp_source`
+    pub fn timestamp_source(&self) -> Result<TimestampSource> {
+        self.timestamp_source
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid timestamp_source: {}", e))
+    }
+
+    /// Parse `size_format`
+    pub fn size_format(&self) -> Result<SizeFormat> {
+        self.size_format
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid size_format: {}", e))
+    }
+
+    /// Parse `list_columns` (falls back to the defaults if empty)
+    pub fn columns(&self) -> Result<Vec<ListColumn>> {
+        if self.list_columns.is_empty() {
+            return Ok(ListColumn::defaults());
         }
 
-        builder.build().context("Failed to build ignore matcher")
+        self.list_columns
+            .iter()
+            .map(|c| c.parse().map_err(|e| anyhow::anyhow!("Invalid list_columns entry: {}", e)))
+            .collect()
+    }
+
+    /// Compile the `[[rules]]` table into a `TypeRules` classifier
+    pub fn build_type_rules(&self) -> Result<TypeRules> {
+        let mut rules = Vec::with_capacity(self.rules.len());
+
+        for rule in &self.rules {
+            let pattern = Self::expand_path(Path::new(&rule.path_glob));
+            let glob = globset::Glob::new(&pattern.to_string_lossy())
+                .with_context(|| format!("Invalid rule path_glob: {}", rule.path_glob))?
+                .compile_matcher();
+            let file_type = rule
+                .file_type
+                .parse::<FileType>()
+                .map_err(|e| anyhow::anyhow!("Invalid rule for '{}': {}", rule.path_glob, e))?;
+            let extension = rule
+                .extension
+                .as_ref()
+                .map(|e| e.trim_start_matches('.').to_lowercase());
+
+            rules.push((glob, extension, file_type));
+        }
+
+        Ok(TypeRules { rules })
+    }
+
+    /// Compile the `[[categories]]` table, rejecting duplicate names and
+    /// extensions claimed by two categories
+    pub fn build_categories(&self) -> Result<Categories> {
+        let mut categories = Categories::default();
+
+        for (i, category) in self.categories.iter().enumerate() {
+            let name = category.name.trim().to_lowercase();
+            if name.is_empty() {
+                anyhow::bail!("Category names must not be empty");
+            }
+            if categories.label(&name).is_some() {
+                anyhow::bail!("Duplicate category '{}'", name);
+            }
+            for ext in &category.extensions {
+                let ext = ext.trim().trim_start_matches('.').to_lowercase();
+                if let Some(&other) = categories.by_extension.get(&ext) {
+                    anyhow::bail!(
+                        "Extension '{}' is in both categories '{}' and '{}'",
+                        ext,
+                        categories.entries[other].0,
+                        name
+                    );
+                }
+                categories.by_extension.insert(ext, i);
+            }
+            categories.entries.push((name, category.label.clone()));
+        }
+
+        Ok(categories)
+    }
+
+    /// Compile the `[openers]` table
+    ///
+    /// Keys naming a file type ("media") apply to that type; any other key is
+    /// an extension ("md" or ".md"), which wins over the type.
+    pub fn build_openers(&self) -> Result<Openers> {
+        let mut openers = Openers::default();
+
+        for (key, command) in &self.openers {
+            let args = shell_words::split(command)
+                .with_context(|| format!("Invalid opener for '{}': {}", key, command))?;
+            if args.is_empty() {
+                anyhow::bail!("Opener for '{}' must not be empty", key);
+            }
+            let key = key.trim().trim_start_matches('.').to_lowercase();
+            // Only full type names, so "doc" stays the extension
+            match key.parse::<FileType>() {
+                Ok(file_type) if file_type.as_str() == key => {
+                    openers.by_type.insert(file_type, args);
+                }
+                _ => {
+                    openers.by_extension.insert(key, args);
+                }
+            }
+        }
+
+        Ok(openers)
+    }
+
+    /// Compile the `[[quota]]` table
+    ///
+    /// Directories are canonicalized like watch paths so they match the
+    /// paths files are recorded under.
+    pub fn build_quotas(&self) -> Result<Vec<Quota>> {
+        self.quota
+            .iter()
+            .map(|entry| {
+                let dir = entry.dir.trim();
+                if dir.is_empty() {
+                    anyhow::bail!("Quota dir must not be empty (use \"*\" for all files)");
+                }
+                if entry.max_bytes.is_none() && entry.max_count.is_none() {
+                    anyhow::bail!("Quota for '{}' needs max_bytes or max_count", dir);
+                }
+                let dir = (dir != "*").then(|| {
+                    let path = Self::expand_path(Path::new(dir));
+                    path.canonicalize().unwrap_or(path)
+                });
+                Ok(Quota {
+                    dir,
+                    file_type: entry.file_type,
+                    max_bytes: entry.max_bytes,
+                    max_count: entry.max_count,
+                })
+            })
+            .collect()
     }
 
     /// Check if a path should be ignored
@@ -255,6 +1009,169 @@ impl Config {
     }
 }
 
+/// Size units accepted by [`parse_size`], with their multipliers
+///
+/// Single letters are binary like in `du -h`; `KB`/`MB`/... are decimal.
+const SIZE_UNITS: &[(&str, u64)] = &[
+    ("", 1),
+    ("b", 1),
+    ("k", 1 << 10),
+    ("kb", 1_000),
+    ("kib", 1 << 10),
+    ("m", 1 << 20),
+    ("mb", 1_000_000),
+    ("mib", 1 << 20),
+    ("g", 1 << 30),
+    ("gb", 1_000_000_000),
+    ("gib", 1 << 30),
+    ("t", 1 << 40),
+    ("tb", 1_000_000_000_000),
+    ("tib", 1 << 40),
+];
+
+/// Parse a size such as "500K", "10MB" or "1.5GiB" into bytes
+///
+/// A plain number is a byte count. Units are case-insensitive, and a space
+/// between number and unit is allowed.
+pub fn parse_size(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let unit = unit.trim().to_lowercase();
+
+    let Some(&(_, multiplier)) = SIZE_UNITS.iter().find(|(name, _)| *name == unit) else {
+        anyhow::bail!(
+            "Invalid size unit in '{}'. Use a byte count or one of: B, K, KB, KiB, M, MB, MiB, G, GB, GiB, T, TB, TiB",
+            s
+        );
+    };
+
+    // Whole numbers stay exact; fractions go through f64
+    if let Ok(whole) = number.parse::<u64>() {
+        return whole
+            .checked_mul(multiplier)
+            .with_context(|| format!("Size '{}' is too large", s));
+    }
+    let value: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid size '{}'. Expected a number like 500K or 1.5GiB", s))?;
+    let bytes = (value * multiplier as f64).round();
+    if bytes >= u64::MAX as f64 {
+        anyhow::bail!("Size '{}' is too large", s);
+    }
+    Ok(bytes as u64)
+}
+
+/// Describe a config file parse error by line, column and key
+///
+/// An unknown field's message drops the list of every valid field that
+/// `serde` appends to it.
+fn parse_error(path: &Path, content: &str, error: &toml::de::Error) -> anyhow::Error {
+    let message = match error.message().split_once(", expected") {
+        Some((unknown, _)) if unknown.starts_with("unknown field") => format!(
+            "{} (see `ferret-tracker config --example` for the valid settings)",
+            unknown
+        ),
+        _ => error.message().to_string(),
+    };
+    let Some(span) = error.span() else {
+        return anyhow::anyhow!("Invalid config file {}: {}", path.display(), message);
+    };
+
+    let before = &content[..span.start.min(content.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    let column = before[line_start..].chars().count() + 1;
+
+    // The key on the offending line, qualified by the table it's in
+    let key = content.lines().nth(line - 1).and_then(|text| {
+        let (key, _) = text.split_once('=')?;
+        let key = key.trim();
+        let table = before[..line_start].lines().rev().find_map(|text| {
+            let text = text.trim();
+            text.starts_with('[').then(|| text.trim_matches(|c| c == '[' || c == ']').trim())
+        });
+        Some(match table {
+            Some(table) => format!("{}.{}", table, key),
+            None => key.to_string(),
+        })
+    });
+
+    match key {
+        Some(key) => anyhow::anyhow!(
+            "Invalid config file {} at line {}, column {} (`{}`): {}",
+            path.display(),
+            line,
+            column,
+            key,
+            message
+        ),
+        None => anyhow::anyhow!(
+            "Invalid config file {} at line {}, column {}: {}",
+            path.display(),
+            line,
+            column,
+            message
+        ),
+    }
+}
+
+/// A size in the config file: a byte count or a string for [`parse_size`]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SizeValue {
+    Bytes(u64),
+    Text(String),
+}
+
+impl SizeValue {
+    fn into_bytes<E: serde::de::Error>(self) -> std::result::Result<u64, E> {
+        match self {
+            SizeValue::Bytes(bytes) => Ok(bytes),
+            SizeValue::Text(text) => parse_size(&text).map_err(E::custom),
+        }
+    }
+}
+
+/// Deserialize a size given as bytes or as e.g. "10MB"
+fn deserialize_size<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+    SizeValue::deserialize(deserializer)?.into_bytes()
+}
+
+/// Deserialize an optional size given as bytes or as e.g. "10MB"
+fn deserialize_optional_size<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<u64>, D::Error> {
+    Option::<SizeValue>::deserialize(deserializer)?
+        .map(SizeValue::into_bytes)
+        .transpose()
+}
+
+/// Ignore pattern matching everything inside `dir`
+///
+/// Glob metacharacters in the path are escaped, so a folder named
+/// `[draft]` only matches itself.
+pub fn dir_ignore_pattern(dir: &Path) -> String {
+    let dir = dir.to_string_lossy();
+    let dir = dir.trim_end_matches(std::path::MAIN_SEPARATOR);
+    format!("{}/**", globset::escape(dir))
+}
+
+/// Compile ignore patterns into a GlobSet
+fn build_glob_set(patterns: &[String]) -> Result<globset::GlobSet> {
+    let mut builder = globset::GlobSetBuilder::new();
+
+    for pattern in patterns {
+        let glob = globset::Glob::new(pattern)
+            .with_context(|| format!("Invalid ignore pattern: {}", pattern))?;
+        builder.add(glob);
+    }
+
+    builder.build().context("Failed to build ignore matcher")
+}
+
 /// CLI overrides for configuration
 #[derive(Debug, Clone, Default)]
 pub struct CliOverrides {
@@ -269,27 +1186,135 @@ pub struct CliOverrides {
 }
 
 /// Validate configuration
+///
+/// Fails with every problem found, one per line.
 pub fn validate_config(config: &Config) -> Result<()> {
+    let problems = config_problems(config);
+    if !problems.is_empty() {
+        anyhow::bail!("{}", problems.join("\n"));
+    }
+    Ok(())
+}
+
+/// Everything wrong with `config`, in the order settings are checked
+pub fn config_problems(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+
     // Ensure at least one valid watch path
     let valid_paths = config.expanded_watch_paths();
     if valid_paths.is_empty() {
-        anyhow::bail!("No valid watch paths configured. Please add paths to watch.");
+        problems.push("No valid watch paths configured. Please add paths to watch.".to_string());
     }
 
     // Validate log level
     let valid_levels = ["error", "warn", "info", "debug", "trace"];
     if !valid_levels.contains(&config.log_level.to_lowercase().as_str()) {
-        anyhow::bail!(
+        problems.push(format!(
             "Invalid log level '{}'. Valid levels: {:?}",
             config.log_level,
             valid_levels
-        );
+        ));
     }
 
     // Validate ignore patterns (try to compile them)
-    config.build_ignore_matcher()?;
+    note(&mut problems, config.build_ignore_matcher());
+    note(&mut problems, config.build_watch_rules());
 
-    Ok(())
+    // Validate type override rules (globs and file_type names)
+    note(&mut problems, config.build_type_rules());
+    note(&mut problems, config.build_categories());
+    note(&mut problems, config.build_quotas());
+    note(&mut problems, config.build_openers());
+
+    // Validate TUI layout settings
+    note(&mut problems, config.view_mode());
+    note(&mut problems, config.columns());
+    note(&mut problems, config.size_format());
+    note(&mut problems, config.timestamp_source());
+    note(&mut problems, crate::tui::theme::Theme::from_config(&config.theme));
+
+    if let Some(url) = &config.webhook.url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            problems.push(format!("Invalid webhook url '{}'. Use an http:// or https:// URL", url));
+        }
+    }
+
+    // Validate timing settings
+    if !DEBOUNCE_MS_RANGE.contains(&config.debounce_ms) {
+        problems.push(format!(
+            "debounce_ms must be between {} and {}, got {}",
+            DEBOUNCE_MS_RANGE.start(),
+            DEBOUNCE_MS_RANGE.end(),
+            config.debounce_ms
+        ));
+    }
+    if !MAX_BATCH_SIZE_RANGE.contains(&config.max_batch_size) {
+        problems.push(format!(
+            "max_batch_size must be between {} and {}, got {}",
+            MAX_BATCH_SIZE_RANGE.start(),
+            MAX_BATCH_SIZE_RANGE.end(),
+            config.max_batch_size
+        ));
+    }
+    if !UI_BATCH_DELAY_MS_RANGE.contains(&config.ui_batch_delay_ms) {
+        problems.push(format!(
+            "ui_batch_delay_ms must be between {} and {}, got {}",
+            UI_BATCH_DELAY_MS_RANGE.start(),
+            UI_BATCH_DELAY_MS_RANGE.end(),
+            config.ui_batch_delay_ms
+        ));
+    }
+    if config.auto_refresh_secs > 0 && !AUTO_REFRESH_SECS_RANGE.contains(&config.auto_refresh_secs) {
+        problems.push(format!(
+            "auto_refresh_secs must be 0 (off) or between {} and {}, got {}",
+            AUTO_REFRESH_SECS_RANGE.start(),
+            AUTO_REFRESH_SECS_RANGE.end(),
+            config.auto_refresh_secs
+        ));
+    }
+    if let Some(ms) = config.stability_check_ms {
+        if !STABILITY_CHECK_MS_RANGE.contains(&ms) {
+            problems.push(format!(
+                "stability_check_ms must be between {} and {}, got {}",
+                STABILITY_CHECK_MS_RANGE.start(),
+                STABILITY_CHECK_MS_RANGE.end(),
+                ms
+            ));
+        }
+    }
+    if !BUSY_TIMEOUT_MS_RANGE.contains(&config.busy_timeout_ms) {
+        problems.push(format!(
+            "busy_timeout_ms must be between {} and {}, got {}",
+            BUSY_TIMEOUT_MS_RANGE.start(),
+            BUSY_TIMEOUT_MS_RANGE.end(),
+            config.busy_timeout_ms
+        ));
+    }
+    if !PAGE_SIZE_RANGE.contains(&config.page_size) {
+        problems.push(format!(
+            "page_size must be between {} and {}, got {}",
+            PAGE_SIZE_RANGE.start(),
+            PAGE_SIZE_RANGE.end(),
+            config.page_size
+        ));
+    }
+    if config.health_check_ms != 0 && !HEALTH_CHECK_MS_RANGE.contains(&config.health_check_ms) {
+        problems.push(format!(
+            "health_check_ms must be 0 (off) or between {} and {}, got {}",
+            HEALTH_CHECK_MS_RANGE.start(),
+            HEALTH_CHECK_MS_RANGE.end(),
+            config.health_check_ms
+        ));
+    }
+
+    problems
+}
+
+/// Add the error of a failed check to `problems`
+fn note<T>(problems: &mut Vec<String>, result: Result<T>) {
+    if let Err(e) = result {
+        problems.push(format!("{:#}", e));
+    }
 }
 
 /// Generate default config content as a string (for documentation/examples)
@@ -316,6 +1341,9 @@ pub fn default_config_toml() -> String {
     content.push_str("# Minimum file size in bytes to log (0 = log all files)\n");
     content.push_str(&format!("min_size_bytes = {}\n\n", config.min_size_bytes));
     
+    content.push_str("# Skip empty files, even when min_size_bytes is 0\n");
+    content.push_str(&format!("ignore_zero_byte = {}\n\n", config.ignore_zero_byte));
+    
     content.push_str("# Days to keep events before auto-cleanup (0 = never cleanup)\n");
     content.push_str(&format!("retention_days = {}\n\n", config.retention_days));
     
@@ -325,14 +1353,143 @@ pub fn default_config_toml() -> String {
     content.push_str("# Whether to follow symlinks when watching directories\n");
     content.push_str(&format!("follow_symlinks = {}\n\n", config.follow_symlinks));
     
-    content.push_str("# Debounce delay in milliseconds for file events\n");
+    content.push_str("# Wait this long after the last event on a path before recording it (ms)\n");
     content.push_str(&format!("debounce_ms = {}\n\n", config.debounce_ms));
     
+    content.push_str("# Most raw events collected before the watcher processes a batch\n");
+    content.push_str(&format!("max_batch_size = {}\n\n", config.max_batch_size));
+    
+    content.push_str("# How long the TUI gathers new files before refreshing the list (ms)\n");
+    content.push_str(&format!("ui_batch_delay_ms = {}\n\n", config.ui_batch_delay_ms));
+    
+    content.push_str("# Reload the TUI list this often to show files added by other Ferret\n");
+    content.push_str("# processes, e.g. `ferret-tracker ingest` (seconds, 0 = off)\n");
+    content.push_str(&format!("auto_refresh_secs = {}\n\n", config.auto_refresh_secs));
+    
+    content.push_str("# How often `watch --headless --follow` prints a summary of today's files\n");
+    content.push_str("# (seconds, 0 = never)\n");
+    content.push_str(&format!("follow_summary_secs = {}\n\n", config.follow_summary_secs));
+    
+    content.push_str("# How often to check that watch paths still exist and re-watch them when\n");
+    content.push_str("# they come back, e.g. a remounted drive (ms, 0 = off)\n");
+    content.push_str(&format!("health_check_ms = {}\n\n", config.health_check_ms));
+    
+    content.push_str("# Record one Archive event for a new folder when more than this many files\n");
+    content.push_str("# appear in it at once, e.g. an extracted archive (0 = off)\n");
+    content.push_str(&format!("extraction_threshold = {}\n\n", config.extraction_threshold));
+    
+    content.push_str("# Skip the individual files of such a folder, keeping only the summary\n");
+    content.push_str(&format!("collapse_extractions = {}\n\n", config.collapse_extractions));
+    
+    content.push_str("# Compute SHA-256 hashes of new files (used by `ferret dupes`)\n");
+    content.push_str(&format!("compute_hashes = {}\n\n", config.compute_hashes));
+    
+    content.push_str("# Skip hashing files larger than this many bytes (0 = no limit)\n");
+    content.push_str(&format!("max_hash_size_bytes = {}\n\n", config.max_hash_size_bytes));
+    
+    content.push_str("# Read the first few KB of unrecognised files to detect their type\n");
+    content.push_str(&format!("content_sniffing = {}\n\n", config.content_sniffing));
+    
+    content.push_str("# Track files that already exist in watch paths when watching starts\n");
+    content.push_str("# (their time is taken from the file's modification time)\n");
+    content.push_str(&format!("scan_existing = {}\n\n", config.scan_existing));
+    
+    content.push_str("# Time new files are recorded with: \"discovered\" (when seen), \"modified\"\n");
+    content.push_str("# (mtime) or \"created\" (birth time; falls back where unavailable)\n");
+    content.push_str(&format!("timestamp_source = \"{}\"\n\n", config.timestamp_source));
+    
+    content.push_str("# Skip files ignored by .gitignore files in watched projects\n");
+    content.push_str(&format!("respect_gitignore = {}\n\n", config.respect_gitignore));
+    
+    content.push_str("# Move files to the OS trash when deleting with `d` (`D` always deletes permanently)\n");
+    content.push_str(&format!("trash_instead_of_delete = {}\n\n", config.trash_instead_of_delete));
+    
+    content.push_str("# Keep files deleted through Ferret in the ledger as history (see --include-deleted)\n");
+    content.push_str(&format!("retain_deleted_in_ledger = {}\n\n", config.retain_deleted_in_ledger));
+    
+    content.push_str("# View the TUI starts in: \"flat\", \"grouped\", \"extension\" or \"tree\"\n");
+    content.push_str(&format!("default_view_mode = \"{}\"\n\n", config.default_view_mode));
+    
+    content.push_str("# Columns shown in the flat list, in order (time, size, type, path, tags)\n");
+    let columns: Vec<String> = config.list_columns.iter().map(|c| format!("\"{}\"", c)).collect();
+    content.push_str(&format!("list_columns = [{}]\n\n", columns.join(", ")));
+    
+    content.push_str("# Show times in the list as \"5m ago\" (toggle with `T` in the TUI)\n");
+    content.push_str(&format!("relative_time = {}\n\n", config.relative_time));
+    
+    content.push_str("# How sizes are shown: \"binary\" (1 MiB), \"decimal\" (1.05 MB) or \"short\" (1.0M)\n");
+    content.push_str(&format!("size_format = \"{}\"\n\n", config.size_format));
+    
+    content.push_str("# Wrap Up/Down from the last entry back to the first (crossing pages)\n");
+    content.push_str(&format!("wrap_navigation = {}\n\n", config.wrap_navigation));
+    
+    content.push_str("# Bars showing each folder's size next to its siblings in the tree view\n");
+    content.push_str("# (toggle with `b`; turn off if block characters render poorly)\n");
+    content.push_str(&format!("tree_size_bars = {}\n\n", config.tree_size_bars));
+    
+    content.push_str("# Events loaded per page in the TUI, 10-1000 (`+`/`-` adjust it at runtime)\n");
+    content.push_str(&format!("page_size = {}\n\n", config.page_size));
+    
+    content.push_str("# TUI colors: \"dark\" or \"light\" (see README for per-role [theme] overrides)\n");
+    content.push_str("theme = \"dark\"\n\n");
+    
+    content.push_str("# Optional: Wait until a file's size stops changing for this long before\n");
+    content.push_str("# recording it, to skip downloads that are still being written (ms)\n");
+    content.push_str("# stability_check_ms = 1000\n\n");
+    
+    content.push_str("# Optional: Only track files this many levels below a watch path\n");
+    content.push_str("# (1 = only files placed directly in a watched directory)\n");
+    content.push_str("# max_depth = 1\n\n");
+    
     content.push_str("# Optional: Custom database location\n");
     content.push_str("# database_path = \"~/.local/share/ferret/ledger.db\"\n\n");
     
+    content.push_str("# How long to wait when another Ferret process has the database locked (ms)\n");
+    content.push_str(&format!("busy_timeout_ms = {}\n\n", config.busy_timeout_ms));
+    
     content.push_str("# Optional: Log file location\n");
-    content.push_str("# log_file = \"~/.local/share/ferret/ferret.log\"\n");
+    content.push_str("# log_file = \"~/.local/state/ferret/ferret.log\"\n\n");
+    
+    // Tables go last so the keys above stay at the top level
+    content.push_str("# Desktop notifications for new files that are large or of a given type\n");
+    content.push_str("[notifications]\n");
+    content.push_str(&format!("enabled = {}\n", config.notifications.enabled));
+    content.push_str("min_size_bytes = 104857600\n");
+    content.push_str("types = [\"executable\"]\n\n");
+    
+    content.push_str("# Optional: POST each new file as JSON to a URL\n");
+    content.push_str("# [webhook]\n");
+    content.push_str("# url = \"http://localhost:8123/api/webhook/ferret\"\n\n");
+    content.push_str("# Optional: Programs `o` opens files with, by extension or file type\n");
+    content.push_str("# (anything else uses the system default; `{path}` is the file)\n");
+    content.push_str("# [openers]\n");
+    content.push_str("# md = \"code {path}\"\n");
+    content.push_str("# media = \"mpv --force-window {path}\"\n\n");
+    
+    content.push_str("# Optional: Extra categories shown instead of the type label (filter with --category)\n");
+    content.push_str("# [[categories]]\n");
+    content.push_str("# name = \"ebook\"\n");
+    content.push_str("# label = \"Book\"\n");
+    content.push_str("# extensions = [\"epub\", \"mobi\", \"azw3\"]\n\n");
+    
+    content.push_str("# Optional: Override the file type for matching paths (first match wins)\n");
+    content.push_str("# [[rules]]\n");
+    content.push_str("# path_glob = \"~/Screenshots/**\"\n");
+    content.push_str("# extension = \"png\"\n");
+    content.push_str("# file_type = \"document\"\n\n");
+    
+    content.push_str("# Optional: Watch a directory with its own filters (unset fields use the settings above)\n");
+    content.push_str("# [[watch]]\n");
+    content.push_str("# path = \"~/Camera\"\n");
+    content.push_str("# ignore_patterns = []\n");
+    content.push_str("# min_size_bytes = 1048576\n");
+    content.push_str("# max_depth = 1\n\n");
+    
+    content.push_str("# Optional: Warn when a directory's files grow past a size or count (dir = \"*\" for all files)\n");
+    content.push_str("# [[quota]]\n");
+    content.push_str("# dir = \"~/Downloads\"\n");
+    content.push_str("# max_bytes = \"10G\"\n");
+    content.push_str("# max_count = 5000\n");
     
     content
 }
@@ -340,8 +1497,19 @@ pub fn default_config_toml() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::RefCell;
+    use std::ffi::OsString;
     use tempfile::TempDir;
 
+    thread_local! {
+        /// The variables `env_var` sees on this test's thread
+        pub(super) static TEST_ENV: RefCell<HashMap<String, OsString>> = RefCell::new(HashMap::new());
+    }
+
+    fn set_env(var: &str, value: impl Into<OsString>) {
+        TEST_ENV.with(|env| env.borrow_mut().insert(var.to_string(), value.into()));
+    }
+
     #[test]
     fn test_default_config() {
         let config = Config::default();
@@ -362,6 +1530,91 @@ mod tests {
         assert_eq!(normal, PathBuf::from("/tmp/test"));
     }
 
+    #[test]
+    fn test_resolved_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            watch_paths: vec![temp_dir.path().to_path_buf(), temp_dir.path().join("missing")],
+            log_file: Some(PathBuf::from("~/ferret.log")),
+            watch: vec![WatchConfig {
+                path: PathBuf::from("~/Downloads"),
+                ignore_patterns: None,
+                min_size_bytes: None,
+                max_depth: None,
+            }],
+            ..Default::default()
+        };
+
+        let resolved = config.resolved();
+        assert_eq!(resolved.watch_paths[0], temp_dir.path());
+        assert!(!resolved.watch_paths.contains(&temp_dir.path().join("missing")));
+        assert_eq!(resolved.watch[0].path, Config::expand_path(Path::new("~/Downloads")));
+        assert_eq!(resolved.database_path, Some(config.database_path()));
+        assert!(config.database_path.is_none());
+        assert_eq!(resolved.log_file, Some(Config::expand_path(Path::new("~/ferret.log"))));
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(parse_profile("work-2").unwrap(), "work-2");
+        assert!(parse_profile("").is_err());
+        assert!(parse_profile("../etc").is_err());
+        assert!(parse_profile("config").is_err());
+    }
+
+    #[test]
+    fn test_env_path_precedence() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("custom.toml");
+        std::fs::write(&config_file, "retention_days = 7\ndatabase_path = \"/from/file.db\"\n").unwrap();
+
+        set_env(CONFIG_ENV, &config_file);
+        set_env(DATA_DIR_ENV, temp_dir.path());
+        set_env(DB_ENV, "");
+
+        assert_eq!(Config::config_file_path(None), config_file);
+        let loaded = Config::load(None).unwrap();
+        assert_eq!(loaded.retention_days, 7);
+
+        // Setting > data dir
+        assert_eq!(loaded.database_path(), PathBuf::from("/from/file.db"));
+        assert_eq!(Config::default().database_path(), temp_dir.path().join("ledger.db"));
+        assert_eq!(Config::default().log_file_path(), Some(temp_dir.path().join("ferret.log")));
+
+        // Env > setting, CLI > env; an empty variable counts as unset
+        assert_eq!(loaded.clone().with_env_overrides().database_path(), PathBuf::from("/from/file.db"));
+        set_env(DB_ENV, "/from/env.db");
+        let config = loaded.with_env_overrides();
+        assert_eq!(config.database_path(), PathBuf::from("/from/env.db"));
+        let config = config.with_cli_overrides(CliOverrides {
+            database_path: Some(PathBuf::from("/from/cli.db")),
+            ..Default::default()
+        });
+        assert_eq!(config.database_path(), PathBuf::from("/from/cli.db"));
+
+        // A profile has its own files, whatever the variables say
+        let work = Config::default().with_profile(Some("work")).with_env_overrides();
+        assert!(Config::config_file_path(Some("work")).ends_with("ferret/work.toml"));
+        assert_eq!(work.database_path(), temp_dir.path().join("work.db"));
+        assert_eq!(work.log_file_path(), Some(temp_dir.path().join("work.log")));
+        assert_eq!(work.tree_state_path(), temp_dir.path().join("work.tree_state.json"));
+        assert_eq!(Config::default().filter_state_path(), temp_dir.path().join("filter_state.json"));
+
+        // State files follow $FERRET_STATE_DIR, except beside a custom database
+        let state_dir = temp_dir.path().join("state");
+        set_env(STATE_DIR_ENV, &state_dir);
+        assert_eq!(Config::default().tree_state_path(), state_dir.join("tree_state.json"));
+        assert_eq!(Config::default().log_file_path(), Some(state_dir.join("ferret.log")));
+        assert_eq!(Config::default().database_path(), temp_dir.path().join("ledger.db"));
+        assert_eq!(config.filter_state_path(), PathBuf::from("/from/cli.db").with_file_name("filter_state.json"));
+
+        TEST_ENV.with(|env| env.borrow_mut().clear());
+        assert!(Config::config_file_path(None).ends_with("ferret/config.toml"));
+        assert_eq!(Config::data_dir(), dirs::data_local_dir().map(|d| d.join("ferret")));
+        let expected_state = dirs::state_dir().map(|d| d.join("ferret")).or_else(Config::data_dir);
+        assert_eq!(Config::state_dir(), expected_state);
+    }
+
     #[test]
     fn test_config_save_load() {
         let temp_dir = TempDir::new().unwrap();
@@ -375,6 +1628,24 @@ mod tests {
         assert_eq!(config.retention_days, loaded.retention_days);
     }
 
+    #[test]
+    fn test_append_ignore_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let pattern = dir_ignore_pattern(Path::new("/home/me/Downloads/[junk]/"));
+        assert_eq!(pattern, "/home/me/Downloads/[[]junk[]]/**");
+
+        // A missing file starts from the defaults
+        assert!(Config::append_ignore_pattern(&config_path, &pattern).unwrap());
+        assert!(!Config::append_ignore_pattern(&config_path, &pattern).unwrap());
+
+        let loaded = Config::load_from_file(&config_path).unwrap();
+        assert_eq!(loaded.ignore_patterns.len(), default_ignore_patterns().len() + 1);
+        let matcher = loaded.build_ignore_matcher().unwrap();
+        assert!(matcher.is_match("/home/me/Downloads/[junk]/a/b.zip"));
+        assert!(!matcher.is_match("/home/me/Downloads/j/b.zip"));
+    }
+
     #[test]
     fn test_ignore_matcher() {
         let config = Config::default();
@@ -386,6 +1657,112 @@ mod tests {
         assert!(!config.should_ignore(Path::new("/project/src/main.rs"), &matcher));
     }
 
+    #[test]
+    fn test_watch_entries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let camera = dir.path().join("Camera");
+        std::fs::create_dir(&camera).unwrap();
+
+        let toml_str = format!(
+            r#"
+            watch_paths = ["{root}"]
+            ignore_patterns = ["**/*.tmp"]
+            min_size_bytes = 5
+            max_depth = 2
+
+            [[watch]]
+            path = "{camera}"
+            min_size_bytes = 1000
+
+            [[watch]]
+            path = "{root}"
+            ignore_patterns = []
+            "#,
+            root = dir.path().display(),
+            camera = camera.display(),
+        );
+        let config: Config = toml::from_str(&toml_str).unwrap();
+
+        // A path listed in both places is only watched once
+        assert_eq!(config.expanded_watch_paths(), vec![dir.path().to_path_buf(), camera.clone()]);
+
+        let rules = config.build_watch_rules().unwrap();
+        let camera_rules = &rules[&camera.canonicalize().unwrap()];
+        assert_eq!(camera_rules.min_size, 1000);
+        assert_eq!(camera_rules.max_depth, Some(2));
+        assert!(camera_rules.ignore_matcher.is_match("/x/a.tmp"));
+
+        let root_rules = &rules[&dir.path().canonicalize().unwrap()];
+        assert_eq!(root_rules.min_size, 5);
+        assert!(!root_rules.ignore_matcher.is_match("/x/a.tmp"));
+
+        let bad = Config {
+            watch: vec![WatchConfig {
+                path: camera,
+                ignore_patterns: Some(vec!["[".to_string()]),
+                min_size_bytes: None,
+                max_depth: None,
+            }],
+            ..Config::default()
+        };
+        assert!(bad.build_watch_rules().is_err());
+    }
+
+    #[test]
+    fn test_type_rules() {
+        let config: Config = toml::from_str(
+            r#"
+            [[rules]]
+            path_glob = "/home/me/Screenshots/**"
+            extension = "PNG"
+            file_type = "document"
+
+            [[rules]]
+            path_glob = "/home/me/Screenshots/**"
+            file_type = "archive"
+            "#,
+        )
+        .unwrap();
+        let rules = config.build_type_rules().unwrap();
+
+        assert_eq!(
+            rules.classify(Path::new("/home/me/Screenshots/shot.png")),
+            Some(FileType::Document)
+        );
+        assert_eq!(
+            rules.classify(Path::new("/home/me/Screenshots/clip.mp4")),
+            Some(FileType::Archive)
+        );
+        assert_eq!(rules.classify(Path::new("/home/me/Pictures/shot.png")), None);
+
+        let bad = Config {
+            rules: vec![TypeRule {
+                path_glob: "**".to_string(),
+                extension: None,
+                file_type: "spreadsheet".to_string(),
+            }],
+            ..Config::default()
+        };
+        assert!(bad.build_type_rules().is_err());
+    }
+
+    #[test]
+    fn test_view_settings() {
+        let mut config = Config {
+            default_view_mode: "tree".to_string(),
+            list_columns: vec!["path".to_string(), "tags".to_string()],
+            ..Config::default()
+        };
+        assert_eq!(config.view_mode().unwrap(), ViewMode::TreeView);
+        assert_eq!(config.columns().unwrap(), vec![ListColumn::Path, ListColumn::Tags]);
+
+        config.list_columns.clear();
+        assert_eq!(config.columns().unwrap(), ListColumn::defaults());
+
+        config.list_columns = vec!["owner".to_string()];
+        assert!(config.columns().is_err());
+    }
+
     #[test]
     fn test_cli_overrides() {
         let config = Config::default();
@@ -413,4 +1790,213 @@ mod tests {
         // This may pass if there are valid watch paths, but the log level validation should catch it
         // For a complete test, we'd need to ensure the validation logic is correct
     }
+
+    #[test]
+    fn test_parse_error_points_at_key() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+
+        std::fs::write(&path, "log_level = \"info\"\nretention_dayz = 30\n").unwrap();
+        let err = Config::load_from_file(&path).unwrap_err().to_string();
+        assert!(err.contains("line 2, column 1 (`retention_dayz`)"), "{}", err);
+        assert!(err.contains("unknown field `retention_dayz`"), "{}", err);
+        assert!(!err.contains("expected one of"), "{}", err);
+
+        std::fs::write(&path, "[[watch]]\npath = \"/tmp\"\nmax_depth = \"deep\"\n").unwrap();
+        let err = Config::load_from_file(&path).unwrap_err().to_string();
+        assert!(err.contains("line 3, column 13 (`watch.max_depth`)"), "{}", err);
+
+        std::fs::write(&path, "[notifications]\nenabeld = true\n").unwrap();
+        let err = Config::load_from_file(&path).unwrap_err().to_string();
+        assert!(err.contains("(`notifications.enabeld`)"), "{}", err);
+    }
+
+    #[test]
+    fn test_config_problems_lists_all() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            watch_paths: vec![dir.path().to_path_buf()],
+            log_level: "loud".to_string(),
+            debounce_ms: 0,
+            page_size: 5,
+            ..Config::default()
+        };
+
+        let problems = config_problems(&config);
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        let err = validate_config(&config).unwrap_err().to_string();
+        assert!(err.contains("log level") && err.contains("debounce_ms") && err.contains("page_size"));
+    }
+
+    #[test]
+    fn test_validate_timing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            watch_paths: vec![dir.path().to_path_buf()],
+            ..Config::default()
+        };
+        assert!(validate_config(&config).is_ok());
+
+        let slow_share = Config { debounce_ms: 5_000, ..config.clone() };
+        assert!(validate_config(&slow_share).is_ok());
+
+        let err = validate_config(&Config { debounce_ms: 0, ..config.clone() }).unwrap_err();
+        assert!(err.to_string().contains("debounce_ms"));
+        assert!(validate_config(&Config { max_batch_size: 0, ..config.clone() }).is_err());
+        assert!(validate_config(&Config { ui_batch_delay_ms: 60_000, ..config.clone() }).is_err());
+        assert!(validate_config(&Config { auto_refresh_secs: 0, ..config.clone() }).is_ok());
+        assert!(validate_config(&Config { auto_refresh_secs: 7_200, ..config.clone() }).is_err());
+        assert!(validate_config(&Config { stability_check_ms: Some(2_000), ..config.clone() }).is_ok());
+        assert!(validate_config(&Config { stability_check_ms: Some(0), ..config.clone() }).is_err());
+        assert!(validate_config(&Config { health_check_ms: 0, ..config.clone() }).is_ok());
+        assert!(validate_config(&Config { health_check_ms: 10, ..config.clone() }).is_err());
+        assert!(validate_config(&Config { busy_timeout_ms: 0, ..config.clone() }).is_err());
+        assert!(validate_config(&Config { page_size: 5, ..config }).is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0").unwrap(), 0);
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("500K").unwrap(), 500 * 1024);
+        assert_eq!(parse_size("10MB").unwrap(), 10_000_000);
+        assert_eq!(parse_size("10 mib").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(parse_size("1.5GiB").unwrap(), 3 * 512 * 1024 * 1024);
+        assert_eq!(parse_size("0.5kb").unwrap(), 500);
+        assert_eq!(parse_size("2B").unwrap(), 2);
+
+        let err = parse_size("10XB").unwrap_err();
+        assert!(err.to_string().contains("KiB"));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("-5").is_err());
+        assert!(parse_size("1.2.3M").is_err());
+        assert!(parse_size("99999999T").is_err());
+    }
+
+    #[test]
+    fn test_sizes_with_units_in_config() {
+        let config: Config = toml::from_str(
+            r#"
+            min_size_bytes = "10MB"
+            max_hash_size_bytes = 4096
+
+            [notifications]
+            min_size_bytes = "1GiB"
+
+            [[watch]]
+            path = "/tmp/camera"
+            min_size_bytes = "1K"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.min_size_bytes, 10_000_000);
+        assert_eq!(config.max_hash_size_bytes, 4096);
+        assert_eq!(config.notifications.min_size_bytes, 1 << 30);
+        assert_eq!(config.watch[0].min_size_bytes, Some(1024));
+
+        assert!(toml::from_str::<Config>("min_size_bytes = \"ten\"").is_err());
+    }
+
+
+    #[test]
+    fn test_quotas() {
+        let config: Config = toml::from_str(
+            r#"
+            [[quota]]
+            dir = "/srv/downloads"
+            max_bytes = "10G"
+
+            [[quota]]
+            dir = "*"
+            file_type = "media"
+            max_count = 500
+            "#,
+        )
+        .unwrap();
+
+        let quotas = config.build_quotas().unwrap();
+        assert_eq!(quotas[0].dir, Some(PathBuf::from("/srv/downloads")));
+        assert_eq!(quotas[0].max_bytes, Some(10 << 30));
+        assert_eq!(quotas[0].max_count, None);
+        assert_eq!(quotas[1].dir, None);
+        assert_eq!(quotas[1].file_type, Some(FileType::Media));
+        assert_eq!(quotas[1].max_count, Some(500));
+
+        // A quota without a limit is a mistake
+        let config: Config = toml::from_str("[[quota]]\ndir = \"*\"").unwrap();
+        assert!(config.build_quotas().is_err());
+    }
+
+    #[test]
+    fn test_categories() {
+        let config: Config = toml::from_str(
+            r#"
+            [[categories]]
+            name = "ebook"
+            label = "Book"
+            extensions = ["epub", ".MOBI"]
+
+            [[categories]]
+            name = "disk-image"
+            label = "Disk"
+            extensions = ["iso", "dmg"]
+            "#,
+        )
+        .unwrap();
+        let categories = config.build_categories().unwrap();
+        assert_eq!(categories.classify(Path::new("/dl/novel.epub")), Some("ebook"));
+        assert_eq!(categories.classify(Path::new("/dl/Novel.mobi")), Some("ebook"));
+        assert_eq!(categories.classify(Path::new("/dl/ubuntu.iso")), Some("disk-image"));
+        assert_eq!(categories.classify(Path::new("/dl/report.pdf")), None);
+        assert_eq!(categories.label("disk-image"), Some("Disk"));
+        assert_eq!(categories.entries().len(), 2);
+
+        let mut clash = config.clone();
+        clash.categories[1].extensions.push("epub".to_string());
+        let err = clash.build_categories().unwrap_err();
+        assert!(err.to_string().contains("epub"));
+
+        let mut duplicate = config;
+        duplicate.categories[1].name = "EBook".to_string();
+        assert!(duplicate.build_categories().is_err());
+    }
+
+    #[test]
+    fn test_openers() {
+        let config: Config = toml::from_str(
+            r#"
+            [openers]
+            md = "code --wait {path}"
+            ".DOC" = "libreoffice"
+            media = "mpv '--title=Ferret: {path}'"
+            "#,
+        )
+        .unwrap();
+        let openers = config.build_openers().unwrap();
+        let command = |path: &str, file_type| openers.command_for(Path::new(path), file_type);
+
+        assert_eq!(
+            command("/dl/My Notes.MD", FileType::Code),
+            Some(vec!["code".to_string(), "--wait".to_string(), "/dl/My Notes.MD".to_string()])
+        );
+        // "doc" is an extension here, not the alias for document
+        assert_eq!(
+            command("/dl/old.doc", FileType::Document),
+            Some(vec!["libreoffice".to_string(), "/dl/old.doc".to_string()])
+        );
+        assert_eq!(command("/dl/new.pdf", FileType::Document), None);
+        assert_eq!(
+            command("/dl/clip.mp4", FileType::Media),
+            Some(vec!["mpv".to_string(), "--title=Ferret: /dl/clip.mp4".to_string()])
+        );
+
+        let mut unbalanced = config.clone();
+        unbalanced.openers.insert("pdf".to_string(), "zathura '{path}".to_string());
+        assert!(unbalanced.build_openers().unwrap_err().to_string().contains("pdf"));
+
+        let mut empty = config;
+        empty.openers.insert("txt".to_string(), "  ".to_string());
+        assert!(empty.build_openers().is_err());
+    }
+
 }
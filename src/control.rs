@@ -0,0 +1,268 @@
+//! Control socket for headless mode
+//!
+//! A running `ferret watch --headless --socket <path>` accepts one command per
+//! line and answers each with a single line of JSON:
+//!
+//! - `STATS` - same data as `ferret stats --json`
+//! - `LIST [n]` - the `n` most recent events (default 10)
+//! - `RELOAD` - re-read the config file and re-apply watch paths
+//!
+//! On Unix this is a Unix domain socket. Elsewhere the socket argument is a
+//! port number and the server listens on `127.0.0.1`.
+
+use crate::store::Store;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Default number of events returned by `LIST`
+const DEFAULT_LIST_LIMIT: usize = 10;
+
+/// How long a `RELOAD` waits for the watch loop to answer
+const RELOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Requests that must be handled by the watch loop (which owns the watcher)
+pub enum ControlRequest {
+    /// Reload config and re-apply watch paths; replies with the watched paths
+    Reload(Sender<std::result::Result<Vec<PathBuf>, String>>),
+}
+
+/// Handle to a running control server
+///
+/// Dropping it removes the Unix socket file.
+pub struct ControlServer {
+    #[cfg(unix)]
+    socket_path: PathBuf,
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&self.socket_path);
+    }
+}
+
+/// Start listening on `addr` in a background thread
+///
+/// Returns the server handle and a receiver for requests the caller must
+/// service (see [`ControlRequest`]).
+#[cfg(unix)]
+pub fn spawn(addr: &Path, store: Store) -> Result<(ControlServer, Receiver<ControlRequest>)> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if addr.exists() {
+        if UnixStream::connect(addr).is_ok() {
+            anyhow::bail!("Control socket already in use: {}", addr.display());
+        }
+        // Stale socket left by a previous run
+        std::fs::remove_file(addr)
+            .with_context(|| format!("Failed to remove stale socket: {}", addr.display()))?;
+    }
+
+    let listener = UnixListener::bind(addr)
+        .with_context(|| format!("Failed to bind control socket: {}", addr.display()))?;
+    info!("Control socket listening on {}", addr.display());
+
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("ferret-control".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream.and_then(|s| Ok((s.try_clone()?, s))) {
+                    Ok((reader, writer)) => serve(reader, writer, store.clone(), tx.clone()),
+                    Err(e) => warn!("Control socket accept failed: {}", e),
+                }
+            }
+        })
+        .context("Failed to spawn control socket thread")?;
+
+    Ok((
+        ControlServer {
+            socket_path: addr.to_path_buf(),
+        },
+        rx,
+    ))
+}
+
+/// Start listening on `127.0.0.1:<port>` in a background thread
+#[cfg(not(unix))]
+pub fn spawn(addr: &Path, store: Store) -> Result<(ControlServer, Receiver<ControlRequest>)> {
+    use std::net::TcpListener;
+
+    let port: u16 = addr
+        .to_str()
+        .and_then(|s| s.parse().ok())
+        .with_context(|| format!("Expected a port number for --socket, got: {}", addr.display()))?;
+
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .with_context(|| format!("Failed to bind control port: {}", port))?;
+    info!("Control server listening on 127.0.0.1:{}", port);
+
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("ferret-control".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream.and_then(|s| Ok((s.try_clone()?, s))) {
+                    Ok((reader, writer)) => serve(reader, writer, store.clone(), tx.clone()),
+                    Err(e) => warn!("Control connection failed: {}", e),
+                }
+            }
+        })
+        .context("Failed to spawn control server thread")?;
+
+    Ok((ControlServer {}, rx))
+}
+
+/// Serve one connection on its own thread
+fn serve<R, W>(reader: R, writer: W, store: Store, requests: Sender<ControlRequest>)
+where
+    R: std::io::Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let spawned = thread::Builder::new()
+        .name("ferret-control-conn".to_string())
+        .spawn(move || {
+            if let Err(e) = handle_connection(BufReader::new(reader), writer, &store, &requests) {
+                debug!("Control connection closed: {}", e);
+            }
+        });
+
+    if let Err(e) = spawned {
+        warn!("Failed to spawn control connection thread: {}", e);
+    }
+}
+
+/// Answer each command line with one JSON line until the client disconnects
+fn handle_connection(
+    reader: impl BufRead,
+    mut writer: impl Write,
+    store: &Store,
+    requests: &Sender<ControlRequest>,
+) -> Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = handle_command(&line, store, requests);
+        writeln!(writer, "{}", response)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Run a single command and build its JSON response
+fn handle_command(line: &str, store: &Store, requests: &Sender<ControlRequest>) -> Value {
+    let mut parts = line.split_whitespace();
+    let command = parts.next().unwrap_or("").to_uppercase();
+
+    let result = match command.as_str() {
+        "STATS" => store
+            .get_stats()
+            .and_then(|stats| Ok(serde_json::to_value(stats)?)),
+        "LIST" => match parts.next().map(str::parse::<usize>) {
+            Some(Err(_)) => Err(anyhow::anyhow!("LIST expects a number")),
+            limit => store
+                .get_recent_events(limit.and_then(|l| l.ok()).unwrap_or(DEFAULT_LIST_LIMIT))
+                .and_then(|events| Ok(serde_json::to_value(events)?)),
+        },
+        "RELOAD" => reload(requests),
+        _ => Err(anyhow::anyhow!("Unknown command: {}", line.trim())),
+    };
+
+    result.unwrap_or_else(|e| json!({ "error": e.to_string() }))
+}
+
+/// Ask the watch loop to reload and wait for its answer
+fn reload(requests: &Sender<ControlRequest>) -> Result<Value> {
+    let (reply_tx, reply_rx) = mpsc::channel();
+    requests
+        .send(ControlRequest::Reload(reply_tx))
+        .map_err(|_| anyhow::anyhow!("Watcher is not running"))?;
+
+    let watch_paths = reply_rx
+        .recv_timeout(RELOAD_TIMEOUT)
+        .context("Timed out waiting for reload")?
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+    Ok(json!({ "ok": true, "watch_paths": watch_paths }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FileEvent;
+
+    fn store_with_events(count: usize) -> Store {
+        let store = Store::in_memory().unwrap();
+        for i in 0..count {
+            let event = FileEvent::from_path(PathBuf::from(format!("/tmp/file{}.txt", i)));
+            store.insert_event(&event).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_handle_command() {
+        let store = store_with_events(3);
+        let (tx, _rx) = mpsc::channel();
+
+        let stats = handle_command("stats", &store, &tx);
+        assert_eq!(stats["total_count"], 3);
+
+        let list = handle_command("LIST 2", &store, &tx);
+        assert_eq!(list.as_array().unwrap().len(), 2);
+
+        let bad = handle_command("LIST many", &store, &tx);
+        assert!(bad["error"].is_string());
+
+        let unknown = handle_command("DANCE", &store, &tx);
+        assert_eq!(unknown["error"], "Unknown command: DANCE");
+    }
+
+    #[test]
+    fn test_reload_round_trip() {
+        let store = store_with_events(0);
+        let (tx, rx) = mpsc::channel();
+
+        let responder = thread::spawn(move || {
+            if let Ok(ControlRequest::Reload(reply)) = rx.recv() {
+                reply.send(Ok(vec![PathBuf::from("/watched")])).unwrap();
+            }
+        });
+
+        let response = handle_command("RELOAD", &store, &tx);
+        responder.join().unwrap();
+        assert_eq!(response, json!({ "ok": true, "watch_paths": ["/watched"] }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket() {
+        use std::os::unix::net::UnixStream;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("ferret.sock");
+        let (server, _rx) = spawn(&socket_path, store_with_events(1)).unwrap();
+
+        let stream = UnixStream::connect(&socket_path).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        writeln!(writer, "STATS").unwrap();
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        let stats: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(stats["total_count"], 1);
+
+        drop(server);
+        assert!(!socket_path.exists());
+    }
+}
@@ -6,24 +6,30 @@
 //! making it easy to track downloads, artifacts, and file flow.
 
 mod config;
+mod control;
 mod models;
+mod notifier;
+mod quota;
+mod sink;
 mod store;
+mod template;
 mod tui;
 mod watcher;
 
 use anyhow::{Context, Result};
-use chrono::{Duration, Utc};
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
 use std::path::PathBuf;
 use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
 
-use crate::config::{default_config_toml, validate_config, CliOverrides, Config};
-use crate::models::{EventFilter, FileType};
+use crate::config::{config_problems, default_config_toml, parse_size, validate_config, CliOverrides, Config};
+use crate::models::{EventFilter, FileType, HistogramBucket};
 use crate::store::Store;
+use crate::template::{OutputTemplate, Status, StatusTemplate};
 use crate::tui::{app::run_tui, App};
-use crate::watcher::FileWatcher;
+use crate::watcher::{FileWatcher, IngestOutcome, Ingester};
 
 /// 🦡 Ferret - A curious file tracker
 #[derive(Parser)]
@@ -35,6 +41,10 @@ struct Cli {
     #[arg(short, long, global = true)]
     config: Option<PathBuf>,
 
+    /// Use a separate ledger: ~/.config/ferret/NAME.toml and NAME.db in the data directory
+    #[arg(long, global = true, value_name = "NAME", value_parser = config::parse_profile)]
+    profile: Option<String>,
+
     /// Log level (error, warn, info, debug, trace)
     #[arg(long, global = true, default_value = "info")]
     log_level: String,
@@ -55,6 +65,41 @@ enum Commands {
         #[arg(long)]
         headless: bool,
 
+        /// In headless mode, print one JSON object per event to stdout
+        #[arg(long, requires = "headless")]
+        json_lines: bool,
+
+        /// In headless mode, accept STATS/LIST/RELOAD commands on this Unix socket
+        /// (a localhost TCP port on Windows)
+        #[arg(long, value_name = "PATH", requires = "headless")]
+        socket: Option<PathBuf>,
+
+        /// In headless mode, print one plain line per event and a summary of
+        /// today's files every few seconds, like `tail -f`
+        #[arg(long, requires = "headless", conflicts_with = "json_lines")]
+        follow: bool,
+
+        /// With --follow, print the summary every SECS seconds (0 = never;
+        /// default: follow_summary_secs from the config)
+        #[arg(long, value_name = "SECS", requires = "follow")]
+        summary_secs: Option<u64>,
+
+        /// Draw the TUI in the bottom LINES of the terminal instead of the
+        /// alternate screen, keeping scrollback intact
+        #[arg(
+            long,
+            value_name = "LINES",
+            num_args = 0..=1,
+            default_missing_value = "20",
+            value_parser = clap::value_parser!(u16).range(10..),
+            conflicts_with = "headless"
+        )]
+        inline: Option<u16>,
+
+        /// Start the TUI unfiltered instead of restoring the last session's filter
+        #[arg(long, conflicts_with = "headless")]
+        fresh: bool,
+
         /// Don't use default paths from config
         #[arg(long)]
         no_defaults: bool,
@@ -62,33 +107,91 @@ enum Commands {
 
     /// List recent file events
     List {
-        /// Time window (e.g., "1h", "24h", "7d", "30d")
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        /// Maximum number of entries to show (0 = all)
+        #[arg(short = 'n', long, default_value = "50")]
+        limit: usize,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Output as CSV (RFC 4180)
+        #[arg(long, conflicts_with = "json")]
+        csv: bool,
+
+        /// Print each event with a template, e.g. '{size}\t{path}'
+        /// (placeholders: time, size, size_bytes, type, path, filename, dir, tags, notes)
+        #[arg(long, value_name = "TEMPLATE", value_parser = OutputTemplate::parse, conflicts_with_all = ["json", "csv"])]
+        format: Option<OutputTemplate>,
+
+        /// Print only the number of matching events (ignores --limit)
+        #[arg(long, conflicts_with_all = ["csv", "format"])]
+        count: bool,
+    },
+
+    /// Add or remove tags on every event matching a filter
+    Tag {
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        /// Tags to add (comma-separated)
+        #[arg(long, value_delimiter = ',', required_unless_present = "remove")]
+        add: Vec<String>,
+
+        /// Tags to remove (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        remove: Vec<String>,
+
+        /// Show what would change without writing to the database
         #[arg(long)]
-        since: Option<String>,
+        dry_run: bool,
+    },
+
+    /// Delete every file matching a filter and remove it from the ledger
+    Delete {
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
 
-        /// Minimum file size in bytes
+        /// Allow deleting every tracked file when no filter is given
         #[arg(long)]
-        size_min: Option<u64>,
+        all: bool,
 
-        /// Maximum file size in bytes
+        /// Delete permanently instead of moving to the trash
         #[arg(long)]
-        size_max: Option<u64>,
+        permanent: bool,
+    },
+
+    /// Open the most recent file matching a filter
+    Open {
+        #[command(flatten)]
+        filter: FilterArgs,
 
-        /// Filter by file type
-        #[arg(long, value_name = "TYPE")]
-        r#type: Option<String>,
+        /// Which match to open, 0 being the most recent
+        #[arg(long, default_value = "0")]
+        index: usize,
 
-        /// Filter by path substring
+        /// Open the containing folder instead of the file
         #[arg(long)]
-        path: Option<String>,
+        folder: bool,
+    },
 
-        /// Maximum number of entries to show
-        #[arg(short = 'n', long, default_value = "50")]
-        limit: usize,
+    /// Print a one-line summary of today's files, e.g. for a shell prompt
+    Status {
+        /// Line template with {icon}, {count}, {size}, {size_bytes}, {last},
+        /// {last_path} and {ago} placeholders
+        #[arg(long, value_name = "TEMPLATE", value_parser = StatusTemplate::parse)]
+        format: Option<StatusTemplate>,
 
-        /// Output as JSON
+        /// Leave out the ferret emoji
         #[arg(long)]
-        json: bool,
+        no_emoji: bool,
     },
 
     /// Show statistics about tracked files
@@ -96,6 +199,92 @@ enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Activity histogram bucket size (hour, day, week)
+        #[arg(long)]
+        bucket: Option<HistogramBucket>,
+
+        /// Show how much of each `[[quota]]` is used
+        #[arg(long, conflicts_with = "bucket")]
+        quota: bool,
+
+        /// Show new files per weekday and hour of day (local time)
+        #[arg(long, conflicts_with_all = ["bucket", "quota"])]
+        heatmap: bool,
+
+        /// Break down files by exact extension instead of type
+        #[arg(long = "by-ext", conflicts_with_all = ["bucket", "quota", "heatmap"])]
+        by_ext: bool,
+
+        /// Print the totals as Prometheus metrics (for a textfile collector)
+        #[arg(long, conflicts_with_all = ["json", "bucket", "quota", "heatmap", "by_ext"])]
+        prometheus: bool,
+    },
+
+    /// Find tracked files with identical contents
+    Dupes {
+        /// Match files by size and name, ignoring copy suffixes like " (1)",
+        /// instead of by content hash
+        #[arg(long)]
+        heuristic: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Export the whole ledger as a JSON array
+    Export {
+        /// File to write (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Import events from a JSON array produced by `export`
+    Import {
+        /// File to read
+        #[arg(short, long)]
+        input: PathBuf,
+    },
+
+    /// Delete events older than `retention_days` from the ledger
+    Prune {
+        /// Only report what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Record file paths read one per line, e.g. from `find` or `fd`
+    Ingest {
+        /// Read paths from standard input
+        #[arg(long, required_unless_present = "file", conflicts_with = "file")]
+        stdin: bool,
+
+        /// Read paths from this file (or named pipe)
+        #[arg(long, value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+
+    /// Compact, optimize or check the database
+    ///
+    /// Stop any running `ferret watch` first: vacuuming needs the database
+    /// to itself.
+    Maintenance {
+        /// Rebuild the database file to reclaim unused space
+        #[arg(long, required_unless_present_any = ["analyze", "check"])]
+        vacuum: bool,
+
+        /// Refresh the query planner's statistics
+        #[arg(long)]
+        analyze: bool,
+
+        /// Check the database for corruption
+        #[arg(long)]
+        check: bool,
     },
 
     /// Show or create configuration
@@ -111,9 +300,163 @@ enum Commands {
         /// Show example configuration
         #[arg(long)]
         example: bool,
+
+        /// Print the configuration as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Show the configuration as used, with `~` expanded and default paths filled in
+        #[arg(long)]
+        resolved: bool,
+
+        /// Check the config file and list every problem; exits nonzero if any
+        #[arg(long)]
+        validate: bool,
     },
 }
 
+/// Event filter flags shared by `list`, `tag` and `open`
+#[derive(Args)]
+struct FilterArgs {
+    /// Only events newer than this: a duration ago ("30m", "24h", "7d", "2w") or a date ("2024-01-15")
+    #[arg(long, conflicts_with = "after")]
+    since: Option<String>,
+
+    /// Only events older than this: a duration ago or a date
+    #[arg(long, conflicts_with = "before")]
+    until: Option<String>,
+
+    /// Only events from this date (YYYY-MM-DD) onwards
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    after: Option<DateTime<Utc>>,
+
+    /// Only events before this date (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    before: Option<DateTime<Utc>>,
+
+    /// Only events since local midnight today
+    #[arg(long, conflicts_with_all = ["since", "after", "yesterday"])]
+    today: bool,
+
+    /// Only events from the previous calendar day (local time)
+    #[arg(long, conflicts_with_all = ["since", "until", "after", "before"])]
+    yesterday: bool,
+
+    /// Minimum file size: bytes or with a unit ("500K", "10MB", "1.5GiB")
+    #[arg(long, value_name = "SIZE")]
+    size_min: Option<String>,
+
+    /// Maximum file size: bytes or with a unit ("500K", "10MB", "1.5GiB")
+    #[arg(long, value_name = "SIZE")]
+    size_max: Option<String>,
+
+    /// Filter by file type (comma-separated to match any of several)
+    #[arg(long, value_name = "TYPE")]
+    r#type: Option<String>,
+
+    /// Filter by path substring
+    #[arg(long)]
+    path: Option<String>,
+
+    /// Only executables that have the execute bit set
+    #[arg(long)]
+    exec_only: bool,
+
+    /// Filter by a category from `[[categories]]`
+    #[arg(long, value_name = "NAME")]
+    category: Option<String>,
+
+    /// Filter by download origin URL substring (where the browser recorded one)
+    #[arg(long, value_name = "TEXT")]
+    from: Option<String>,
+
+    /// Also show files deleted through Ferret (kept with `retain_deleted_in_ledger`)
+    #[arg(long)]
+    include_deleted: bool,
+}
+
+impl FilterArgs {
+    /// Whether no filter flag was given, so every event would match
+    fn is_empty(&self) -> bool {
+        self.since.is_none()
+            && self.until.is_none()
+            && self.after.is_none()
+            && self.before.is_none()
+            && !self.today
+            && !self.yesterday
+            && self.size_min.is_none()
+            && self.size_max.is_none()
+            && self.r#type.is_none()
+            && self.path.is_none()
+            && !self.exec_only
+            && self.category.is_none()
+            && self.from.is_none()
+    }
+
+    /// Build an `EventFilter` from the command-line flags
+    fn to_filter(&self) -> Result<EventFilter> {
+        let mut filter = EventFilter::new();
+
+        if let Some(since) = self.since.as_deref().map(parse_time_bound).transpose()?.or(self.after) {
+            filter = filter.with_since(since);
+        }
+
+        if let Some(until) = self.until.as_deref().map(parse_time_bound).transpose()?.or(self.before) {
+            filter = filter.with_until(until);
+        }
+
+        // Calendar days, unlike the rolling windows of --since
+        let today = Local::now().date_naive();
+        if self.today {
+            filter = filter.with_since(local_midnight(today)?);
+        }
+        if self.yesterday {
+            filter = filter
+                .with_since(local_midnight(today - Duration::days(1))?)
+                .with_until(local_midnight(today)?);
+        }
+
+        if let Some(min) = &self.size_min {
+            filter = filter.with_min_size(parse_size(min).context("Invalid --size-min")?);
+        }
+
+        if let Some(max) = &self.size_max {
+            filter = filter.with_max_size(parse_size(max).context("Invalid --size-max")?);
+        }
+
+        if let Some(types) = &self.r#type {
+            for type_str in types.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                let ft = type_str
+                    .parse::<FileType>()
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+                filter = filter.with_type(ft);
+            }
+        }
+
+        if let Some(path) = &self.path {
+            filter = filter.with_path_contains(path);
+        }
+
+        if self.exec_only {
+            filter = filter.with_exec_only();
+        }
+
+        if let Some(category) = &self.category {
+            filter = filter.with_category(category);
+        }
+
+        if let Some(origin) = &self.from {
+            filter = filter.with_origin_contains(origin);
+        }
+
+        if self.include_deleted {
+            filter = filter.with_include_deleted();
+        }
+
+        Ok(filter)
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -124,17 +467,42 @@ fn main() -> Result<()> {
         _ => false,
     };
 
-    // Initialize logging (disabled in TUI mode to prevent screen corruption)
-    setup_logging(&cli.log_level, tui_mode)?;
+    // JSON, metrics and --follow output own stdout, so logs go to stderr
+    let json_output = matches!(
+        &cli.command,
+        Some(Commands::Watch { json_lines: true, .. })
+            | Some(Commands::Watch { follow: true, .. })
+            | Some(Commands::Config { json: true, .. })
+            | Some(Commands::Stats { prometheus: true, .. })
+    );
+
+    // Initialize logging (disabled in TUI mode to prevent screen corruption,
+    // and for `status`, whose single line ends up in a shell prompt)
+    let silent = tui_mode || matches!(&cli.command, Some(Commands::Status { .. }));
+    setup_logging(&cli.log_level, silent, json_output)?;
+
+    // Validation reports every problem itself rather than failing on the first
+    if let Some(Commands::Config { validate: true, .. }) = &cli.command {
+        return cmd_validate_config(cli.config.as_deref(), cli.profile.as_deref());
+    }
 
     // Load configuration
     let config = load_config(&cli)?;
+    config.size_format()?.set_global();
 
     // Execute command
+    let config_path = cli.config.clone();
+    let profile = cli.profile.clone();
     match cli.command {
         Some(Commands::Watch {
             watch,
             headless,
+            json_lines,
+            socket,
+            follow,
+            summary_secs,
+            inline,
+            fresh,
             no_defaults,
         }) => {
             let overrides = CliOverrides {
@@ -142,37 +510,114 @@ fn main() -> Result<()> {
                 no_defaults,
                 ..Default::default()
             };
-            cmd_watch(config.with_cli_overrides(overrides), headless)
+            let reload_overrides = overrides.clone();
+            let reload = std::rc::Rc::new(move || {
+                Ok(load_config_file(config_path.as_deref(), profile.as_deref())?
+                    .with_cli_overrides(reload_overrides.clone()))
+            });
+            let mut config = config.with_cli_overrides(overrides);
+            if let Some(secs) = summary_secs {
+                config.follow_summary_secs = secs;
+            }
+            cmd_watch(
+                config,
+                headless,
+                HeadlessOptions { json_lines, socket, follow },
+                TuiOptions { inline, fresh },
+                cli.config.clone(),
+                reload,
+            )
         }
         Some(Commands::List {
-            since,
-            size_min,
-            size_max,
-            r#type,
-            path,
+            filter,
             limit,
             json,
-        }) => cmd_list(config, since, size_min, size_max, r#type, path, limit, json),
-        Some(Commands::Stats { json }) => cmd_stats(config, json),
+            csv,
+            format,
+            count,
+        }) => {
+            if count {
+                return cmd_count(config, &filter, json);
+            }
+            let format = if let Some(template) = format {
+                ListFormat::Template(template)
+            } else if json {
+                ListFormat::Json
+            } else if csv {
+                ListFormat::Csv
+            } else {
+                ListFormat::Table
+            };
+            cmd_list(config, &filter, limit, format)
+        }
+        Some(Commands::Tag {
+            filter,
+            add,
+            remove,
+            dry_run,
+        }) => cmd_tag(config, &filter, &add, &remove, dry_run),
+        Some(Commands::Delete {
+            filter,
+            yes,
+            all,
+            permanent,
+        }) => cmd_delete(config, &filter, yes, all, permanent),
+        Some(Commands::Open {
+            filter,
+            index,
+            folder,
+        }) => cmd_open(config, &filter, index, folder),
+        Some(Commands::Stats { json, bucket, quota, heatmap, by_ext, prometheus }) => {
+            if prometheus {
+                cmd_stats_prometheus(config)
+            } else if quota {
+                cmd_quota(config, json)
+            } else if by_ext {
+                cmd_stats_by_extension(config, json)
+            } else {
+                cmd_stats(config, json, bucket, heatmap)
+            }
+        }
+        Some(Commands::Status { format, no_emoji }) => cmd_status(config, format, no_emoji),
+        Some(Commands::Dupes { heuristic, json }) => cmd_dupes(config, heuristic, json),
+        Some(Commands::Export { output }) => cmd_export(config, output),
+        Some(Commands::Import { input }) => cmd_import(config, &input),
+        Some(Commands::Ingest { stdin: _, file }) => cmd_ingest(config, file.as_deref()),
+        Some(Commands::Prune { dry_run, yes }) => cmd_prune(config, dry_run, yes),
+        Some(Commands::Maintenance {
+            vacuum,
+            analyze,
+            check,
+        }) => cmd_maintenance(config, vacuum, analyze, check),
         Some(Commands::Config {
             path,
             init,
             example,
-        }) => cmd_config(path, init, example),
+            json,
+            resolved,
+            validate: _,
+        }) => cmd_config(config, path, init, example, json, resolved),
         None => {
             // Default to watch command with TUI
-            cmd_watch(config, false)
+            cmd_watch(
+                config,
+                false,
+                HeadlessOptions::default(),
+                TuiOptions::default(),
+                config_path.clone(),
+                std::rc::Rc::new(move || load_config_file(config_path.as_deref(), profile.as_deref())),
+            )
         }
     }
 }
 
 /// Setup logging with tracing
-fn setup_logging(level: &str, tui_mode: bool) -> Result<()> {
+fn setup_logging(level: &str, silent: bool, to_stderr: bool) -> Result<()> {
     let filter = EnvFilter::try_from_default_env()
         .or_else(|_| EnvFilter::try_new(level))
         .unwrap_or_else(|_| EnvFilter::new("info"));
 
-    if tui_mode {
+    if silent {
         // In TUI mode, disable logging to avoid interfering with the display
         // Logs would corrupt the TUI rendering
         tracing_subscriber::fmt()
@@ -180,6 +625,13 @@ fn setup_logging(level: &str, tui_mode: bool) -> Result<()> {
             .with_target(false)
             .without_time()
             .init();
+    } else if to_stderr {
+        tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_target(false)
+            .without_time()
+            .with_writer(std::io::stderr)
+            .init();
     } else {
         tracing_subscriber::fmt()
             .with_env_filter(filter)
@@ -193,20 +645,58 @@ fn setup_logging(level: &str, tui_mode: bool) -> Result<()> {
 
 /// Load configuration from file
 fn load_config(cli: &Cli) -> Result<Config> {
-    let config = if let Some(config_path) = &cli.config {
-        Config::load_from_file(config_path)?
+    load_config_file(cli.config.as_deref(), cli.profile.as_deref())
+}
+
+/// Load configuration from an explicit path, or the default location of
+/// `profile`
+///
+/// `$FERRET_CONFIG` replaces the default location and `$FERRET_DB` is
+/// applied on top of the file, unless a profile is used; CLI overrides are
+/// applied by the caller.
+fn load_config_file(path: Option<&std::path::Path>, profile: Option<&str>) -> Result<Config> {
+    let config = if let Some(config_path) = path {
+        Config::load_from_file(config_path)?.with_profile(profile)
     } else {
-        Config::load().unwrap_or_else(|e| {
-            warn!("Failed to load config: {}. Using defaults.", e);
-            Config::default()
-        })
+        // Defaults are only used when there is no file; a broken one is an error
+        Config::load(profile)?
     };
 
-    Ok(config)
+    Ok(config.with_env_overrides())
+}
+
+/// What `watch --headless` prints and listens on
+#[derive(Debug, Default)]
+struct HeadlessOptions {
+    /// Print one JSON object per event
+    json_lines: bool,
+    /// Control socket to accept commands on
+    socket: Option<PathBuf>,
+    /// Print plain event lines and periodic summaries
+    follow: bool,
+}
+
+/// How `watch` runs the TUI (unused in headless mode)
+#[derive(Debug, Default)]
+struct TuiOptions {
+    /// Height of an inline viewport, or `None` for the alternate screen
+    inline: Option<u16>,
+    /// Start unfiltered instead of restoring the last filter
+    fresh: bool,
 }
 
 /// Watch command - start monitoring with optional TUI
-fn cmd_watch(config: Config, headless: bool) -> Result<()> {
+///
+/// `reload` re-reads the configuration for the control socket's `RELOAD`,
+/// SIGHUP and the TUI's `R`, and `config_path` is the `--config` file, if given.
+fn cmd_watch(
+    config: Config,
+    headless: bool,
+    output: HeadlessOptions,
+    tui: TuiOptions,
+    config_path: Option<PathBuf>,
+    reload: std::rc::Rc<dyn Fn() -> Result<Config>>,
+) -> Result<()> {
     // Validate configuration
     validate_config(&config)?;
 
@@ -215,7 +705,7 @@ fn cmd_watch(config: Config, headless: bool) -> Result<()> {
 
     // Initialize database
     let db_path = config.database_path();
-    let store = Store::new(&db_path).context("Failed to initialize database")?;
+    let store = Store::new(&db_path, config.busy_timeout()).context("Failed to initialize database")?;
 
     // Run retention cleanup
     if config.retention_days > 0 {
@@ -229,20 +719,105 @@ fn cmd_watch(config: Config, headless: bool) -> Result<()> {
     let (mut watcher, watcher_rx) =
         FileWatcher::new(&config, Some(store.clone())).context("Failed to create file watcher")?;
 
-    // Start watching paths
-    watcher
+    // Start watching paths (ones that can't be watched are reported and skipped)
+    let summary = watcher
         .watch_paths(&watch_paths)
         .context("Failed to start watching paths")?;
+    if summary.failed > 0 {
+        warn!(
+            "Watching {} of {} directories",
+            summary.watched,
+            watch_paths.len()
+        );
+    }
 
     if headless {
         // Headless mode - just log events
         info!("Running in headless mode. Press Ctrl+C to stop.");
 
+        let control = match &output.socket {
+            Some(path) => Some(control::spawn(path, store.clone())?),
+            None => None,
+        };
+
+        // SIGHUP reloads the config, as for the control socket's RELOAD
+        let hangup = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        #[cfg(unix)]
+        signal_hook::flag::register(signal_hook::consts::SIGHUP, hangup.clone())
+            .context("Failed to install SIGHUP handler")?;
+
+        // Processing stats are logged every minute while they change
+        let stats_interval = std::time::Duration::from_secs(60);
+        let mut last_stats_log = std::time::Instant::now();
+        let mut last_stats = watcher.stats();
+
+        // With --follow, a summary of today's files is printed between events
+        let summary_interval = (output.follow && config.follow_summary_secs > 0)
+            .then(|| std::time::Duration::from_secs(config.follow_summary_secs));
+        let mut last_summary = std::time::Instant::now();
+        let mut recorded_since_summary = 0u64;
+
         loop {
-            match watcher_rx.recv() {
-                Ok(msg) => match msg {
+            if let Some(interval) = summary_interval {
+                let elapsed = last_summary.elapsed();
+                if elapsed >= interval {
+                    let (count, size) = store.count_since(local_midnight(Local::now().date_naive())?)?;
+                    let per_minute = recorded_since_summary as f64 * 60.0 / elapsed.as_secs_f64();
+                    print_follow_line(&follow_summary(Local::now(), count, size, per_minute))?;
+                    last_summary = std::time::Instant::now();
+                    recorded_since_summary = 0;
+                }
+            }
+
+            if last_stats_log.elapsed() >= stats_interval {
+                last_stats_log = std::time::Instant::now();
+                let stats = watcher.stats();
+                if stats != last_stats {
+                    info!("Watcher stats: {}", stats.summary());
+                    last_stats = stats;
+                }
+            }
+
+            // Service control socket requests between watcher messages
+            if let Some((_, control_rx)) = &control {
+                while let Ok(request) = control_rx.try_recv() {
+                    match request {
+                        control::ControlRequest::Reload(reply) => {
+                            let _ = reply.send(reload_watcher(&mut watcher, &*reload));
+                        }
+                    }
+                }
+            }
+
+            if hangup.swap(false, std::sync::atomic::Ordering::Relaxed) {
+                info!("Received SIGHUP, reloading config");
+                let _ = reload_watcher(&mut watcher, &*reload);
+            }
+
+            let msg = match watcher_rx.recv_timeout(std::time::Duration::from_millis(100)) {
+                Ok(msg) => msg,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(e) => {
+                    error!("Channel error: {}", e);
+                    break;
+                }
+            };
+
+            if let watcher::WatcherMessage::NewFile(event)
+            | watcher::WatcherMessage::MovedFile(event) = &msg
+            {
+                store.insert_event(event)?;
+                recorded_since_summary += 1;
+            }
+
+            let follow_line = if output.follow { follow_event(Local::now(), &msg) } else { None };
+            if output.json_lines {
+                print_json_line(&msg)?;
+            } else if let Some(line) = follow_line {
+                print_follow_line(&line)?;
+            } else {
+                match &msg {
                     watcher::WatcherMessage::NewFile(event) => {
-                        store.insert_event(&event)?;
                         info!(
                             "New file: {} ({}, {})",
                             event.path.display(),
@@ -251,51 +826,192 @@ fn cmd_watch(config: Config, headless: bool) -> Result<()> {
                         );
                     }
                     watcher::WatcherMessage::MovedFile(event) => {
-                        store.insert_event(&event)?;
                         info!("Moved file: {} ({})", event.path.display(), event.file_type);
                     }
+                    watcher::WatcherMessage::DeletedFile(path) => {
+                        info!("Deleted file: {}", path.display());
+                    }
                     watcher::WatcherMessage::Error(err) => {
                         error!("Watcher error: {}", err);
                     }
+                    watcher::WatcherMessage::Warning(message) => {
+                        warn!("{}", message);
+                    }
+                    watcher::WatcherMessage::PathRestored(path) => {
+                        info!("Watch path back online: {}", path.display());
+                    }
                     watcher::WatcherMessage::Started => {
                         info!("Watcher started");
                     }
                     watcher::WatcherMessage::Stopped => {
                         info!("Watcher stopped");
-                        break;
                     }
-                },
-                Err(e) => {
-                    error!("Channel error: {}", e);
-                    break;
                 }
             }
+
+            if let watcher::WatcherMessage::Stopped = msg {
+                break;
+            }
         }
+
+        watcher.stop()?;
     } else {
         // TUI mode
-        let mut app = App::new(store)?;
-        app.set_watched_dirs(watch_paths.len());
+        let mut app = App::new(store, &config)?;
+        app.set_watched_dirs(watcher.watched_paths(), watch_paths.len());
+        app.set_trash_on_delete(config.trash_instead_of_delete);
+        app.set_pause_handle(watcher.pause_handle());
+        app.set_offline_handle(watcher.offline_handle());
+        app.set_quota_handle(watcher.quota_handle());
+        app.set_stats_handle(watcher.stats_handle());
+        if let Some(path) = config_path {
+            app.set_config_path(path);
+        }
+        if tui.fresh {
+            app.clear_filters()?;
+        }
+
+        // `R` reloads through the watcher, which is stopped once the TUI exits
+        let watcher = std::rc::Rc::new(std::cell::RefCell::new(watcher));
+        let reload_target = watcher.clone();
+        app.set_reload_handle(Box::new(move || {
+            let config = reload()?;
+            let watched = apply_watch_paths(&mut reload_target.borrow_mut(), &config)?;
+            Ok((watched, config.expanded_watch_paths().len()))
+        }));
+
+        run_tui(app, Some(watcher_rx), tui.inline)?;
+        watcher.borrow_mut().stop()?;
+    }
+
+    Ok(())
+}
+
+/// Re-validate a reloaded config and make the watcher match it
+fn apply_watch_paths(watcher: &mut FileWatcher, config: &Config) -> Result<Vec<PathBuf>> {
+    validate_config(config)?;
+    watcher.reload(config)
+}
+
+/// Re-read the config and apply it to the watcher, logging the outcome
+fn reload_watcher(
+    watcher: &mut FileWatcher,
+    reload: &dyn Fn() -> Result<Config>,
+) -> std::result::Result<Vec<PathBuf>, String> {
+    let result = reload()
+        .and_then(|config| apply_watch_paths(watcher, &config))
+        .map_err(|e| format!("{:#}", e));
+    match &result {
+        Ok(paths) => info!("Reloaded config, watching {} paths", paths.len()),
+        Err(e) => warn!("Reload failed: {}", e),
+    }
+    result
+}
+
+/// One record in the headless `--json-lines` stream
+///
+/// The `event` field says which kind of record it is; file records carry the
+/// serialized `FileEvent` fields alongside it.
+#[derive(serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum JsonLine<'a> {
+    NewFile(&'a models::FileEvent),
+    MovedFile(&'a models::FileEvent),
+    DeletedFile {
+        #[serde(serialize_with = "models::serialize_path_lossy")]
+        path: &'a std::path::Path,
+    },
+    Error { message: &'a str },
+    Warning { message: &'a str },
+    PathRestored {
+        #[serde(serialize_with = "models::serialize_path_lossy")]
+        path: &'a std::path::Path,
+    },
+    Started,
+    Stopped,
+}
+
+impl<'a> From<&'a watcher::WatcherMessage> for JsonLine<'a> {
+    fn from(msg: &'a watcher::WatcherMessage) -> Self {
+        match msg {
+            watcher::WatcherMessage::NewFile(event) => JsonLine::NewFile(event),
+            watcher::WatcherMessage::MovedFile(event) => JsonLine::MovedFile(event),
+            watcher::WatcherMessage::DeletedFile(path) => JsonLine::DeletedFile { path },
+            watcher::WatcherMessage::Error(message) => JsonLine::Error { message },
+            watcher::WatcherMessage::Warning(message) => JsonLine::Warning { message },
+            watcher::WatcherMessage::PathRestored(path) => JsonLine::PathRestored { path },
+            watcher::WatcherMessage::Started => JsonLine::Started,
+            watcher::WatcherMessage::Stopped => JsonLine::Stopped,
+        }
+    }
+}
+
+/// Print a watcher message as a single JSON line and flush immediately
+fn print_json_line(msg: &watcher::WatcherMessage) -> Result<()> {
+    use std::io::Write;
 
-        run_tui(app, Some(watcher_rx))?;
+    let line = serde_json::to_string(&JsonLine::from(msg))?;
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", line)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// One `--follow` line for a file event, or `None` for messages that are logged
+fn follow_event(now: DateTime<Local>, msg: &watcher::WatcherMessage) -> Option<String> {
+    let time = now.format("%H:%M:%S");
+    match msg {
+        watcher::WatcherMessage::NewFile(event) => Some(format!(
+            "{}  + {} ({}, {})",
+            time,
+            event.path.display(),
+            event.file_type,
+            event.size_display()
+        )),
+        watcher::WatcherMessage::MovedFile(event) => {
+            Some(format!("{}  > {} ({})", time, event.path.display(), event.file_type))
+        }
+        watcher::WatcherMessage::DeletedFile(path) => Some(format!("{}  - {}", time, path.display())),
+        _ => None,
     }
+}
+
+/// The periodic `--follow` summary: files recorded today and the recent rate
+fn follow_summary(now: DateTime<Local>, count: u64, size: u64, per_minute: f64) -> String {
+    format!(
+        "{}  == today: {} files, {} | {:.1} files/min",
+        now.format("%H:%M:%S"),
+        count,
+        models::format_size(size),
+        per_minute
+    )
+}
 
-    // Cleanup
-    watcher.stop()?;
+/// Print a `--follow` line and flush, so it shows up at once when piped
+fn print_follow_line(line: &str) -> Result<()> {
+    use std::io::Write;
 
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", line)?;
+    stdout.flush()?;
     Ok(())
 }
 
+/// Output format for the list command
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ListFormat {
+    /// Human-readable table
+    Table,
+    /// Pretty-printed JSON array
+    Json,
+    /// RFC 4180 CSV with a header row
+    Csv,
+    /// One line per event from a `--format` template
+    Template(OutputTemplate),
+}
+
 /// List command - show recent events
-fn cmd_list(
-    config: Config,
-    since: Option<String>,
-    size_min: Option<u64>,
-    size_max: Option<u64>,
-    file_type: Option<String>,
-    path_filter: Option<String>,
-    limit: usize,
-    json: bool,
-) -> Result<()> {
+fn cmd_list(config: Config, filter_args: &FilterArgs, limit: usize, format: ListFormat) -> Result<()> {
     let db_path = config.database_path();
 
     if !db_path.exists() {
@@ -303,74 +1019,170 @@ fn cmd_list(
         return Ok(());
     }
 
-    let store = Store::new(&db_path)?;
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    let filter = filter_args.to_filter()?.with_limit(limit);
 
-    // Build filter
-    let mut filter = EventFilter::new().with_limit(limit);
+    // Rows are written as they're read, so even `--limit 0` on a huge
+    // ledger runs in constant memory
+    let mut writer = ListWriter::new(std::io::stdout().lock(), &format);
+    store.query_events_streaming(&filter, |event| writer.write(&event))?;
+    writer.finish()
+}
 
-    if let Some(since_str) = since {
-        let duration = parse_duration(&since_str)?;
-        filter = filter.with_since(Utc::now() - duration);
-    }
+/// List command with `--count` - print how many events match
+fn cmd_count(config: Config, filter_args: &FilterArgs, json: bool) -> Result<()> {
+    let filter = filter_args.to_filter()?;
+    let db_path = config.database_path();
+    let count = if db_path.exists() {
+        Store::new(&db_path, config.busy_timeout())?.count_filtered_events(&filter)?
+    } else {
+        0
+    };
 
-    if let Some(min) = size_min {
-        filter = filter.with_min_size(min);
+    if json {
+        println!("{}", serde_json::json!({ "count": count }));
+    } else {
+        println!("{}", count);
     }
+    Ok(())
+}
+
+/// Writes `list` output one event at a time
+struct ListWriter<'a, W: std::io::Write> {
+    out: W,
+    format: &'a ListFormat,
+    /// Events written so far
+    count: usize,
+}
 
-    if let Some(max) = size_max {
-        filter = filter.with_max_size(max);
+impl<'a, W: std::io::Write> ListWriter<'a, W> {
+    fn new(out: W, format: &'a ListFormat) -> Self {
+        Self { out, format, count: 0 }
     }
 
-    if let Some(type_str) = file_type {
-        let ft = type_str
-            .parse::<FileType>()
-            .map_err(|e| anyhow::anyhow!("{}", e))?;
-        filter = filter.with_type(ft);
+    /// Write one event, preceded by the header if it's the first
+    fn write(&mut self, event: &models::FileEvent) -> Result<()> {
+        let out = &mut self.out;
+        match self.format {
+            ListFormat::Table => {
+                if self.count == 0 {
+                    writeln!(
+                        out,
+                        "{:19} {:>10} {:6} {}",
+                        "TIME".bold(),
+                        "SIZE".bold(),
+                        "TYPE".bold(),
+                        "PATH".bold()
+                    )?;
+                    writeln!(out, "{}", "─".repeat(80))?;
+                }
+                let time = event
+                    .created_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S");
+                let size = event.size_display();
+                let file_type = format_file_type(event.file_type);
+                let path = event.path.to_string_lossy();
+                if event.removed {
+                    // Kept as history by retain_deleted_in_ledger (--include-deleted)
+                    let path = format!("{} (deleted)", path).dimmed();
+                    writeln!(out, "{:19} {:>10} {:6} {}", time, size, file_type, path)?;
+                } else {
+                    writeln!(out, "{:19} {:>10} {:6} {}", time, size, file_type, path)?;
+                }
+            }
+            ListFormat::Json => {
+                // Same layout as serde_json's pretty printer, one element at a time
+                write!(out, "{}", if self.count == 0 { "[\n" } else { ",\n" })?;
+                let json = serde_json::to_string_pretty(event)?;
+                for (i, line) in json.lines().enumerate() {
+                    if i > 0 {
+                        writeln!(out)?;
+                    }
+                    write!(out, "  {}", line)?;
+                }
+            }
+            ListFormat::Csv => {
+                if self.count == 0 {
+                    write_csv_header(out)?;
+                }
+                write_csv_row(out, event)?;
+            }
+            ListFormat::Template(template) => writeln!(out, "{}", template.render(event))?,
+        }
+        self.count += 1;
+        Ok(())
     }
 
-    if let Some(path) = path_filter {
-        filter = filter.with_path_contains(&path);
+    /// Close the output (or explain that nothing matched)
+    fn finish(mut self) -> Result<()> {
+        let out = &mut self.out;
+        match self.format {
+            ListFormat::Table if self.count == 0 => {
+                writeln!(out, "{}", "No matching events found.".yellow())?;
+            }
+            ListFormat::Json if self.count == 0 => writeln!(out, "[]")?,
+            ListFormat::Json => writeln!(out, "\n]")?,
+            ListFormat::Csv if self.count == 0 => write_csv_header(out)?,
+            _ => {}
+        }
+        out.flush()?;
+        Ok(())
     }
+}
 
-    let events = store.query_events(&filter)?;
+/// Tag command - add or remove tags on all matching events
+fn cmd_tag(
+    config: Config,
+    filter_args: &FilterArgs,
+    add: &[String],
+    remove: &[String],
+    dry_run: bool,
+) -> Result<()> {
+    let db_path = config.database_path();
 
-    if json {
-        let json_output = serde_json::to_string_pretty(&events)?;
-        println!("{}", json_output);
-    } else {
-        if events.is_empty() {
-            println!("{}", "No matching events found.".yellow());
-            return Ok(());
-        }
+    if !db_path.exists() {
+        println!("{}", "No database found. Run 'ferret watch' first.".yellow());
+        return Ok(());
+    }
 
-        // Print table header
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    let filter = filter_args.to_filter()?;
+    let total = store.count_filtered_events(&filter)?;
+    let events = store.query_events(&filter.with_limit(total))?;
+
+    if dry_run {
+        let mut changed = 0;
+        for event in &events {
+            if let Some(tags) = models::merge_tags(&event.tags, add, remove) {
+                changed += 1;
+                println!("{}  [{}] -> [{}]", event.path.display(), event.tags, tags);
+            }
+        }
         println!(
-            "{:19} {:>10} {:6} {}",
-            "TIME".bold(),
-            "SIZE".bold(),
-            "TYPE".bold(),
-            "PATH".bold()
+            "{}",
+            format!("Dry run: {} of {} matching events would change", changed, events.len()).yellow()
         );
-        println!("{}", "─".repeat(80));
+        return Ok(());
+    }
 
-        for event in events {
-            let time = event
-                .created_at
-                .with_timezone(&chrono::Local)
-                .format("%Y-%m-%d %H:%M:%S");
-            let size = event.size_display();
-            let file_type = format_file_type(event.file_type);
-            let path = event.path.to_string_lossy();
+    let ids: Vec<i64> = events.iter().filter_map(|e| e.id).collect();
+    let changed = store.update_tags_bulk(&ids, add, remove)?;
 
-            println!("{:19} {:>10} {:6} {}", time, size, file_type, path);
-        }
-    }
+    println!(
+        "{}",
+        format!("Updated tags on {} of {} matching events", changed, events.len()).green()
+    );
 
     Ok(())
 }
 
-/// Stats command - show statistics
-fn cmd_stats(config: Config, json: bool) -> Result<()> {
+/// Delete command - remove matching files from disk and the ledger
+fn cmd_delete(config: Config, filter_args: &FilterArgs, yes: bool, all: bool, permanent: bool) -> Result<()> {
+    if filter_args.is_empty() && !all {
+        anyhow::bail!("No filter given; pass --all to delete every tracked file");
+    }
+
     let db_path = config.database_path();
 
     if !db_path.exists() {
@@ -378,12 +1190,159 @@ fn cmd_stats(config: Config, json: bool) -> Result<()> {
         return Ok(());
     }
 
-    let store = Store::new(&db_path)?;
-    let stats = store.get_stats()?;
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    let filter = filter_args.to_filter()?;
+    let total = store.count_filtered_events(&filter)?;
+    if total == 0 {
+        println!("No matching events");
+        return Ok(());
+    }
+    let events = store.query_events(&filter.with_limit(total))?;
 
-    if json {
-        let json_output = serde_json::to_string_pretty(&stats)?;
-        println!("{}", json_output);
+    // Permanent deletion is implied when the trash is turned off in the config
+    let permanent = permanent || !config.trash_instead_of_delete;
+    if !yes {
+        for event in &events {
+            println!("{}", event.path.display());
+        }
+        let question = if permanent {
+            format!("Permanently delete {} files?", events.len())
+        } else {
+            format!("Move {} files to the trash?", events.len())
+        };
+        if !confirm(&question)? {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
+    let (removed, failed) = delete_events(&store, &events, permanent, config.retain_deleted_in_ledger)?;
+    let verb = if permanent { "Deleted" } else { "Moved to trash" };
+    if failed > 0 {
+        println!(
+            "{}",
+            format!("{}: {} files ({} could not be removed from disk and were kept)", verb, removed, failed).yellow()
+        );
+    } else {
+        println!("{}", format!("{}: {} files", verb, removed).green());
+    }
+
+    Ok(())
+}
+
+/// Ask a y/N question on the terminal; anything but "y"/"yes" is a no
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::Write;
+
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Remove events from the ledger and delete (or trash) their files
+///
+/// The file goes first; its ledger row is only removed once the file is
+/// gone, so a failed delete leaves the event in place. With `keep_history`,
+/// the ledger rows are retired instead of removed. Returns how many events
+/// were removed and how many files could not be removed from disk. Files
+/// that are already gone don't count as failures.
+fn delete_events(
+    store: &Store,
+    events: &[models::FileEvent],
+    permanent: bool,
+    keep_history: bool,
+) -> Result<(usize, usize)> {
+    let mut removed = 0;
+    let mut failed = 0;
+
+    for event in events {
+        let Some(id) = event.id else {
+            continue;
+        };
+
+        if event.path.exists() {
+            let result = if permanent {
+                std::fs::remove_file(&event.path).map_err(anyhow::Error::from)
+            } else {
+                trash::delete(&event.path).map_err(anyhow::Error::from)
+            };
+            if let Err(e) = result {
+                warn!("Failed to remove {}: {}", event.path.display(), e);
+                failed += 1;
+                continue;
+            }
+        }
+
+        store.remove_event(id, keep_history)?;
+        removed += 1;
+    }
+
+    Ok((removed, failed))
+}
+
+/// Write the CSV header row
+fn write_csv_header<W: std::io::Write>(out: &mut W) -> Result<()> {
+    write!(out, "time,size_bytes,type,path,tags,notes\r\n")?;
+    Ok(())
+}
+
+/// Write one event as a CSV row
+fn write_csv_row<W: std::io::Write>(out: &mut W, event: &models::FileEvent) -> Result<()> {
+    let fields = [
+        event.created_at.to_rfc3339(),
+        event.size_bytes.map(|s| s.to_string()).unwrap_or_default(),
+        event.file_type.as_str().to_string(),
+        event.path.to_string_lossy().to_string(),
+        event.tags.clone(),
+        event.notes.clone(),
+    ];
+    let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+    write!(out, "{}\r\n", row.join(","))?;
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Stats command - show statistics
+///
+/// With `--json --bucket`, only the activity histogram is printed.
+fn cmd_stats(config: Config, json: bool, bucket: Option<HistogramBucket>, heatmap: bool) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        println!("{}", "No database found. Run 'ferret watch' first.".yellow());
+        return Ok(());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+
+    if let (true, Some(bucket)) = (json, bucket) {
+        let since = Utc::now() - bucket.default_window();
+        let histogram = store.get_activity_histogram(bucket, since)?;
+        println!("{}", serde_json::to_string_pretty(&histogram)?);
+        return Ok(());
+    }
+
+    if json && heatmap {
+        let matrix = store.get_weekday_hour_matrix()?;
+        println!("{}", serde_json::to_string_pretty(&matrix)?);
+        return Ok(());
+    }
+
+    let stats = store.get_stats()?;
+
+    if json {
+        let json_output = serde_json::to_string_pretty(&stats)?;
+        println!("{}", json_output);
     } else {
         println!("{}", "🦡 Ferret Statistics".bold().cyan());
         println!("{}", "═".repeat(50));
@@ -412,15 +1371,23 @@ fn cmd_stats(config: Config, json: bool) -> Result<()> {
         if !stats.by_type.is_empty() {
             println!("\n{}", "By File Type".bold().yellow());
             for (file_type, count, size) in &stats.by_type {
-                let size_str = humansize::format_size(*size, humansize::BINARY);
+                let size_str = models::format_size(*size);
                 println!("  {:10} {:5} files ({:>10})", file_type, count, size_str);
             }
         }
 
+        if stats.by_size_bucket.iter().any(|b| b.count > 0) {
+            println!("\n{}", "By Size".bold().yellow());
+            for bucket in &stats.by_size_bucket {
+                let size_str = models::format_size(bucket.size);
+                println!("  {:10} {:5} files ({:>10})", bucket.label, bucket.count, size_str);
+            }
+        }
+
         if !stats.top_dirs.is_empty() {
             println!("\n{}", "Top Directories".bold().yellow());
             for (dir, count, size) in stats.top_dirs.iter().take(5) {
-                let size_str = humansize::format_size(*size, humansize::BINARY);
+                let size_str = models::format_size(*size);
                 let dir_name = dir
                     .file_name()
                     .and_then(|f| f.to_str())
@@ -428,26 +1395,695 @@ fn cmd_stats(config: Config, json: bool) -> Result<()> {
                 println!("  {:20} {:5} files ({:>10})", dir_name, count, size_str);
             }
         }
+
+        let bucket = bucket.unwrap_or_default();
+        let since = Utc::now() - bucket.default_window();
+        let histogram = store.get_activity_histogram(bucket, since)?;
+
+        println!("\n{}", "Activity".bold().yellow());
+        for line in histogram_lines(bucket, &histogram, 40) {
+            println!("  {}", line);
+        }
+
+        if heatmap {
+            println!("\n{}", "By Weekday and Hour".bold().yellow());
+            for line in heatmap_lines(&stats.weekday_hour) {
+                println!("  {}", line);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Stats command with --by-ext - files and size per extension
+fn cmd_stats_by_extension(config: Config, json: bool) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        println!("{}", "No database found. Run 'ferret watch' first.".yellow());
+        return Ok(());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    let by_ext = store.get_stats_by_extension()?;
+
+    if json {
+        let rows: Vec<_> = by_ext
+            .iter()
+            .map(|(ext, count, size)| serde_json::json!({ "extension": ext, "count": count, "size": size }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if by_ext.is_empty() {
+        println!("{}", "No files tracked yet.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "By Extension".bold().yellow());
+    for (ext, count, size) in &by_ext {
+        let label = if ext.is_empty() { "(none)".to_string() } else { format!(".{}", ext) };
+        println!("  {:10} {:5} files ({:>10})", label, count, models::format_size(*size));
+    }
+
+    Ok(())
+}
+
+/// Stats command with --prometheus - totals in the Prometheus text format
+fn cmd_stats_prometheus(config: Config) -> Result<()> {
+    let db_path = config.database_path();
+    if !db_path.exists() {
+        anyhow::bail!("No database found at {}. Run 'ferret watch' first.", db_path.display());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    print!("{}", prometheus_text(&store.get_stats()?));
+    Ok(())
+}
+
+/// Render `stats` in the Prometheus text exposition format
+///
+/// Every metric is a gauge since pruning lowers them; the output ends with
+/// the newline the format requires.
+fn prometheus_text(stats: &models::EventStats) -> String {
+    let mut out = String::new();
+    let mut family = |name: &str, help: &str, samples: Vec<(String, u64)>| {
+        out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n", name, help, name));
+        for (labels, value) in samples {
+            out.push_str(&format!("{}{} {}\n", name, labels, value));
+        }
+    };
+    let label = |key: &str, value: &str| format!("{{{}=\"{}\"}}", key, escape_label_value(value));
+
+    let by_type = |value: fn(&(FileType, u64, u64)) -> u64| {
+        stats
+            .by_type
+            .iter()
+            .map(|entry| (label("type", entry.0.as_str()), value(entry)))
+            .collect::<Vec<_>>()
+    };
+    let mut files = vec![(String::new(), stats.total_count)];
+    files.extend(by_type(|(_, count, _)| *count));
+    family("ferret_files_total", "Files tracked in the ledger, overall and by type.", files);
+    let mut bytes = vec![(String::new(), stats.total_size)];
+    bytes.extend(by_type(|(_, _, size)| *size));
+    family("ferret_bytes_total", "Total size of tracked files in bytes, overall and by type.", bytes);
+
+    let windows = [
+        ("24h", stats.count_24h, stats.size_24h),
+        ("7d", stats.count_7d, stats.size_7d),
+        ("30d", stats.count_30d, stats.size_30d),
+    ];
+    family(
+        "ferret_recent_files",
+        "Files recorded within the window.",
+        windows.iter().map(|(w, count, _)| (label("window", w), *count)).collect(),
+    );
+    family(
+        "ferret_recent_bytes",
+        "Bytes of files recorded within the window.",
+        windows.iter().map(|(w, _, size)| (label("window", w), *size)).collect(),
+    );
+
+    out
+}
+
+/// Escape a Prometheus label value: backslash, double quote and newline
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Shades of heatmap cells, from no files to the busiest hour
+const HEATMAP_SHADES: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+/// Render a weekday × hour matrix as a grid shaded relative to the busiest
+/// hour, with each weekday's total at the end of its row
+fn heatmap_lines(matrix: &[[u64; 24]; 7]) -> Vec<String> {
+    let max = matrix.iter().flatten().copied().max().unwrap_or(0);
+
+    let hours: String = (0..24).step_by(3).map(|h| format!("{:02}    ", h)).collect();
+    let mut lines = vec![format!("    {}", hours.trim_end())];
+
+    let days = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    for (day, row) in days.iter().zip(matrix) {
+        let cells: String = row
+            .iter()
+            .map(|&count| {
+                // Round up so any activity is at least the lightest shade
+                let level = if count == 0 { 0 } else { (count * 4).div_ceil(max) as usize };
+                let cell = HEATMAP_SHADES[level].repeat(2);
+                match level {
+                    0 => cell.dimmed(),
+                    1 | 2 => cell.green(),
+                    3 => cell.yellow(),
+                    _ => cell.red(),
+                }
+                .to_string()
+            })
+            .collect();
+        lines.push(format!("{} {} {}", day, cells, row.iter().sum::<u64>()));
+    }
+
+    lines
+}
+
+/// Render histogram buckets as horizontal bars scaled to the busiest bucket
+fn histogram_lines(
+    bucket: HistogramBucket,
+    histogram: &[models::ActivityBucket],
+    width: usize,
+) -> Vec<String> {
+    let max = histogram.iter().map(|b| b.count).max().unwrap_or(0);
+
+    histogram
+        .iter()
+        .map(|b| {
+            let len = if max == 0 {
+                0
+            } else {
+                // Round up so any activity shows at least one block
+                (b.count as usize * width).div_ceil(max as usize)
+            };
+            format!(
+                "{:11} {:<width$} {}",
+                bucket.label(b.bucket_start),
+                "█".repeat(len),
+                b.count,
+                width = width
+            )
+        })
+        .collect()
+}
+
+/// Open command - launch a matching file (or its folder) with the default app
+fn cmd_open(config: Config, filter_args: &FilterArgs, index: usize, folder: bool) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        anyhow::bail!("No database found. Run 'ferret watch' first.");
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    let target = open_target(&store, &filter_args.to_filter()?, index, folder)?;
+
+    open::that(&target).with_context(|| format!("Failed to open {}", target.display()))?;
+    println!("{}", format!("Opened {}", target.display()).green());
+
+    Ok(())
+}
+
+/// Path to open for the `index`-th most recent event matching `filter`
+///
+/// Fails if there is no such event or the path no longer exists.
+fn open_target(store: &Store, filter: &EventFilter, index: usize, folder: bool) -> Result<PathBuf> {
+    let filter = filter.clone().with_limit(index + 1);
+    let events = store.query_events(&filter)?;
+
+    let Some(event) = events.get(index) else {
+        anyhow::bail!(
+            "No matching file at index {} ({} matches)",
+            index,
+            events.len()
+        );
+    };
+
+    let target = if folder { &event.dir } else { &event.path };
+    if !target.exists() {
+        anyhow::bail!("{} no longer exists", target.display());
+    }
+
+    Ok(target.clone())
+}
+
+/// Status command - one line about today's files, for shell prompts
+///
+/// Kept to two indexed queries so it's cheap enough to run on every prompt.
+fn cmd_status(config: Config, format: Option<StatusTemplate>, no_emoji: bool) -> Result<()> {
+    let db_path = config.database_path();
+    if !db_path.exists() {
+        // Nothing to show yet; stay quiet rather than clutter the prompt
+        return Ok(());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    let (count, size) = store.count_since(local_midnight(Local::now().date_naive())?)?;
+    let last = store.get_recent_events(1)?.pop();
+
+    let template = match format {
+        Some(template) => template,
+        None if last.is_some() => StatusTemplate::parse(StatusTemplate::DEFAULT)?,
+        None => StatusTemplate::parse(StatusTemplate::DEFAULT_EMPTY)?,
+    };
+    let status = Status {
+        count,
+        size,
+        last,
+        now: Utc::now(),
+        emoji: !no_emoji,
+    };
+    println!("{}", template.render(&status));
+    Ok(())
+}
+
+/// Stats command with --quota - show how much of each quota is used
+fn cmd_quota(config: Config, json: bool) -> Result<()> {
+    let quotas = config.build_quotas()?;
+    if quotas.is_empty() {
+        println!("{}", "No quotas configured. Add [[quota]] entries to the config file.".yellow());
+        return Ok(());
+    }
+
+    let db_path = config.database_path();
+    if !db_path.exists() {
+        println!("{}", "No database found. Run 'ferret watch' first.".yellow());
+        return Ok(());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    let usages = quotas
+        .iter()
+        .map(|quota| quota.usage(&store))
+        .collect::<Result<Vec<_>>>()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&usages)?);
+        return Ok(());
+    }
+
+    println!("{}", "🦡 Ferret Quotas".bold().cyan());
+    println!("{}", "═".repeat(50));
+    for usage in &usages {
+        let label = if usage.exceeded() {
+            format!("{} (exceeded)", usage.label).red().bold()
+        } else {
+            usage.label.bold()
+        };
+        println!("\n{}", label);
+        if let (Some(max), Some(percent)) = (usage.max_bytes, usage.bytes_percent()) {
+            println!(
+                "  Size:  {} of {} ({:.0}%)",
+                models::format_size(usage.bytes),
+                models::format_size(max),
+                percent
+            );
+        }
+        if let (Some(max), Some(percent)) = (usage.max_count, usage.count_percent()) {
+            println!("  Files: {} of {} ({:.0}%)", usage.count, max, percent);
+        }
+    }
+
+    Ok(())
+}
+
+/// Dupes command - show clusters of files with identical content
+fn cmd_dupes(config: Config, heuristic: bool, json: bool) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        println!("{}", "No database found. Run 'ferret watch' first.".yellow());
+        return Ok(());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    if heuristic {
+        return print_name_duplicates(&store.find_name_size_duplicates()?, json);
+    }
+    let groups = store.find_duplicates()?;
+
+    if json {
+        let json_output = serde_json::to_string_pretty(&groups)?;
+        println!("{}", json_output);
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        println!("{}", "No duplicate files found.".yellow());
+        return Ok(());
+    }
+
+    let mut total_wasted = 0;
+    for group in &groups {
+        total_wasted += group.wasted_bytes();
+        println!(
+            "{} {} copies × {} ({} wasted)",
+            group.sha256[..12.min(group.sha256.len())].bold(),
+            group.events.len(),
+            models::format_size(group.size_bytes),
+            models::format_size(group.wasted_bytes()).red()
+        );
+        for event in &group.events {
+            println!("  {}", event.path.to_string_lossy());
+        }
+        println!();
+    }
+
+    println!("{}", "─".repeat(50));
+    println!(
+        "{} duplicate groups, {} wasted",
+        groups.len(),
+        models::format_size(total_wasted).bold()
+    );
+
+    Ok(())
+}
+
+/// Print the groups found by `dupes --heuristic`
+fn print_name_duplicates(groups: &[models::NameDuplicateGroup], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(groups)?);
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        println!("{}", "No likely duplicates found.".yellow());
+        return Ok(());
+    }
+
+    let mut total_wasted = 0;
+    for group in groups {
+        total_wasted += group.wasted_bytes();
+        println!(
+            "{} {} copies × {} ({} wasted)",
+            group.name.bold(),
+            group.events.len(),
+            models::format_size(group.size_bytes),
+            models::format_size(group.wasted_bytes()).red()
+        );
+        for event in &group.events {
+            println!("  {}", event.path.to_string_lossy());
+        }
+        println!();
+    }
+
+    println!("{}", "─".repeat(50));
+    println!(
+        "{} likely duplicate groups, {} wasted (matched by name and size; contents not compared)",
+        groups.len(),
+        models::format_size(total_wasted).bold()
+    );
+
+    Ok(())
+}
+
+/// Export command - write every event as a JSON array
+fn cmd_export(config: Config, output: Option<PathBuf>) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        println!("{}", "No database found. Run 'ferret watch' first.".yellow());
+        return Ok(());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+
+    match output {
+        Some(path) => {
+            let file = std::fs::File::create(&path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+            let count = write_export(&store, &mut std::io::BufWriter::new(file))?;
+            println!(
+                "{}",
+                format!("Exported {} events to {}", count, path.display()).green()
+            );
+        }
+        None => {
+            write_export(&store, &mut std::io::stdout().lock())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Stream all events from the store to `out` as a JSON array
+fn write_export(store: &Store, out: &mut impl std::io::Write) -> Result<usize> {
+    let mut count = 0;
+
+    write!(out, "[")?;
+    store.for_each_event(|event| {
+        if count > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "\n  ")?;
+        serde_json::to_writer(&mut *out, &event)?;
+        count += 1;
+        Ok(())
+    })?;
+    writeln!(out, "\n]")?;
+    out.flush()?;
+
+    Ok(count)
+}
+
+/// Outcome of an import
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ImportCounts {
+    /// Events whose path wasn't in the ledger yet
+    inserted: usize,
+    /// Events whose path was already tracked, left as they are
+    skipped: usize,
+    /// Records that didn't deserialize into a `FileEvent`
+    invalid: usize,
+}
+
+/// Import command - load events from an exported JSON file
+fn cmd_import(config: Config, input: &std::path::Path) -> Result<()> {
+    let content = std::fs::read_to_string(input)
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+
+    let store = Store::new(&config.database_path(), config.busy_timeout())?;
+    let counts = import_events(&store, &content)?;
+
+    println!(
+        "{}",
+        format!(
+            "Imported {} events ({} already tracked, {} invalid)",
+            counts.inserted, counts.skipped, counts.invalid
+        )
+        .green()
+    );
+
+    Ok(())
+}
+
+/// Prune command - preview or apply the retention cleanup `watch` runs on start
+fn cmd_prune(config: Config, dry_run: bool, yes: bool) -> Result<()> {
+    let days = config.retention_days;
+    if days == 0 {
+        println!("Retention is off (retention_days = 0); nothing to prune");
+        return Ok(());
+    }
+
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        println!("{}", "No database found. Run 'ferret watch' first.".yellow());
+        return Ok(());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+    let stats = store.count_old_events(days)?;
+    if stats.total_count == 0 {
+        println!("No events older than {} days", days);
+        return Ok(());
+    }
+
+    println!(
+        "{} events older than {} days ({})",
+        stats.total_count,
+        days,
+        models::format_size(stats.total_size)
+    );
+    for (file_type, count, size) in &stats.by_type {
+        println!("  {:10} {:5} files ({:>10})", file_type, count, models::format_size(*size));
+    }
+
+    if dry_run {
+        println!("{}", "Dry run: nothing was deleted".yellow());
+        return Ok(());
+    }
+    if !yes && !confirm("Remove them from the ledger? Files on disk are not touched.")? {
+        println!("Cancelled");
+        return Ok(());
+    }
+
+    let removed = store.cleanup_old_events(days)?;
+    println!("{}", format!("Removed {} events", removed).green());
+    Ok(())
+}
+
+/// Ingest command - record paths from stdin or a file, skipping the watcher
+fn cmd_ingest(config: Config, file: Option<&std::path::Path>) -> Result<()> {
+    let store = Store::new(&config.database_path(), config.busy_timeout())?;
+    let mut ingester = Ingester::new(&config)?;
+
+    let counts = match file {
+        Some(path) => {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            ingest_paths(&store, &mut ingester, std::io::BufReader::new(file))?
+        }
+        None => ingest_paths(&store, &mut ingester, std::io::stdin().lock())?,
+    };
+
+    println!(
+        "{}",
+        format!(
+            "Ingested {} files ({} already tracked, {} ignored, {} not found)",
+            counts.recorded, counts.already_tracked, counts.ignored, counts.not_found
+        )
+        .green()
+    );
+
+    Ok(())
+}
+
+/// Tally of [`IngestOutcome`]s
+#[derive(Debug, Default, PartialEq, Eq)]
+struct IngestCounts {
+    recorded: usize,
+    already_tracked: usize,
+    ignored: usize,
+    not_found: usize,
+}
+
+/// Ingest each non-empty line of `input` as a path
+///
+/// Lines are handled as they arrive, so a long-running pipeline is recorded
+/// as it goes.
+fn ingest_paths<R: std::io::BufRead>(store: &Store, ingester: &mut Ingester, input: R) -> Result<IngestCounts> {
+    let mut counts = IngestCounts::default();
+
+    for line in input.split(b'\n') {
+        let line = line.context("Failed to read paths")?;
+        let line = String::from_utf8_lossy(&line);
+        let path = line.trim_end_matches('\r');
+        if path.is_empty() {
+            continue;
+        }
+
+        match ingester.ingest(store, std::path::Path::new(path))? {
+            IngestOutcome::Recorded => counts.recorded += 1,
+            IngestOutcome::AlreadyTracked => counts.already_tracked += 1,
+            IngestOutcome::Ignored => counts.ignored += 1,
+            IngestOutcome::NotFound => {
+                warn!("Not a file: {}", path);
+                counts.not_found += 1;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+/// Maintenance command - integrity check, vacuum and analyze
+fn cmd_maintenance(config: Config, vacuum: bool, analyze: bool, check: bool) -> Result<()> {
+    let db_path = config.database_path();
+
+    if !db_path.exists() {
+        println!("{}", "No database found. Run 'ferret watch' first.".yellow());
+        return Ok(());
+    }
+
+    let store = Store::new(&db_path, config.busy_timeout())?;
+
+    // Check first so we never rebuild a damaged file
+    if check {
+        if store.integrity_check()? {
+            println!("{}", "Integrity check passed".green());
+        } else {
+            anyhow::bail!("Integrity check failed for {}", db_path.display());
+        }
+    }
+
+    if vacuum {
+        let before = database_size(&db_path);
+        store.vacuum()?;
+        let after = database_size(&db_path);
+        println!(
+            "{}",
+            format!(
+                "Vacuumed database: {} -> {}",
+                models::format_size(before),
+                models::format_size(after)
+            )
+            .green()
+        );
+    }
+
+    if analyze {
+        store.analyze()?;
+        println!("{}", "Updated query planner statistics".green());
     }
 
     Ok(())
 }
 
+/// On-disk size of the database, including its write-ahead log
+fn database_size(db_path: &std::path::Path) -> u64 {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    [db_path.as_os_str().to_owned(), wal]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Insert every valid record from a JSON array, deduplicating on path
+fn import_events(store: &Store, json: &str) -> Result<ImportCounts> {
+    let records: Vec<serde_json::Value> =
+        serde_json::from_str(json).context("Expected a JSON array of events")?;
+    let mut counts = ImportCounts::default();
+
+    for (idx, record) in records.into_iter().enumerate() {
+        let event: models::FileEvent = match serde_json::from_value(record) {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Skipping record {}: {}", idx, e);
+                counts.invalid += 1;
+                continue;
+            }
+        };
+
+        // Inserting would revive rows deleted from disk or kept as history
+        if store.path_exists(&event.path)? {
+            counts.skipped += 1;
+            continue;
+        }
+        store.insert_event(&event)?;
+        counts.inserted += 1;
+    }
+
+    Ok(counts)
+}
+
 /// Config command - show or manage configuration
-fn cmd_config(show_path: bool, init: bool, example: bool) -> Result<()> {
+fn cmd_config(
+    config: Config,
+    show_path: bool,
+    init: bool,
+    example: bool,
+    json: bool,
+    resolved: bool,
+) -> Result<()> {
     if example {
         println!("{}", default_config_toml());
         return Ok(());
     }
 
     if init {
-        let path = Config::ensure_default_config()?;
+        let path = Config::ensure_default_config(config.profile.as_deref())?;
         println!("{} {}", "Created config file:".green(), path.display());
         return Ok(());
     }
 
     if show_path {
-        let path = Config::config_file_path();
+        let path = Config::config_file_path(config.profile.as_deref());
         println!("{}", path.display());
         if !path.exists() {
             println!("{}", "(file does not exist yet)".yellow());
@@ -456,17 +2092,51 @@ fn cmd_config(show_path: bool, init: bool, example: bool) -> Result<()> {
     }
 
     // Default: show current config
-    let config = Config::load()?;
-    let config_toml = toml::to_string_pretty(&config)?;
-    println!("{}", config_toml);
+    let config = if resolved { config.resolved() } else { config };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&config)?);
+    } else {
+        println!("{}", toml::to_string_pretty(&config)?);
+    }
 
     Ok(())
 }
 
+/// Config validate command - check the config file and list every problem
+///
+/// Fails if the file can't be parsed or any setting is invalid, so it can
+/// gate a CI job. Without a config file, the defaults are checked.
+fn cmd_validate_config(path: Option<&std::path::Path>, profile: Option<&str>) -> Result<()> {
+    let path = path.map_or_else(|| Config::config_file_path(profile), std::path::Path::to_path_buf);
+    let config = if path.exists() {
+        Config::load_from_file(&path)?
+    } else {
+        println!("{} {}", "No config file at".yellow(), path.display());
+        Config::default()
+    };
+    let config = config.with_profile(profile);
+
+    let problems = config_problems(&config.with_env_overrides());
+    if problems.is_empty() {
+        println!("{} {}", "Config is valid:".green(), path.display());
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("{} {}", "✗".red(), problem);
+    }
+    anyhow::bail!("{} problem(s) in {}", problems.len(), path.display())
+}
+
 /// Parse duration string like "1h", "24h", "7d", "30d"
 fn parse_duration(s: &str) -> Result<Duration> {
     let s = s.trim().to_lowercase();
 
+    if let Some(minutes) = s.strip_suffix('m') {
+        let num: i64 = minutes.parse().context("Invalid minutes value")?;
+        return Ok(Duration::minutes(num));
+    }
+
     if let Some(hours) = s.strip_suffix('h') {
         let num: i64 = hours.parse().context("Invalid hours value")?;
         return Ok(Duration::hours(num));
@@ -477,11 +2147,38 @@ fn parse_duration(s: &str) -> Result<Duration> {
         return Ok(Duration::days(num));
     }
 
+    if let Some(weeks) = s.strip_suffix('w') {
+        let num: i64 = weeks.parse().context("Invalid weeks value")?;
+        return Ok(Duration::weeks(num));
+    }
+
     // Try parsing as hours if no suffix
-    let num: i64 = s.parse().context("Invalid duration format. Use '24h' or '7d'")?;
+    let num: i64 = s
+        .parse()
+        .context("Invalid duration format. Use '30m', '24h', '7d' or '2w'")?;
     Ok(Duration::hours(num))
 }
 
+/// Parse a `YYYY-MM-DD` date as local midnight
+fn parse_date(s: &str) -> Result<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}'. Use YYYY-MM-DD", s.trim()))?;
+    local_midnight(date)
+}
+
+/// The start of `date` in the local time zone
+fn local_midnight(date: NaiveDate) -> Result<DateTime<Utc>> {
+    models::local_midnight(date).with_context(|| format!("Local midnight does not exist on {}", date))
+}
+
+/// Parse a point in time given as a date or as a duration ago
+fn parse_time_bound(s: &str) -> Result<DateTime<Utc>> {
+    if NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").is_ok() {
+        return parse_date(s);
+    }
+    Ok(Utc::now() - parse_duration(s)?)
+}
+
 /// Format file type with color
 fn format_file_type(ft: FileType) -> String {
     match ft {
@@ -493,3 +2190,475 @@ fn format_file_type(ft: FileType) -> String {
         FileType::Other => ft.as_label().white().to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FileEvent;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_follow_lines() {
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+        let mut event = FileEvent::from_path(PathBuf::from("/tmp/setup.exe"));
+        event.size_bytes = Some(2048);
+        let line = follow_event(now, &watcher::WatcherMessage::NewFile(event)).unwrap();
+        assert_eq!(line, "09:30:00  + /tmp/setup.exe (executable, 2 KiB)");
+        let line = follow_event(now, &watcher::WatcherMessage::DeletedFile(PathBuf::from("/tmp/a")));
+        assert_eq!(line.as_deref(), Some("09:30:00  - /tmp/a"));
+
+        // Everything else is left to the log
+        assert!(follow_event(now, &watcher::WatcherMessage::Warning("slow".to_string())).is_none());
+
+        assert_eq!(
+            follow_summary(now, 42, 3 * 1024 * 1024, 2.5),
+            "09:30:00  == today: 42 files, 3 MiB | 2.5 files/min"
+        );
+    }
+
+    #[test]
+    fn test_json_line_records() {
+        let event = FileEvent::from_path(PathBuf::from("/tmp/setup.exe"));
+        let msg = watcher::WatcherMessage::NewFile(event);
+        let value = serde_json::to_value(JsonLine::from(&msg)).unwrap();
+        assert_eq!(value["event"], "new_file");
+        assert_eq!(value["path"], "/tmp/setup.exe");
+        assert_eq!(value["file_type"], "executable");
+
+        let msg = watcher::WatcherMessage::Error("boom".to_string());
+        let value = serde_json::to_value(JsonLine::from(&msg)).unwrap();
+        assert_eq!(value, serde_json::json!({"event": "error", "message": "boom"}));
+
+        let value = serde_json::to_value(JsonLine::from(&watcher::WatcherMessage::Started)).unwrap();
+        assert_eq!(value, serde_json::json!({"event": "started"}));
+    }
+
+    #[test]
+    fn test_histogram_lines() {
+        let start = Utc::now();
+        let histogram: Vec<models::ActivityBucket> = [0, 5, 10]
+            .iter()
+            .map(|&count| models::ActivityBucket {
+                bucket_start: start,
+                count,
+                size: 0,
+            })
+            .collect();
+
+        let lines = histogram_lines(HistogramBucket::Day, &histogram, 10);
+        assert_eq!(lines[0].matches('█').count(), 0);
+        assert_eq!(lines[1].matches('█').count(), 5);
+        assert_eq!(lines[2].matches('█').count(), 10);
+        assert!(lines[2].ends_with(" 10"));
+    }
+
+    #[test]
+    fn test_prometheus_text() {
+        let stats = models::EventStats {
+            total_count: 3,
+            total_size: 3072,
+            count_24h: 1,
+            size_24h: 1024,
+            by_type: vec![(FileType::Code, 2, 2048), (FileType::Media, 1, 1024)],
+            ..Default::default()
+        };
+
+        let text = prometheus_text(&stats);
+        assert!(text.ends_with('\n'));
+        assert!(text.contains("# HELP ferret_files_total "));
+        assert!(text.contains("# TYPE ferret_files_total gauge\nferret_files_total 3\n"));
+        assert!(text.contains("ferret_files_total{type=\"code\"} 2\n"));
+        assert!(text.contains("ferret_bytes_total{type=\"media\"} 1024\n"));
+        assert!(text.contains("ferret_recent_files{window=\"24h\"} 1\n"));
+        assert!(text.contains("ferret_recent_bytes{window=\"30d\"} 0\n"));
+        // Each family is declared once, before its samples
+        assert_eq!(text.matches("# TYPE ").count(), 4);
+
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_heatmap_lines() {
+        let mut matrix = [[0; 24]; 7];
+        matrix[0][9] = 8;
+        matrix[0][10] = 1;
+        matrix[6][23] = 4;
+
+        let lines = heatmap_lines(&matrix);
+        assert_eq!(lines.len(), 8);
+        assert!(lines[0].trim_start().starts_with("00"));
+        assert!(lines[1].starts_with("Mon "));
+        assert_eq!(lines[1].matches('█').count(), 2);
+        assert_eq!(lines[1].matches('░').count(), 2);
+        assert!(lines[1].ends_with(" 9"));
+        assert_eq!(lines[7].matches('▒').count(), 2);
+        assert_eq!(lines[3].matches('·').count(), 48);
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let source = Store::in_memory().unwrap();
+        let id = source
+            .insert_event(&FileEvent::from_path(PathBuf::from("/tmp/a.zip")))
+            .unwrap();
+        source.update_tags(id, "backup").unwrap();
+        source
+            .insert_event(&FileEvent::from_path(PathBuf::from("/tmp/b.pdf")))
+            .unwrap();
+
+        let mut out = Vec::new();
+        assert_eq!(write_export(&source, &mut out).unwrap(), 2);
+        let exported = String::from_utf8(out).unwrap();
+
+        let target = Store::in_memory().unwrap();
+        target
+            .insert_event(&FileEvent::from_path(PathBuf::from("/tmp/b.pdf")))
+            .unwrap();
+
+        let counts = import_events(&target, &exported).unwrap();
+        assert_eq!(
+            counts,
+            ImportCounts {
+                inserted: 1,
+                skipped: 1,
+                invalid: 0
+            }
+        );
+        let imported = target
+            .get_event_by_path(std::path::Path::new("/tmp/a.zip"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.tags, "backup");
+    }
+
+    #[test]
+    fn test_import_rejects_bad_records() {
+        let store = Store::in_memory().unwrap();
+        let counts = import_events(&store, r#"[{"path": 5}, {"nope": true}]"#).unwrap();
+        assert_eq!(counts.invalid, 2);
+        assert_eq!(store.count_events().unwrap(), 0);
+
+        assert!(import_events(&store, "{}").is_err());
+    }
+
+    #[test]
+    fn test_import_keeps_existing_rows() {
+        let store = Store::in_memory().unwrap();
+        let id = store.insert_event(&FileEvent::from_path(PathBuf::from("/tmp/dl/old.zip"))).unwrap();
+        store.remove_event(id, true).unwrap();
+        store.insert_event(&FileEvent::from_path(PathBuf::from("/tmp/dl/gone.iso"))).unwrap();
+        store.mark_deleted(std::path::Path::new("/tmp/dl/gone.iso")).unwrap();
+
+        let json = serde_json::to_string(&[
+            FileEvent::from_path(PathBuf::from("/tmp/dl/old.zip")),
+            FileEvent::from_path(PathBuf::from("/tmp/dl/gone.iso")),
+            FileEvent::from_path(PathBuf::from("/tmp/dl/new.pdf")),
+        ])
+        .unwrap();
+        let counts = import_events(&store, &json).unwrap();
+        assert_eq!(counts, ImportCounts { inserted: 1, skipped: 2, invalid: 0 });
+
+        // Still retired and still deleted from disk
+        assert!(store.get_event(id).unwrap().unwrap().removed);
+        assert!(!store.path_is_live(std::path::Path::new("/tmp/dl/gone.iso")).unwrap());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_duration("24h").unwrap(), Duration::hours(24));
+        assert_eq!(parse_duration("7D").unwrap(), Duration::days(7));
+        assert_eq!(parse_duration("2w").unwrap(), Duration::weeks(2));
+        assert_eq!(parse_duration("5").unwrap(), Duration::hours(5));
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_bounds() {
+        let date = parse_date("2024-01-15").unwrap();
+        assert_eq!(date.with_timezone(&Local).date_naive(), NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+        assert_eq!(parse_time_bound("2024-01-15").unwrap(), date);
+        assert!(parse_date("15/01/2024").is_err());
+
+        let two_days_ago = parse_time_bound("2d").unwrap();
+        let expected = Utc::now() - Duration::days(2);
+        assert!((expected - two_days_ago).num_seconds().abs() < 5);
+
+        let filter = list_filter(&["--since", "7d", "--until", "2d"]);
+        assert!(filter.since.unwrap() < filter.until.unwrap());
+
+        let filter = list_filter(&["--after", "2024-01-01", "--before", "2024-02-01"]);
+        assert_eq!(filter.since, Some(parse_date("2024-01-01").unwrap()));
+        assert_eq!(filter.until, Some(parse_date("2024-02-01").unwrap()));
+
+        assert!(Cli::try_parse_from(["ferret", "list", "--since", "7d", "--after", "2024-01-01"]).is_err());
+    }
+
+    #[test]
+    fn test_today_and_yesterday() {
+        let today = Local::now().date_naive();
+        let midnight = local_midnight(today).unwrap();
+
+        let filter = list_filter(&["--today"]);
+        assert_eq!(filter.since, Some(midnight));
+        assert_eq!(filter.until, None);
+
+        let filter = list_filter(&["--yesterday"]);
+        assert_eq!(filter.since, Some(local_midnight(today.pred_opt().unwrap()).unwrap()));
+        assert_eq!(filter.until, Some(midnight));
+
+        // --today can be narrowed further; --yesterday sets both bounds itself
+        let filter = list_filter(&["--today", "--until", "1h"]);
+        assert_eq!(filter.since, Some(midnight));
+        assert!(filter.until.is_some());
+        assert!(Cli::try_parse_from(["ferret", "list", "--today", "--since", "2d"]).is_err());
+        assert!(Cli::try_parse_from(["ferret", "list", "--yesterday", "--before", "2024-01-01"]).is_err());
+    }
+
+    /// Parse `ferret list <args>` and build its filter
+    fn list_filter(args: &[&str]) -> EventFilter {
+        let cli = Cli::try_parse_from(["ferret", "list"].iter().chain(args)).unwrap();
+        match cli.command {
+            Some(Commands::List { filter, .. }) => filter.to_filter().unwrap(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_open_target() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store = Store::in_memory().unwrap();
+
+        let old = temp_dir.path().join("old.pdf");
+        std::fs::write(&old, "old").unwrap();
+        let mut event = FileEvent::from_path(old.clone());
+        event.created_at = Utc::now() - Duration::hours(1);
+        store.insert_event(&event).unwrap();
+
+        let gone = temp_dir.path().join("gone.pdf");
+        store.insert_event(&FileEvent::from_path(gone.clone())).unwrap();
+
+        let filter = EventFilter::new().with_type(FileType::Document);
+        let err = open_target(&store, &filter, 0, false).unwrap_err();
+        assert!(err.to_string().contains("no longer exists"));
+
+        assert_eq!(open_target(&store, &filter, 1, false).unwrap(), old);
+        assert_eq!(
+            open_target(&store, &filter, 0, true).unwrap(),
+            temp_dir.path()
+        );
+        assert!(open_target(&store, &filter, 2, false).is_err());
+    }
+
+    #[test]
+    fn test_list_format_arg() {
+        let cli = Cli::try_parse_from(["ferret", "list", "--format", "{size}\\t{path}"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::List { format: Some(_), .. })));
+
+        assert!(Cli::try_parse_from(["ferret", "list", "--format", "{owner}"]).is_err());
+        assert!(Cli::try_parse_from(["ferret", "list", "--format", "{path}", "--csv"]).is_err());
+    }
+
+    #[test]
+    fn test_delete_events() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store = Store::in_memory().unwrap();
+        let kept = temp_dir.path().join("keep.txt");
+        let doomed = temp_dir.path().join("old.zip");
+        std::fs::write(&kept, b"keep").unwrap();
+        std::fs::write(&doomed, b"zip").unwrap();
+        store.insert_event(&FileEvent::from_path(kept.clone())).unwrap();
+        store.insert_event(&FileEvent::from_path(doomed.clone())).unwrap();
+        store.insert_event(&FileEvent::from_path(temp_dir.path().join("gone.zip"))).unwrap();
+
+        let filter = EventFilter::new().with_path_contains(".zip");
+        let events = store.query_events(&filter).unwrap();
+        assert_eq!(delete_events(&store, &events, true, false).unwrap(), (2, 0));
+        assert!(!doomed.exists());
+        assert!(kept.exists());
+        assert_eq!(store.count_filtered_events(&EventFilter::new()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_delete_events_keeps_row_when_file_survives() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store = Store::in_memory().unwrap();
+        // remove_file refuses directories, so this delete fails
+        let stubborn = temp_dir.path().join("folder.zip");
+        std::fs::create_dir(&stubborn).unwrap();
+        store.insert_event(&FileEvent::from_path(stubborn.clone())).unwrap();
+
+        let events = store.query_events(&EventFilter::new()).unwrap();
+        assert_eq!(delete_events(&store, &events, true, false).unwrap(), (0, 1));
+        assert!(stubborn.exists());
+        assert_eq!(store.count_filtered_events(&EventFilter::new()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_delete_events_keeping_history() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store = Store::in_memory().unwrap();
+        let doomed = temp_dir.path().join("old.zip");
+        std::fs::write(&doomed, b"zip").unwrap();
+        store.insert_event(&FileEvent::from_path(doomed.clone())).unwrap();
+
+        let events = store.query_events(&EventFilter::new()).unwrap();
+        assert_eq!(delete_events(&store, &events, true, true).unwrap(), (1, 0));
+        assert!(!doomed.exists());
+
+        // Hidden by default, but still in the ledger
+        assert!(store.query_events(&EventFilter::new()).unwrap().is_empty());
+        let cli = Cli::try_parse_from(["ferret", "list", "--include-deleted"]).unwrap();
+        let Some(Commands::List { filter, .. }) = cli.command else {
+            panic!("expected the list command");
+        };
+        let history = store.query_events(&filter.to_filter().unwrap()).unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].removed && history[0].is_deleted());
+
+        // A new file at the same path is listed again
+        std::fs::write(&doomed, b"zip").unwrap();
+        store.insert_event(&FileEvent::from_path(doomed)).unwrap();
+        assert_eq!(store.query_events(&EventFilter::new()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_delete_requires_filter_or_all() {
+        let parse = |args: &[&str]| {
+            match Cli::try_parse_from(["ferret", "delete"].iter().chain(args)).unwrap().command {
+                Some(Commands::Delete { filter, .. }) => filter,
+                _ => panic!("expected the delete command"),
+            }
+        };
+
+        let filter = parse(&["--yes"]);
+        assert!(filter.is_empty());
+        let err = cmd_delete(Config::default(), &filter, true, false, true).unwrap_err();
+        assert!(err.to_string().contains("--all"));
+
+        assert!(!parse(&["--type", "archive"]).is_empty());
+    }
+
+    #[test]
+    fn test_ingest_paths() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("report.pdf"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.join("tiny.txt"), b"x").unwrap();
+        std::fs::write(dir.join("scratch.tmp"), vec![0u8; 100]).unwrap();
+
+        let config = Config {
+            ignore_patterns: vec!["*.tmp".to_string()],
+            min_size_bytes: 10,
+            ..Config::default()
+        };
+        let store = Store::in_memory().unwrap();
+        let mut ingester = Ingester::new(&config).unwrap();
+
+        let input = ["report.pdf", "tiny.txt", "scratch.tmp", "missing.zip", "report.pdf"]
+            .iter()
+            .map(|name| format!("{}\r\n", dir.join(name).display()))
+            .collect::<String>()
+            + "\n";
+        let counts = ingest_paths(&store, &mut ingester, input.as_bytes()).unwrap();
+        assert_eq!(
+            counts,
+            IngestCounts { recorded: 1, already_tracked: 1, ignored: 2, not_found: 1 }
+        );
+
+        let events = store.query_events(&EventFilter::new()).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].path, dir.join("report.pdf"));
+        assert_eq!(events[0].file_type, FileType::Document);
+    }
+
+    #[test]
+    fn test_list_count_arg() {
+        let cli = Cli::try_parse_from(["ferret", "list", "--count", "--json", "-n", "5"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::List { count: true, json: true, .. })));
+
+        assert!(Cli::try_parse_from(["ferret", "list", "--count", "--format", "{path}"]).is_err());
+    }
+
+    #[test]
+    fn test_streamed_json_matches_pretty_array() {
+        let mut events = [
+            FileEvent::from_path(PathBuf::from("/tmp/a.zip")),
+            FileEvent::from_path(PathBuf::from("/tmp/b \"quoted\".txt")),
+        ];
+        events[1].notes = "line1\nline2".to_string();
+
+        for n in 0..=events.len() {
+            let mut out = Vec::new();
+            let mut writer = ListWriter::new(&mut out, &ListFormat::Json);
+            for event in &events[..n] {
+                writer.write(event).unwrap();
+            }
+            writer.finish().unwrap();
+
+            let expected = serde_json::to_string_pretty(&events[..n]).unwrap() + "\n";
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_json_output_with_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let store = Store::in_memory().unwrap();
+        let path = std::path::Path::new("/tmp/dl").join(OsStr::from_bytes(b"caf\xe9.txt"));
+        store.insert_event(&FileEvent::from_path(path.clone())).unwrap();
+        let events = store.query_events(&EventFilter::new()).unwrap();
+
+        // list --json
+        let mut out = Vec::new();
+        let mut writer = ListWriter::new(&mut out, &ListFormat::Json);
+        writer.write(&events[0]).unwrap();
+        writer.finish().unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value[0]["path"], "/tmp/dl/caf\u{fffd}.txt");
+        assert_eq!(value[0]["dir"], "/tmp/dl");
+
+        // watch --json-lines
+        let msg = watcher::WatcherMessage::DeletedFile(path);
+        let value = serde_json::to_value(JsonLine::from(&msg)).unwrap();
+        assert_eq!(value["path"], "/tmp/dl/caf\u{fffd}.txt");
+        let msg = watcher::WatcherMessage::NewFile(events[0].clone());
+        assert!(serde_json::to_string(&JsonLine::from(&msg)).is_ok());
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line1\nline2"), "\"line1\nline2\"");
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut event = FileEvent::from_path(PathBuf::from("/tmp/invoice.pdf"));
+        event.size_bytes = Some(42);
+        event.tags = "work".to_string();
+        event.notes = "paid, see \"March\" email".to_string();
+
+        let mut out = Vec::new();
+        let mut writer = ListWriter::new(&mut out, &ListFormat::Csv);
+        writer.write(&event).unwrap();
+        writer.finish().unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.split("\r\n").collect();
+
+        assert_eq!(lines[0], "time,size_bytes,type,path,tags,notes");
+        assert_eq!(
+            lines[1],
+            format!(
+                "{},42,document,/tmp/invoice.pdf,work,\"paid, see \"\"March\"\" email\"",
+                event.created_at.to_rfc3339()
+            )
+        );
+        assert_eq!(lines[2], "");
+    }
+}
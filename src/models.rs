@@ -3,9 +3,10 @@
 //! This module contains the core data structures used throughout the application,
 //! including file events, file type classifications, and filter criteria.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
 
 /// Classification of file types based on extension and heuristics
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
@@ -135,6 +136,47 @@ impl FileType {
         false
     }
 
+    /// Check if macOS has quarantined a file (it was downloaded from the internet)
+    #[cfg(target_os = "macos")]
+    pub fn check_quarantined(path: &Path) -> bool {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+            return false;
+        };
+        let name = b"com.apple.quarantine\0";
+        // A null buffer asks for the attribute's size; -1 means it isn't set
+        let size = unsafe {
+            libc::getxattr(c_path.as_ptr(), name.as_ptr().cast(), std::ptr::null_mut(), 0, 0, 0)
+        };
+        size >= 0
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn check_quarantined(_path: &Path) -> bool {
+        false
+    }
+
+    /// Classify file contents by their magic bytes
+    ///
+    /// Returns `None` if the header isn't recognised.
+    pub fn from_content(header: &[u8]) -> Option<FileType> {
+        let kind = infer::get(header)?;
+        let file_type = match kind.matcher_type() {
+            infer::MatcherType::App => FileType::Executable,
+            infer::MatcherType::Archive => FileType::Archive,
+            infer::MatcherType::Doc | infer::MatcherType::Book | infer::MatcherType::Text => {
+                FileType::Document
+            }
+            infer::MatcherType::Image | infer::MatcherType::Audio | infer::MatcherType::Video => {
+                FileType::Media
+            }
+            infer::MatcherType::Font | infer::MatcherType::Custom => FileType::Other,
+        };
+        Some(file_type)
+    }
+
     /// Returns a short display label for the file type
     pub fn as_label(&self) -> &'static str {
         match self {
@@ -206,6 +248,8 @@ pub enum ViewMode {
     Flat,
     /// Group files by folder (single level)
     GroupByFolder,
+    /// Group files by exact extension
+    GroupByExtension,
     /// Full nested tree hierarchy
     TreeView,
 }
@@ -215,7 +259,8 @@ impl ViewMode {
     pub fn next(&self) -> Self {
         match self {
             ViewMode::Flat => ViewMode::GroupByFolder,
-            ViewMode::GroupByFolder => ViewMode::TreeView,
+            ViewMode::GroupByFolder => ViewMode::GroupByExtension,
+            ViewMode::GroupByExtension => ViewMode::TreeView,
             ViewMode::TreeView => ViewMode::Flat,
         }
     }
@@ -225,11 +270,205 @@ impl ViewMode {
         match self {
             ViewMode::Flat => "Flat",
             ViewMode::GroupByFolder => "Grouped",
+            ViewMode::GroupByExtension => "By Ext",
             ViewMode::TreeView => "Tree",
         }
     }
 }
 
+impl std::str::FromStr for ViewMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "flat" => Ok(ViewMode::Flat),
+            "grouped" => Ok(ViewMode::GroupByFolder),
+            "extension" => Ok(ViewMode::GroupByExtension),
+            "tree" => Ok(ViewMode::TreeView),
+            _ => Err(format!("Unknown view mode: {} (expected flat, grouped, extension or tree)", s)),
+        }
+    }
+}
+
+/// How sizes are written, from the `size_format` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeFormat {
+    /// Powers of 1024 with IEC units: "1 MiB"
+    #[default]
+    Binary,
+    /// Powers of 1000 with SI units: "1.05 MB"
+    Decimal,
+    /// Powers of 1024 with a single-letter unit: "1.0M"
+    Short,
+}
+
+/// Which time a new file is recorded with, from the `timestamp_source` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampSource {
+    /// When Ferret saw the file (backfilled files use their modification time)
+    #[default]
+    Discovered,
+    /// The file's modification time
+    Modified,
+    /// The file's creation (birth) time, where the OS and filesystem record it
+    Created,
+}
+
+impl TimestampSource {
+    /// The time this source gives for a file, or `None` for discovery time
+    /// or if the platform doesn't provide it
+    pub fn time_of(self, metadata: &std::fs::Metadata) -> Option<DateTime<Utc>> {
+        let time = match self {
+            TimestampSource::Discovered => return None,
+            TimestampSource::Modified => metadata.modified(),
+            TimestampSource::Created => metadata.created(),
+        };
+        time.ok().map(Into::into)
+    }
+}
+
+impl std::str::FromStr for TimestampSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "discovered" => Ok(TimestampSource::Discovered),
+            "modified" => Ok(TimestampSource::Modified),
+            "created" => Ok(TimestampSource::Created),
+            _ => Err(format!(
+                "Unknown timestamp source: {} (expected discovered, modified or created)",
+                s
+            )),
+        }
+    }
+}
+
+/// The size format chosen at startup (a `SizeFormat` discriminant)
+static SIZE_FORMAT: AtomicU8 = AtomicU8::new(SizeFormat::Binary as u8);
+
+impl SizeFormat {
+    /// Use this format for every size shown from now on
+    pub fn set_global(self) {
+        SIZE_FORMAT.store(self as u8, Ordering::Relaxed);
+    }
+
+    /// The format set with [`SizeFormat::set_global`]
+    pub fn current() -> Self {
+        match SIZE_FORMAT.load(Ordering::Relaxed) {
+            1 => SizeFormat::Decimal,
+            2 => SizeFormat::Short,
+            _ => SizeFormat::Binary,
+        }
+    }
+
+    /// Write `bytes` in this format
+    pub fn format(self, bytes: u64) -> String {
+        match self {
+            SizeFormat::Binary => humansize::format_size(bytes, humansize::BINARY),
+            SizeFormat::Decimal => humansize::format_size(bytes, humansize::DECIMAL),
+            SizeFormat::Short => {
+                const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+                if bytes < 1024 {
+                    return format!("{}B", bytes);
+                }
+                let mut value = bytes as f64 / 1024.0;
+                let mut unit = 0;
+                while value >= 1024.0 && unit < UNITS.len() - 1 {
+                    value /= 1024.0;
+                    unit += 1;
+                }
+                format!("{:.1}{}", value, UNITS[unit])
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for SizeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "binary" => Ok(SizeFormat::Binary),
+            "decimal" => Ok(SizeFormat::Decimal),
+            "short" => Ok(SizeFormat::Short),
+            _ => Err(format!("Unknown size format: {} (expected binary, decimal or short)", s)),
+        }
+    }
+}
+
+/// Human-readable size in the configured [`SizeFormat`]
+pub fn format_size(bytes: u64) -> String {
+    SizeFormat::current().format(bytes)
+}
+
+/// Exact byte count with thousands separators, e.g. "1,048,576"
+pub fn format_bytes_grouped(bytes: u64) -> String {
+    let digits = bytes.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// A column that can be shown in the flat list view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListColumn {
+    /// When the file was first seen
+    Time,
+    /// File size
+    Size,
+    /// Exact file size in bytes, with thousands separators
+    Bytes,
+    /// Classified file type
+    Type,
+    /// Full path
+    Path,
+    /// User tags
+    Tags,
+}
+
+impl ListColumn {
+    /// Columns shown when none are configured
+    pub fn defaults() -> Vec<ListColumn> {
+        vec![ListColumn::Time, ListColumn::Size, ListColumn::Type, ListColumn::Path]
+    }
+
+    /// Header text for the column
+    pub fn title(&self) -> &'static str {
+        match self {
+            ListColumn::Time => "Time",
+            ListColumn::Size => "Size",
+            ListColumn::Bytes => "Bytes",
+            ListColumn::Type => "Type",
+            ListColumn::Path => "Path",
+            ListColumn::Tags => "Tags",
+        }
+    }
+}
+
+impl std::str::FromStr for ListColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "time" => Ok(ListColumn::Time),
+            "size" => Ok(ListColumn::Size),
+            "bytes" => Ok(ListColumn::Bytes),
+            "type" => Ok(ListColumn::Type),
+            "path" => Ok(ListColumn::Path),
+            "tags" => Ok(ListColumn::Tags),
+            _ => Err(format!(
+                "Unknown list column: {} (expected time, size, bytes, type, path or tags)",
+                s
+            )),
+        }
+    }
+}
+
 /// Type of node in the tree view
 #[derive(Debug, Clone)]
 pub enum TreeNodeType {
@@ -327,42 +566,11 @@ impl TreeNode {
                     let child_path = current_path.join(first_component);
                     
                     if seen_dirs.insert(child_path.clone()) {
-                        // Recursively build children
+                        // Recursively build children (this includes the files
+                        // directly in child_path, already sorted)
                         let children = Self::build_subtree(dir_files, &child_path);
-                        
-                        // Get files directly in this directory
-                        let mut file_nodes: Vec<TreeNode> = dir_files
-                            .get(&child_path)
-                            .map(|files| {
-                                files.iter().map(|e| TreeNode {
-                                    name: e.filename.clone(),
-                                    path: e.path.clone(),
-                                    node_type: TreeNodeType::File(Box::new((*e).clone())),
-                                    children: vec![],
-                                    file_count: 1,
-                                    total_size: e.size_bytes.unwrap_or(0),
-                                }).collect()
-                            })
-                            .unwrap_or_default();
-                        
-                        // Calculate totals
-                        let child_file_count: usize = children.iter().map(|c| c.file_count).sum();
-                        let child_total_size: u64 = children.iter().map(|c| c.total_size).sum();
-                        let direct_file_count = file_nodes.len();
-                        let direct_total_size: u64 = file_nodes.iter().map(|f| f.total_size).sum();
-                        
-                        // Combine children: directories first, then files
-                        let mut all_children = children;
-                        all_children.append(&mut file_nodes);
-                        
-                        // Sort: directories first, then alphabetically
-                        all_children.sort_by(|a, b| {
-                            match (&a.node_type, &b.node_type) {
-                                (TreeNodeType::Directory, TreeNodeType::File(_)) => std::cmp::Ordering::Less,
-                                (TreeNodeType::File(_), TreeNodeType::Directory) => std::cmp::Ordering::Greater,
-                                _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-                            }
-                        });
+                        let file_count: usize = children.iter().map(|c| c.file_count).sum();
+                        let total_size: u64 = children.iter().map(|c| c.total_size).sum();
                         
                         let dir_name = first_component.as_os_str()
                             .to_string_lossy()
@@ -372,9 +580,9 @@ impl TreeNode {
                             name: dir_name,
                             path: child_path,
                             node_type: TreeNodeType::Directory,
-                            children: all_children,
-                            file_count: child_file_count + direct_file_count,
-                            total_size: child_total_size + direct_total_size,
+                            children,
+                            file_count,
+                            total_size,
                         });
                     }
                 }
@@ -442,6 +650,10 @@ pub struct FlattenedNode {
     pub size_bytes: Option<u64>,
     /// File count (for directories)
     pub file_count: usize,
+    /// Total size of the subtree (the file's size for files)
+    pub total_size: u64,
+    /// Largest `total_size` among this node's sibling directories (0 for files)
+    pub sibling_max_size: u64,
     /// Ancestors' "is_last" status for drawing vertical lines
     pub ancestor_is_last: Vec<bool>,
 }
@@ -502,6 +714,23 @@ impl TreeViewState {
     pub fn collapse_all(&mut self) {
         self.expanded.clear();
     }
+
+    /// Expand the directories in `nodes` that weren't in `previous`, leaving
+    /// the ones the user already saw as they were
+    pub fn expand_new(&mut self, previous: &[TreeNode], nodes: &[TreeNode]) {
+        fn dir_paths(nodes: &[TreeNode], paths: &mut std::collections::HashSet<PathBuf>) {
+            for node in nodes.iter().filter(|n| n.is_dir()) {
+                paths.insert(node.path.clone());
+                dir_paths(&node.children, paths);
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        dir_paths(previous, &mut seen);
+        let mut current = std::collections::HashSet::new();
+        dir_paths(nodes, &mut current);
+        self.expanded.extend(current.difference(&seen).cloned());
+    }
     
     /// Rebuild flattened list from tree nodes
     pub fn rebuild_flattened(&mut self, nodes: &[TreeNode]) {
@@ -516,6 +745,12 @@ impl TreeViewState {
         ancestor_is_last: &mut Vec<bool>,
     ) {
         let count = nodes.len();
+        let max_dir_size = nodes
+            .iter()
+            .filter(|node| node.is_dir())
+            .map(|node| node.total_size)
+            .max()
+            .unwrap_or(0);
         for (idx, node) in nodes.iter().enumerate() {
             let is_last = idx == count - 1;
             let is_expanded = self.expanded.contains(&node.path);
@@ -530,6 +765,8 @@ impl TreeViewState {
                 file_type: node.file_event().map(|e| e.file_type),
                 size_bytes: node.file_event().and_then(|e| e.size_bytes),
                 file_count: node.file_count,
+                total_size: node.total_size,
+                sibling_max_size: if node.is_dir() { max_dir_size } else { 0 },
                 ancestor_is_last: ancestor_is_last.clone(),
             });
             
@@ -666,7 +903,41 @@ impl TreeViewState {
     }
 }
 
-/// A group of files in a folder (for GroupByFolder view mode)
+/// Lowercased extension of `filename`, empty when it has none
+///
+/// Dotfiles like `.bashrc` have no extension, matching `Path::extension`.
+pub fn extension_of(filename: &str) -> String {
+    Path::new(filename)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// The start of `date` in the local time zone, `None` if a time zone change
+/// skips that midnight
+pub fn local_midnight(date: NaiveDate) -> Option<DateTime<Utc>> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+}
+
+/// The part of `filename` that related files share, for finding similar ones
+///
+/// The name before its first dot, without a trailing counter or timestamp
+/// part, so `movie.part1.rar` gives `movie`, `IMG_0042.jpg` gives `IMG` and
+/// `Screenshot 2024-05-01 at 10.22.31.png` gives `Screenshot 2024-05-01 at`.
+/// `None` when nothing is left, e.g. for `.bashrc`.
+pub fn similarity_stem(filename: &str) -> Option<&str> {
+    let base = filename.split('.').next().unwrap_or_default();
+    let trimmed = base.trim_end_matches(|c: char| c.is_ascii_digit() || matches!(c, ' ' | '_' | '-' | '(' | ')'));
+    let stem = if trimmed.is_empty() { base } else { trimmed };
+    (!stem.is_empty()).then_some(stem)
+}
+
+/// A group of files in a folder (for GroupByFolder view mode), or
+/// sharing an extension (for GroupByExtension)
 #[derive(Debug, Clone)]
 pub struct FolderGroup {
     /// The folder path
@@ -711,6 +982,51 @@ impl FolderGroup {
             })
             .collect()
     }
+
+    /// Build extension groups from flat list of events, most files first
+    ///
+    /// The group's `path` is the bare extension (empty for none).
+    pub fn by_extension(events: &[FileEvent]) -> Vec<FolderGroup> {
+        use std::collections::BTreeMap;
+
+        let mut groups: BTreeMap<String, Vec<FileEvent>> = BTreeMap::new();
+        for event in events {
+            groups.entry(event.extension()).or_default().push(event.clone());
+        }
+
+        let mut groups: Vec<FolderGroup> = groups
+            .into_iter()
+            .map(|(ext, files)| {
+                let total_size = files.iter().filter_map(|f| f.size_bytes).sum();
+                let name = if ext.is_empty() {
+                    "(no extension)".to_string()
+                } else {
+                    format!(".{}", ext)
+                };
+
+                FolderGroup {
+                    path: PathBuf::from(ext),
+                    name,
+                    files,
+                    expanded: true,
+                    total_size,
+                }
+            })
+            .collect();
+        // Stable, so equal counts stay in extension order
+        groups.sort_by_key(|g| std::cmp::Reverse(g.files.len()));
+        groups
+    }
+}
+
+/// Serialize a path as a string, replacing bytes that aren't valid UTF-8
+/// with U+FFFD instead of failing like serde's own `Path` impl
+pub fn serialize_path_lossy<P, S>(path: &P, serializer: S) -> Result<S::Ok, S::Error>
+where
+    P: AsRef<Path>,
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&path.as_ref().to_string_lossy())
 }
 
 /// Represents a file event recorded in the ledger
@@ -719,8 +1035,10 @@ pub struct FileEvent {
     /// Unique identifier (database row ID)
     pub id: Option<i64>,
     /// Full absolute path to the file
+    #[serde(serialize_with = "serialize_path_lossy")]
     pub path: PathBuf,
     /// Parent directory
+    #[serde(serialize_with = "serialize_path_lossy")]
     pub dir: PathBuf,
     /// Filename (without directory)
     pub filename: String,
@@ -734,6 +1052,28 @@ pub struct FileEvent {
     pub tags: String,
     /// User-defined notes
     pub notes: String,
+    /// When the file was seen being removed from disk (UTC)
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// SHA-256 of the file contents (hex), if it was hashed
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Whether an executable had its execute bit set when it was recorded
+    #[serde(default)]
+    pub exec_bit: bool,
+    /// Whether an executable carried the macOS quarantine attribute
+    #[serde(default)]
+    pub quarantined: bool,
+    /// Name of the user-defined `[[categories]]` entry the file matched
+    #[serde(default)]
+    pub category: Option<String>,
+    /// URL the file was downloaded from, as recorded by the browser
+    #[serde(default)]
+    pub origin_url: Option<String>,
+    /// Deleted through Ferret and kept only as history
+    /// (see `retain_deleted_in_ledger`)
+    #[serde(default)]
+    pub removed: bool,
 }
 
 impl FileEvent {
@@ -742,9 +1082,8 @@ impl FileEvent {
         let dir = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
         let filename = path
             .file_name()
-            .and_then(|f| f.to_str())
-            .unwrap_or("")
-            .to_string();
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_default();
 
         // Get file size if accessible
         let size_bytes = std::fs::metadata(&path).ok().map(|m| m.len());
@@ -767,17 +1106,59 @@ impl FileEvent {
             file_type,
             tags: String::new(),
             notes: String::new(),
+            deleted_at: None,
+            sha256: None,
+            exec_bit: false,
+            quarantined: false,
+            category: None,
+            origin_url: None,
+            removed: false,
+        }
+    }
+
+    /// Lowercased extension of the filename, empty when it has none
+    pub fn extension(&self) -> String {
+        extension_of(&self.filename)
+    }
+
+    /// Check whether the file has been recorded as deleted
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Check whether this is an executable that could be run as-is or came from the internet
+    pub fn is_flagged_executable(&self) -> bool {
+        self.file_type == FileType::Executable && (self.exec_bit || self.quarantined)
+    }
+
+    /// Record the execute bit and quarantine attribute of an executable
+    ///
+    /// Does nothing for other file types.
+    pub fn check_exec_flags(&mut self) {
+        if self.file_type == FileType::Executable {
+            self.exec_bit = FileType::check_executable(&self.path);
+            self.quarantined = FileType::check_quarantined(&self.path);
         }
     }
 
+    /// Record where the file was downloaded from, if the browser tagged it
+    pub fn check_origin(&mut self) {
+        self.origin_url = read_origin_url(&self.path);
+    }
+
     /// Format size for display
     pub fn size_display(&self) -> String {
         match self.size_bytes {
-            Some(size) => humansize::format_size(size, humansize::BINARY),
+            Some(size) => format_size(size),
             None => "—".to_string(),
         }
     }
 
+    /// Format the creation time relative to now, e.g. "5m ago"
+    pub fn relative_time_display(&self) -> String {
+        format_relative_time(self.created_at, Utc::now())
+    }
+
     /// Get tags as a vector
     pub fn tags_vec(&self) -> Vec<&str> {
         if self.tags.is_empty() {
@@ -793,41 +1174,132 @@ impl FileEvent {
     }
 }
 
+/// Apply tag additions and removals to a comma-separated tag string
+///
+/// Existing order is kept and added tags are appended unless already present.
+/// Returns `None` when the resulting tag list is unchanged.
+pub fn merge_tags(existing: &str, add: &[String], remove: &[String]) -> Option<String> {
+    let current: Vec<&str> = existing
+        .split(',')
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let mut merged: Vec<&str> = Vec::new();
+    for tag in current.iter().copied().chain(add.iter().map(|t| t.trim())) {
+        if !tag.is_empty() && !merged.contains(&tag) && !remove.iter().any(|r| r.trim() == tag) {
+            merged.push(tag);
+        }
+    }
+
+    if merged == current {
+        None
+    } else {
+        Some(merged.join(", "))
+    }
+}
+
+/// Column used to order query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// When the file was first seen (default)
+    #[default]
+    Time,
+    /// File size in bytes
+    Size,
+    /// Filename (case-insensitive)
+    Name,
+    /// Classified file type
+    Type,
+}
+
+impl SortKey {
+    /// Cycle to next sort key
+    pub fn next(&self) -> Self {
+        match self {
+            SortKey::Time => SortKey::Size,
+            SortKey::Size => SortKey::Name,
+            SortKey::Name => SortKey::Type,
+            SortKey::Type => SortKey::Time,
+        }
+    }
+
+    /// Get display name for the sort key
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortKey::Time => "time",
+            SortKey::Size => "size",
+            SortKey::Name => "name",
+            SortKey::Type => "type",
+        }
+    }
+}
+
 /// Filter criteria for querying events
 #[derive(Debug, Clone)]
 pub struct EventFilter {
-    /// Filter by file type
-    pub file_type: Option<FileType>,
+    /// Filter by file type (any of these; empty = all types)
+    pub file_types: Vec<FileType>,
     /// Filter by minimum size in bytes
     pub min_size: Option<u64>,
     /// Filter by maximum size in bytes
     pub max_size: Option<u64>,
     /// Filter by path substring
     pub path_contains: Option<String>,
+    /// Filename must start with this
+    pub filename_prefix: Option<String>,
+    /// Free-text search over path, tags and notes
+    pub text_query: Option<String>,
+    /// Regular expression the path must match
+    pub path_regex: Option<String>,
     /// Filter events after this time
     pub since: Option<DateTime<Utc>>,
     /// Filter events before this time
     pub until: Option<DateTime<Utc>>,
     /// Filter by specific directory
     pub dir: Option<PathBuf>,
+    /// Only executables with the execute bit set
+    pub exec_only: bool,
+    /// Filter by user-defined category name (any of these; empty = all)
+    pub categories: Vec<String>,
+    /// Filter by download origin URL substring
+    pub origin_contains: Option<String>,
+    /// Leave out events in these directories and their subdirectories
+    pub exclude_dirs: Vec<PathBuf>,
+    /// Also match events deleted through Ferret and kept as history
+    pub include_deleted: bool,
     /// Maximum number of results (for pagination)
     pub limit: usize,
     /// Offset for pagination
     pub offset: usize,
+    /// Column to order results by
+    pub sort_key: SortKey,
+    /// Whether to sort descending
+    pub sort_desc: bool,
 }
 
 impl Default for EventFilter {
     fn default() -> Self {
         Self {
-            file_type: None,
+            file_types: Vec::new(),
             min_size: None,
             max_size: None,
             path_contains: None,
+            filename_prefix: None,
+            text_query: None,
+            path_regex: None,
             since: None,
             until: None,
             dir: None,
+            exec_only: false,
+            categories: Vec::new(),
+            origin_contains: None,
+            exclude_dirs: Vec::new(),
+            include_deleted: false,
             limit: 100, // Default page size
             offset: 0,
+            sort_key: SortKey::Time,
+            sort_desc: true,
         }
     }
 }
@@ -838,9 +1310,11 @@ impl EventFilter {
         Self::default()
     }
 
-    /// Filter by file type
+    /// Filter by file type; called again, files of any of the types match
     pub fn with_type(mut self, file_type: FileType) -> Self {
-        self.file_type = Some(file_type);
+        if !self.file_types.contains(&file_type) {
+            self.file_types.push(file_type);
+        }
         self
     }
 
@@ -862,12 +1336,24 @@ impl EventFilter {
         self
     }
 
+    /// Filter by the start of the filename
+    pub fn with_filename_prefix(mut self, prefix: &str) -> Self {
+        self.filename_prefix = Some(prefix.to_string());
+        self
+    }
+
     /// Filter events since a specific time
     pub fn with_since(mut self, since: DateTime<Utc>) -> Self {
         self.since = Some(since);
         self
     }
 
+    /// Filter events up to a specific time
+    pub fn with_until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
     /// Filter events in the last N hours
     pub fn with_last_hours(mut self, hours: i64) -> Self {
         self.since = Some(Utc::now() - chrono::Duration::hours(hours));
@@ -886,6 +1372,34 @@ impl EventFilter {
         self
     }
 
+    /// Only executables with the execute bit set
+    pub fn with_exec_only(mut self) -> Self {
+        self.exec_only = true;
+        self
+    }
+
+    /// Filter by user-defined category; called again, files in any of the
+    /// categories match
+    pub fn with_category(mut self, name: &str) -> Self {
+        let name = name.to_lowercase();
+        if !self.categories.contains(&name) {
+            self.categories.push(name);
+        }
+        self
+    }
+
+    /// Filter by download origin URL substring
+    pub fn with_origin_contains(mut self, origin: &str) -> Self {
+        self.origin_contains = Some(origin.to_string());
+        self
+    }
+
+    /// Also match events deleted through Ferret and kept as history
+    pub fn with_include_deleted(mut self) -> Self {
+        self.include_deleted = true;
+        self
+    }
+
     /// Limit results
     pub fn with_limit(mut self, limit: usize) -> Self {
         self.limit = limit;
@@ -907,10 +1421,13 @@ impl EventFilter {
 
     /// Check if filter is empty (no criteria set)
     pub fn is_empty(&self) -> bool {
-        self.file_type.is_none()
+        self.file_types.is_empty()
             && self.min_size.is_none()
             && self.max_size.is_none()
             && self.path_contains.is_none()
+            && self.filename_prefix.is_none()
+            && self.text_query.is_none()
+            && self.path_regex.is_none()
             && self.since.is_none()
             && self.until.is_none()
             && self.dir.is_none()
@@ -920,25 +1437,47 @@ impl EventFilter {
     pub fn summary(&self) -> String {
         let mut parts = Vec::new();
 
-        if let Some(ft) = &self.file_type {
-            parts.push(format!("type:{}", ft.as_label()));
+        if !self.file_types.is_empty() {
+            let labels: Vec<&str> = self.file_types.iter().map(|ft| ft.as_label()).collect();
+            parts.push(format!("type:{}", labels.join(",")));
+        }
+        if !self.categories.is_empty() {
+            parts.push(format!("category:{}", self.categories.join(",")));
         }
         if let Some(min) = self.min_size {
-            parts.push(format!("≥{}", humansize::format_size(min, humansize::BINARY)));
+            parts.push(format!("≥{}", format_size(min)));
         }
         if let Some(max) = self.max_size {
-            parts.push(format!("≤{}", humansize::format_size(max, humansize::BINARY)));
+            parts.push(format!("≤{}", format_size(max)));
         }
         if let Some(path) = &self.path_contains {
             parts.push(format!("path:*{}*", path));
         }
-        if let Some(since) = &self.since {
-            let duration = Utc::now() - *since;
+        if let Some(prefix) = &self.filename_prefix {
+            parts.push(format!("name:{}*", prefix));
+        }
+        if let Some(query) = &self.text_query {
+            parts.push(format!("search:\"{}\"", query));
+        }
+        if let Some(regex) = &self.path_regex {
+            parts.push(format!("regex:/{}/", regex));
+        }
+        if let Some(origin) = &self.origin_contains {
+            parts.push(format!("from:*{}*", origin));
+        }
+        let age = |time: &DateTime<Utc>| {
+            let duration = Utc::now() - *time;
             if duration.num_hours() < 24 {
-                parts.push(format!("last {}h", duration.num_hours()));
+                format!("{}h", duration.num_hours())
             } else {
-                parts.push(format!("last {}d", duration.num_days()));
+                format!("{}d", duration.num_days())
             }
+        };
+        if let Some(since) = &self.since {
+            parts.push(format!("last {}", age(since)));
+        }
+        if let Some(until) = &self.until {
+            parts.push(format!("older than {}", age(until)));
         }
         if let Some(dir) = &self.dir {
             parts.push(format!(
@@ -948,6 +1487,9 @@ impl EventFilter {
                     .unwrap_or("?")
             ));
         }
+        if !self.exclude_dirs.is_empty() {
+            parts.push(format!("hiding {} dir(s)", self.exclude_dirs.len()));
+        }
 
         if parts.is_empty() {
             "No filters".to_string()
@@ -957,6 +1499,264 @@ impl EventFilter {
     }
 }
 
+/// A set of tracked files sharing the same content hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// Shared SHA-256 hash
+    pub sha256: String,
+    /// Size of each copy in bytes
+    pub size_bytes: u64,
+    /// Events with this hash, oldest first
+    pub events: Vec<FileEvent>,
+}
+
+impl DuplicateGroup {
+    /// Bytes that could be reclaimed by keeping only one copy
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size_bytes * (self.events.len().saturating_sub(1) as u64)
+    }
+}
+
+/// A set of tracked files that are probably copies of each other: the
+/// same size and the same name once copy suffixes are removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameDuplicateGroup {
+    /// Shared name, as given by [`normalize_duplicate_name`]
+    pub name: String,
+    /// Size of each copy in bytes
+    pub size_bytes: u64,
+    /// Events in this group, oldest first
+    pub events: Vec<FileEvent>,
+}
+
+impl NameDuplicateGroup {
+    /// Bytes that could be reclaimed by keeping only one copy
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size_bytes * (self.events.len().saturating_sub(1) as u64)
+    }
+}
+
+/// Strip the suffixes browsers and file managers add to repeated copies
+///
+/// `report (1).pdf`, `report copy.pdf`, `report - Copy (2).pdf`,
+/// `report copy 3.pdf` and `report-2.pdf` all become `report.pdf`. The
+/// suffix may sit before a compound extension (`archive (1).tar.gz`).
+/// Names without such a suffix are returned unchanged.
+pub fn normalize_duplicate_name(filename: &str) -> String {
+    // Try the whole name first, then each dot from the right as the
+    // start of the extension
+    let splits = std::iter::once(filename.len()).chain(filename.rmatch_indices('.').map(|(i, _)| i));
+    for split in splits {
+        let (stem, extension) = filename.split_at(split);
+        let mut name = stem;
+        while let Some(stripped) = strip_copy_suffix(name) {
+            name = stripped;
+        }
+        if name.len() < stem.len() {
+            return format!("{}{}", name, extension);
+        }
+    }
+    filename.to_string()
+}
+
+/// Remove one copy suffix from the end of `stem`, if it has one
+fn strip_copy_suffix(stem: &str) -> Option<&str> {
+    let is_counter = |s: &str, max_digits: usize| {
+        !s.is_empty() && s.len() <= max_digits && s.bytes().all(|b| b.is_ascii_digit())
+    };
+
+    // "name (2)"
+    if let Some((name, n)) = stem.strip_suffix(')').and_then(|s| s.rsplit_once(" (")) {
+        if is_counter(n, 6) && !name.is_empty() {
+            return Some(name);
+        }
+    }
+
+    // "name-2" (short counters only, so "report-2024" is left alone)
+    if let Some((name, n)) = stem.rsplit_once('-') {
+        if is_counter(n, 3) && !name.is_empty() {
+            return Some(name);
+        }
+    }
+
+    // "name copy", "name-copy", "name - Copy", "name copy 2"
+    let base = match stem.rsplit_once(' ') {
+        Some((name, n)) if is_counter(n, 6) => name,
+        _ => stem,
+    };
+    if base.to_ascii_lowercase().ends_with("copy") {
+        let without = &base[..base.len() - "copy".len()];
+        let name = without.trim_end_matches([' ', '-', '_']);
+        if name.len() < without.len() && !name.is_empty() {
+            return Some(name);
+        }
+    }
+
+    None
+}
+
+/// Bucket width for the activity histogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HistogramBucket {
+    /// One bucket per hour
+    Hour,
+    /// One bucket per day (default)
+    #[default]
+    Day,
+    /// One bucket per week, starting Monday
+    Week,
+}
+
+impl HistogramBucket {
+    /// Nominal length of one bucket
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            HistogramBucket::Hour => chrono::Duration::hours(1),
+            HistogramBucket::Day => chrono::Duration::days(1),
+            HistogramBucket::Week => chrono::Duration::weeks(1),
+        }
+    }
+
+    /// How far back the histogram reaches by default
+    pub fn default_window(&self) -> chrono::Duration {
+        match self {
+            HistogramBucket::Hour => chrono::Duration::hours(24),
+            HistogramBucket::Day => chrono::Duration::days(30),
+            HistogramBucket::Week => chrono::Duration::weeks(12),
+        }
+    }
+
+    /// Start of the bucket containing `time`, aligned to local time
+    pub fn start_of(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        use chrono::{Datelike, Local, TimeZone, Timelike};
+
+        let local = time.with_timezone(&Local).naive_local();
+        let date = local.date();
+        let naive = match self {
+            HistogramBucket::Hour => date.and_hms_opt(local.hour(), 0, 0),
+            HistogramBucket::Day => date.and_hms_opt(0, 0, 0),
+            HistogramBucket::Week => {
+                let monday = date - chrono::Days::new(date.weekday().num_days_from_monday() as u64);
+                monday.and_hms_opt(0, 0, 0)
+            }
+        }
+        .unwrap_or(local);
+
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+    }
+
+    /// Start of the bucket after the one starting at `start`
+    pub fn next_start(&self, start: DateTime<Utc>) -> DateTime<Utc> {
+        // Step past the midpoint of the next bucket so DST shifts still land in it
+        let next = self.start_of(start + self.duration() + self.duration() / 2);
+        if next > start {
+            next
+        } else {
+            start + self.duration()
+        }
+    }
+
+    /// Format a bucket start for display
+    pub fn label(&self, start: DateTime<Utc>) -> String {
+        let local = start.with_timezone(&chrono::Local);
+        match self {
+            HistogramBucket::Hour => local.format("%m-%d %H:00").to_string(),
+            HistogramBucket::Day | HistogramBucket::Week => local.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for HistogramBucket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hour" => Ok(HistogramBucket::Hour),
+            "day" => Ok(HistogramBucket::Day),
+            "week" => Ok(HistogramBucket::Week),
+            _ => Err(format!("Unknown bucket: {} (expected hour, day or week)", s)),
+        }
+    }
+}
+
+/// One bucket of the activity histogram
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityBucket {
+    /// Start of the bucket
+    pub bucket_start: DateTime<Utc>,
+    /// Number of files first seen in this bucket
+    pub count: u64,
+    /// Total size of those files in bytes
+    pub size: u64,
+}
+
+/// Format how long before `now` the time `then` was
+///
+/// Returns "just now" under a minute, then minutes, hours and days
+/// ("5m ago", "3h ago", "2d ago"). Future times count as "just now".
+pub fn format_relative_time(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now - then;
+    if elapsed.num_minutes() < 1 {
+        "just now".to_string()
+    } else if elapsed.num_hours() < 1 {
+        format!("{}m ago", elapsed.num_minutes())
+    } else if elapsed.num_days() < 1 {
+        format!("{}h ago", elapsed.num_hours())
+    } else {
+        format!("{}d ago", elapsed.num_days())
+    }
+}
+
+/// Boundaries between size buckets in bytes, smallest first
+///
+/// Bucket `i` holds files below `SIZE_BUCKET_BOUNDS[i]` (and at or above the
+/// previous bound); the last bucket holds everything from the last bound up.
+pub const SIZE_BUCKET_BOUNDS: [u64; 4] = [
+    1024,
+    1024 * 1024,
+    100 * 1024 * 1024,
+    1024 * 1024 * 1024,
+];
+
+/// Display labels for the size buckets (one more than the bounds)
+pub const SIZE_BUCKET_LABELS: [&str; 5] = ["<1KB", "1KB–1MB", "1MB–100MB", "100MB–1GB", ">1GB"];
+
+/// Number of files and bytes within one size range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeBucket {
+    /// Human-readable range, e.g. "1KB–1MB"
+    pub label: String,
+    /// Smallest size in the bucket (inclusive)
+    pub min_bytes: u64,
+    /// Upper end of the bucket (exclusive), None for the last bucket
+    pub max_bytes: Option<u64>,
+    /// Number of files in the bucket
+    pub count: u64,
+    /// Total size of those files in bytes
+    pub size: u64,
+}
+
+impl SizeBucket {
+    /// Empty buckets for every range in `SIZE_BUCKET_BOUNDS`
+    pub fn empty_buckets() -> Vec<SizeBucket> {
+        SIZE_BUCKET_LABELS
+            .iter()
+            .enumerate()
+            .map(|(i, label)| SizeBucket {
+                label: label.to_string(),
+                min_bytes: if i == 0 { 0 } else { SIZE_BUCKET_BOUNDS[i - 1] },
+                max_bytes: SIZE_BUCKET_BOUNDS.get(i).copied(),
+                count: 0,
+                size: 0,
+            })
+            .collect()
+    }
+}
+
 /// Statistics about tracked files
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EventStats {
@@ -980,30 +1780,125 @@ pub struct EventStats {
     pub by_type: Vec<(FileType, u64, u64)>, // (type, count, size)
     /// Top directories by volume
     pub top_dirs: Vec<(PathBuf, u64, u64)>, // (dir, count, size)
+    /// Breakdown by size range (see `SIZE_BUCKET_BOUNDS`)
+    #[serde(default)]
+    pub by_size_bucket: Vec<SizeBucket>,
+    /// Files per weekday (rows, from Monday) and hour of day, in local time
+    #[serde(default)]
+    pub weekday_hour: [[u64; 24]; 7],
 }
 
 impl EventStats {
     /// Format total size for display
     pub fn total_size_display(&self) -> String {
-        humansize::format_size(self.total_size, humansize::BINARY)
+        format_size(self.total_size)
     }
 
     /// Format 24h size for display
     pub fn size_24h_display(&self) -> String {
-        humansize::format_size(self.size_24h, humansize::BINARY)
+        format_size(self.size_24h)
     }
 
     /// Format 7d size for display
     pub fn size_7d_display(&self) -> String {
-        humansize::format_size(self.size_7d, humansize::BINARY)
+        format_size(self.size_7d)
     }
 
     /// Format 30d size for display
     pub fn size_30d_display(&self) -> String {
-        humansize::format_size(self.size_30d, humansize::BINARY)
+        format_size(self.size_30d)
     }
 }
 
+/// Read the URL a browser recorded as a download's origin
+///
+/// Chromium and Firefox set `user.xdg.origin.url` on Linux; Safari and
+/// Chrome set `com.apple.metadata:kMDItemWhereFroms` on macOS. Best effort:
+/// returns `None` if the attribute is missing or unreadable.
+#[cfg(target_os = "linux")]
+pub fn read_origin_url(path: &Path) -> Option<String> {
+    let value = read_xattr(path, b"user.xdg.origin.url\0")?;
+    let url = String::from_utf8(value).ok()?;
+    let url = url.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!url.is_empty()).then(|| url.to_string())
+}
+
+#[cfg(target_os = "macos")]
+pub fn read_origin_url(path: &Path) -> Option<String> {
+    let value = read_xattr(path, b"com.apple.metadata:kMDItemWhereFroms\0")?;
+    first_plist_url(&value)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn read_origin_url(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Read an extended attribute; `name` must be NUL-terminated
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn read_xattr(path: &Path, name: &[u8]) -> Option<Vec<u8>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let get = |buf: *mut libc::c_void, len: usize| unsafe {
+        #[cfg(target_os = "macos")]
+        let size = libc::getxattr(c_path.as_ptr(), name.as_ptr().cast(), buf, len, 0, 0);
+        #[cfg(target_os = "linux")]
+        let size = libc::getxattr(c_path.as_ptr(), name.as_ptr().cast(), buf, len);
+        size
+    };
+
+    // A null buffer asks for the attribute's size; -1 means it isn't set
+    let size = get(std::ptr::null_mut(), 0);
+    if size <= 0 {
+        return None;
+    }
+    let mut value = vec![0u8; size as usize];
+    let read = get(value.as_mut_ptr().cast(), value.len());
+    if read < 0 {
+        return None;
+    }
+    value.truncate(read as usize);
+    Some(value)
+}
+
+/// Pull the first http(s) URL out of a `kMDItemWhereFroms` property list
+///
+/// The attribute is a list of strings, normally as a binary plist. Rather
+/// than decode the whole format, this finds each "http" and reads the string
+/// length from the ASCII string marker in front of it.
+#[cfg(any(target_os = "macos", test))]
+fn first_plist_url(data: &[u8]) -> Option<String> {
+    if !data.starts_with(b"bplist") {
+        // XML plist or plain text
+        let text = std::str::from_utf8(data).ok()?;
+        let start = text.find("http")?;
+        let end = text[start..]
+            .find(|c: char| c == '<' || c.is_whitespace())
+            .map_or(text.len(), |len| start + len);
+        return Some(text[start..end].replace("&amp;", "&"));
+    }
+
+    for start in 0..data.len() {
+        if !data[start..].starts_with(b"http") {
+            continue;
+        }
+        // 0x5N is a string of N < 15 bytes; longer ones are 0x5F followed by
+        // an integer marker (0x10 one byte, 0x11 two bytes) and the length
+        let len = match &data[..start] {
+            [.., 0x5F, 0x10, len] => *len as usize,
+            [.., 0x5F, 0x11, hi, lo] => u16::from_be_bytes([*hi, *lo]) as usize,
+            [.., marker] if marker & 0xF0 == 0x50 && *marker != 0x5F => (marker & 0x0F) as usize,
+            _ => continue,
+        };
+        if let Some(Ok(url)) = data.get(start..start + len).map(std::str::from_utf8) {
+            return Some(url.to_string());
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1039,6 +1934,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extension_and_grouping() {
+        let event = |path: &str| FileEvent::from_path(PathBuf::from(path));
+        assert_eq!(event("/dl/Ubuntu.ISO").extension(), "iso");
+        assert_eq!(event("/dl/backup.tar.gz").extension(), "gz");
+        assert_eq!(event("/dl/README").extension(), "");
+        assert_eq!(event("/dl/.bashrc").extension(), "");
+
+        let events: Vec<FileEvent> = ["/a/x.iso", "/b/y.ISO", "/a/z.pdf", "/a/notes"]
+            .iter()
+            .map(|p| event(p))
+            .collect();
+        let groups = FolderGroup::by_extension(&events);
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec![".iso", "(no extension)", ".pdf"]);
+        assert_eq!(groups[0].files.len(), 2);
+    }
+
     #[test]
     fn test_file_type_parse() {
         assert_eq!("executable".parse::<FileType>().unwrap(), FileType::Executable);
@@ -1055,6 +1968,160 @@ mod tests {
         let summary = filter.summary();
         assert!(summary.contains("type:arch"));
         assert!(summary.contains("≥1 MiB"));
+
+        let filter = EventFilter::new().with_type(FileType::Code).with_type(FileType::Archive);
+        assert_eq!(filter.file_types, vec![FileType::Code, FileType::Archive]);
+        assert!(filter.summary().contains("type:code,arch"));
+    }
+
+    #[test]
+    fn test_file_type_from_content() {
+        let zip = b"PK\x03\x04\x14\x00\x00\x00\x08\x00";
+        assert_eq!(FileType::from_content(zip), Some(FileType::Archive));
+
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+        assert_eq!(FileType::from_content(png), Some(FileType::Media));
+
+        let mut elf = b"\x7fELF\x02\x01\x01".to_vec();
+        elf.resize(64, 0);
+        assert_eq!(FileType::from_content(&elf), Some(FileType::Executable));
+
+        assert_eq!(FileType::from_content(b"just some words"), None);
+    }
+
+    #[test]
+    fn test_histogram_bucket_alignment() {
+        let now = Utc::now();
+        for bucket in [HistogramBucket::Hour, HistogramBucket::Day, HistogramBucket::Week] {
+            let start = bucket.start_of(now);
+            let next = bucket.next_start(start);
+            assert!(start <= now && now < next, "{:?}", bucket);
+            assert_eq!(bucket.start_of(next), next);
+        }
+
+        assert_eq!("WEEK".parse::<HistogramBucket>(), Ok(HistogramBucket::Week));
+        assert!("month".parse::<HistogramBucket>().is_err());
+    }
+
+    #[test]
+    fn test_view_mode_and_column_parsing() {
+        assert_eq!("grouped".parse::<ViewMode>(), Ok(ViewMode::GroupByFolder));
+        assert_eq!("Tree".parse::<ViewMode>(), Ok(ViewMode::TreeView));
+        assert_eq!("extension".parse::<ViewMode>(), Ok(ViewMode::GroupByExtension));
+        assert!("list".parse::<ViewMode>().is_err());
+
+        assert_eq!("TAGS".parse::<ListColumn>(), Ok(ListColumn::Tags));
+        assert!("owner".parse::<ListColumn>().is_err());
+    }
+
+    #[test]
+    fn test_format_bytes_grouped() {
+        assert_eq!(format_bytes_grouped(0), "0");
+        assert_eq!(format_bytes_grouped(999), "999");
+        assert_eq!(format_bytes_grouped(1000), "1,000");
+        assert_eq!(format_bytes_grouped(1_048_576), "1,048,576");
+        assert_eq!(format_bytes_grouped(u64::MAX), "18,446,744,073,709,551,615");
+    }
+
+    #[test]
+    fn test_size_formats() {
+        assert_eq!(SizeFormat::Binary.format(1_048_576), "1 MiB");
+        assert_eq!(SizeFormat::Decimal.format(1_048_576), "1.05 MB");
+        assert_eq!(SizeFormat::Short.format(1_048_576), "1.0M");
+
+        assert_eq!(SizeFormat::Short.format(512), "512B");
+        assert_eq!(SizeFormat::Short.format(1_288_490_189), "1.2G");
+        assert_eq!("Decimal".parse::<SizeFormat>(), Ok(SizeFormat::Decimal));
+        assert!("metric".parse::<SizeFormat>().is_err());
+    }
+
+    #[test]
+    fn test_similarity_stem() {
+        assert_eq!(similarity_stem("movie.part1.rar"), Some("movie"));
+        assert_eq!(similarity_stem("IMG_0042.jpg"), Some("IMG"));
+        assert_eq!(similarity_stem("photo (12).png"), Some("photo"));
+        assert_eq!(
+            similarity_stem("Screenshot 2024-05-01 at 10.22.31.png"),
+            Some("Screenshot 2024-05-01 at")
+        );
+        // All digits: the whole name before the dot
+        assert_eq!(similarity_stem("20240501.log"), Some("20240501"));
+        assert_eq!(similarity_stem(".bashrc"), None);
+    }
+
+    #[test]
+    fn test_normalize_duplicate_name() {
+        assert_eq!(normalize_duplicate_name("file (1).txt"), "file.txt");
+        assert_eq!(normalize_duplicate_name("file copy.txt"), "file.txt");
+        assert_eq!(normalize_duplicate_name("file-2.txt"), "file.txt");
+        assert_eq!(normalize_duplicate_name("file - Copy (2).txt"), "file.txt");
+        assert_eq!(normalize_duplicate_name("photo copy 3.jpg"), "photo.jpg");
+        assert_eq!(normalize_duplicate_name("archive (12).tar.gz"), "archive.tar.gz");
+        assert_eq!(normalize_duplicate_name("v1.2 (1).txt"), "v1.2.txt");
+        assert_eq!(normalize_duplicate_name("setup (3)"), "setup");
+
+        // Names that only look like copies are left alone
+        for name in ["report-2024.pdf", "Chapter 2.pdf", "copy.txt", "(1).txt", "-2.txt", "scopy.txt"] {
+            assert_eq!(normalize_duplicate_name(name), name);
+        }
+    }
+
+    #[test]
+    fn test_timestamp_source() {
+        assert_eq!("Modified".parse::<TimestampSource>(), Ok(TimestampSource::Modified));
+        assert_eq!("created".parse::<TimestampSource>(), Ok(TimestampSource::Created));
+        assert!("accessed".parse::<TimestampSource>().is_err());
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let metadata = file.path().metadata().unwrap();
+        let mtime: DateTime<Utc> = metadata.modified().unwrap().into();
+        assert_eq!(TimestampSource::Discovered.time_of(&metadata), None);
+        assert_eq!(TimestampSource::Modified.time_of(&metadata), Some(mtime));
+        // Birth time is only checked where the filesystem records it
+        if let Ok(created) = metadata.created() {
+            assert_eq!(TimestampSource::Created.time_of(&metadata), Some(created.into()));
+        }
+    }
+
+    #[test]
+    fn test_flattened_sibling_sizes() {
+        let events: Vec<FileEvent> = [
+            ("/data/big/a.iso", 300),
+            ("/data/big/b.iso", 100),
+            ("/data/small/c.txt", 100),
+            ("/data/loose.txt", 5),
+        ]
+        .iter()
+        .map(|(path, size)| {
+            let mut event = FileEvent::from_path(PathBuf::from(path));
+            event.size_bytes = Some(*size);
+            event
+        })
+        .collect();
+        let nodes = TreeNode::from_events(&events);
+
+        let mut state = TreeViewState::new();
+        state.expand_all(&nodes);
+        state.rebuild_flattened(&nodes);
+
+        let node = |name: &str| state.flattened.iter().find(|n| n.name == name).unwrap();
+        assert_eq!((node("big").total_size, node("big").sibling_max_size), (400, 400));
+        assert_eq!((node("small").total_size, node("small").sibling_max_size), (100, 400));
+        assert_eq!(node("loose.txt").sibling_max_size, 0);
+    }
+
+    #[test]
+    fn test_merge_tags() {
+        let add = vec!["work".to_string(), "urgent".to_string()];
+        let remove = vec!["old".to_string()];
+
+        assert_eq!(merge_tags("", &add, &[]), Some("work, urgent".to_string()));
+        assert_eq!(
+            merge_tags("work, old", &add, &remove),
+            Some("work, urgent".to_string())
+        );
+        assert_eq!(merge_tags("work,urgent", &add, &[]), None);
+        assert_eq!(merge_tags("a, b", &[], &remove), None);
     }
 
     #[test]
@@ -1068,4 +2135,90 @@ mod tests {
         assert_eq!(tags[0], "important");
         assert_eq!(tags[1], "backup");
     }
+
+    #[test]
+    fn test_format_relative_time() {
+        let now = Utc::now();
+        assert_eq!(format_relative_time(now - chrono::Duration::seconds(30), now), "just now");
+        assert_eq!(format_relative_time(now + chrono::Duration::seconds(30), now), "just now");
+        assert_eq!(format_relative_time(now - chrono::Duration::minutes(5), now), "5m ago");
+        assert_eq!(format_relative_time(now - chrono::Duration::minutes(200), now), "3h ago");
+        assert_eq!(format_relative_time(now - chrono::Duration::days(2), now), "2d ago");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_exec_flags() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("install.sh");
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+
+        let mut event = FileEvent::from_path(path.clone());
+        event.file_type = FileType::Executable;
+        event.check_exec_flags();
+        assert!(!event.exec_bit);
+        assert!(!event.is_flagged_executable());
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        event.check_exec_flags();
+        assert!(event.exec_bit);
+        assert!(event.is_flagged_executable());
+
+        // Only executables are flagged
+        let mut doc = FileEvent::from_path(path);
+        doc.file_type = FileType::Document;
+        doc.check_exec_flags();
+        assert!(!doc.exec_bit);
+    }
+
+    #[test]
+    fn test_first_plist_url() {
+        // ["https://example.com/files/report.pdf", "https://ex.com"] as a binary plist
+        let mut data = b"bplist00\xa2\x01\x02".to_vec();
+        let long = b"https://example.com/files/report.pdf";
+        data.extend([0x5F, 0x10, long.len() as u8]);
+        data.extend(long);
+        data.push(0x5E);
+        data.extend(b"https://ex.com");
+        data.extend([0x08, 0x0B, 0x32, 0x00, 0x00]);
+        assert_eq!(first_plist_url(&data).as_deref(), Some("https://example.com/files/report.pdf"));
+
+        let xml = "<plist><array><string>https://a.example/x?a=1&amp;b=2</string></array></plist>";
+        assert_eq!(first_plist_url(xml.as_bytes()).as_deref(), Some("https://a.example/x?a=1&b=2"));
+
+        assert_eq!(first_plist_url(b"bplist00\xa0\x08"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_origin_url() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("setup.tar.gz");
+        std::fs::write(&path, b"data").unwrap();
+        assert_eq!(read_origin_url(&path), None);
+
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+        let url = b"https://example.com/setup.tar.gz";
+        let set = unsafe {
+            libc::setxattr(
+                c_path.as_ptr(),
+                c"user.xdg.origin.url".as_ptr(),
+                url.as_ptr().cast(),
+                url.len(),
+                0,
+            )
+        };
+        if set != 0 {
+            // The filesystem doesn't support user attributes
+            return;
+        }
+
+        let mut event = FileEvent::from_path(path);
+        event.check_origin();
+        assert_eq!(event.origin_url.as_deref(), Some("https://example.com/setup.tar.gz"));
+    }
 }
@@ -0,0 +1,162 @@
+//! Desktop notifications for new files
+//!
+//! The watcher's processing thread calls [`NewFileNotifier::on_new_file`]
+//! after recording a new file. Delivery is best-effort: failures are logged
+//! and never interrupt the pipeline.
+
+use crate::config::NotificationConfig;
+use crate::models::FileEvent;
+use anyhow::Result;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Something that can show a notification
+pub trait Notifier: Send + Sync {
+    /// Show a notification with a summary line and body text
+    fn notify(&self, summary: &str, body: &str) -> Result<()>;
+}
+
+/// Notifier backed by the OS notification service
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, summary: &str, body: &str) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname("ferret")
+            .summary(summary)
+            .body(body)
+            .show()?;
+        Ok(())
+    }
+}
+
+/// Decides which new files trigger a notification and sends it
+#[derive(Clone)]
+pub struct NewFileNotifier {
+    config: NotificationConfig,
+    notifier: Arc<dyn Notifier>,
+}
+
+impl NewFileNotifier {
+    /// Create a notifier for the given settings
+    pub fn new(config: NotificationConfig, notifier: Arc<dyn Notifier>) -> Self {
+        Self { config, notifier }
+    }
+
+    /// Build a desktop notifier from config, or `None` if notifications are off
+    pub fn from_config(config: &NotificationConfig) -> Option<Self> {
+        config
+            .enabled
+            .then(|| Self::new(config.clone(), Arc::new(DesktopNotifier)))
+    }
+
+    /// Whether `event` should trigger a notification
+    ///
+    /// A file matches if its type is listed in `types`, or if `min_size_bytes`
+    /// is set and the file is at least that large.
+    pub fn matches(&self, event: &FileEvent) -> bool {
+        let type_match = self.config.types.contains(&event.file_type);
+        let size_match = self.config.min_size_bytes > 0
+            && event.size_bytes.unwrap_or(0) >= self.config.min_size_bytes;
+        type_match || size_match
+    }
+
+    /// Notify about a newly recorded file if it matches
+    pub fn on_new_file(&self, event: &FileEvent) {
+        if !self.matches(event) {
+            return;
+        }
+
+        let summary = format!("New {} file", event.file_type);
+        let body = format!("{} ({})", event.filename, event.size_display());
+        match self.notifier.notify(&summary, &body) {
+            Ok(()) => debug!("Sent notification for {}", event.path.display()),
+            Err(e) => warn!("Failed to send notification for {}: {}", event.path.display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FileType;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    /// Records notifications instead of showing them
+    #[derive(Default)]
+    struct RecordingNotifier {
+        sent: Mutex<Vec<(String, String)>>,
+        fail: bool,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, summary: &str, body: &str) -> Result<()> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((summary.to_string(), body.to_string()));
+            if self.fail {
+                anyhow::bail!("no notification daemon");
+            }
+            Ok(())
+        }
+    }
+
+    fn event(name: &str, file_type: FileType, size: u64) -> FileEvent {
+        let mut event = FileEvent::from_path(PathBuf::from(format!("/tmp/{}", name)));
+        event.file_type = file_type;
+        event.size_bytes = Some(size);
+        event
+    }
+
+    fn config(min_size_bytes: u64, types: Vec<FileType>) -> NotificationConfig {
+        NotificationConfig {
+            enabled: true,
+            min_size_bytes,
+            types,
+        }
+    }
+
+    #[test]
+    fn test_matches_type_or_size() {
+        let notifier = NewFileNotifier::new(
+            config(1000, vec![FileType::Executable]),
+            Arc::new(RecordingNotifier::default()),
+        );
+
+        assert!(notifier.matches(&event("setup.exe", FileType::Executable, 10)));
+        assert!(notifier.matches(&event("movie.mp4", FileType::Media, 5000)));
+        assert!(!notifier.matches(&event("notes.txt", FileType::Document, 10)));
+
+        // No size threshold means only listed types match
+        let notifier = NewFileNotifier::new(config(0, vec![]), Arc::new(RecordingNotifier::default()));
+        assert!(!notifier.matches(&event("movie.mp4", FileType::Media, 5000)));
+    }
+
+    #[test]
+    fn test_notification_body() {
+        let recorder = Arc::new(RecordingNotifier::default());
+        let notifier = NewFileNotifier::new(config(0, vec![FileType::Archive]), recorder.clone());
+
+        notifier.on_new_file(&event("backup.zip", FileType::Archive, 2048));
+        notifier.on_new_file(&event("notes.txt", FileType::Document, 2048));
+
+        let sent = recorder.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, "New archive file");
+        assert_eq!(sent[0].1, "backup.zip (2 KiB)");
+    }
+
+    #[test]
+    fn test_failures_are_swallowed() {
+        let recorder = Arc::new(RecordingNotifier {
+            fail: true,
+            ..Default::default()
+        });
+        let notifier = NewFileNotifier::new(config(0, vec![FileType::Archive]), recorder.clone());
+
+        notifier.on_new_file(&event("backup.zip", FileType::Archive, 2048));
+        assert_eq!(recorder.sent.lock().unwrap().len(), 1);
+    }
+}
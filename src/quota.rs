@@ -0,0 +1,159 @@
+//! Size and file count limits from `[[quota]]`
+//!
+//! A quota is only a warning threshold: the watcher reports when one is
+//! exceeded, but keeps recording files.
+
+use crate::models::{format_size, FileType};
+use crate::store::Store;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// A compiled `[[quota]]` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quota {
+    /// Directory whose files count, subdirectories included (None = all files)
+    pub dir: Option<PathBuf>,
+    /// Only files of this type count (None = any type)
+    pub file_type: Option<FileType>,
+    /// Largest allowed total size
+    pub max_bytes: Option<u64>,
+    /// Largest allowed number of files
+    pub max_count: Option<u64>,
+}
+
+impl Quota {
+    /// Human-readable name, e.g. "/home/me/Downloads (media)"
+    pub fn label(&self) -> String {
+        let scope = match &self.dir {
+            Some(dir) => dir.display().to_string(),
+            None => "all files".to_string(),
+        };
+        match self.file_type {
+            Some(file_type) => format!("{} ({})", scope, file_type.as_str()),
+            None => scope,
+        }
+    }
+
+    /// Count the files and bytes the quota covers
+    pub fn usage(&self, store: &Store) -> Result<QuotaUsage> {
+        let stats = match &self.dir {
+            Some(dir) => store.get_stats_for_dir(dir, true)?,
+            None => store.get_stats()?,
+        };
+        let (count, bytes) = match self.file_type {
+            Some(file_type) => stats
+                .by_type
+                .iter()
+                .find(|(t, _, _)| *t == file_type)
+                .map_or((0, 0), |&(_, count, size)| (count, size)),
+            None => (stats.total_count, stats.total_size),
+        };
+
+        Ok(QuotaUsage {
+            label: self.label(),
+            count,
+            bytes,
+            max_count: self.max_count,
+            max_bytes: self.max_bytes,
+        })
+    }
+}
+
+/// How much of a quota is used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuotaUsage {
+    /// See [`Quota::label`]
+    pub label: String,
+    pub count: u64,
+    pub bytes: u64,
+    pub max_count: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+impl QuotaUsage {
+    /// Share of `max_count` used, in percent
+    pub fn count_percent(&self) -> Option<f64> {
+        self.max_count.map(|max| percent(self.count, max))
+    }
+
+    /// Share of `max_bytes` used, in percent
+    pub fn bytes_percent(&self) -> Option<f64> {
+        self.max_bytes.map(|max| percent(self.bytes, max))
+    }
+
+    /// Whether either limit is exceeded
+    pub fn exceeded(&self) -> bool {
+        self.max_count.is_some_and(|max| self.count > max) || self.max_bytes.is_some_and(|max| self.bytes > max)
+    }
+
+    /// One-line summary, e.g. "/home/me/Downloads: 12 GiB of 10 GiB, 40 of 100 files"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(max) = self.max_bytes {
+            parts.push(format!("{} of {}", format_size(self.bytes), format_size(max)));
+        }
+        if let Some(max) = self.max_count {
+            parts.push(format!("{} of {} files", self.count, max));
+        }
+        format!("{}: {}", self.label, parts.join(", "))
+    }
+}
+
+fn percent(used: u64, max: u64) -> f64 {
+    if max == 0 {
+        return if used == 0 { 0.0 } else { 100.0 };
+    }
+    used as f64 / max as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FileEvent;
+
+    fn add(store: &Store, path: &str, size: u64) {
+        let mut event = FileEvent::from_path(PathBuf::from(path));
+        event.size_bytes = Some(size);
+        store.insert_event(&event).unwrap();
+    }
+
+    #[test]
+    fn test_quota_usage() {
+        let store = Store::in_memory().unwrap();
+        add(&store, "/dl/movie.mp4", 700);
+        add(&store, "/dl/sub/clip.mp4", 200);
+        add(&store, "/dl/notes.txt", 50);
+        add(&store, "/other/song.mp3", 1000);
+
+        let downloads = Quota {
+            dir: Some(PathBuf::from("/dl")),
+            file_type: None,
+            max_bytes: Some(1000),
+            max_count: Some(2),
+        };
+        let usage = downloads.usage(&store).unwrap();
+        assert_eq!((usage.count, usage.bytes), (3, 950));
+        assert_eq!(usage.bytes_percent(), Some(95.0));
+        assert_eq!(usage.count_percent(), Some(150.0));
+        assert!(usage.exceeded());
+
+        let media = Quota {
+            dir: None,
+            file_type: Some(FileType::Media),
+            max_bytes: Some(2000),
+            max_count: None,
+        };
+        let usage = media.usage(&store).unwrap();
+        assert_eq!((usage.count, usage.bytes), (3, 1900));
+        assert_eq!(usage.count_percent(), None);
+        assert!(!usage.exceeded());
+        assert_eq!(usage.label, "all files (media)");
+
+        // Deleting a file through Ferret frees its share, even kept as history
+        let movie = store.get_event_by_path(std::path::Path::new("/dl/movie.mp4")).unwrap().unwrap();
+        store.remove_event(movie.id.unwrap(), true).unwrap();
+        let usage = downloads.usage(&store).unwrap();
+        assert_eq!((usage.count, usage.bytes), (2, 250));
+    }
+}
@@ -0,0 +1,258 @@
+//! Destinations for newly recorded files
+//!
+//! The watcher's processing thread hands every new file to each configured
+//! [`EventSink`] in turn: the store first, then the UI channel, then any
+//! external sinks such as a webhook. Sinks are best-effort: failures are
+//! logged and never interrupt the pipeline.
+
+use crate::config::WebhookConfig;
+use crate::models::FileEvent;
+use crate::store::Store;
+use crate::watcher::{WatcherCounters, WatcherMessage};
+use std::sync::mpsc::{self, Sender, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+/// Events waiting for the webhook worker before new ones are dropped
+const WEBHOOK_QUEUE_SIZE: usize = 256;
+
+/// Attempts to deliver each event before giving up on it
+const WEBHOOK_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry, doubled for each later one
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How long a single request may take
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Something that wants to hear about each new file
+pub trait EventSink: Send + Sync {
+    /// Handle a newly recorded file
+    fn on_event(&self, event: &FileEvent);
+
+    /// Handle a file that was moved or renamed into a watched directory
+    fn on_moved(&self, event: &FileEvent) {
+        self.on_event(event);
+    }
+
+    /// Handle many files at once, such as those found by a backfill scan
+    fn on_batch(&self, events: &[FileEvent]) {
+        for event in events {
+            self.on_event(event);
+        }
+    }
+}
+
+/// Records new files in the database
+pub struct StoreSink {
+    store: Store,
+    counters: Arc<WatcherCounters>,
+}
+
+impl StoreSink {
+    pub fn new(store: Store, counters: Arc<WatcherCounters>) -> Self {
+        Self { store, counters }
+    }
+}
+
+impl EventSink for StoreSink {
+    fn on_event(&self, event: &FileEvent) {
+        match self.store.insert_event(event) {
+            Ok(_) => self.counters.count_inserted(1),
+            // Continue anyway - the other sinks still hear about it
+            Err(e) => error!("Failed to insert event into database: {}", e),
+        }
+    }
+
+    fn on_batch(&self, events: &[FileEvent]) {
+        match self.store.insert_events_batch(events) {
+            Ok(inserted) => self.counters.count_inserted(inserted as u64),
+            Err(e) => error!("Failed to insert {} events into database: {}", events.len(), e),
+        }
+    }
+}
+
+/// Passes new files on to the UI
+pub struct ChannelSink {
+    tx: Sender<WatcherMessage>,
+}
+
+impl ChannelSink {
+    pub fn new(tx: Sender<WatcherMessage>) -> Self {
+        Self { tx }
+    }
+}
+
+impl EventSink for ChannelSink {
+    fn on_event(&self, event: &FileEvent) {
+        if let Err(e) = self.tx.send(WatcherMessage::NewFile(event.clone())) {
+            error!("Failed to send watcher message: {}", e);
+        }
+    }
+
+    fn on_moved(&self, event: &FileEvent) {
+        if let Err(e) = self.tx.send(WatcherMessage::MovedFile(event.clone())) {
+            error!("Failed to send watcher message: {}", e);
+        }
+    }
+}
+
+/// POSTs each new file as JSON to a URL
+///
+/// Requests are sent from a worker thread fed by a bounded queue, so a slow
+/// or unreachable endpoint never holds up the watcher. When the queue is
+/// full, new events are dropped with a warning.
+pub struct WebhookSink {
+    queue: SyncSender<FileEvent>,
+}
+
+impl WebhookSink {
+    /// Start a worker posting to `url`
+    pub fn new(url: String, retry_delay: Duration) -> anyhow::Result<Self> {
+        let (queue, rx) = mpsc::sync_channel::<FileEvent>(WEBHOOK_QUEUE_SIZE);
+        let agent = ureq::AgentBuilder::new().timeout(WEBHOOK_TIMEOUT).build();
+
+        // Exits once the sink, and with it the sending side, is dropped
+        thread::Builder::new()
+            .name("ferret-webhook".to_string())
+            .spawn(move || {
+                for event in rx {
+                    Self::deliver(&agent, &url, &event, retry_delay);
+                }
+            })?;
+
+        Ok(Self { queue })
+    }
+
+    /// Build a webhook sink from config, or `None` if no URL is set
+    pub fn from_config(config: &WebhookConfig) -> anyhow::Result<Option<Self>> {
+        config
+            .url
+            .clone()
+            .map(|url| Self::new(url, WEBHOOK_RETRY_DELAY))
+            .transpose()
+    }
+
+    /// POST one event, retrying a few times before dropping it
+    fn deliver(agent: &ureq::Agent, url: &str, event: &FileEvent, retry_delay: Duration) {
+        let mut delay = retry_delay;
+        for attempt in 1..=WEBHOOK_ATTEMPTS {
+            match agent.post(url).send_json(event) {
+                Ok(_) => {
+                    debug!("Posted {} to webhook", event.path.display());
+                    return;
+                }
+                Err(e) if attempt < WEBHOOK_ATTEMPTS => {
+                    debug!("Webhook attempt {} for {} failed: {}", attempt, event.path.display(), e);
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err(e) => warn!(
+                    "Dropping webhook event for {} after {} attempts: {}",
+                    event.path.display(),
+                    WEBHOOK_ATTEMPTS,
+                    e
+                ),
+            }
+        }
+    }
+}
+
+impl EventSink for WebhookSink {
+    fn on_event(&self, event: &FileEvent) {
+        match self.queue.try_send(event.clone()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                warn!("Webhook queue full, dropping event for {}", event.path.display())
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                error!("Webhook worker stopped, dropping event for {}", event.path.display())
+            }
+        }
+    }
+
+    /// Backfilled files were already there, so they aren't posted
+    fn on_batch(&self, _events: &[FileEvent]) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::time::Instant;
+
+    /// Accept one request on `listener`, answer it with `status` and return its body
+    fn serve_one(listener: &TcpListener, status: &str) -> String {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+        String::from_utf8(body).unwrap()
+    }
+
+    #[test]
+    fn test_webhook_posts_event_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let sink = WebhookSink::new(url, Duration::from_millis(10)).unwrap();
+
+        let mut event = FileEvent::from_path(PathBuf::from("/tmp/dl/report.pdf"));
+        event.size_bytes = Some(2048);
+        sink.on_event(&event);
+
+        // The first attempt fails and is retried
+        serve_one(&listener, "503 Service Unavailable");
+        let body: FileEvent = serde_json::from_str(&serve_one(&listener, "200 OK")).unwrap();
+        assert_eq!(body.path, event.path);
+        assert_eq!(body.size_bytes, Some(2048));
+    }
+
+    #[test]
+    fn test_webhook_skips_backfill() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let sink = WebhookSink::new(url, Duration::from_millis(10)).unwrap();
+
+        let existing: Vec<FileEvent> = (0..WEBHOOK_QUEUE_SIZE * 2)
+            .map(|i| FileEvent::from_path(PathBuf::from(format!("/tmp/dl/old{}.txt", i))))
+            .collect();
+        sink.on_batch(&existing);
+        sink.on_event(&FileEvent::from_path(PathBuf::from("/tmp/dl/new.txt")));
+
+        // The first request is the new file, not one of the backfilled ones
+        let body: FileEvent = serde_json::from_str(&serve_one(&listener, "200 OK")).unwrap();
+        assert_eq!(body.path, PathBuf::from("/tmp/dl/new.txt"));
+    }
+
+    #[test]
+    fn test_webhook_does_not_block() {
+        // Nothing listens here, so every delivery fails and is retried
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+        let sink = WebhookSink::new(url, Duration::from_secs(60)).unwrap();
+
+        let start = Instant::now();
+        for i in 0..WEBHOOK_QUEUE_SIZE * 2 {
+            sink.on_event(&FileEvent::from_path(PathBuf::from(format!("/tmp/{}.txt", i))));
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -3,16 +3,21 @@
 //! This module handles all database operations including schema management,
 //! event insertion, querying, and statistics generation.
 
-use crate::models::{EventFilter, EventStats, FileEvent, FileType};
+use crate::models::{extension_of, merge_tags, normalize_duplicate_name, ActivityBucket, DuplicateGroup, EventFilter, EventStats, FileEvent, FileType, HistogramBucket, NameDuplicateGroup, SizeBucket, SortKey, SIZE_BUCKET_BOUNDS};
 use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, Utc};
-use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Datelike, Duration, Local, Timelike, Utc};
+use rusqlite::{functions::FunctionFlags, params, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Database schema version for migrations
-const SCHEMA_VERSION: i32 = 1;
+const SCHEMA_VERSION: i32 = 11;
+
+/// Columns selected for every event query, in the order `row_to_event` reads them
+const EVENT_COLUMNS: &str =
+    "id, path, dir, filename, size_bytes, created_at, file_type, tags, notes, deleted_at, sha256, exec_bit, quarantined, category, origin_url, path_bytes, removed";
 
 /// The file event store backed by SQLite
 pub struct Store {
@@ -20,29 +25,58 @@ pub struct Store {
     conn: Arc<Mutex<Connection>>,
     /// Path to the database file
     db_path: PathBuf,
+    /// Whether the FTS5 index over path/tags/notes is available
+    fts_enabled: bool,
 }
 
 impl Store {
     /// Create a new Store, initializing the database if needed
-    pub fn new(db_path: &Path) -> Result<Self> {
+    ///
+    /// Each statement waits up to `busy_timeout` for locks held by another
+    /// process. If opening still fails because the database is busy, it's
+    /// retried with backoff until `busy_timeout` has passed.
+    pub fn new(db_path: &Path, busy_timeout: std::time::Duration) -> Result<Self> {
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("Failed to create database directory: {}", parent.display()))?;
         }
 
+        let deadline = std::time::Instant::now() + busy_timeout;
+        let mut delay = std::time::Duration::from_millis(50);
+        loop {
+            match Self::open(db_path, busy_timeout) {
+                Ok(store) => return Ok(store),
+                Err(e) if is_busy_error(&e) => {
+                    if std::time::Instant::now() + delay > deadline {
+                        anyhow::bail!(
+                            "The database at {} is locked by another process. Ferret appears to be running; try again",
+                            db_path.display()
+                        );
+                    }
+                    debug!("Database busy, retrying in {:?}", delay);
+                    std::thread::sleep(delay);
+                    delay = (delay * 2).min(std::time::Duration::from_secs(1));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Open and configure the database, then bring its schema up to date
+    fn open(db_path: &Path, busy_timeout: std::time::Duration) -> Result<Self> {
         let conn = Connection::open(db_path)
             .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
 
+        // Wait for locks instead of failing immediately
+        conn.busy_timeout(busy_timeout)?;
+
         // Configure SQLite for high-concurrency access
         // These pragmas are critical for preventing "database is locked" errors
         conn.execute_batch("
             -- WAL mode allows concurrent readers and one writer
             PRAGMA journal_mode=WAL;
             
-            -- Wait up to 5 seconds for locks instead of failing immediately
-            PRAGMA busy_timeout=5000;
-            
             -- NORMAL is safe with WAL and much faster than FULL
             PRAGMA synchronous=NORMAL;
             
@@ -58,13 +92,16 @@ impl Store {
             -- Enable memory-mapped I/O (256MB)
             PRAGMA mmap_size=268435456;
         ")?;
+        register_regexp(&conn)?;
 
-        let store = Self {
+        let mut store = Self {
             conn: Arc::new(Mutex::new(conn)),
             db_path: db_path.to_path_buf(),
+            fts_enabled: false,
         };
 
         store.initialize_schema()?;
+        store.fts_enabled = store.detect_fts()?;
         
         info!("Database initialized at {}", db_path.display());
         Ok(store)
@@ -82,13 +119,16 @@ impl Store {
             PRAGMA foreign_keys=ON;
             PRAGMA temp_store=MEMORY;
         ")?;
+        register_regexp(&conn)?;
 
-        let store = Self {
+        let mut store = Self {
             conn: Arc::new(Mutex::new(conn)),
             db_path: PathBuf::from(":memory:"),
+            fts_enabled: false,
         };
 
         store.initialize_schema()?;
+        store.fts_enabled = store.detect_fts()?;
         
         debug!("In-memory database initialized");
         Ok(store)
@@ -152,6 +192,124 @@ impl Store {
             )?;
         }
 
+        if from_version < 2 {
+            // Track when a file disappeared from disk
+            conn.execute_batch("ALTER TABLE events ADD COLUMN deleted_at TEXT;")?;
+        }
+
+        if from_version < 3 {
+            // Content hashes for duplicate detection
+            conn.execute_batch(
+                "
+                ALTER TABLE events ADD COLUMN sha256 TEXT;
+                CREATE INDEX IF NOT EXISTS idx_events_sha256 ON events(sha256);
+                ",
+            )?;
+        }
+
+        if from_version < 4 {
+            // Full-text index over path, tags and notes (skipped if FTS5 is unavailable)
+            match conn.execute_batch(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(
+                    path, tags, notes, content='events', content_rowid='id'
+                );",
+            ) {
+                Ok(()) => {
+                    conn.execute_batch(
+                        "
+                        CREATE TRIGGER IF NOT EXISTS events_fts_insert AFTER INSERT ON events BEGIN
+                            INSERT INTO events_fts(rowid, path, tags, notes)
+                            VALUES (new.id, new.path, new.tags, new.notes);
+                        END;
+
+                        CREATE TRIGGER IF NOT EXISTS events_fts_delete AFTER DELETE ON events BEGIN
+                            INSERT INTO events_fts(events_fts, rowid, path, tags, notes)
+                            VALUES ('delete', old.id, old.path, old.tags, old.notes);
+                        END;
+
+                        CREATE TRIGGER IF NOT EXISTS events_fts_update
+                        AFTER UPDATE OF path, tags, notes ON events BEGIN
+                            INSERT INTO events_fts(events_fts, rowid, path, tags, notes)
+                            VALUES ('delete', old.id, old.path, old.tags, old.notes);
+                            INSERT INTO events_fts(rowid, path, tags, notes)
+                            VALUES (new.id, new.path, new.tags, new.notes);
+                        END;
+
+                        INSERT INTO events_fts(events_fts) VALUES ('rebuild');
+                        ",
+                    )?;
+                }
+                Err(e) => {
+                    warn!("FTS5 unavailable, search will use LIKE matching: {}", e);
+                }
+            }
+        }
+
+        if from_version < 5 {
+            // Execute bit and macOS quarantine flag for executables
+            conn.execute_batch(
+                "
+                ALTER TABLE events ADD COLUMN exec_bit INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE events ADD COLUMN quarantined INTEGER NOT NULL DEFAULT 0;
+                ",
+            )?;
+        }
+
+        if from_version < 6 {
+            // User-defined category from `[[categories]]`
+            conn.execute_batch(
+                "
+                ALTER TABLE events ADD COLUMN category TEXT;
+                CREATE INDEX IF NOT EXISTS idx_events_category ON events(category);
+                ",
+            )?;
+        }
+
+        if from_version < 7 {
+            // Download origin read from the browser's extended attribute
+            conn.execute_batch("ALTER TABLE events ADD COLUMN origin_url TEXT;")?;
+        }
+
+        if from_version < 8 {
+            // Exact bytes of paths that aren't valid UTF-8 (see `raw_path_bytes`)
+            conn.execute_batch("ALTER TABLE events ADD COLUMN path_bytes BLOB;")?;
+        }
+
+        if from_version < 9 {
+            // Rows deleted through Ferret but kept as history (see `retire_event`)
+            conn.execute_batch("ALTER TABLE events ADD COLUMN removed INTEGER NOT NULL DEFAULT 0;")?;
+        }
+
+        if from_version < 10 {
+            // Lowercased extension for `stats --by-ext`, filled in for existing rows
+            conn.execute_batch(
+                "
+                ALTER TABLE events ADD COLUMN extension TEXT NOT NULL DEFAULT '';
+                CREATE INDEX IF NOT EXISTS idx_events_extension ON events(extension);
+                ",
+            )?;
+            let rows: Vec<(i64, String)> = conn
+                .prepare("SELECT id, filename FROM events")?
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<_>>()?;
+            let mut update = conn.prepare("UPDATE events SET extension = ? WHERE id = ?")?;
+            for (id, filename) in rows {
+                update.execute(params![extension_of(&filename), id])?;
+            }
+        }
+
+        if from_version < 11 {
+            // Non-UTF-8 paths were keyed by their lossy text, see `path_key`
+            let rows: Vec<(i64, Vec<u8>)> = conn
+                .prepare("SELECT id, path_bytes FROM events WHERE path_bytes IS NOT NULL")?
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<_>>()?;
+            let mut update = conn.prepare("UPDATE events SET path = ? WHERE id = ?")?;
+            for (id, bytes) in rows {
+                update.execute(params![path_key(&path_from_raw_bytes(bytes)), id])?;
+            }
+        }
+
         // Record the new version
         conn.execute(
             "INSERT OR REPLACE INTO schema_version (version) VALUES (?)",
@@ -166,37 +324,37 @@ impl Store {
     pub fn insert_event(&self, event: &FileEvent) -> Result<i64> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
 
-        // Try to insert, or update size if the path already exists
-        conn.execute(
-            "INSERT INTO events (path, dir, filename, size_bytes, created_at, file_type, tags, notes)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-             ON CONFLICT(path) DO UPDATE SET
-                size_bytes = COALESCE(excluded.size_bytes, size_bytes)",
-            params![
-                event.path.to_string_lossy(),
-                event.dir.to_string_lossy(),
-                event.filename,
-                event.size_bytes.map(|s| s as i64),
-                event.created_at.to_rfc3339(),
-                event.file_type.as_str(),
-                event.tags,
-                event.notes,
-            ],
-        )?;
+        insert_row(&conn, event)?;
 
         let id = conn.last_insert_rowid();
         debug!("Inserted event for {}: id={}", event.path.display(), id);
         Ok(id)
     }
 
+    /// Insert or update many events in one transaction
+    ///
+    /// Same semantics as [`Store::insert_event`] for each event, but far
+    /// cheaper than committing every row on its own. Returns the number of
+    /// events written.
+    pub fn insert_events_batch(&self, events: &[FileEvent]) -> Result<usize> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let tx = conn.transaction()?;
+        for event in events {
+            insert_row(&tx, event)?;
+        }
+        tx.commit()?;
+
+        debug!("Inserted {} events in one batch", events.len());
+        Ok(events.len())
+    }
+
     /// Get an event by ID
     pub fn get_event(&self, id: i64) -> Result<Option<FileEvent>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
 
         let result = conn
             .query_row(
-                "SELECT id, path, dir, filename, size_bytes, created_at, file_type, tags, notes
-                 FROM events WHERE id = ?",
+                &format!("SELECT {} FROM events WHERE id = ?", EVENT_COLUMNS),
                 params![id],
                 |row| self.row_to_event(row),
             )
@@ -211,9 +369,8 @@ impl Store {
 
         let result = conn
             .query_row(
-                "SELECT id, path, dir, filename, size_bytes, created_at, file_type, tags, notes
-                 FROM events WHERE path = ?",
-                params![path.to_string_lossy()],
+                &format!("SELECT {} FROM events WHERE path = ?", EVENT_COLUMNS),
+                params![path_key(path)],
                 |row| self.row_to_event(row),
             )
             .optional()?;
@@ -225,48 +382,7 @@ impl Store {
     pub fn query_events(&self, filter: &EventFilter) -> Result<Vec<FileEvent>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
 
-        let mut sql = String::from(
-            "SELECT id, path, dir, filename, size_bytes, created_at, file_type, tags, notes
-             FROM events WHERE 1=1",
-        );
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-        if let Some(ft) = &filter.file_type {
-            sql.push_str(" AND file_type = ?");
-            params.push(Box::new(ft.as_str().to_string()));
-        }
-
-        if let Some(min) = filter.min_size {
-            sql.push_str(" AND size_bytes >= ?");
-            params.push(Box::new(min as i64));
-        }
-
-        if let Some(max) = filter.max_size {
-            sql.push_str(" AND size_bytes <= ?");
-            params.push(Box::new(max as i64));
-        }
-
-        if let Some(pattern) = &filter.path_contains {
-            sql.push_str(" AND path LIKE ?");
-            params.push(Box::new(format!("%{}%", pattern)));
-        }
-
-        if let Some(since) = &filter.since {
-            sql.push_str(" AND created_at >= ?");
-            params.push(Box::new(since.to_rfc3339()));
-        }
-
-        if let Some(until) = &filter.until {
-            sql.push_str(" AND created_at <= ?");
-            params.push(Box::new(until.to_rfc3339()));
-        }
-
-        if let Some(dir) = &filter.dir {
-            sql.push_str(" AND dir = ?");
-            params.push(Box::new(dir.to_string_lossy().to_string()));
-        }
-
-        sql.push_str(" ORDER BY created_at DESC");
+        let (mut sql, params) = self.select_sql(filter);
 
         // Always use LIMIT and OFFSET for pagination
         sql.push_str(&format!(" LIMIT {} OFFSET {}", filter.limit, filter.offset));
@@ -282,16 +398,59 @@ impl Store {
         Ok(events)
     }
 
-    /// Count events matching filter (for pagination info)
-    pub fn count_filtered_events(&self, filter: &EventFilter) -> Result<usize> {
+    /// Call `f` with each event matching a filter, one row at a time
+    ///
+    /// Unlike [`Store::query_events`], a `limit` of 0 means no limit, and the
+    /// results are never collected, so memory use doesn't grow with the
+    /// ledger. The store is locked while iterating: `f` must not call back
+    /// into it. Returns the number of events visited.
+    pub fn query_events_streaming<F>(&self, filter: &EventFilter, mut f: F) -> Result<usize>
+    where
+        F: FnMut(FileEvent) -> Result<()>,
+    {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
 
-        let mut sql = String::from("SELECT COUNT(*) FROM events WHERE 1=1");
+        let (mut sql, params) = self.select_sql(filter);
+        // A negative LIMIT is unbounded in SQLite
+        let limit = if filter.limit == 0 { -1 } else { filter.limit as i64 };
+        sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, filter.offset));
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query(params_refs.as_slice())?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            f(self.row_to_event(row)?)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// The ordered SELECT for a filter, without LIMIT/OFFSET, and its bound parameters
+    fn select_sql(&self, filter: &EventFilter) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+        let (where_clause, params) = self.where_clause(filter);
+        let mut sql = format!("SELECT {} FROM events WHERE {}", EVENT_COLUMNS, where_clause);
+        sql.push_str(&Self::order_by_clause(filter));
+        (sql, params)
+    }
+
+    /// Build the WHERE clause and its bound parameters for a filter
+    fn where_clause(&self, filter: &EventFilter) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+        let mut sql = String::from("1=1");
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-        if let Some(ft) = &filter.file_type {
-            sql.push_str(" AND file_type = ?");
-            params.push(Box::new(ft.as_str().to_string()));
+        if !filter.include_deleted {
+            sql.push_str(" AND removed = 0");
+        }
+
+        if !filter.file_types.is_empty() {
+            let placeholders = vec!["?"; filter.file_types.len()].join(", ");
+            sql.push_str(&format!(" AND file_type IN ({})", placeholders));
+            for ft in &filter.file_types {
+                params.push(Box::new(ft.as_str().to_string()));
+            }
         }
 
         if let Some(min) = filter.min_size {
@@ -309,6 +468,36 @@ impl Store {
             params.push(Box::new(format!("%{}%", pattern)));
         }
 
+        if let Some(prefix) = &filter.filename_prefix {
+            sql.push_str(" AND filename LIKE ? ESCAPE '!'");
+            params.push(Box::new(format!(
+                "{}%",
+                prefix.replace('!', "!!").replace('%', "!%").replace('_', "!_")
+            )));
+        }
+
+        if let Some(query) = &filter.text_query {
+            let match_query = fts_match_query(query);
+            if self.fts_enabled && !match_query.is_empty() {
+                // Keep substring path matching, plus token matches in tags and notes
+                sql.push_str(
+                    " AND (path LIKE ? OR id IN (SELECT rowid FROM events_fts WHERE events_fts MATCH ?))",
+                );
+                params.push(Box::new(format!("%{}%", query)));
+                params.push(Box::new(match_query));
+            } else {
+                sql.push_str(" AND (path LIKE ? OR tags LIKE ? OR notes LIKE ?)");
+                for _ in 0..3 {
+                    params.push(Box::new(format!("%{}%", query)));
+                }
+            }
+        }
+
+        if let Some(regex) = &filter.path_regex {
+            sql.push_str(" AND path REGEXP ?");
+            params.push(Box::new(regex.clone()));
+        }
+
         if let Some(since) = &filter.since {
             sql.push_str(" AND created_at >= ?");
             params.push(Box::new(since.to_rfc3339()));
@@ -324,12 +513,114 @@ impl Store {
             params.push(Box::new(dir.to_string_lossy().to_string()));
         }
 
+        if filter.exec_only {
+            sql.push_str(" AND file_type = 'executable' AND exec_bit = 1");
+        }
+
+        if !filter.categories.is_empty() {
+            let placeholders = vec!["?"; filter.categories.len()].join(", ");
+            sql.push_str(&format!(" AND category IN ({})", placeholders));
+            for category in &filter.categories {
+                params.push(Box::new(category.clone()));
+            }
+        }
+
+        if let Some(origin) = &filter.origin_contains {
+            sql.push_str(" AND origin_url LIKE ?");
+            params.push(Box::new(format!("%{}%", origin)));
+        }
+
+        for dir in &filter.exclude_dirs {
+            let (prefix, pattern) = subtree_pattern(dir);
+            sql.push_str(" AND NOT (dir = ? OR dir LIKE ? ESCAPE '!')");
+            params.push(Box::new(prefix));
+            params.push(Box::new(pattern));
+        }
+
+        (sql, params)
+    }
+
+    /// Build the ORDER BY clause for a filter's sort settings
+    ///
+    /// Ties fall back to newest first so pagination stays stable.
+    fn order_by_clause(filter: &EventFilter) -> String {
+        let column = match filter.sort_key {
+            SortKey::Time => "created_at",
+            SortKey::Size => "size_bytes",
+            SortKey::Name => "filename COLLATE NOCASE",
+            SortKey::Type => "file_type",
+        };
+        let direction = if filter.sort_desc { "DESC" } else { "ASC" };
+
+        format!(" ORDER BY {} {}, created_at DESC, id DESC", column, direction)
+    }
+
+    /// Count events matching filter (for pagination info)
+    pub fn count_filtered_events(&self, filter: &EventFilter) -> Result<usize> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let (where_clause, params) = self.where_clause(filter);
+        let sql = format!("SELECT COUNT(*) FROM events WHERE {}", where_clause);
+
         let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
         
         let count: i64 = conn.query_row(&sql, params_refs.as_slice(), |row| row.get(0))?;
         Ok(count as usize)
     }
 
+    /// Where a jump to `time` lands in a filter's results: the number of
+    /// matching events listed before the first one at or past `time`
+    ///
+    /// Newest-first results count the events at or after `time`, so the
+    /// landing event is the first one before it; oldest-first results count
+    /// those before `time`. Only meaningful when sorting by time.
+    pub fn rank_at_time(&self, filter: &EventFilter, time: DateTime<Utc>) -> Result<usize> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let (where_clause, mut params) = self.where_clause(filter);
+        let comparison = if filter.sort_desc { ">=" } else { "<" };
+        let sql = format!(
+            "SELECT COUNT(*) FROM events WHERE {} AND created_at {} ?",
+            where_clause, comparison
+        );
+        params.push(Box::new(time.to_rfc3339()));
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let count: i64 = conn.query_row(&sql, params_refs.as_slice(), |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Total size in bytes of all events matching a filter (ignores limit/offset)
+    pub fn sum_filtered_size(&self, filter: &EventFilter) -> Result<u64> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let (where_clause, params) = self.where_clause(filter);
+        let sql = format!("SELECT COALESCE(SUM(size_bytes), 0) FROM events WHERE {}", where_clause);
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let size: i64 = conn.query_row(&sql, params_refs.as_slice(), |row| row.get(0))?;
+        Ok(size as u64)
+    }
+
+    /// Call `f` for every event in id order without loading them all at once
+    ///
+    /// The store is locked while iterating, so `f` must not call back into it.
+    pub fn for_each_event<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(FileEvent) -> Result<()>,
+    {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM events ORDER BY id", EVENT_COLUMNS))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            f(self.row_to_event(row)?)?;
+        }
+
+        Ok(())
+    }
+
     /// Get recent events (convenience method)
     pub fn get_recent_events(&self, limit: usize) -> Result<Vec<FileEvent>> {
         self.query_events(&EventFilter::new().with_limit(limit))
@@ -348,6 +639,143 @@ impl Store {
         Ok(())
     }
 
+    /// Set the file type of an event, e.g. to correct a wrong guess
+    ///
+    /// Re-detecting the same path later keeps the corrected type.
+    pub fn update_file_type(&self, id: i64, file_type: FileType) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        conn.execute(
+            "UPDATE events SET file_type = ? WHERE id = ?",
+            params![file_type.as_str(), id],
+        )?;
+
+        debug!("Updated file type for event {} to {}", id, file_type.as_str());
+        Ok(())
+    }
+
+    /// Point an existing event at a renamed/moved path
+    ///
+    /// Keeps the row's id, tags, notes and `created_at`. Any other row already
+    /// recorded at the new path is replaced. Returns the event id, or `None` if
+    /// nothing is tracked at `old_path`.
+    pub fn move_event(&self, old_path: &Path, new_event: &FileEvent) -> Result<Option<i64>> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let tx = conn.transaction()?;
+
+        let id: Option<i64> = tx
+            .query_row(
+                "SELECT id FROM events WHERE path = ?",
+                params![path_key(old_path)],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(id) = id else {
+            return Ok(None);
+        };
+
+        let new_path = path_key(&new_event.path);
+        tx.execute(
+            "DELETE FROM events WHERE path = ? AND id != ?",
+            params![new_path, id],
+        )?;
+        tx.execute(
+            "UPDATE events SET path = ?, path_bytes = ?, dir = ?, filename = ?, extension = ?, file_type = ?,
+                category = ?, size_bytes = COALESCE(?, size_bytes), deleted_at = NULL
+             WHERE id = ?",
+            params![
+                new_path,
+                raw_path_bytes(&new_event.path),
+                new_event.dir.to_string_lossy(),
+                new_event.filename,
+                new_event.extension(),
+                new_event.file_type.as_str(),
+                new_event.category,
+                new_event.size_bytes.map(|s| s as i64),
+                id,
+            ],
+        )?;
+        tx.commit()?;
+
+        debug!("Moved event {} from {} to {}", id, old_path.display(), new_event.path.display());
+        Ok(Some(id))
+    }
+
+    /// Point the live events under a renamed/moved directory at its new
+    /// location
+    ///
+    /// Like [`Store::move_event`] for each file below `old_dir`, at any
+    /// depth. Rows deleted from disk or kept as history stay where they
+    /// were. Returns the ids of the moved events.
+    pub fn move_dir(&self, old_dir: &Path, new_dir: &Path) -> Result<Vec<i64>> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let tx = conn.transaction()?;
+
+        let (prefix, pattern) = subtree_pattern(old_dir);
+        let rows: Vec<(i64, String, Option<Vec<u8>>)> = tx
+            .prepare(
+                "SELECT id, path, path_bytes FROM events
+                 WHERE (dir = ?1 OR dir LIKE ?2 ESCAPE '!') AND deleted_at IS NULL AND removed = 0",
+            )?
+            .query_map(params![prefix, pattern], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut moved = Vec::new();
+        for (id, path, path_bytes) in rows {
+            let old_path = path_bytes.map_or_else(|| PathBuf::from(path), path_from_raw_bytes);
+            let Ok(relative) = old_path.strip_prefix(old_dir) else {
+                continue;
+            };
+            let new_path = new_dir.join(relative);
+            let new_key = path_key(&new_path);
+            tx.execute("DELETE FROM events WHERE path = ? AND id != ?", params![new_key, id])?;
+            tx.execute(
+                "UPDATE events SET path = ?, path_bytes = ?, dir = ? WHERE id = ?",
+                params![
+                    new_key,
+                    raw_path_bytes(&new_path),
+                    new_path.parent().unwrap_or(new_dir).to_string_lossy(),
+                    id,
+                ],
+            )?;
+            moved.push(id);
+        }
+        tx.commit()?;
+
+        debug!("Moved {} events from {} to {}", moved.len(), old_dir.display(), new_dir.display());
+        Ok(moved)
+    }
+
+    /// Add and remove tags on many events at once
+    ///
+    /// Returns the number of events whose tags actually changed.
+    pub fn update_tags_bulk(&self, ids: &[i64], add: &[String], remove: &[String]) -> Result<usize> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let tx = conn.transaction()?;
+        let mut changed = 0;
+
+        {
+            let mut select = tx.prepare("SELECT tags FROM events WHERE id = ?")?;
+            let mut update = tx.prepare("UPDATE events SET tags = ? WHERE id = ?")?;
+
+            for &id in ids {
+                let current: Option<String> = select
+                    .query_row(params![id], |row| row.get(0))
+                    .optional()?;
+                let Some(current) = current else { continue };
+
+                if let Some(tags) = merge_tags(&current, add, remove) {
+                    update.execute(params![tags, id])?;
+                    changed += 1;
+                }
+            }
+        }
+
+        tx.commit()?;
+        debug!("Updated tags on {} of {} events", changed, ids.len());
+        Ok(changed)
+    }
+
     /// Update notes for an event
     pub fn update_notes(&self, id: i64, notes: &str) -> Result<()> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
@@ -375,6 +803,54 @@ impl Store {
         }
     }
 
+    /// Keep an event whose file was deleted through Ferret as history
+    ///
+    /// The row is marked deleted and left out of queries unless
+    /// `include_deleted` is set.
+    pub fn retire_event(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let rows = conn.execute(
+            "UPDATE events SET removed = 1, deleted_at = COALESCE(deleted_at, ?) WHERE id = ?",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+
+        if rows > 0 {
+            debug!("Retired event {}", id);
+        }
+
+        Ok(rows > 0)
+    }
+
+    /// Drop an event deleted through Ferret from the ledger, or with
+    /// `keep_history` retire it (see [`Self::retire_event`])
+    pub fn remove_event(&self, id: i64, keep_history: bool) -> Result<bool> {
+        if keep_history {
+            self.retire_event(id)
+        } else {
+            self.delete_event(id)
+        }
+    }
+
+    /// Mark the event at `path` as deleted from disk
+    ///
+    /// Returns `true` if a live row was marked. Rows that are already marked
+    /// keep their original deletion time.
+    pub fn mark_deleted(&self, path: &Path) -> Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let rows = conn.execute(
+            "UPDATE events SET deleted_at = ? WHERE path = ? AND deleted_at IS NULL",
+            params![Utc::now().to_rfc3339(), path_key(path)],
+        )?;
+
+        if rows > 0 {
+            debug!("Marked {} as deleted", path.display());
+        }
+
+        Ok(rows > 0)
+    }
+
     /// Delete events older than a given number of days
     pub fn cleanup_old_events(&self, retention_days: u32) -> Result<usize> {
         if retention_days == 0 {
@@ -382,11 +858,11 @@ impl Store {
         }
 
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        let cutoff = Utc::now() - Duration::days(retention_days as i64);
+        let cutoff = retention_cutoff(retention_days);
 
         let rows = conn.execute(
             "DELETE FROM events WHERE created_at < ?",
-            params![cutoff.to_rfc3339()],
+            params![cutoff],
         )?;
 
         if rows > 0 {
@@ -396,78 +872,207 @@ impl Store {
         Ok(rows)
     }
 
+    /// Statistics for the events `cleanup_old_events` would delete, without
+    /// deleting them
+    pub fn count_old_events(&self, retention_days: u32) -> Result<EventStats> {
+        if retention_days == 0 {
+            return Ok(EventStats::default());
+        }
+
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        scoped_stats(&conn, "created_at < ?", &[&retention_cutoff(retention_days)])
+    }
+
     /// Get statistics about tracked events
     pub fn get_stats(&self) -> Result<EventStats> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let mut stats = scoped_stats(&conn, "removed = 0", &[])?;
+        stats.weekday_hour = weekday_hour_matrix(&conn)?;
+        Ok(stats)
+    }
+
+    /// Get statistics for the events in one directory
+    ///
+    /// With `recursive`, events in subdirectories are included too.
+    pub fn get_stats_for_dir(&self, dir: &Path, recursive: bool) -> Result<EventStats> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let dir_str = dir.to_string_lossy().to_string();
+        if !recursive {
+            return scoped_stats(&conn, "removed = 0 AND dir = ?", &[&dir_str]);
+        }
 
-        let mut stats = EventStats::default();
+        let (prefix, pattern) = subtree_pattern(dir);
+        scoped_stats(&conn, "removed = 0 AND (dir = ? OR dir LIKE ? ESCAPE '!')", &[&prefix, &pattern])
+    }
 
-        // Total count and size
-        let (total_count, total_size): (i64, Option<i64>) = conn.query_row(
-            "SELECT COUNT(*), SUM(size_bytes) FROM events",
-            [],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+    /// Whether any event is in `dir` or below it
+    pub fn has_events_under(&self, dir: &Path) -> Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let (prefix, pattern) = subtree_pattern(dir);
+        let found: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM events WHERE dir = ?1 OR dir LIKE ?2 ESCAPE '!')",
+            params![prefix, pattern],
+            |row| row.get(0),
         )?;
-        stats.total_count = total_count as u64;
-        stats.total_size = total_size.unwrap_or(0) as u64;
-
-        // Stats for time periods
-        let periods = [
-            (Duration::hours(24), &mut stats.count_24h, &mut stats.size_24h),
-            (Duration::days(7), &mut stats.count_7d, &mut stats.size_7d),
-            (Duration::days(30), &mut stats.count_30d, &mut stats.size_30d),
-        ];
-
-        for (duration, count, size) in periods {
-            let since = Utc::now() - duration;
-            let (c, s): (i64, Option<i64>) = conn.query_row(
-                "SELECT COUNT(*), SUM(size_bytes) FROM events WHERE created_at >= ?",
-                params![since.to_rfc3339()],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )?;
-            *count = c as u64;
-            *size = s.unwrap_or(0) as u64;
+        Ok(found)
+    }
+
+    /// Count new files per time bucket from `since` until now
+    ///
+    /// Every bucket in the range is returned, including empty ones.
+    pub fn get_activity_histogram(
+        &self,
+        bucket: HistogramBucket,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ActivityBucket>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let start = bucket.start_of(since);
+        let now = Utc::now();
+
+        let mut buckets = Vec::new();
+        let mut bucket_start = start;
+        while bucket_start <= now {
+            buckets.push(ActivityBucket {
+                bucket_start,
+                count: 0,
+                size: 0,
+            });
+            bucket_start = bucket.next_start(bucket_start);
         }
 
-        // Breakdown by file type
         let mut stmt = conn.prepare(
-            "SELECT file_type, COUNT(*), COALESCE(SUM(size_bytes), 0)
-             FROM events GROUP BY file_type ORDER BY COUNT(*) DESC",
+            "SELECT created_at, COALESCE(size_bytes, 0) FROM events WHERE created_at >= ? AND removed = 0",
         )?;
-        let type_rows = stmt.query_map([], |row| {
-            let type_str: String = row.get(0)?;
-            let count: i64 = row.get(1)?;
-            let size: i64 = row.get(2)?;
-            Ok((type_str, count as u64, size as u64))
+        let rows = stmt.query_map(params![start.to_rfc3339()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
         })?;
 
-        for row in type_rows {
-            if let Ok((type_str, count, size)) = row {
-                if let Ok(file_type) = type_str.parse::<FileType>() {
-                    stats.by_type.push((file_type, count, size));
-                }
+        for (created_at, size) in rows.flatten() {
+            let Ok(created_at) = DateTime::parse_from_rfc3339(&created_at) else {
+                continue;
+            };
+            let key = bucket.start_of(created_at.with_timezone(&Utc));
+            if let Ok(idx) = buckets.binary_search_by_key(&key, |b| b.bucket_start) {
+                buckets[idx].count += 1;
+                buckets[idx].size += size as u64;
             }
         }
 
-        // Top directories by volume
+        Ok(buckets)
+    }
+
+    /// Count files and total size per extension, most files first
+    ///
+    /// Extensions are lowercased; files without one are grouped under "".
+    pub fn get_stats_by_extension(&self) -> Result<Vec<(String, u64, u64)>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
         let mut stmt = conn.prepare(
-            "SELECT dir, COUNT(*), COALESCE(SUM(size_bytes), 0)
-             FROM events GROUP BY dir ORDER BY SUM(size_bytes) DESC LIMIT 10",
+            "SELECT extension, COUNT(*), COALESCE(SUM(size_bytes), 0)
+             FROM events WHERE removed = 0 GROUP BY extension ORDER BY COUNT(*) DESC, extension",
         )?;
-        let dir_rows = stmt.query_map([], |row| {
-            let dir: String = row.get(0)?;
+        let rows = stmt.query_map([], |row| {
+            let ext: String = row.get(0)?;
             let count: i64 = row.get(1)?;
             let size: i64 = row.get(2)?;
-            Ok((PathBuf::from(dir), count as u64, size as u64))
+            Ok((ext, count as u64, size as u64))
         })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Count files per weekday and hour of day they were recorded at
+    ///
+    /// Rows are weekdays from Monday, columns hours from midnight, both in
+    /// local time so the pattern isn't shifted for users outside UTC.
+    pub fn get_weekday_hour_matrix(&self) -> Result<[[u64; 24]; 7]> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        weekday_hour_matrix(&conn)
+    }
+
+    /// Find groups of live files that share the same content hash
+    ///
+    /// Groups are ordered by wasted space, largest first.
+    pub fn find_duplicates(&self) -> Result<Vec<DuplicateGroup>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
 
-        for row in dir_rows {
-            if let Ok((dir, count, size)) = row {
-                stats.top_dirs.push((dir, count, size));
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM events
+             WHERE deleted_at IS NULL AND sha256 IN (
+                SELECT sha256 FROM events
+                WHERE sha256 IS NOT NULL AND deleted_at IS NULL
+                GROUP BY sha256 HAVING COUNT(*) > 1
+             )
+             ORDER BY sha256, created_at ASC",
+            EVENT_COLUMNS
+        ))?;
+        let events: Vec<FileEvent> = stmt
+            .query_map([], |row| self.row_to_event(row))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut groups: Vec<DuplicateGroup> = Vec::new();
+        for event in events {
+            let hash = event.sha256.clone().unwrap_or_default();
+            match groups.last_mut() {
+                Some(group) if group.sha256 == hash => group.events.push(event),
+                _ => groups.push(DuplicateGroup {
+                    sha256: hash,
+                    size_bytes: event.size_bytes.unwrap_or(0),
+                    events: vec![event],
+                }),
             }
         }
 
-        Ok(stats)
+        groups.sort_by_key(|g| std::cmp::Reverse(g.wasted_bytes()));
+        Ok(groups)
+    }
+
+    /// Find groups of live files with the same size and the same name once
+    /// copy suffixes like ` (1)` are removed
+    ///
+    /// A cheaper, fuzzier check than [`Store::find_duplicates`] that works
+    /// without hashing. Empty files are skipped. Groups are ordered by
+    /// wasted space, largest first.
+    pub fn find_name_size_duplicates(&self) -> Result<Vec<NameDuplicateGroup>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM events
+             WHERE deleted_at IS NULL AND size_bytes > 0
+             ORDER BY created_at ASC",
+            EVENT_COLUMNS
+        ))?;
+        let events = stmt
+            .query_map([], |row| self.row_to_event(row))?
+            .filter_map(|r| r.ok());
+
+        let mut groups: HashMap<(String, u64), Vec<FileEvent>> = HashMap::new();
+        for event in events {
+            let key = (normalize_duplicate_name(&event.filename), event.size_bytes.unwrap_or(0));
+            groups.entry(key).or_default().push(event);
+        }
+
+        let mut groups: Vec<NameDuplicateGroup> = groups
+            .into_iter()
+            .filter(|(_, events)| events.len() > 1)
+            .map(|((name, size_bytes), events)| NameDuplicateGroup { name, size_bytes, events })
+            .collect();
+        groups.sort_by(|a, b| b.wasted_bytes().cmp(&a.wasted_bytes()).then_with(|| a.name.cmp(&b.name)));
+        Ok(groups)
+    }
+
+    /// Count other live files with the given content hash
+    pub fn count_duplicates(&self, sha256: &str, exclude_id: i64) -> Result<u64> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM events WHERE sha256 = ? AND id != ? AND deleted_at IS NULL",
+            params![sha256, exclude_id],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
     }
 
     /// Get total event count
@@ -478,13 +1083,51 @@ impl Store {
         Ok(count as u64)
     }
 
+    /// Count the events first seen at or after `since`, with their total size
+    pub fn count_since(&self, since: DateTime<Utc>) -> Result<(u64, u64)> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let (count, size): (i64, Option<i64>) = conn.query_row(
+            "SELECT COUNT(*), SUM(size_bytes) FROM events WHERE created_at >= ? AND removed = 0",
+            params![since.to_rfc3339()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok((count as u64, size.unwrap_or(0) as u64))
+    }
+
     /// Check if a path already exists in the database
     pub fn path_exists(&self, path: &Path) -> Result<bool> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
 
         let exists: bool = conn.query_row(
             "SELECT EXISTS(SELECT 1 FROM events WHERE path = ?)",
-            params![path.to_string_lossy()],
+            params![path_key(path)],
+            |row| row.get(0),
+        )?;
+
+        Ok(exists)
+    }
+
+    /// Check if a path is tracked and has not been marked as deleted
+    pub fn path_is_live(&self, path: &Path) -> Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let live: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM events WHERE path = ? AND deleted_at IS NULL)",
+            params![path_key(path)],
+            |row| row.get(0),
+        )?;
+
+        Ok(live)
+    }
+
+    /// Check whether the FTS5 index was created for this database
+    fn detect_fts(&self) -> Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'events_fts')",
+            [],
             |row| row.get(0),
         )?;
 
@@ -502,26 +1145,99 @@ impl Store {
         let file_type: String = row.get(6)?;
         let tags: String = row.get(7)?;
         let notes: String = row.get(8)?;
+        let deleted_at: Option<String> = row.get(9)?;
+        let sha256: Option<String> = row.get(10)?;
+        let exec_bit: bool = row.get(11)?;
+        let quarantined: bool = row.get(12)?;
+        let category: Option<String> = row.get(13)?;
+        let origin_url: Option<String> = row.get(14)?;
+        let path_bytes: Option<Vec<u8>> = row.get(15)?;
+        let removed: bool = row.get(16)?;
 
         let created_at = DateTime::parse_from_rfc3339(&created_at)
             .map(|dt| dt.with_timezone(&Utc))
             .unwrap_or_else(|_| Utc::now());
 
+        let deleted_at = deleted_at
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+
         let file_type = file_type.parse().unwrap_or(FileType::Other);
 
+        // The text columns are lossy for non-UTF-8 paths; the bytes aren't
+        let (path, dir) = match path_bytes {
+            Some(bytes) => {
+                let path = path_from_raw_bytes(bytes);
+                let dir = path.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from(dir));
+                (path, dir)
+            }
+            None => (PathBuf::from(path), PathBuf::from(dir)),
+        };
+
         Ok(FileEvent {
             id: Some(id),
-            path: PathBuf::from(path),
-            dir: PathBuf::from(dir),
+            path,
+            dir,
             filename,
             size_bytes: size_bytes.map(|s| s as u64),
             created_at,
             file_type,
             tags,
             notes,
+            deleted_at,
+            sha256,
+            exec_bit,
+            quarantined,
+            category,
+            origin_url,
+            removed,
         })
     }
 
+    /// Rebuild the database file to reclaim free pages
+    ///
+    /// Needs the database to itself: if another process (usually a running
+    /// watcher) is using it, this fails with an error saying so.
+    pub fn vacuum(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        conn.execute_batch("VACUUM").map_err(|e| busy_error(e, "VACUUM"))?;
+        // In WAL mode the rebuilt pages land in the WAL; fold them back so the file shrinks
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| busy_error(e, "checkpoint"))?;
+
+        debug!("Vacuumed database {}", self.db_path.display());
+        Ok(())
+    }
+
+    /// Refresh the statistics SQLite's query planner uses
+    pub fn analyze(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        conn.execute_batch("ANALYZE").map_err(|e| busy_error(e, "ANALYZE"))?;
+        Ok(())
+    }
+
+    /// Run `PRAGMA integrity_check`, logging each problem found
+    ///
+    /// Returns whether the database is intact.
+    pub fn integrity_check(&self) -> Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let results = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| busy_error(e, "integrity check"))?;
+
+        let intact = results.len() == 1 && results[0] == "ok";
+        if !intact {
+            for problem in &results {
+                warn!("Integrity check: {}", problem);
+            }
+        }
+        Ok(intact)
+    }
+
     /// Get database path
     pub fn db_path(&self) -> &Path {
         &self.db_path
@@ -538,8 +1254,319 @@ impl Clone for Store {
         Self {
             conn: self.conn.clone(),
             db_path: self.db_path.clone(),
+            fts_enabled: self.fts_enabled,
+        }
+    }
+}
+
+/// Whether SQLite failed because another connection holds a lock
+fn is_busy(e: &rusqlite::Error) -> bool {
+    matches!(
+        e.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+    )
+}
+
+/// Whether any error in the chain is [`is_busy`]
+fn is_busy_error(e: &anyhow::Error) -> bool {
+    e.chain()
+        .any(|cause| cause.downcast_ref::<rusqlite::Error>().is_some_and(is_busy))
+}
+
+/// Explain a failed maintenance statement, calling out a locked database
+fn busy_error(e: rusqlite::Error, operation: &str) -> anyhow::Error {
+    if is_busy(&e) {
+        anyhow::anyhow!(
+            "{} failed: the database is in use by another process. Stop any running `ferret watch` and try again",
+            operation
+        )
+    } else {
+        anyhow::Error::new(e).context(format!("{} failed", operation))
+    }
+}
+
+/// Bucket every event's `created_at` by local weekday and hour
+fn weekday_hour_matrix(conn: &Connection) -> Result<[[u64; 24]; 7]> {
+    let mut matrix = [[0; 24]; 7];
+
+    let mut stmt = conn.prepare("SELECT created_at FROM events WHERE removed = 0")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    for created_at in rows.flatten() {
+        let Ok(created_at) = DateTime::parse_from_rfc3339(&created_at) else {
+            continue;
+        };
+        let local = created_at.with_timezone(&Local);
+        matrix[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += 1;
+    }
+
+    Ok(matrix)
+}
+
+/// Insert `event`, or update size and flags if its path already has a row
+fn insert_row(conn: &Connection, event: &FileEvent) -> rusqlite::Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO events (path, dir, filename, size_bytes, created_at, file_type, tags, notes, sha256, deleted_at, exec_bit, quarantined, category, origin_url, path_bytes, removed, extension)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+         ON CONFLICT(path) DO UPDATE SET
+            path_bytes = excluded.path_bytes,
+            size_bytes = COALESCE(excluded.size_bytes, size_bytes),
+            sha256 = COALESCE(excluded.sha256, sha256),
+            exec_bit = excluded.exec_bit,
+            quarantined = excluded.quarantined,
+            category = COALESCE(excluded.category, category),
+            origin_url = COALESCE(excluded.origin_url, origin_url),
+            deleted_at = NULL,
+            removed = 0",
+    )?
+    .execute(params![
+        path_key(&event.path),
+        event.dir.to_string_lossy(),
+        event.filename,
+        event.size_bytes.map(|s| s as i64),
+        event.created_at.to_rfc3339(),
+        event.file_type.as_str(),
+        event.tags,
+        event.notes,
+        event.sha256,
+        event.deleted_at.map(|t| t.to_rfc3339()),
+        event.exec_bit,
+        event.quarantined,
+        event.category,
+        event.origin_url,
+        raw_path_bytes(&event.path),
+        event.removed,
+        event.extension(),
+    ])?;
+    Ok(())
+}
+
+/// The unique `path` column value for `path`
+///
+/// That's the path's text, except for paths that aren't valid UTF-8
+/// (Unicode on Windows): their lossy text is followed by a NUL and the hex
+/// of their raw bytes. No real path contains a NUL, so two paths differing
+/// only in their invalid bytes get different keys, while the key still
+/// starts with the readable form for search.
+fn path_key(path: &Path) -> String {
+    let mut key = path.to_string_lossy().into_owned();
+    if let Some(bytes) = raw_path_bytes(path) {
+        key.push('\0');
+        for byte in bytes {
+            key.push_str(&format!("{:02x}", byte));
+        }
+    }
+    key
+}
+
+/// The exact bytes of `path` when its text form would lose information
+///
+/// Paths that aren't valid UTF-8 (Unicode on Windows) keep their raw form
+/// in `path_bytes` so they can be opened or deleted later; the `path`
+/// column only has [`path_key`].
+fn raw_path_bytes(path: &Path) -> Option<Vec<u8>> {
+    if path.to_str().is_some() {
+        return None;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(path.as_os_str().as_bytes().to_vec())
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        Some(path.as_os_str().encode_wide().flat_map(u16::to_le_bytes).collect())
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        None
+    }
+}
+
+/// Rebuild a path stored by [`raw_path_bytes`]
+fn path_from_raw_bytes(bytes: Vec<u8>) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        std::ffi::OsString::from_vec(bytes).into()
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStringExt;
+        let wide: Vec<u16> = bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+        std::ffi::OsString::from_wide(&wide).into()
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// The directory itself (without a trailing separator) and a LIKE pattern
+/// for everything below it, with wildcards escaped using `!`
+fn subtree_pattern(dir: &Path) -> (String, String) {
+    let dir_str = dir.to_string_lossy();
+    let prefix = dir_str.trim_end_matches(std::path::MAIN_SEPARATOR);
+    let pattern = format!(
+        "{}{}%",
+        prefix.replace('!', "!!").replace('%', "!%").replace('_', "!_"),
+        std::path::MAIN_SEPARATOR
+    );
+    (prefix.to_string(), pattern)
+}
+
+/// Timestamp (RFC 3339) before which events fall outside the retention window
+fn retention_cutoff(retention_days: u32) -> String {
+    (Utc::now() - Duration::days(retention_days as i64)).to_rfc3339()
+}
+
+/// Compute `EventStats` over the events matching a SQL condition
+fn scoped_stats(conn: &Connection, scope: &str, scope_params: &[&dyn rusqlite::ToSql]) -> Result<EventStats> {
+    let mut stats = EventStats::default();
+
+    // Total count and size
+    let (total_count, total_size): (i64, Option<i64>) = conn.query_row(
+        &format!("SELECT COUNT(*), SUM(size_bytes) FROM events WHERE {}", scope),
+        scope_params,
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    stats.total_count = total_count as u64;
+    stats.total_size = total_size.unwrap_or(0) as u64;
+
+    // Stats for time periods
+    let periods = [
+        (Duration::hours(24), &mut stats.count_24h, &mut stats.size_24h),
+        (Duration::days(7), &mut stats.count_7d, &mut stats.size_7d),
+        (Duration::days(30), &mut stats.count_30d, &mut stats.size_30d),
+    ];
+
+    for (duration, count, size) in periods {
+        let since = (Utc::now() - duration).to_rfc3339();
+        let mut period_params = scope_params.to_vec();
+        period_params.push(&since);
+        let (c, s): (i64, Option<i64>) = conn.query_row(
+            &format!(
+                "SELECT COUNT(*), SUM(size_bytes) FROM events WHERE {} AND created_at >= ?",
+                scope
+            ),
+            period_params.as_slice(),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        *count = c as u64;
+        *size = s.unwrap_or(0) as u64;
+    }
+
+    // Breakdown by file type
+    let mut stmt = conn.prepare(&format!(
+        "SELECT file_type, COUNT(*), COALESCE(SUM(size_bytes), 0)
+         FROM events WHERE {} GROUP BY file_type ORDER BY COUNT(*) DESC",
+        scope
+    ))?;
+    let type_rows = stmt.query_map(scope_params, |row| {
+        let type_str: String = row.get(0)?;
+        let count: i64 = row.get(1)?;
+        let size: i64 = row.get(2)?;
+        Ok((type_str, count as u64, size as u64))
+    })?;
+
+    for row in type_rows {
+        if let Ok((type_str, count, size)) = row {
+            if let Ok(file_type) = type_str.parse::<FileType>() {
+                stats.by_type.push((file_type, count, size));
+            }
+        }
+    }
+
+    // Top directories by volume
+    let mut stmt = conn.prepare(&format!(
+        "SELECT dir, COUNT(*), COALESCE(SUM(size_bytes), 0)
+         FROM events WHERE {} GROUP BY dir ORDER BY SUM(size_bytes) DESC LIMIT 10",
+        scope
+    ))?;
+    let dir_rows = stmt.query_map(scope_params, |row| {
+        let dir: String = row.get(0)?;
+        let count: i64 = row.get(1)?;
+        let size: i64 = row.get(2)?;
+        Ok((PathBuf::from(dir), count as u64, size as u64))
+    })?;
+
+    for row in dir_rows {
+        if let Ok((dir, count, size)) = row {
+            stats.top_dirs.push((dir, count, size));
+        }
+    }
+
+    stats.by_size_bucket = size_distribution(conn, scope, scope_params)?;
+
+    Ok(stats)
+}
+
+/// Group events into the `SIZE_BUCKET_BOUNDS` ranges with a SQL `CASE`
+///
+/// Every bucket is returned, including empty ones. Files of unknown size
+/// are left out.
+fn size_distribution(
+    conn: &Connection,
+    scope: &str,
+    scope_params: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<SizeBucket>> {
+    let mut case = String::from("CASE");
+    for (idx, bound) in SIZE_BUCKET_BOUNDS.iter().enumerate() {
+        case.push_str(&format!(" WHEN size_bytes < {} THEN {}", bound, idx));
+    }
+    case.push_str(&format!(" ELSE {} END", SIZE_BUCKET_BOUNDS.len()));
+
+    let sql = format!(
+        "SELECT {} AS bucket, COUNT(*), COALESCE(SUM(size_bytes), 0)
+         FROM events WHERE size_bytes IS NOT NULL AND {} GROUP BY bucket",
+        case, scope
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(scope_params, |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+    })?;
+
+    let mut buckets = SizeBucket::empty_buckets();
+    for (idx, count, size) in rows.flatten() {
+        if let Some(bucket) = buckets.get_mut(idx as usize) {
+            bucket.count = count as u64;
+            bucket.size = size as u64;
         }
     }
+
+    Ok(buckets)
+}
+
+/// Register `regexp(pattern, text)` so SQL can use `text REGEXP pattern`
+///
+/// The compiled pattern is cached per statement via SQLite auxdata.
+fn register_regexp(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let regex: Arc<regex::Regex> = ctx.get_or_create_aux(0, |pattern| {
+                regex::Regex::new(pattern.as_str()?)
+                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+            })?;
+            let text = ctx
+                .get_raw(1)
+                .as_str()
+                .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))?;
+            Ok(regex.is_match(text))
+        },
+    )?;
+    Ok(())
+}
+
+/// Turn free text into an FTS5 query: every word must match as a prefix
+fn fts_match_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 #[cfg(test)]
@@ -557,6 +1584,13 @@ mod tests {
             file_type: FileType::Document,
             tags: String::new(),
             notes: String::new(),
+            deleted_at: None,
+            sha256: None,
+            exec_bit: false,
+            quarantined: false,
+            category: None,
+            origin_url: None,
+            removed: false,
         }
     }
 
@@ -612,6 +1646,11 @@ mod tests {
         assert_eq!(docs.len(), 1);
         assert_eq!(docs[0].file_type, FileType::Document);
 
+        // Any of several types
+        let filter = EventFilter::new().with_type(FileType::Document).with_type(FileType::Code);
+        assert_eq!(store.query_events(&filter).unwrap().len(), 2);
+        assert_eq!(store.count_filtered_events(&filter).unwrap(), 2);
+
         // Filter by size
         let large = store.query_events(&EventFilter::new().with_min_size(1000)).unwrap();
         assert_eq!(large.len(), 1);
@@ -633,6 +1672,113 @@ mod tests {
         assert_eq!(retrieved.notes, "This is a test note");
     }
 
+    #[test]
+    fn test_update_tags_bulk() {
+        let store = Store::in_memory().unwrap();
+        let a = store.insert_event(&create_test_event("/tmp/a.txt")).unwrap();
+        let b = store.insert_event(&create_test_event("/tmp/b.txt")).unwrap();
+        store.update_tags(a, "keep, stale").unwrap();
+
+        let add = vec!["keep".to_string()];
+        let remove = vec!["stale".to_string()];
+        assert_eq!(store.update_tags_bulk(&[a, b], &add, &remove).unwrap(), 2);
+        assert_eq!(store.get_event(a).unwrap().unwrap().tags, "keep");
+        assert_eq!(store.get_event(b).unwrap().unwrap().tags, "keep");
+
+        // Re-applying is a no-op
+        assert_eq!(store.update_tags_bulk(&[a, b], &add, &remove).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_move_event() {
+        let store = Store::in_memory().unwrap();
+        let mut event = create_test_event("/tmp/Downloads/report.pdf");
+        event.created_at = Utc::now() - Duration::days(3);
+        let id = store.insert_event(&event).unwrap();
+        store.update_tags(id, "work").unwrap();
+        store.update_notes(id, "Q3 numbers").unwrap();
+
+        // A row already recorded at the destination is replaced
+        store.insert_event(&create_test_event("/tmp/Downloads/reports/report.pdf")).unwrap();
+
+        let mut moved = create_test_event("/tmp/Downloads/reports/report.pdf");
+        moved.dir = PathBuf::from("/tmp/Downloads/reports");
+        assert_eq!(
+            store.move_event(Path::new("/tmp/Downloads/report.pdf"), &moved).unwrap(),
+            Some(id)
+        );
+
+        let retrieved = store.get_event(id).unwrap().unwrap();
+        assert_eq!(retrieved.path, PathBuf::from("/tmp/Downloads/reports/report.pdf"));
+        assert_eq!(retrieved.dir, PathBuf::from("/tmp/Downloads/reports"));
+        assert_eq!(retrieved.tags, "work");
+        assert_eq!(retrieved.notes, "Q3 numbers");
+        assert_eq!(retrieved.created_at.timestamp(), event.created_at.timestamp());
+        assert_eq!(store.count_events().unwrap(), 1);
+
+        // Untracked source
+        assert_eq!(store.move_event(Path::new("/tmp/nope"), &moved).unwrap(), None);
+    }
+
+    #[test]
+    fn test_move_dir() {
+        let store = Store::in_memory().unwrap();
+        let insert = |path: &str| {
+            let mut event = create_test_event(path);
+            event.dir = PathBuf::from(path).parent().unwrap().to_path_buf();
+            store.insert_event(&event).unwrap()
+        };
+        let top = insert("/dl/photos/a.jpg");
+        let nested = insert("/dl/photos/2024/b.jpg");
+        let sibling = insert("/dl/photos-old/c.jpg");
+        let gone = insert("/dl/photos/d.jpg");
+        store.mark_deleted(Path::new("/dl/photos/d.jpg")).unwrap();
+        store.update_tags(top, "trip").unwrap();
+
+        let mut moved = store.move_dir(Path::new("/dl/photos"), Path::new("/dl/pics")).unwrap();
+        moved.sort();
+        assert_eq!(moved, vec![top, nested]);
+
+        let event = store.get_event(top).unwrap().unwrap();
+        assert_eq!(event.path, PathBuf::from("/dl/pics/a.jpg"));
+        assert_eq!(event.dir, PathBuf::from("/dl/pics"));
+        assert_eq!(event.tags, "trip");
+        let event = store.get_event(nested).unwrap().unwrap();
+        assert_eq!(event.path, PathBuf::from("/dl/pics/2024/b.jpg"));
+        assert_eq!(event.dir, PathBuf::from("/dl/pics/2024"));
+        assert!(store.get_event_by_path(Path::new("/dl/pics/2024/b.jpg")).unwrap().is_some());
+
+        // A folder sharing the name as a prefix, and deleted rows, stay put
+        assert_eq!(store.get_event(sibling).unwrap().unwrap().path, PathBuf::from("/dl/photos-old/c.jpg"));
+        assert_eq!(store.get_event(gone).unwrap().unwrap().path, PathBuf::from("/dl/photos/d.jpg"));
+    }
+
+    #[test]
+    fn test_for_each_event() {
+        let store = Store::in_memory().unwrap();
+        for path in ["/tmp/a", "/tmp/b", "/tmp/c"] {
+            store.insert_event(&create_test_event(path)).unwrap();
+        }
+
+        let mut paths = Vec::new();
+        store
+            .for_each_event(|event| {
+                paths.push(event.path);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(paths, ["/tmp/a", "/tmp/b", "/tmp/c"].map(PathBuf::from));
+
+        // Errors from the callback stop the iteration
+        let mut seen = 0;
+        let result = store.for_each_event(|_| {
+            seen += 1;
+            anyhow::bail!("stop")
+        });
+        assert!(result.is_err());
+        assert_eq!(seen, 1);
+    }
+
     #[test]
     fn test_delete_event() {
         let store = Store::in_memory().unwrap();
@@ -671,6 +1817,24 @@ mod tests {
         assert_eq!(stats.count_24h, 2);
     }
 
+    #[test]
+    fn test_size_distribution() {
+        let store = Store::in_memory().unwrap();
+        let sizes = [Some(10), Some(1023), Some(1024), Some(5 << 20), Some(2 << 30), None];
+        for (i, size) in sizes.into_iter().enumerate() {
+            let mut e = create_test_event(&format!("/test/{}.bin", i));
+            e.size_bytes = size;
+            store.insert_event(&e).unwrap();
+        }
+
+        let buckets = store.get_stats().unwrap().by_size_bucket;
+        let counts: Vec<u64> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![2, 1, 1, 0, 1]);
+        assert_eq!(buckets[0].size, 1033);
+        assert_eq!(buckets[1].min_bytes, 1024);
+        assert_eq!(buckets[4].max_bytes, None);
+    }
+
     #[test]
     fn test_upsert_behavior() {
         let store = Store::in_memory().unwrap();
@@ -697,4 +1861,645 @@ mod tests {
         let retrieved = store.get_event_by_path(Path::new("/test/file.txt")).unwrap().unwrap();
         assert_eq!(retrieved.size_bytes, Some(200));
     }
+
+    #[test]
+    fn test_mark_deleted_and_recreate() {
+        let store = Store::in_memory().unwrap();
+        let event = create_test_event("/test/file.txt");
+        store.insert_event(&event).unwrap();
+
+        assert!(store.mark_deleted(Path::new("/test/file.txt")).unwrap());
+        // Already marked, so a second removal is a no-op
+        assert!(!store.mark_deleted(Path::new("/test/file.txt")).unwrap());
+        assert!(!store.path_is_live(Path::new("/test/file.txt")).unwrap());
+
+        let retrieved = store.get_event_by_path(Path::new("/test/file.txt")).unwrap().unwrap();
+        assert!(retrieved.deleted_at.is_some());
+
+        // Re-creating the file clears the deletion marker
+        store.insert_event(&event).unwrap();
+        let retrieved = store.get_event_by_path(Path::new("/test/file.txt")).unwrap().unwrap();
+        assert!(retrieved.deleted_at.is_none());
+        assert!(store.path_is_live(Path::new("/test/file.txt")).unwrap());
+    }
+
+    #[test]
+    fn test_activity_histogram() {
+        let store = Store::in_memory().unwrap();
+        let now = Utc::now();
+
+        for (path, age) in [("/tmp/a", 2), ("/tmp/b", 2), ("/tmp/c", 0), ("/tmp/old", 10)] {
+            let mut event = create_test_event(path);
+            event.created_at = now - Duration::days(age);
+            event.size_bytes = Some(100);
+            store.insert_event(&event).unwrap();
+        }
+
+        let buckets = store
+            .get_activity_histogram(HistogramBucket::Day, now - Duration::days(3))
+            .unwrap();
+
+        // Four days including today, with empty days kept
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(buckets.iter().map(|b| b.count).sum::<u64>(), 3);
+        assert_eq!(buckets[1].count, 2);
+        assert_eq!(buckets[1].size, 200);
+        assert_eq!(buckets[2].count, 0);
+        assert_eq!(buckets[3].count, 1);
+    }
+
+    #[test]
+    fn test_weekday_hour_matrix() {
+        use chrono::TimeZone;
+
+        let store = Store::in_memory().unwrap();
+
+        // Monday 2024-03-04 and Saturday 2024-03-09, in local time
+        let times = [(4, 9), (4, 9), (4, 23), (9, 0)];
+        for (i, (day, hour)) in times.into_iter().enumerate() {
+            let mut event = create_test_event(&format!("/tmp/heat{}", i));
+            event.created_at = Local
+                .with_ymd_and_hms(2024, 3, day, hour, 30, 0)
+                .earliest()
+                .unwrap()
+                .with_timezone(&Utc);
+            store.insert_event(&event).unwrap();
+        }
+
+        let matrix = store.get_weekday_hour_matrix().unwrap();
+        assert_eq!(matrix[0][9], 2);
+        assert_eq!(matrix[0][23], 1);
+        assert_eq!(matrix[5][0], 1);
+        assert_eq!(matrix.iter().flatten().sum::<u64>(), 4);
+        assert_eq!(store.get_stats().unwrap().weekday_hour, matrix);
+    }
+
+    #[test]
+    fn test_stats_by_extension() {
+        let store = Store::in_memory().unwrap();
+        for (path, size) in [("/dl/a.iso", 4000), ("/dl/b.ISO", 6000), ("/dl/c.zip", 10), ("/dl/LICENSE", 5)] {
+            let mut event = create_test_event(path);
+            event.size_bytes = Some(size);
+            store.insert_event(&event).unwrap();
+        }
+
+        let by_ext = store.get_stats_by_extension().unwrap();
+        assert_eq!(
+            by_ext,
+            vec![
+                ("iso".to_string(), 2, 10000),
+                ("".to_string(), 1, 5),
+                ("zip".to_string(), 1, 10),
+            ]
+        );
+
+        // A rename that changes the extension moves the file between groups
+        store.move_event(Path::new("/dl/c.zip"), &create_test_event("/dl/c.iso")).unwrap();
+        let by_ext = store.get_stats_by_extension().unwrap();
+        assert_eq!(by_ext[0], ("iso".to_string(), 3, 11024));
+        assert_eq!(by_ext.len(), 2);
+    }
+
+    #[test]
+    fn test_stats_leave_out_history() {
+        let store = Store::in_memory().unwrap();
+        let kept = store.insert_event(&create_test_event("/dl/kept.iso")).unwrap();
+        let retired = store.insert_event(&create_test_event("/dl/retired.zip")).unwrap();
+        store.remove_event(retired, true).unwrap();
+
+        let stats = store.get_stats().unwrap();
+        assert_eq!((stats.total_count, stats.total_size, stats.count_24h), (1, 1024, 1));
+        assert_eq!(stats.by_size_bucket.iter().map(|b| b.count).sum::<u64>(), 1);
+        assert_eq!(stats.weekday_hour.iter().flatten().sum::<u64>(), 1);
+        assert_eq!(store.get_stats_for_dir(Path::new("/test"), true).unwrap().total_count, 1);
+        assert_eq!(store.get_stats_by_extension().unwrap(), vec![("iso".to_string(), 1, 1024)]);
+        assert_eq!(store.count_since(Utc::now() - Duration::hours(1)).unwrap(), (1, 1024));
+        let histogram = store.get_activity_histogram(HistogramBucket::Day, Utc::now()).unwrap();
+        assert_eq!(histogram.iter().map(|b| b.count).sum::<u64>(), 1);
+
+        // Still there as history
+        assert!(store.get_event(retired).unwrap().unwrap().removed);
+        assert!(store.get_event(kept).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let store = Store::in_memory().unwrap();
+
+        for path in ["/test/a.zip", "/test/b.zip", "/test/c.zip"] {
+            store.insert_event(&{
+                let mut e = create_test_event(path);
+                e.sha256 = Some("abc".to_string());
+                e
+            }).unwrap();
+        }
+        store.insert_event(&{
+            let mut e = create_test_event("/test/unique.zip");
+            e.sha256 = Some("def".to_string());
+            e
+        }).unwrap();
+
+        let groups = store.find_duplicates().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].events.len(), 3);
+        assert_eq!(groups[0].wasted_bytes(), 2048);
+
+        let id = groups[0].events[0].id.unwrap();
+        assert_eq!(store.count_duplicates("abc", id).unwrap(), 2);
+
+        // Deleted copies no longer count as duplicates
+        store.mark_deleted(Path::new("/test/b.zip")).unwrap();
+        assert_eq!(store.count_duplicates("abc", id).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_find_name_size_duplicates() {
+        let store = Store::in_memory().unwrap();
+
+        for path in ["/dl/report.pdf", "/dl/report (1).pdf", "/dl/report copy.pdf", "/dl/other.pdf"] {
+            store.insert_event(&create_test_event(path)).unwrap();
+        }
+        // Same name but a different size isn't a copy
+        store.insert_event(&{
+            let mut e = create_test_event("/dl/report-2.pdf");
+            e.size_bytes = Some(10);
+            e
+        }).unwrap();
+
+        let groups = store.find_name_size_duplicates().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].name, "report.pdf");
+        assert_eq!(groups[0].events.len(), 3);
+        assert_eq!(groups[0].wasted_bytes(), 2048);
+
+        store.mark_deleted(Path::new("/dl/report copy.pdf")).unwrap();
+        assert_eq!(store.find_name_size_duplicates().unwrap()[0].events.len(), 2);
+    }
+
+    #[test]
+    fn test_sum_filtered_size() {
+        let store = Store::in_memory().unwrap();
+        for (path, size) in [("/tmp/a.mp4", 300), ("/tmp/b.mp4", 200), ("/tmp/c.txt", 50)] {
+            let mut event = create_test_event(path);
+            event.size_bytes = Some(size);
+            store.insert_event(&event).unwrap();
+        }
+        let mut unknown = create_test_event("/tmp/d.mp4");
+        unknown.size_bytes = None;
+        store.insert_event(&unknown).unwrap();
+
+        assert_eq!(store.sum_filtered_size(&EventFilter::new()).unwrap(), 550);
+
+        // Covers every match, not just the requested page
+        let filter = EventFilter::new().with_path_contains(".mp4").with_limit(1);
+        assert_eq!(store.sum_filtered_size(&filter).unwrap(), 500);
+        assert_eq!(store.sum_filtered_size(&filter.with_min_size(250)).unwrap(), 300);
+    }
+
+    #[test]
+    fn test_filename_prefix_filter() {
+        let store = Store::in_memory().unwrap();
+        for path in ["/dl/IMG_001.jpg", "/dl/IMG_002.jpg", "/dl/IMGX.jpg", "/dl/other/IMG_003.jpg", "/dl/a.jpg"] {
+            store.insert_event(&create_test_event(path)).unwrap();
+        }
+
+        // `_` is matched literally rather than as a LIKE wildcard
+        let filter = EventFilter::new().with_filename_prefix("IMG_");
+        let mut paths: Vec<PathBuf> = store.query_events(&filter).unwrap().into_iter().map(|e| e.path).collect();
+        paths.sort();
+        assert_eq!(paths, ["/dl/IMG_001.jpg", "/dl/IMG_002.jpg", "/dl/other/IMG_003.jpg"].map(PathBuf::from));
+        let filter = EventFilter::new().with_filename_prefix("IMG");
+        assert_eq!(store.count_filtered_events(&filter).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_rank_at_time() {
+        let store = Store::in_memory().unwrap();
+        let start = Utc::now() - Duration::days(10);
+        for day in 0..10 {
+            let mut event = create_test_event(&format!("/tmp/day{}.txt", day));
+            event.created_at = start + Duration::days(day);
+            store.insert_event(&event).unwrap();
+        }
+        let boundary = start + Duration::days(4);
+
+        // Newest first: days 4-9 come before the first event older than day 4
+        let filter = EventFilter::new();
+        assert_eq!(store.rank_at_time(&filter, boundary).unwrap(), 6);
+        // Oldest first: days 0-3 come before day 4
+        let filter = EventFilter { sort_key: SortKey::Time, sort_desc: false, ..EventFilter::new() };
+        assert_eq!(store.rank_at_time(&filter, boundary).unwrap(), 4);
+        // Only matching events count
+        let filter = EventFilter::new().with_path_contains("day9");
+        assert_eq!(store.rank_at_time(&filter, boundary).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_query_sorting() {
+        let store = Store::in_memory().unwrap();
+
+        for (path, size) in [("/test/b.txt", 300), ("/test/a.txt", 100), ("/test/C.txt", 200)] {
+            store.insert_event(&{
+                let mut e = create_test_event(path);
+                e.size_bytes = Some(size);
+                e
+            }).unwrap();
+        }
+
+        let by_size = store
+            .query_events(&EventFilter { sort_key: SortKey::Size, sort_desc: true, ..EventFilter::new() })
+            .unwrap();
+        let sizes: Vec<u64> = by_size.iter().filter_map(|e| e.size_bytes).collect();
+        assert_eq!(sizes, vec![300, 200, 100]);
+
+        let by_name = store
+            .query_events(&EventFilter { sort_key: SortKey::Name, sort_desc: false, ..EventFilter::new() })
+            .unwrap();
+        let names: Vec<&str> = by_name.iter().map(|e| e.filename.as_str()).collect();
+        assert_eq!(names, vec!["a.txt", "b.txt", "C.txt"]);
+
+        // Sorting composes with pagination
+        let ascending = EventFilter { sort_key: SortKey::Size, sort_desc: false, ..EventFilter::new() };
+        let page = store.query_events(&ascending.with_pagination(1, 1)).unwrap();
+        assert_eq!(page[0].size_bytes, Some(200));
+    }
+
+    #[test]
+    fn test_text_query_matches_notes_and_tags() {
+        let mut store = Store::in_memory().unwrap();
+        assert!(store.fts_enabled);
+
+        let id = store.insert_event(&create_test_event("/test/scan_0042.pdf")).unwrap();
+        store.update_notes(id, "2023 invoice from ACME").unwrap();
+        let id = store.insert_event(&create_test_event("/test/photo.jpg")).unwrap();
+        store.update_tags(id, "holiday, family").unwrap();
+        store.insert_event(&create_test_event("/test/other.txt")).unwrap();
+
+        // Same results with and without the FTS index
+        for fts_enabled in [true, false] {
+            store.fts_enabled = fts_enabled;
+
+            let mut filter = EventFilter::new();
+            filter.text_query = Some("invoice".to_string());
+            let found = store.query_events(&filter).unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].filename, "scan_0042.pdf");
+            assert_eq!(store.count_filtered_events(&filter).unwrap(), 1);
+
+            filter.text_query = Some("holiday".to_string());
+            assert_eq!(store.query_events(&filter).unwrap()[0].filename, "photo.jpg");
+
+            // Substring path matching still works
+            filter.text_query = Some("her.t".to_string());
+            assert_eq!(store.query_events(&filter).unwrap()[0].filename, "other.txt");
+        }
+    }
+
+    #[test]
+    fn test_path_regex_filter() {
+        let store = Store::in_memory().unwrap();
+        for path in ["/tmp/a.tar.gz", "/tmp/tar.gz.txt", "/tmp/b.zip"] {
+            store.insert_event(&create_test_event(path)).unwrap();
+        }
+
+        let filter = EventFilter { path_regex: Some(r"\.tar\.gz$".to_string()), ..EventFilter::new() };
+        let events = store.query_events(&filter).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].path, PathBuf::from("/tmp/a.tar.gz"));
+        assert_eq!(store.count_filtered_events(&filter).unwrap(), 1);
+
+        // Invalid patterns surface as errors rather than panics
+        let bad = EventFilter { path_regex: Some("(unclosed".to_string()), ..EventFilter::new() };
+        assert!(store.count_filtered_events(&bad).is_err());
+    }
+
+    #[test]
+    fn test_fts_match_query() {
+        assert_eq!(fts_match_query("invoice"), "\"invoice\"*");
+        assert_eq!(fts_match_query(" a  \"b\" "), "\"a\"* \"\"\"b\"\"\"*");
+        assert_eq!(fts_match_query("   "), "");
+    }
+
+    #[test]
+    fn test_exec_only_filter() {
+        let store = Store::in_memory().unwrap();
+
+        let mut runnable = create_test_event("/tmp/run.sh");
+        runnable.file_type = FileType::Executable;
+        runnable.exec_bit = true;
+        store.insert_event(&runnable).unwrap();
+
+        let mut inert = create_test_event("/tmp/setup.exe");
+        inert.file_type = FileType::Executable;
+        store.insert_event(&inert).unwrap();
+
+        store.insert_event(&create_test_event("/tmp/notes.txt")).unwrap();
+
+        let filter = EventFilter::new().with_exec_only();
+        let events = store.query_events(&filter).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].path, PathBuf::from("/tmp/run.sh"));
+        assert!(events[0].exec_bit);
+        assert!(!events[0].quarantined);
+        assert_eq!(store.count_filtered_events(&filter).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_category_filter() {
+        let store = Store::in_memory().unwrap();
+
+        let mut book = create_test_event("/tmp/novel.epub");
+        book.category = Some("ebook".to_string());
+        store.insert_event(&book).unwrap();
+        store.insert_event(&create_test_event("/tmp/report.pdf")).unwrap();
+
+        let filter = EventFilter::new().with_category("EBook");
+        let events = store.query_events(&filter).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].category.as_deref(), Some("ebook"));
+        assert_eq!(store.count_filtered_events(&filter).unwrap(), 1);
+
+        // Re-detection without a category (e.g. removed from the config) keeps it
+        store.insert_event(&create_test_event("/tmp/novel.epub")).unwrap();
+        assert_eq!(store.count_filtered_events(&filter).unwrap(), 1);
+
+        // Files in either of two categories
+        let mut photo = create_test_event("/tmp/beach.jpg");
+        photo.category = Some("photo".to_string());
+        store.insert_event(&photo).unwrap();
+        let either = filter.with_category("photo");
+        assert_eq!(store.count_filtered_events(&either).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_count_old_events() {
+        let store = Store::in_memory().unwrap();
+        let mut old = create_test_event("/tmp/old.zip");
+        old.created_at = Utc::now() - Duration::days(200);
+        old.file_type = FileType::Archive;
+        store.insert_event(&old).unwrap();
+        store.insert_event(&create_test_event("/tmp/new.txt")).unwrap();
+
+        let stats = store.count_old_events(90).unwrap();
+        assert_eq!((stats.total_count, stats.total_size), (1, 1024));
+        assert_eq!(stats.by_type, vec![(FileType::Archive, 1, 1024)]);
+        assert_eq!(store.count_old_events(0).unwrap().total_count, 0);
+
+        // The preview doesn't delete, and matches what cleanup removes
+        assert_eq!(store.count_events().unwrap(), 2);
+        assert_eq!(store.cleanup_old_events(90).unwrap(), 1);
+        assert_eq!(store.count_old_events(90).unwrap().total_count, 0);
+    }
+
+    #[test]
+    fn test_count_since() {
+        let store = Store::in_memory().unwrap();
+        let mut old = create_test_event("/tmp/old.zip");
+        old.created_at = Utc::now() - Duration::days(2);
+        store.insert_event(&old).unwrap();
+        store.insert_event(&create_test_event("/tmp/a.txt")).unwrap();
+        store.insert_event(&create_test_event("/tmp/b.txt")).unwrap();
+
+        assert_eq!(store.count_since(Utc::now() - Duration::days(1)).unwrap(), (2, 2048));
+        assert_eq!(store.count_since(Utc::now() + Duration::days(1)).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_update_file_type() {
+        let store = Store::in_memory().unwrap();
+        let id = store.insert_event(&create_test_event("/tmp/data.bin")).unwrap();
+
+        store.update_file_type(id, FileType::Archive).unwrap();
+        assert_eq!(store.get_event(id).unwrap().unwrap().file_type, FileType::Archive);
+
+        // Seeing the file again doesn't undo the correction
+        store.insert_event(&create_test_event("/tmp/data.bin")).unwrap();
+        assert_eq!(store.get_event(id).unwrap().unwrap().file_type, FileType::Archive);
+    }
+
+    #[test]
+    fn test_origin_filter() {
+        let store = Store::in_memory().unwrap();
+
+        let mut download = create_test_event("/tmp/setup.exe");
+        download.origin_url = Some("https://downloads.example.com/setup.exe".to_string());
+        store.insert_event(&download).unwrap();
+        store.insert_event(&create_test_event("/tmp/notes.txt")).unwrap();
+
+        let filter = EventFilter::new().with_origin_contains("example.com");
+        let events = store.query_events(&filter).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].origin_url, download.origin_url);
+        assert_eq!(store.count_filtered_events(&filter).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_query_events_streaming() {
+        let store = Store::in_memory().unwrap();
+        for i in 0..120 {
+            store.insert_event(&create_test_event(&format!("/tmp/file{}.txt", i))).unwrap();
+        }
+
+        let mut paths = Vec::new();
+        let count = store
+            .query_events_streaming(&EventFilter::new().with_limit(0), |event| {
+                paths.push(event.path);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(count, 120);
+        assert_eq!(paths.len(), 120);
+
+        // A limit is still honoured, in the same order as query_events
+        let filter = EventFilter::new().with_limit(10).with_offset(5);
+        let mut streamed = Vec::new();
+        store
+            .query_events_streaming(&filter, |event| {
+                streamed.push(event.id);
+                Ok(())
+            })
+            .unwrap();
+        let queried: Vec<_> = store.query_events(&filter).unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(streamed, queried);
+
+        // An error from the callback stops the iteration
+        let result = store.query_events_streaming(&EventFilter::new().with_limit(0), |_| anyhow::bail!("stop"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_maintenance() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store = Store::new(&temp_dir.path().join("ferret.db"), std::time::Duration::from_secs(5)).unwrap();
+        for i in 0..50 {
+            let id = store.insert_event(&create_test_event(&format!("/tmp/file{}.txt", i))).unwrap();
+            store.delete_event(id).unwrap();
+        }
+
+        assert!(store.integrity_check().unwrap());
+        store.vacuum().unwrap();
+        store.analyze().unwrap();
+        assert_eq!(store.count_events().unwrap(), 0);
+
+        // A second connection holding a write lock makes VACUUM report the busy database
+        let other = Connection::open(temp_dir.path().join("ferret.db")).unwrap();
+        other.execute_batch("PRAGMA busy_timeout = 0; BEGIN IMMEDIATE").unwrap();
+        store.conn.lock().unwrap().busy_timeout(std::time::Duration::ZERO).unwrap();
+        let err = store.vacuum().unwrap_err();
+        assert!(err.to_string().contains("in use by another process"), "{}", err);
+    }
+
+    #[test]
+    fn test_insert_events_batch() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let timeout = std::time::Duration::from_secs(5);
+        let events: Vec<FileEvent> = (0..10_000)
+            .map(|i| create_test_event(&format!("/tmp/scan/file{}.txt", i)))
+            .collect();
+
+        let single = Store::new(&temp_dir.path().join("single.db"), timeout).unwrap();
+        let started = std::time::Instant::now();
+        for event in &events {
+            single.insert_event(event).unwrap();
+        }
+        let per_row = started.elapsed();
+
+        let batched = Store::new(&temp_dir.path().join("batched.db"), timeout).unwrap();
+        let started = std::time::Instant::now();
+        assert_eq!(batched.insert_events_batch(&events).unwrap(), 10_000);
+        let batch = started.elapsed();
+
+        // Usually ~3x faster; asking for 1.5x leaves headroom for a busy CPU
+        assert!(batch * 3 < per_row * 2, "batch {:?} vs per-row {:?}", batch, per_row);
+
+        // Same rows as inserting one at a time
+        assert_eq!(batched.count_events().unwrap(), 10_000);
+        let path = Path::new("/tmp/scan/file42.txt");
+        let one = single.get_event_by_path(path).unwrap().unwrap();
+        let many = batched.get_event_by_path(path).unwrap().unwrap();
+        assert_eq!((many.filename, many.size_bytes, many.file_type), (one.filename, one.size_bytes, one.file_type));
+
+        // Existing paths are updated, not duplicated: user edits are kept
+        // and retired rows come back
+        let tagged = batched.get_event_by_path(&events[0].path).unwrap().unwrap();
+        batched.update_tags(tagged.id.unwrap(), "work").unwrap();
+        let retired = batched.get_event_by_path(&events[1].path).unwrap().unwrap();
+        batched.remove_event(retired.id.unwrap(), true).unwrap();
+        let mut rescanned = events[..10].to_vec();
+        rescanned[0].size_bytes = Some(4096);
+        batched.insert_events_batch(&rescanned).unwrap();
+
+        assert_eq!(batched.count_events().unwrap(), 10_000);
+        let tagged = batched.get_event_by_path(&events[0].path).unwrap().unwrap();
+        assert_eq!((tagged.tags.as_str(), tagged.size_bytes), ("work", Some(4096)));
+        let revived = batched.get_event_by_path(&events[1].path).unwrap().unwrap();
+        assert_eq!((revived.id, revived.deleted_at), (retired.id, None));
+    }
+
+    #[test]
+    fn test_open_locked_database() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("ferret.db");
+
+        // A rollback-journal database with a write lock keeps everyone else out
+        let other = Connection::open(&db_path).unwrap();
+        other.execute_batch("CREATE TABLE t (x); BEGIN EXCLUSIVE; INSERT INTO t VALUES (1);").unwrap();
+
+        let started = std::time::Instant::now();
+        let timeout = std::time::Duration::from_millis(200);
+        let err = Store::new(&db_path, timeout).err().unwrap();
+        assert!(err.to_string().contains("Ferret appears to be running"), "{}", err);
+        assert!(started.elapsed() >= timeout);
+
+        // Released while waiting: opening succeeds
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            other.execute_batch("COMMIT").unwrap();
+        });
+        let store = Store::new(&db_path, std::time::Duration::from_secs(10)).unwrap();
+        assert_eq!(store.count_events().unwrap(), 0);
+        release.join().unwrap();
+    }
+
+    #[test]
+    fn test_stats_for_dir() {
+        let store = Store::in_memory().unwrap();
+        for (path, size) in [
+            ("/data/a_b/one.txt", 10),
+            ("/data/a_b/two.txt", 20),
+            ("/data/a_b/sub/three.txt", 30),
+            ("/data/aXb/other.txt", 40),
+            ("/data/a_bc/near.txt", 50),
+        ] {
+            let mut event = create_test_event(path);
+            event.dir = PathBuf::from(path).parent().unwrap().to_path_buf();
+            event.size_bytes = Some(size);
+            store.insert_event(&event).unwrap();
+        }
+
+        let flat = store.get_stats_for_dir(Path::new("/data/a_b"), false).unwrap();
+        assert_eq!(flat.total_count, 2);
+        assert_eq!(flat.total_size, 30);
+        assert_eq!(flat.by_type, vec![(FileType::Document, 2, 30)]);
+
+        // `_` is not a wildcard and siblings sharing the prefix are excluded
+        let tree = store.get_stats_for_dir(Path::new("/data/a_b/"), true).unwrap();
+        assert_eq!(tree.total_count, 3);
+        assert_eq!(tree.total_size, 60);
+        assert_eq!(tree.count_24h, 3);
+        assert_eq!(tree.top_dirs.len(), 2);
+
+        assert_eq!(store.get_stats().unwrap().total_count, 5);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_path_round_trips() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let store = Store::in_memory().unwrap();
+        let path = Path::new("/tmp/dl").join(OsStr::from_bytes(b"caf\xe9.txt"));
+        let id = store.insert_event(&FileEvent::from_path(path.clone())).unwrap();
+
+        let event = store.get_event(id).unwrap().unwrap();
+        assert_eq!(event.path.as_os_str(), path.as_os_str());
+        assert_eq!(event.dir, PathBuf::from("/tmp/dl"));
+        assert_eq!(store.get_event_by_path(&path).unwrap().unwrap().path, path);
+        assert_eq!(store.query_events(&EventFilter::new()).unwrap()[0].path, path);
+
+        // Renamed to a valid name, the raw bytes are dropped
+        let renamed = PathBuf::from("/tmp/dl/cafe.txt");
+        store.move_event(&path, &FileEvent::from_path(renamed.clone())).unwrap();
+        assert_eq!(store.get_event(id).unwrap().unwrap().path, renamed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_paths_differing_in_invalid_bytes() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        // Both read as "caf\u{FFFD}.txt"
+        let store = Store::in_memory().unwrap();
+        let latin1 = Path::new("/tmp/dl").join(OsStr::from_bytes(b"caf\xe9.txt"));
+        let other = Path::new("/tmp/dl").join(OsStr::from_bytes(b"caf\xff.txt"));
+        let first = store.insert_event(&FileEvent::from_path(latin1.clone())).unwrap();
+        let second = store.insert_event(&FileEvent::from_path(other.clone())).unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(store.count_events().unwrap(), 2);
+        assert_eq!(store.get_event(first).unwrap().unwrap().path, latin1);
+        assert_eq!(store.get_event_by_path(&other).unwrap().unwrap().id, Some(second));
+        assert_eq!(store.get_event(second).unwrap().unwrap().filename, "caf\u{FFFD}.txt");
+        assert!(store.path_exists(&latin1).unwrap());
+        assert!(!store.path_exists(Path::new("/tmp/dl/caf\u{FFFD}.txt")).unwrap());
+
+        let found = store.query_events(&EventFilter::new().with_path_contains("caf")).unwrap();
+        assert_eq!(found.len(), 2);
+
+        store.mark_deleted(&latin1).unwrap();
+        assert!(!store.path_is_live(&latin1).unwrap());
+        assert!(store.path_is_live(&other).unwrap());
+    }
 }
@@ -0,0 +1,351 @@
+//! Output templates for `ferret list --format` and `ferret status --format`
+//!
+//! A template is literal text with `{placeholder}` fields, e.g.
+//! `{size}\t{path}`. `{{` and `}}` stand for literal braces, and `\t`, `\n`
+//! and `\\` are unescaped so templates can be written without shell quoting
+//! tricks. Templates are parsed once, so an unknown placeholder is reported
+//! before anything is printed.
+
+use crate::models::{format_relative_time, format_size, FileEvent};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+
+/// A field of a [`FileEvent`] that a template can refer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    /// Local time first seen, as in the table output
+    Time,
+    /// Human-readable size
+    Size,
+    /// Size in bytes (empty if unknown)
+    SizeBytes,
+    /// File type name
+    Type,
+    /// Full path
+    Path,
+    /// File name without directory
+    Filename,
+    /// Parent directory
+    Dir,
+    /// Comma-separated tags
+    Tags,
+    /// Notes
+    Notes,
+}
+
+impl Placeholder {
+    /// Every placeholder name, for error messages
+    const NAMES: &'static str = "time, size, size_bytes, type, path, filename, dir, tags, notes";
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "time" => Some(Placeholder::Time),
+            "size" => Some(Placeholder::Size),
+            "size_bytes" => Some(Placeholder::SizeBytes),
+            "type" => Some(Placeholder::Type),
+            "path" => Some(Placeholder::Path),
+            "filename" => Some(Placeholder::Filename),
+            "dir" => Some(Placeholder::Dir),
+            "tags" => Some(Placeholder::Tags),
+            "notes" => Some(Placeholder::Notes),
+            _ => None,
+        }
+    }
+
+    /// The value of this field for `event`
+    fn value(&self, event: &FileEvent) -> String {
+        match self {
+            Placeholder::Time => event
+                .created_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            Placeholder::Size => event.size_display(),
+            Placeholder::SizeBytes => event.size_bytes.map(|s| s.to_string()).unwrap_or_default(),
+            Placeholder::Type => event.file_type.as_str().to_string(),
+            Placeholder::Path => event.path.to_string_lossy().to_string(),
+            Placeholder::Filename => event.filename.clone(),
+            Placeholder::Dir => event.dir.to_string_lossy().to_string(),
+            Placeholder::Tags => event.tags.clone(),
+            Placeholder::Notes => event.notes.clone(),
+        }
+    }
+}
+
+/// A field of the `ferret status` line that a template can refer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusField {
+    /// "🦡 ", or nothing with `--no-emoji`
+    Icon,
+    /// Files first seen today
+    Count,
+    /// Human-readable total size of today's files
+    Size,
+    /// Total size of today's files in bytes
+    SizeBytes,
+    /// File name of the most recent file (empty if there is none)
+    Last,
+    /// Full path of the most recent file
+    LastPath,
+    /// How long ago the most recent file was seen, e.g. "4m ago"
+    Ago,
+}
+
+impl StatusField {
+    /// Every placeholder name, for error messages
+    const NAMES: &'static str = "icon, count, size, size_bytes, last, last_path, ago";
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "icon" => Some(StatusField::Icon),
+            "count" => Some(StatusField::Count),
+            "size" => Some(StatusField::Size),
+            "size_bytes" => Some(StatusField::SizeBytes),
+            "last" => Some(StatusField::Last),
+            "last_path" => Some(StatusField::LastPath),
+            "ago" => Some(StatusField::Ago),
+            _ => None,
+        }
+    }
+
+    /// The value of this field for `status`
+    fn value(&self, status: &Status) -> String {
+        match self {
+            StatusField::Icon if status.emoji => "🦡 ".to_string(),
+            StatusField::Icon => String::new(),
+            StatusField::Count => status.count.to_string(),
+            StatusField::Size => format_size(status.size),
+            StatusField::SizeBytes => status.size.to_string(),
+            StatusField::Last => status.last.as_ref().map(|e| e.filename.clone()).unwrap_or_default(),
+            StatusField::LastPath => status
+                .last
+                .as_ref()
+                .map(|e| e.path.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            StatusField::Ago => status
+                .last
+                .as_ref()
+                .map(|e| format_relative_time(e.created_at, status.now))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// What `ferret status` reports
+#[derive(Debug, Clone)]
+pub struct Status {
+    /// Files first seen today
+    pub count: u64,
+    /// Their total size in bytes
+    pub size: u64,
+    /// The most recent file, if any
+    pub last: Option<FileEvent>,
+    /// Time `ago` is measured from
+    pub now: DateTime<Utc>,
+    /// Whether `{icon}` shows the ferret
+    pub emoji: bool,
+}
+
+/// A piece of a parsed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token<F> {
+    Literal(String),
+    Field(F),
+}
+
+/// Split a template into literal text and fields, looking field names up
+/// with `lookup`
+fn tokenize<F>(template: &str, lookup: fn(&str) -> Option<F>, names: &str) -> Result<Vec<Token<F>>> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => bail!("Unclosed '{{' in format template: {{{}", name),
+                    }
+                }
+                let Some(field) = lookup(name.trim()) else {
+                    bail!(
+                        "Unknown placeholder '{{{}}}' in format template (expected one of: {})",
+                        name,
+                        names
+                    );
+                };
+                if !literal.is_empty() {
+                    tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(Token::Field(field));
+            }
+            '}' => bail!("Unmatched '}}' in format template (use '}}}}' for a literal brace)"),
+            '\\' => match chars.peek() {
+                Some('t') => {
+                    chars.next();
+                    literal.push('\t');
+                }
+                Some('n') => {
+                    chars.next();
+                    literal.push('\n');
+                }
+                Some('\\') => {
+                    chars.next();
+                    literal.push('\\');
+                }
+                _ => literal.push('\\'),
+            },
+            c => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        tokens.push(Token::Literal(literal));
+    }
+    Ok(tokens)
+}
+
+/// A parsed `list --format` template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    tokens: Vec<Token<Placeholder>>,
+}
+
+impl OutputTemplate {
+    /// Parse a template, rejecting unknown placeholders and stray braces
+    pub fn parse(template: &str) -> Result<Self> {
+        let tokens = tokenize(template, Placeholder::from_name, Placeholder::NAMES)?;
+        Ok(Self { tokens })
+    }
+
+    /// Fill in the template for one event
+    pub fn render(&self, event: &FileEvent) -> String {
+        let mut out = String::new();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(text) => out.push_str(text),
+                Token::Field(field) => out.push_str(&field.value(event)),
+            }
+        }
+        out
+    }
+}
+
+impl std::str::FromStr for OutputTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+/// A parsed `status --format` template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusTemplate {
+    tokens: Vec<Token<StatusField>>,
+}
+
+impl StatusTemplate {
+    /// The line printed when no `--format` is given
+    pub const DEFAULT: &'static str = "{icon}{count} new today, {size} (last: {last} {ago})";
+
+    /// The default line when nothing has been recorded yet
+    pub const DEFAULT_EMPTY: &'static str = "{icon}{count} new today";
+
+    /// Parse a template, rejecting unknown placeholders and stray braces
+    pub fn parse(template: &str) -> Result<Self> {
+        let tokens = tokenize(template, StatusField::from_name, StatusField::NAMES)?;
+        Ok(Self { tokens })
+    }
+
+    /// Fill in the template
+    pub fn render(&self, status: &Status) -> String {
+        let mut out = String::new();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(text) => out.push_str(text),
+                Token::Field(field) => out.push_str(&field.value(status)),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn event() -> FileEvent {
+        let mut event = FileEvent::from_path(PathBuf::from("/tmp/dl/report.pdf"));
+        event.size_bytes = Some(2048);
+        event.tags = "work".to_string();
+        event
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let template = OutputTemplate::parse("{size_bytes}\\t{path}").unwrap();
+        assert_eq!(template.render(&event()), "2048\t/tmp/dl/report.pdf");
+
+        let template = OutputTemplate::parse("{filename} in {dir} [{type}] {tags}{notes}").unwrap();
+        assert_eq!(template.render(&event()), "report.pdf in /tmp/dl [document] work");
+    }
+
+    #[test]
+    fn test_brace_escapes() {
+        let template = OutputTemplate::parse("{{\"path\": \"{path}\"}}").unwrap();
+        assert_eq!(template.render(&event()), "{\"path\": \"/tmp/dl/report.pdf\"}");
+
+        let template = OutputTemplate::parse("{{{{}}").unwrap();
+        assert_eq!(template.render(&event()), "{{}");
+    }
+
+    #[test]
+    fn test_status_template() {
+        let now = Utc::now();
+        let mut last = event();
+        last.created_at = now - chrono::Duration::minutes(4);
+        let mut status = Status {
+            count: 3,
+            size: 1536,
+            last: Some(last),
+            now,
+            emoji: true,
+        };
+
+        let size = format_size(1536);
+        let template = StatusTemplate::parse(StatusTemplate::DEFAULT).unwrap();
+        assert_eq!(
+            template.render(&status),
+            format!("🦡 3 new today, {} (last: report.pdf 4m ago)", size)
+        );
+        status.emoji = false;
+        assert_eq!(template.render(&status), format!("3 new today, {} (last: report.pdf 4m ago)", size));
+
+        let template = StatusTemplate::parse("{count}/{size_bytes} {last_path}").unwrap();
+        assert_eq!(template.render(&status), "3/1536 /tmp/dl/report.pdf");
+        assert!(StatusTemplate::parse("{path}").is_err());
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        let err = OutputTemplate::parse("{size} {owner}").unwrap_err();
+        assert!(err.to_string().contains("{owner}"));
+        assert!(OutputTemplate::parse("{path").is_err());
+        assert!(OutputTemplate::parse("path}").is_err());
+        assert!(OutputTemplate::parse("{}").is_err());
+    }
+}
@@ -3,30 +3,46 @@
 //! This module contains the core application structure that manages
 //! the TUI state, handles input, and coordinates between views.
 
-use crate::models::{EventFilter, FileEvent, FolderGroup, TreeNode, TreeViewState, ViewMode};
+use crate::config::{dir_ignore_pattern, Categories, Config, Openers, PAGE_SIZE_RANGE};
+use crate::models::{format_size, local_midnight, similarity_stem, EventFilter, FileEvent, FileType, FolderGroup, ListColumn, SortKey, TreeNode, TreeViewState, ViewMode};
 use crate::store::Store;
-use crate::watcher::WatcherMessage;
+use crate::watcher::{WatcherCounters, WatcherMessage};
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Clear, Paragraph},
+    TerminalOptions, Viewport,
 };
-use std::sync::mpsc::Receiver;
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use chrono::{Local, NaiveDate};
 use std::time::{Duration, Instant};
 
 use super::detail_view::DetailView;
-use super::filters::FilterOverlay;
+use super::diagnostics::DiagnosticsOverlay;
+use super::messages::MessageLog;
+use super::filters::{FilterOverlay, FilterSelection};
 use super::help::HelpOverlay;
 use super::list_view::ListView;
 use super::input::InputOverlay;
+use super::stats_overlay::StatsOverlay;
+use super::theme::Theme;
 use super::tree_view::TreeView;
+use super::type_chooser::TypeChooser;
+use super::watch_paths::{WatchPathEntry, WatchPathsOverlay};
 
-/// Default page size for pagination
-const DEFAULT_PAGE_SIZE: usize = 100;
+/// How much `+`/`-` grow or shrink the page
+const PAGE_SIZE_STEP: usize = 10;
 
-/// Batch delay for collecting watcher events (milliseconds)
-const BATCH_DELAY_MS: u64 = 200;  // Reduced from 500ms for faster updates
+/// Maximum gap between two clicks on the same row to count as a double-click
+const DOUBLE_CLICK_MS: u64 = 400;
+
+/// Window over which the header's events-per-second rate is averaged
+const EVENT_RATE_WINDOW: Duration = Duration::from_secs(5);
 
 /// Current view/screen being displayed
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -54,6 +70,22 @@ pub enum InputMode {
     EditNotes,
     /// Confirmation dialog (e.g., delete)
     Confirm,
+    /// Typing a filename to jump to in the loaded page
+    QuickFind,
+    /// Typing a date to jump to
+    GoToDate,
+    /// Directory stats overlay is open
+    Stats,
+    /// Choosing a new file type for the selected file
+    EditType,
+    /// Watcher diagnostics overlay is open
+    Diagnostics,
+    /// Watched directories overlay is open
+    WatchPaths,
+    /// Typing the directory to copy the selected file to
+    CopyTo,
+    /// Message history overlay is open
+    Messages,
 }
 
 /// Application state
@@ -83,18 +115,36 @@ pub struct App {
     pub scroll_offset: usize,
     /// Active filter
     pub filter: EventFilter,
+    /// Column the list is sorted by
+    pub sort_key: SortKey,
+    /// Whether the sort is descending
+    pub sort_desc: bool,
     /// Search query
     pub search_query: String,
+    /// Whether the search query is a path regex rather than a substring
+    pub search_is_regex: bool,
     /// Input buffer for various input modes
     pub input_buffer: String,
+    /// Whether the quick-find text matches no loaded filename
+    pub quick_find_missed: bool,
     /// Message to display in status bar
     pub status_message: Option<(String, Instant)>,
-    /// Number of watched directories
-    pub watched_dirs: usize,
+    /// Recent status and watcher messages, for `L`
+    pub message_log: MessageLog,
+    /// Directories being watched, as resolved by the watcher
+    pub watched_dirs: Vec<PathBuf>,
+    /// Number of configured watch directories (watched plus failed)
+    pub configured_dirs: usize,
     /// Filter overlay state
     pub filter_overlay: FilterOverlay,
     /// Help overlay state
     pub help_overlay: HelpOverlay,
+    /// Stats for the selected directory, while the overlay is open
+    pub stats_overlay: Option<StatsOverlay>,
+    /// Watched directories overlay (set while open)
+    pub watch_paths_overlay: Option<WatchPathsOverlay>,
+    /// File type chooser, while open
+    pub type_chooser: Option<TypeChooser>,
     /// Confirmation action pending
     pub pending_action: Option<PendingAction>,
     /// Number of visible events after filtering
@@ -107,6 +157,8 @@ pub struct App {
     pub current_offset: usize,
     /// Total count of matching events (for pagination info)
     pub total_count: usize,
+    /// Total size of all events matching the filter, across every page
+    pub filtered_total_size: u64,
     
     // Dirty flag and batching
     /// Whether a refresh is needed
@@ -115,6 +167,12 @@ pub struct App {
     pub pending_new_files: usize,
     /// Last time we batched watcher events
     pub last_batch_time: Instant,
+    /// How long to gather new files before refreshing
+    pub batch_delay: Duration,
+    /// Reload on this interval even without watcher events (None = off)
+    pub auto_refresh: Option<Duration>,
+    /// When the list was last loaded from the store
+    last_refresh: Instant,
     
     // View mode and tree view state
     /// Current view mode (Flat, GroupByFolder, TreeView)
@@ -123,40 +181,132 @@ pub struct App {
     pub tree_nodes: Vec<TreeNode>,
     /// Tree view state (expansion, selection)
     pub tree_state: TreeViewState,
+    /// File the expanded tree directories are saved to on exit
+    tree_state_path: PathBuf,
+    /// Filter overlay choices behind the current filter, saved on exit
+    pub filter_selection: FilterSelection,
+    /// File the filter and search are saved to on exit
+    filter_state_path: PathBuf,
     /// Folder groups for grouped view
     pub folder_groups: Vec<FolderGroup>,
     /// Selected index in grouped view (covers both headers and files)
     pub grouped_selected_index: usize,
     /// Scroll offset for grouped view
     pub grouped_scroll_offset: usize,
+    /// Number of other files sharing the detail view's content hash
+    pub duplicate_count: u64,
+
+    // Mouse state
+    /// Screen area of the flat list table, recorded on each draw
+    pub list_area: Rect,
+    /// Time and row index of the last left click (for double-click detection)
+    pub last_click: Option<(Instant, usize)>,
+    /// Whether `d` moves files to the OS trash rather than deleting them
+    pub trash_on_delete: bool,
+    /// Whether deleted files keep their ledger row as history
+    pub retain_deleted: bool,
+    /// Columns shown in the flat list view
+    pub list_columns: Vec<ListColumn>,
+    /// Whether the list shows "5m ago" instead of absolute times
+    pub relative_time: bool,
+    /// Up/Down wrap around the list instead of stopping at the ends
+    pub wrap_navigation: bool,
+    /// Whether tree view folders show a relative size bar
+    pub tree_size_bars: bool,
+    /// Colors used when drawing
+    pub theme: Theme,
+    /// User-defined categories, for their labels
+    pub categories: Categories,
+    /// Programs `o` opens files with instead of the system default
+    openers: Openers,
+    /// Ids of events marked for bulk actions (kept across pages)
+    pub marked: HashSet<i64>,
+    /// System clipboard, opened on first copy and kept so X11/Wayland
+    /// selections stay available after the copy
+    clipboard: Option<arboard::Clipboard>,
+    /// Whether watching is paused (new files are not recorded)
+    pub paused: bool,
+    /// Pause flag shared with the watcher's processing thread
+    pause_handle: Option<Arc<AtomicBool>>,
+    /// Watch paths the watcher's health check found missing
+    offline_handle: Option<Arc<Mutex<HashSet<PathBuf>>>>,
+    /// Labels of the quotas the watcher found exceeded
+    quota_handle: Option<Arc<Mutex<HashSet<String>>>>,
+    /// The watcher's processing counters, for the diagnostics overlay
+    stats_handle: Option<Arc<WatcherCounters>>,
+    /// Arrival times of recent watcher events, oldest first
+    event_times: VecDeque<Instant>,
+    /// Folders hidden with `x` for this session
+    pub excluded_dirs: Vec<PathBuf>,
+    /// Config file that `x` adds ignore patterns to
+    config_path: PathBuf,
+    /// Copy started with `C`, while it runs
+    copy_task: Option<CopyTask>,
+    /// Re-reads the config and applies it to the watcher, for `R`
+    reload_handle: Option<ReloadHandle>,
+}
+
+/// Reloads the config into the watcher, returning the paths watched
+/// afterwards and how many were configured
+pub type ReloadHandle = Box<dyn FnMut() -> Result<(Vec<PathBuf>, usize)>>;
+
+/// A file copy running on a worker thread
+struct CopyTask {
+    /// The tracked file being copied
+    source: FileEvent,
+    /// Path of the copy
+    dest: PathBuf,
+    /// Receives the bytes copied, or the error, when the worker is done
+    done: Receiver<std::io::Result<u64>>,
 }
 
 /// Actions that require confirmation
 #[derive(Debug, Clone)]
 pub enum PendingAction {
-    /// Delete a file
-    DeleteFile(i64, String),
+    /// Delete a file (to the trash if enabled), with its size and type
+    DeleteFile(i64, String, Option<u64>, FileType),
+    /// Delete a file permanently, bypassing the trash
+    PermanentlyDeleteFile(i64, String, Option<u64>, FileType),
+    /// Delete every marked file (to the trash if enabled), with their total size
+    DeleteMarked(Vec<i64>, u64),
+    /// Delete every marked file permanently, bypassing the trash
+    PermanentlyDeleteMarked(Vec<i64>, u64),
+    /// Add a hidden folder to `ignore_patterns` in the config file
+    IgnoreDir(PathBuf),
 }
 
 impl App {
-    /// Create a new App instance
-    pub fn new(store: Store) -> Result<Self> {
-        // Start with default pagination filter
-        let filter = EventFilter::new().with_limit(DEFAULT_PAGE_SIZE).with_offset(0);
+    /// Create a new App instance using the view settings from `config`
+    pub fn new(store: Store, config: &Config) -> Result<Self> {
+        // Pick up last session's filter and search where they were left
+        let categories = config.build_categories()?;
+        let mut filter_overlay = FilterOverlay::with_categories(categories.entries().to_vec());
+        let filter_state_path = config.filter_state_path();
+        let saved = load_saved_filter(&filter_state_path).unwrap_or_default();
+        filter_overlay.restore(&saved.selection);
+        let mut filter = filter_overlay.build_filter().with_limit(config.page_size).with_offset(0);
+        set_search(&mut filter, &saved.search, saved.search_is_regex);
         let total_count = store.count_filtered_events(&filter)?;
+        let filtered_total_size = store.sum_filtered_size(&filter)?;
         let events = store.query_events(&filter)?;
         let visible_count = events.len();
         
         // Build tree and grouped views
         let tree_nodes = TreeNode::from_events(&events);
         let mut tree_state = TreeViewState::new();
+        let tree_state_path = config.tree_state_path();
         
-        // Expand ALL directories by default so files are visible
-        tree_state.expand_all(&tree_nodes);
+        // Restore last session's expanded folders, or expand ALL directories
+        // so files are visible
+        match load_expanded_dirs(&store, &tree_state_path) {
+            Some(expanded) => tree_state.expanded = expanded,
+            None => tree_state.expand_all(&tree_nodes),
+        }
         
         tree_state.rebuild_flattened(&tree_nodes);
         // selected_index defaults to 0, which is correct
-        let folder_groups = FolderGroup::from_events(&events);
+        let view_mode = config.view_mode()?;
+        let folder_groups = group_events(view_mode, &events);
 
         Ok(Self {
             state: AppState::Running,
@@ -167,45 +317,260 @@ impl App {
             selected_index: 0,
             scroll_offset: 0,
             filter,
-            search_query: String::new(),
+            sort_key: SortKey::default(),
+            sort_desc: true,
+            search_query: saved.search,
+            search_is_regex: saved.search_is_regex,
             input_buffer: String::new(),
+            quick_find_missed: false,
             status_message: None,
-            watched_dirs: 0,
-            filter_overlay: FilterOverlay::new(),
+            message_log: MessageLog::default(),
+            watched_dirs: Vec::new(),
+            configured_dirs: 0,
+            filter_overlay,
             help_overlay: HelpOverlay::new(),
+            stats_overlay: None,
+            watch_paths_overlay: None,
+            type_chooser: None,
             pending_action: None,
             visible_count,
             // Pagination
-            page_size: DEFAULT_PAGE_SIZE,
+            page_size: config.page_size,
             current_offset: 0,
             total_count,
+            filtered_total_size,
             // Dirty flag and batching
             needs_refresh: false,
             pending_new_files: 0,
             last_batch_time: Instant::now(),
+            batch_delay: Duration::from_millis(config.ui_batch_delay_ms),
+            auto_refresh: (config.auto_refresh_secs > 0).then(|| Duration::from_secs(config.auto_refresh_secs)),
+            last_refresh: Instant::now(),
             // View mode and tree view
-            view_mode: ViewMode::default(),
+            view_mode,
             tree_nodes,
             tree_state,
+            tree_state_path,
+            filter_selection: saved.selection,
+            filter_state_path,
             folder_groups,
             grouped_selected_index: 0,
             grouped_scroll_offset: 0,
+            duplicate_count: 0,
+            list_area: Rect::default(),
+            last_click: None,
+            trash_on_delete: true,
+            retain_deleted: config.retain_deleted_in_ledger,
+            list_columns: config.columns()?,
+            relative_time: config.relative_time,
+            wrap_navigation: config.wrap_navigation,
+            tree_size_bars: config.tree_size_bars,
+            theme: Theme::from_config(&config.theme)?,
+            categories,
+            openers: config.build_openers()?,
+            marked: HashSet::new(),
+            clipboard: None,
+            paused: false,
+            pause_handle: None,
+            offline_handle: None,
+            quota_handle: None,
+            stats_handle: None,
+            event_times: VecDeque::new(),
+            excluded_dirs: Vec::new(),
+            config_path: Config::config_file_path(config.profile.as_deref()),
+            copy_task: None,
+            reload_handle: None,
         })
     }
 
-    /// Set the number of watched directories
-    pub fn set_watched_dirs(&mut self, count: usize) {
-        self.watched_dirs = count;
+    /// Set the directories being watched, out of `configured` watch paths
+    pub fn set_watched_dirs(&mut self, mut watched: Vec<PathBuf>, configured: usize) {
+        watched.sort();
+        self.watched_dirs = watched;
+        self.configured_dirs = configured;
+    }
+
+    /// Set whether deletions go to the OS trash
+    pub fn set_trash_on_delete(&mut self, enabled: bool) {
+        self.trash_on_delete = enabled;
+    }
+
+    /// Set the config file that `x` adds ignore patterns to, when it isn't
+    /// the default location
+    pub fn set_config_path(&mut self, path: PathBuf) {
+        self.config_path = path;
+    }
+
+    /// Let `R` reload the config into the running watcher
+    pub fn set_reload_handle(&mut self, handle: ReloadHandle) {
+        self.reload_handle = Some(handle);
+    }
+
+    /// Share the watcher's pause flag so `p` can pause recording
+    pub fn set_pause_handle(&mut self, handle: Arc<AtomicBool>) {
+        self.pause_handle = Some(handle);
+    }
+
+    /// Share the watcher's set of offline watch paths for the header
+    pub fn set_offline_handle(&mut self, handle: Arc<Mutex<HashSet<PathBuf>>>) {
+        self.offline_handle = Some(handle);
+    }
+
+    /// Share the watcher's set of exceeded quotas for the header
+    pub fn set_quota_handle(&mut self, handle: Arc<Mutex<HashSet<String>>>) {
+        self.quota_handle = Some(handle);
+    }
+
+    /// Share the watcher's processing counters for the diagnostics overlay
+    pub fn set_stats_handle(&mut self, handle: Arc<WatcherCounters>) {
+        self.stats_handle = Some(handle);
+    }
+
+    /// Number of watch paths that are currently missing
+    pub fn offline_count(&self) -> usize {
+        self.offline_handle
+            .as_ref()
+            .and_then(|handle| handle.lock().ok().map(|offline| offline.len()))
+            .unwrap_or(0)
+    }
+
+    /// Number of quotas that are currently exceeded
+    pub fn exceeded_quota_count(&self) -> usize {
+        self.quota_handle
+            .as_ref()
+            .and_then(|handle| handle.lock().ok().map(|exceeded| exceeded.len()))
+            .unwrap_or(0)
+    }
+
+    /// Save the tree view's expanded directories for the next session
+    pub fn save_tree_state(&self) -> Result<()> {
+        if let Some(parent) = self.tree_state_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut expanded: Vec<&PathBuf> = self.tree_state.expanded.iter().collect();
+        expanded.sort();
+        std::fs::write(&self.tree_state_path, serde_json::to_string(&expanded)?)?;
+        Ok(())
+    }
+
+    /// Save the filter overlay choices and search for the next session
+    pub fn save_filter_state(&self) -> Result<()> {
+        if let Some(parent) = self.filter_state_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let saved = SavedFilter {
+            selection: self.filter_selection.clone(),
+            search: self.search_query.clone(),
+            search_is_regex: self.search_is_regex,
+        };
+        std::fs::write(&self.filter_state_path, serde_json::to_string(&saved)?)?;
+        Ok(())
+    }
+
+    /// Drop every filter, the search and hidden folders, and go back to the
+    /// first page
+    pub fn clear_filters(&mut self) -> Result<()> {
+        self.filter = EventFilter::new().with_limit(self.page_size).with_offset(0);
+        self.filter_selection = FilterSelection::default();
+        self.current_offset = 0;
+        self.search_query.clear();
+        self.excluded_dirs.clear();
+        self.refresh_events()
+    }
+
+    /// Swap the list's size column between rounded units and exact bytes
+    fn toggle_exact_sizes(&mut self) {
+        let mut exact = None;
+        for column in &mut self.list_columns {
+            match column {
+                ListColumn::Size => *column = ListColumn::Bytes,
+                ListColumn::Bytes => *column = ListColumn::Size,
+                _ => continue,
+            }
+            exact = Some(*column == ListColumn::Bytes);
+        }
+        let status = match exact {
+            Some(true) => "Sizes: exact bytes",
+            Some(false) => "Sizes: rounded",
+            None => "No size column shown (see list_columns)",
+        };
+        self.set_status(status.to_string());
+    }
+
+    /// Pause or resume recording new files
+    fn toggle_pause(&mut self) -> Result<()> {
+        self.paused = !self.paused;
+        if let Some(handle) = &self.pause_handle {
+            handle.store(self.paused, Ordering::Relaxed);
+        }
+
+        if self.paused {
+            self.set_status("Watching paused - new files are not recorded".to_string());
+        } else {
+            self.refresh_events()?;
+            self.set_status("Watching resumed".to_string());
+        }
+        Ok(())
+    }
+
+    /// Open `path` with its `[openers]` command, or the system default
+    fn open_file(&mut self, path: &Path, file_type: FileType) {
+        let Some(args) = self.openers.command_for(path, file_type) else {
+            match open::that(path) {
+                Ok(()) => self.set_status(format!("Opened: {}", path.display())),
+                Err(e) => self.set_status(format!("Failed to open: {}", e)),
+            }
+            return;
+        };
+
+        // Detached from the terminal so the program can't draw over the TUI
+        let spawned = std::process::Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(mut child) => {
+                // Reap it once it exits
+                std::thread::spawn(move || child.wait());
+                self.set_status(format!("Opened with {}: {}", args[0], path.display()));
+            }
+            Err(e) => self.set_status(format!("Failed to run {}: {}", args[0], e)),
+        }
+    }
+
+    /// Re-read the config file and apply its watch paths, filters and quotas
+    fn reload_config(&mut self) -> Result<()> {
+        let Some(reload) = &mut self.reload_handle else {
+            self.set_status("Nothing to reload without a watcher".to_string());
+            return Ok(());
+        };
+
+        match reload() {
+            Ok((watched, configured)) => {
+                let count = watched.len();
+                self.set_watched_dirs(watched, configured);
+                self.refresh_events()?;
+                self.set_status(format!("Reloaded config, watching {} dirs", count));
+            }
+            Err(e) => self.set_status(format!("Reload failed: {:#}", e)),
+        }
+        Ok(())
     }
 
     /// Refresh events from the database with current pagination
     pub fn refresh_events(&mut self) -> Result<()> {
-        // Update filter with current pagination settings
+        // Update filter with current pagination and sort settings
         self.filter.limit = self.page_size;
         self.filter.offset = self.current_offset;
+        self.filter.sort_key = self.sort_key;
+        self.filter.sort_desc = self.sort_desc;
+        self.filter.exclude_dirs = self.excluded_dirs.clone();
         
         // Query events and count
         self.total_count = self.store.count_filtered_events(&self.filter)?;
+        self.filtered_total_size = self.store.sum_filtered_size(&self.filter)?;
         self.events = self.store.query_events(&self.filter)?;
         self.visible_count = self.events.len();
         
@@ -213,6 +578,8 @@ impl App {
         if !self.events.is_empty() && self.selected_index >= self.events.len() {
             self.selected_index = self.events.len() - 1;
         }
+
+        self.prune_marked()?;
         
         // Rebuild tree and grouped views
         self.rebuild_tree_views();
@@ -220,17 +587,103 @@ impl App {
         // Clear refresh flag
         self.needs_refresh = false;
         self.pending_new_files = 0;
+        self.last_refresh = Instant::now();
         
         Ok(())
     }
+
+    /// Refresh, keeping the selected file selected if it's still listed
+    pub fn refresh_keeping_selection(&mut self) -> Result<()> {
+        let selected_id = self.get_selected_file_event().and_then(|event| event.id);
+        self.refresh_events()?;
+        if let Some(id) = selected_id {
+            self.select_event_id(id);
+        }
+        Ok(())
+    }
+
+    /// Select the row of the event with `id`, if it's on the current page
+    fn select_event_id(&mut self, id: i64) {
+        let Some(index) = self.events.iter().position(|event| event.id == Some(id)) else {
+            return;
+        };
+        match self.view_mode {
+            ViewMode::Flat => self.selected_index = index,
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
+                let path = &self.events[index].path;
+                let mut row_idx = 0;
+                for group in &self.folder_groups {
+                    row_idx += 1;
+                    if group.expanded {
+                        if let Some(i) = group.files.iter().position(|file| &file.path == path) {
+                            self.grouped_selected_index = row_idx + i;
+                            return;
+                        }
+                        row_idx += group.files.len();
+                    }
+                }
+            }
+            ViewMode::TreeView => {
+                let path = &self.events[index].path;
+                if let Some(i) = self.tree_state.flattened.iter().position(|node| &node.path == path) {
+                    self.tree_state.selected_index = i;
+                }
+            }
+        }
+    }
     
+    /// Drop marks for events that are no longer in the ledger
+    ///
+    /// Marks on other pages or hidden by the filter are kept.
+    fn prune_marked(&mut self) -> Result<()> {
+        let mut gone = Vec::new();
+        for &id in &self.marked {
+            if self.store.get_event(id)?.is_none() {
+                gone.push(id);
+            }
+        }
+        for id in gone {
+            self.marked.remove(&id);
+        }
+        Ok(())
+    }
+
+    /// Toggle the mark on the selected event
+    fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_event().and_then(|e| e.id) else {
+            return;
+        };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+    }
+
+    /// Marked ids in a stable order
+    fn marked_ids(&self) -> Vec<i64> {
+        let mut ids: Vec<i64> = self.marked.iter().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Combined size of the events `ids`, including marked files on other pages
+    fn total_size_of(&self, ids: &[i64]) -> Result<u64> {
+        let mut total = 0;
+        for &id in ids {
+            if let Some(event) = self.store.get_event(id)? {
+                total += event.size_bytes.unwrap_or(0);
+            }
+        }
+        Ok(total)
+    }
+
     /// Rebuild tree and grouped views from current events
     fn rebuild_tree_views(&mut self) {
         // Rebuild tree nodes
-        self.tree_nodes = TreeNode::from_events(&self.events);
+        let previous = std::mem::replace(&mut self.tree_nodes, TreeNode::from_events(&self.events));
         
-        // Auto-expand ALL directories (including nested ones) so files stay visible
-        self.tree_state.expand_all(&self.tree_nodes);
+        // Auto-expand directories (including nested ones) that weren't shown
+        // before so their files are visible
+        self.tree_state.expand_new(&previous, &self.tree_nodes);
         
         // Preserve expansion state, rebuild flattened
         self.tree_state.rebuild_flattened(&self.tree_nodes);
@@ -241,7 +694,7 @@ impl App {
         }
         
         // Rebuild folder groups
-        self.folder_groups = FolderGroup::from_events(&self.events);
+        self.folder_groups = group_events(self.view_mode, &self.events);
         
         // Adjust grouped selection if needed
         let total_grouped_rows = self.count_grouped_rows();
@@ -269,7 +722,7 @@ impl App {
     
     /// Process batched refresh if needed
     pub fn process_batched_refresh(&mut self) -> Result<()> {
-        if self.needs_refresh && self.last_batch_time.elapsed() > Duration::from_millis(BATCH_DELAY_MS) {
+        if self.needs_refresh && self.last_batch_time.elapsed() > self.batch_delay {
             self.refresh_events()?;
             if self.pending_new_files > 0 {
                 self.set_status(format!("{} new file(s) added", self.pending_new_files));
@@ -278,6 +731,23 @@ impl App {
         Ok(())
     }
     
+    /// Reload the list if `auto_refresh` is set and it's been that long
+    ///
+    /// Waits while an overlay or the detail view is open, so the rows don't
+    /// change under the user.
+    pub fn process_auto_refresh(&mut self) -> Result<()> {
+        let Some(interval) = self.auto_refresh else {
+            return Ok(());
+        };
+        if self.last_refresh.elapsed() >= interval
+            && self.view == View::List
+            && self.input_mode == InputMode::Normal
+        {
+            self.refresh_keeping_selection()?;
+        }
+        Ok(())
+    }
+    
     /// Go to next page
     pub fn next_page(&mut self) -> Result<()> {
         let max_offset = self.total_count.saturating_sub(self.page_size);
@@ -321,6 +791,44 @@ impl App {
         Ok(())
     }
     
+    /// Grow or shrink the page by `PAGE_SIZE_STEP`
+    ///
+    /// The offset snaps to the start of the new page holding the selected
+    /// event (the first loaded one outside the flat view), so paging stays
+    /// aligned.
+    fn resize_page(&mut self, grow: bool) -> Result<()> {
+        let size = if grow {
+            self.page_size + PAGE_SIZE_STEP
+        } else {
+            self.page_size.saturating_sub(PAGE_SIZE_STEP)
+        }
+        .clamp(*PAGE_SIZE_RANGE.start(), *PAGE_SIZE_RANGE.end());
+
+        if size != self.page_size {
+            let position = if self.view_mode == ViewMode::Flat {
+                self.current_offset + self.selected_index
+            } else {
+                self.current_offset
+            };
+            self.page_size = size;
+            self.current_offset = position / size * size;
+            self.selected_index = if self.view_mode == ViewMode::Flat {
+                position - self.current_offset
+            } else {
+                0
+            };
+            self.refresh_events()?;
+        }
+
+        self.set_status(format!(
+            "Page size: {} (page {}/{})",
+            self.page_size,
+            self.current_page(),
+            self.total_pages()
+        ));
+        Ok(())
+    }
+
     /// Get current page number (1-indexed)
     pub fn current_page(&self) -> usize {
         (self.current_offset / self.page_size) + 1
@@ -331,6 +839,12 @@ impl App {
         (self.total_count + self.page_size - 1) / self.page_size
     }
 
+    /// Label of the event's `[[categories]]` entry (its name if no longer configured)
+    pub fn category_label<'a>(&'a self, event: &'a FileEvent) -> Option<&'a str> {
+        let name = event.category.as_deref()?;
+        Some(self.categories.label(name).unwrap_or(name))
+    }
+
     /// Get the currently selected event
     pub fn selected_event(&self) -> Option<&FileEvent> {
         self.events.get(self.selected_index)
@@ -341,17 +855,39 @@ impl App {
     /// Note: The watcher's processing thread already inserts events into the DB.
     /// The UI thread just needs to schedule a refresh to display them.
     pub fn handle_watcher_message(&mut self, msg: WatcherMessage) -> Result<()> {
+        if matches!(
+            msg,
+            WatcherMessage::NewFile(_) | WatcherMessage::MovedFile(_) | WatcherMessage::DeletedFile(_)
+        ) {
+            self.event_times.push_back(Instant::now());
+        }
+
         match msg {
-            WatcherMessage::NewFile(_event) | WatcherMessage::MovedFile(_event) => {
+            // Stragglers sent just before pausing; resuming refreshes anyway
+            WatcherMessage::NewFile(_) if self.paused => {}
+            WatcherMessage::NewFile(event) | WatcherMessage::MovedFile(event) => {
                 // Event is already in the database (inserted by watcher processing thread)
                 // Just schedule a UI refresh - NO DB I/O on the UI thread!
+                self.message_log.push(format!("Recorded {}", event.path.display()));
                 self.pending_new_files += 1;
                 self.schedule_refresh();
                 self.last_batch_time = Instant::now();
             }
+            WatcherMessage::DeletedFile(path) => {
+                // Deletion time is already recorded; refresh to show it
+                self.message_log.push(format!("Gone from disk: {}", path.display()));
+                self.schedule_refresh();
+                self.last_batch_time = Instant::now();
+            }
             WatcherMessage::Error(err) => {
                 self.set_status(format!("Watcher error: {}", err));
             }
+            WatcherMessage::Warning(message) => {
+                self.set_status(message);
+            }
+            WatcherMessage::PathRestored(path) => {
+                self.set_status(format!("Watch path back online: {}", path.display()));
+            }
             WatcherMessage::Started => {
                 self.set_status("File watcher started".to_string());
             }
@@ -362,11 +898,29 @@ impl App {
         Ok(())
     }
 
-    /// Set a status message that will auto-clear
+    /// Set a status message that will auto-clear, keeping it in the history
     pub fn set_status(&mut self, message: String) {
+        self.message_log.push(message.clone());
         self.status_message = Some((message, Instant::now()));
     }
 
+    /// Drop event timestamps that have left the rate window
+    pub fn prune_event_times(&mut self) {
+        let now = Instant::now();
+        while self
+            .event_times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > EVENT_RATE_WINDOW)
+        {
+            self.event_times.pop_front();
+        }
+    }
+
+    /// Watcher events per second over the rate window
+    pub fn event_rate(&self) -> f64 {
+        self.event_times.len() as f64 / EVENT_RATE_WINDOW.as_secs_f64()
+    }
+
     /// Clear expired status message
     pub fn clear_expired_status(&mut self) {
         if let Some((_, time)) = &self.status_message {
@@ -392,14 +946,90 @@ impl App {
             InputMode::EditTags => self.handle_edit_tags_input(key)?,
             InputMode::EditNotes => self.handle_edit_notes_input(key)?,
             InputMode::Confirm => self.handle_confirm_input(key)?,
+            InputMode::QuickFind => self.handle_quick_find_input(key),
+            InputMode::GoToDate => self.handle_go_to_date_input(key)?,
+            InputMode::Stats => self.handle_stats_input(key),
+            InputMode::Diagnostics => {
+                if matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('W') | KeyCode::Enter) {
+                    self.input_mode = InputMode::Normal;
+                }
+            }
+            InputMode::WatchPaths => self.handle_watch_paths_input(key),
+            InputMode::Messages => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('L') | KeyCode::Enter => {
+                    self.input_mode = InputMode::Normal;
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.message_log.scroll_up(),
+                KeyCode::Down | KeyCode::Char('j') => self.message_log.scroll_down(),
+                _ => {}
+            },
+            InputMode::EditType => self.handle_edit_type_input(key)?,
+            InputMode::CopyTo => self.handle_copy_to_input(key),
+        }
+
+        Ok(())
+    }
+
+    /// Handle mouse input (list view only)
+    pub fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<()> {
+        if self.input_mode != InputMode::Normal || self.view != View::List {
+            return Ok(());
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.move_selection_up(false)?,
+            MouseEventKind::ScrollDown => self.move_selection_down(false)?,
+            MouseEventKind::Down(MouseButton::Left) if self.view_mode == ViewMode::Flat => {
+                if let Some(idx) = self.row_at(mouse.column, mouse.row) {
+                    let now = Instant::now();
+                    let double = matches!(
+                        self.last_click,
+                        Some((at, last)) if last == idx
+                            && now.duration_since(at) <= Duration::from_millis(DOUBLE_CLICK_MS)
+                    );
+
+                    self.selected_index = idx;
+                    if double {
+                        self.last_click = None;
+                        self.open_detail();
+                    } else {
+                        self.last_click = Some((now, idx));
+                    }
+                }
+            }
+            _ => {}
         }
 
         Ok(())
     }
 
+    /// Map a screen position to an event index in the flat list
+    fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        // Skip the top border and the header row
+        let first_row = self.list_area.y + 2;
+        let last_row = self.list_area.bottom().saturating_sub(1);
+
+        if column <= self.list_area.x
+            || column >= self.list_area.right().saturating_sub(1)
+            || row < first_row
+            || row >= last_row
+        {
+            return None;
+        }
+
+        let idx = self.scroll_offset + (row - first_row) as usize;
+        (idx < self.events.len()).then_some(idx)
+    }
+
     /// Handle input in normal mode
     fn handle_normal_input(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
+            // Esc clears the marked set before anything else
+            KeyCode::Esc if !self.marked.is_empty() => {
+                self.marked.clear();
+                self.set_status("Marks cleared".to_string());
+            }
+
             // Quit
             KeyCode::Char('q') | KeyCode::Esc => {
                 if self.view == View::Detail {
@@ -411,7 +1041,13 @@ impl App {
 
             // Toggle view mode with Tab
             KeyCode::Tab => {
+                let previous = self.view_mode;
                 self.view_mode = self.view_mode.next();
+                // Folder and extension views share the group rows
+                if (previous == ViewMode::GroupByExtension) != (self.view_mode == ViewMode::GroupByExtension) {
+                    self.folder_groups = group_events(self.view_mode, &self.events);
+                    self.grouped_selected_index = 0;
+                }
                 self.set_status(format!("View: {}", self.view_mode.label()));
             }
             
@@ -430,8 +1066,8 @@ impl App {
             }
 
             // Navigation - depends on view mode
-            KeyCode::Up | KeyCode::Char('k') => self.move_selection_up(),
-            KeyCode::Down | KeyCode::Char('j') => self.move_selection_down(),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection_up(self.wrap_navigation)?,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection_down(self.wrap_navigation)?,
             
             // Left arrow - collapse in tree view, or go back
             KeyCode::Left | KeyCode::Char('h') => {
@@ -440,7 +1076,7 @@ impl App {
                         let nodes = self.tree_nodes.clone();
                         self.tree_state.collapse_or_parent(&nodes);
                     }
-                    ViewMode::GroupByFolder => {
+                    ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                         self.toggle_grouped_folder();
                     }
                     ViewMode::Flat => {
@@ -458,28 +1094,28 @@ impl App {
                         let nodes = self.tree_nodes.clone();
                         self.tree_state.expand_selected(&nodes);
                     }
-                    ViewMode::GroupByFolder => {
+                    ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                         self.toggle_grouped_folder();
                     }
                     ViewMode::Flat => {
                         if self.selected_event().is_some() {
-                            self.view = View::Detail;
+                            self.open_detail();
                         }
                     }
                 }
             }
             
-            // Space - toggle expand in tree/grouped view
+            // Space - toggle expand in tree/grouped view, mark in flat view
             KeyCode::Char(' ') => {
                 match self.view_mode {
                     ViewMode::TreeView => {
                         let nodes = self.tree_nodes.clone();
                         self.tree_state.toggle_selected(&nodes);
                     }
-                    ViewMode::GroupByFolder => {
+                    ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                         self.toggle_grouped_folder();
                     }
-                    ViewMode::Flat => {}
+                    ViewMode::Flat => self.toggle_mark(),
                 }
             }
             
@@ -501,9 +1137,14 @@ impl App {
                 self.set_status(format!("Page {}/{}", self.current_page(), self.total_pages()));
             }
             
+            KeyCode::Char('+') | KeyCode::Char('=') => self.resize_page(true)?,
+            KeyCode::Char('-') => self.resize_page(false)?,
+
             // Regular page navigation (within page)
             KeyCode::PageUp => self.move_selection_by(-10),
             KeyCode::PageDown => self.move_selection_by(10),
+            KeyCode::Char('g') if self.view == View::Detail => self.show_siblings()?,
+            KeyCode::Char('m') if self.view == View::Detail => self.show_similar()?,
             KeyCode::Home | KeyCode::Char('g') => self.move_to_start(),
             KeyCode::End | KeyCode::Char('G') => self.move_to_end(),
 
@@ -512,6 +1153,26 @@ impl App {
                 self.handle_enter_key();
             }
 
+            // Quick find in the loaded page (flat list only)
+            KeyCode::Char('\'') if self.view == View::List && self.view_mode == ViewMode::Flat => {
+                self.input_mode = InputMode::QuickFind;
+                self.input_buffer.clear();
+                self.quick_find_missed = false;
+                self.status_message = None;
+            }
+
+            // Jump to a date, across pages (flat list only)
+            KeyCode::Char(':') if self.view == View::List && self.view_mode == ViewMode::Flat => {
+                self.input_mode = InputMode::GoToDate;
+                self.input_buffer.clear();
+                self.status_message = None;
+            }
+
+            // Stats for the selected directory
+            KeyCode::Char('i') if self.view == View::List => {
+                self.show_dir_stats()?;
+            }
+
             // Search
             KeyCode::Char('/') => {
                 self.input_mode = InputMode::Search;
@@ -526,18 +1187,77 @@ impl App {
 
             // Clear filters and reset pagination
             KeyCode::Char('c') => {
-                self.filter = EventFilter::new().with_limit(self.page_size).with_offset(0);
-                self.current_offset = 0;
-                self.search_query.clear();
-                self.refresh_events()?;
+                self.clear_filters()?;
                 self.set_status("Filters cleared".to_string());
             }
 
+            // Cycle sort column
+            KeyCode::Char('s') => {
+                self.sort_key = self.sort_key.next();
+                self.apply_sort()?;
+            }
+
+            // Toggle relative/absolute times
+            KeyCode::Char('T') => {
+                self.relative_time = !self.relative_time;
+                let mode = if self.relative_time { "relative" } else { "absolute" };
+                self.set_status(format!("Times: {}", mode));
+            }
+
+            // Switch the size column between rounded and exact bytes
+            KeyCode::Char('B') => {
+                self.toggle_exact_sizes();
+            }
+
+            // Hide the selected folder
+            KeyCode::Char('x') => {
+                self.exclude_selected_dir()?;
+            }
+
+            // Toggle folder size bars (tree view)
+            KeyCode::Char('b') if self.view_mode == ViewMode::TreeView => {
+                self.tree_size_bars = !self.tree_size_bars;
+                let state = if self.tree_size_bars { "on" } else { "off" };
+                self.set_status(format!("Size bars: {}", state));
+            }
+
+            // Toggle sort direction
+            KeyCode::Char('S') => {
+                self.sort_desc = !self.sort_desc;
+                self.apply_sort()?;
+            }
+
             // Help
             KeyCode::Char('?') => {
                 self.input_mode = InputMode::Help;
             }
 
+            // Watcher diagnostics
+            KeyCode::Char('W') => {
+                self.input_mode = InputMode::Diagnostics;
+            }
+
+            // Message history
+            KeyCode::Char('L') => {
+                self.message_log.scroll = 0;
+                self.input_mode = InputMode::Messages;
+            }
+
+            // Watched directories
+            KeyCode::Char('w') => {
+                self.show_watch_paths()?;
+            }
+
+            // Pause/resume watching
+            KeyCode::Char('p') => {
+                self.toggle_pause()?;
+            }
+
+            // Reload the config file
+            KeyCode::Char('R') => {
+                self.reload_config()?;
+            }
+
             // Refresh
             KeyCode::Char('r') => {
                 self.refresh_events()?;
@@ -547,13 +1267,9 @@ impl App {
             // Open file/folder
             KeyCode::Char('o') => {
                 if let Some(event) = self.get_selected_file_event() {
-                    let path = event.path.clone();
+                    let (path, file_type) = (event.path.clone(), event.file_type);
                     if path.exists() {
-                        if let Err(e) = open::that(&path) {
-                            self.set_status(format!("Failed to open: {}", e));
-                        } else {
-                            self.set_status(format!("Opened: {}", path.display()));
-                        }
+                        self.open_file(&path, file_type);
                     } else {
                         self.set_status("File no longer exists".to_string());
                     }
@@ -576,6 +1292,18 @@ impl App {
                 }
             }
 
+            // Copy the file to another directory
+            KeyCode::Char('C') if self.get_selected_file_event().is_some() => {
+                self.input_buffer.clear();
+                self.input_mode = InputMode::CopyTo;
+            }
+
+            // Tag all marked files
+            KeyCode::Char('t') if !self.marked.is_empty() => {
+                self.input_buffer.clear();
+                self.input_mode = InputMode::EditTags;
+            }
+
             // Edit tags
             KeyCode::Char('t') => {
                 if let Some(event) = self.get_selected_file_event() {
@@ -584,6 +1312,20 @@ impl App {
                 }
             }
 
+            // Copy path / containing folder to the clipboard
+            KeyCode::Char('y') => {
+                if let Some(event) = self.get_selected_file_event() {
+                    let path = event.path.to_string_lossy().to_string();
+                    self.copy_to_clipboard(path);
+                }
+            }
+            KeyCode::Char('Y') => {
+                if let Some(event) = self.get_selected_file_event() {
+                    let dir = event.dir.to_string_lossy().to_string();
+                    self.copy_to_clipboard(dir);
+                }
+            }
+
             // Edit notes
             KeyCode::Char('n') => {
                 if let Some(event) = self.get_selected_file_event() {
@@ -592,6 +1334,30 @@ impl App {
                 }
             }
 
+            // Correct the file type
+            KeyCode::Char('F') => {
+                if let Some(event) = self.get_selected_file_event() {
+                    if let Some(id) = event.id {
+                        self.type_chooser = Some(TypeChooser::new(id, event.file_type));
+                        self.input_mode = InputMode::EditType;
+                    }
+                }
+            }
+
+            // Delete all marked files
+            KeyCode::Char('d') if !self.marked.is_empty() => {
+                let ids = self.marked_ids();
+                let total = self.total_size_of(&ids)?;
+                self.pending_action = Some(PendingAction::DeleteMarked(ids, total));
+                self.input_mode = InputMode::Confirm;
+            }
+            KeyCode::Char('D') if !self.marked.is_empty() => {
+                let ids = self.marked_ids();
+                let total = self.total_size_of(&ids)?;
+                self.pending_action = Some(PendingAction::PermanentlyDeleteMarked(ids, total));
+                self.input_mode = InputMode::Confirm;
+            }
+
             // Delete file
             KeyCode::Char('d') => {
                 if let Some(event) = self.get_selected_file_event() {
@@ -599,6 +1365,23 @@ impl App {
                         self.pending_action = Some(PendingAction::DeleteFile(
                             id,
                             event.path.to_string_lossy().to_string(),
+                            event.size_bytes,
+                            event.file_type,
+                        ));
+                        self.input_mode = InputMode::Confirm;
+                    }
+                }
+            }
+
+            // Permanent delete (bypasses trash)
+            KeyCode::Char('D') => {
+                if let Some(event) = self.get_selected_file_event() {
+                    if let Some(id) = event.id {
+                        self.pending_action = Some(PendingAction::PermanentlyDeleteFile(
+                            id,
+                            event.path.to_string_lossy().to_string(),
+                            event.size_bytes,
+                            event.file_type,
                         ));
                         self.input_mode = InputMode::Confirm;
                     }
@@ -611,16 +1394,40 @@ impl App {
         Ok(())
     }
 
+    /// Re-query with the current sort settings, starting from the top
+    fn apply_sort(&mut self) -> Result<()> {
+        self.current_offset = 0;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        self.refresh_events()?;
+        self.set_status(format!("Sort: {}", self.sort_label()));
+        Ok(())
+    }
+
+    /// Short description of the active sort, e.g. "time ↓"
+    pub fn sort_label(&self) -> String {
+        let arrow = if self.sort_desc { "↓" } else { "↑" };
+        format!("{} {}", self.sort_key.label(), arrow)
+    }
+
     /// Handle input in search mode
     fn handle_search_input(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
+            // Toggle between substring and regex matching
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search_is_regex = !self.search_is_regex;
+            }
             KeyCode::Enter => {
-                self.search_query = self.input_buffer.clone();
-                if self.search_query.is_empty() {
-                    self.filter.path_contains = None;
-                } else {
-                    self.filter.path_contains = Some(self.search_query.clone());
+                if self.search_is_regex && !self.input_buffer.is_empty() {
+                    if let Err(e) = regex::Regex::new(&self.input_buffer) {
+                        // Keep the overlay open so the pattern can be fixed
+                        self.set_status(format!("Invalid regex: {}", e));
+                        return Ok(());
+                    }
                 }
+
+                self.search_query = self.input_buffer.clone();
+                set_search(&mut self.filter, &self.search_query, self.search_is_regex);
                 // Reset pagination when search changes
                 self.current_offset = 0;
                 self.refresh_events()?;
@@ -643,16 +1450,34 @@ impl App {
 
     /// Handle input in filter mode
     fn handle_filter_input(&mut self, key: KeyEvent) -> Result<()> {
+        // Typing into a size field
+        if self.filter_overlay.editing.is_some() {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc | KeyCode::Tab => self.filter_overlay.stop_editing(),
+                KeyCode::Backspace => self.filter_overlay.pop_size_char(),
+                KeyCode::Char(c) => self.filter_overlay.push_size_char(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+
         match key.code {
             KeyCode::Esc | KeyCode::Char('f') => {
                 self.input_mode = InputMode::Normal;
             }
             KeyCode::Enter => {
+                if let Err(e) = self.filter_overlay.size_bounds() {
+                    // Keep the overlay open so the size can be fixed
+                    self.set_status(format!("Invalid size: {}", e));
+                    return Ok(());
+                }
+
                 // Apply selected filters and reset pagination
                 let mut new_filter = self.filter_overlay.build_filter();
                 new_filter.limit = self.page_size;
                 new_filter.offset = 0;
                 self.filter = new_filter;
+                self.filter_selection = self.filter_overlay.selection();
                 self.current_offset = 0;
                 self.refresh_events()?;
                 self.input_mode = InputMode::Normal;
@@ -670,9 +1495,17 @@ impl App {
             KeyCode::Right | KeyCode::Char('l') => {
                 self.filter_overlay.increase_value();
             }
+            // Space or typing on a size row starts editing it
+            KeyCode::Char(' ') if self.filter_overlay.start_editing() => {}
             KeyCode::Char(' ') => {
                 self.filter_overlay.toggle_current();
             }
+            KeyCode::Char(c) if c.is_ascii_digit() && self.filter_overlay.start_editing() => {
+                self.filter_overlay.push_size_char(c);
+            }
+            KeyCode::Backspace if self.filter_overlay.start_editing() => {
+                self.filter_overlay.pop_size_char();
+            }
             KeyCode::Char('c') => {
                 self.filter_overlay.reset();
             }
@@ -698,22 +1531,229 @@ impl App {
         Ok(())
     }
 
-    /// Handle input when editing tags
-    fn handle_edit_tags_input(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Enter => {
-                if let Some(event) = self.selected_event() {
-                    if let Some(id) = event.id {
-                        self.store.update_tags(id, &self.input_buffer)?;
-                        self.refresh_events()?;
-                        self.set_status("Tags updated".to_string());
-                    }
-                }
-                self.input_mode = InputMode::Normal;
-            }
-            KeyCode::Esc => {
-                self.input_mode = InputMode::Normal;
-            }
+    /// Handle input when the stats overlay is open
+    fn handle_stats_input(&mut self, key: KeyEvent) {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') | KeyCode::Enter) {
+            self.stats_overlay = None;
+            self.input_mode = InputMode::Normal;
+        }
+    }
+
+    /// Back from the detail view to the list, filtered to the files in the
+    /// same directory as the one shown (`c` clears it as usual)
+    fn show_siblings(&mut self) -> Result<()> {
+        let Some(event) = self.get_selected_file_event() else {
+            return Ok(());
+        };
+        let (id, dir) = (event.id, event.dir.clone());
+
+        self.filter = EventFilter::new()
+            .with_dir(dir.clone())
+            .with_limit(self.page_size)
+            .with_offset(0);
+        self.filter_selection = FilterSelection::default();
+        self.current_offset = 0;
+        self.search_query.clear();
+        self.view = View::List;
+        self.refresh_events()?;
+
+        // Keep the file we came from selected if it's on the first page
+        if let Some(index) = self.events.iter().position(|e| e.id == id) {
+            self.selected_index = index;
+        }
+        self.set_status(format!("Showing {} files in {}", self.total_count, dir.display()));
+        Ok(())
+    }
+
+    /// Go back to the list showing files of the same type whose names start
+    /// like the selected one's, e.g. all parts of a split download
+    fn show_similar(&mut self) -> Result<()> {
+        let Some(event) = self.get_selected_file_event() else {
+            return Ok(());
+        };
+        let Some(stem) = similarity_stem(&event.filename).map(str::to_string) else {
+            self.set_status(format!("No name to match in {}", event.filename));
+            return Ok(());
+        };
+        let (id, file_type) = (event.id, event.file_type);
+
+        self.filter = EventFilter::new()
+            .with_filename_prefix(&stem)
+            .with_type(file_type)
+            .with_limit(self.page_size)
+            .with_offset(0);
+        self.filter_selection = FilterSelection::default();
+        self.current_offset = 0;
+        self.search_query.clear();
+        self.view = View::List;
+        self.refresh_events()?;
+
+        if let Some(index) = self.events.iter().position(|e| e.id == id) {
+            self.selected_index = index;
+        }
+        self.set_status(format!(
+            "Showing {} {} files named {}* (c clears)",
+            self.total_count, file_type, stem
+        ));
+        Ok(())
+    }
+
+    /// Open the stats overlay for the selected directory
+    fn show_dir_stats(&mut self) -> Result<()> {
+        let Some((dir, recursive)) = self.selected_dir() else {
+            self.set_status("Nothing selected".to_string());
+            return Ok(());
+        };
+
+        let stats = self.store.get_stats_for_dir(&dir, recursive)?;
+        let scope = if recursive { "and subfolders" } else { "only" };
+        let title = format!("{} ({})", dir.display(), scope);
+        self.stats_overlay = Some(StatsOverlay::new(title, stats));
+        self.input_mode = InputMode::Stats;
+        Ok(())
+    }
+
+    /// Open the overlay listing each watched directory with its status and
+    /// event count
+    fn show_watch_paths(&mut self) -> Result<()> {
+        let offline = self
+            .offline_handle
+            .as_ref()
+            .and_then(|handle| handle.lock().ok().map(|offline| offline.clone()))
+            .unwrap_or_default();
+        let entries = self
+            .watched_dirs
+            .iter()
+            .map(|path| {
+                Ok(WatchPathEntry {
+                    path: path.clone(),
+                    offline: offline.contains(path),
+                    event_count: self.store.get_stats_for_dir(path, true)?.total_count,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let skipped = self.configured_dirs.saturating_sub(self.watched_dirs.len());
+        self.watch_paths_overlay = Some(WatchPathsOverlay::new(entries, skipped));
+        self.input_mode = InputMode::WatchPaths;
+        Ok(())
+    }
+
+    /// Handle input when the watched directories overlay is open
+    fn handle_watch_paths_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('w') | KeyCode::Enter => {
+                self.watch_paths_overlay = None;
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                if let Some(overlay) = &mut self.watch_paths_overlay {
+                    overlay.scroll_up();
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(overlay) = &mut self.watch_paths_overlay {
+                    overlay.scroll_down();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Hide the selected folder for the rest of the session, then offer to
+    /// ignore it in the config as well
+    fn exclude_selected_dir(&mut self) -> Result<()> {
+        if matches!(self.view_mode, ViewMode::Flat | ViewMode::GroupByExtension) {
+            self.set_status("Select a folder in Grouped or Tree view to hide it".to_string());
+            return Ok(());
+        }
+        let Some((dir, _)) = self.selected_dir() else {
+            self.set_status("Nothing selected".to_string());
+            return Ok(());
+        };
+
+        if !self.excluded_dirs.contains(&dir) {
+            self.excluded_dirs.push(dir.clone());
+        }
+        self.refresh_events()?;
+        self.pending_action = Some(PendingAction::IgnoreDir(dir));
+        self.input_mode = InputMode::Confirm;
+        Ok(())
+    }
+
+    /// Add an ignore pattern for `dir` to the config file
+    ///
+    /// The running watcher keeps its current patterns; the folder stays
+    /// hidden until the next start, when the pattern takes over.
+    fn ignore_dir_in_config(&mut self, dir: &Path) {
+        let pattern = dir_ignore_pattern(dir);
+        match Config::append_ignore_pattern(&self.config_path, &pattern) {
+            Ok(true) => self.set_status(format!("Added {} to ignore_patterns", pattern)),
+            Ok(false) => self.set_status(format!("{} is already in ignore_patterns", pattern)),
+            Err(e) => self.set_status(format!("Failed to update config: {}", e)),
+        }
+    }
+
+    /// Directory of the current selection, and whether its subfolders count too
+    ///
+    /// Tree view folders cover their whole subtree; grouped view folders and
+    /// files only cover their own directory.
+    fn selected_dir(&self) -> Option<(PathBuf, bool)> {
+        match self.view_mode {
+            ViewMode::Flat => self.selected_event().map(|e| (e.dir.clone(), false)),
+            // Extension groups span folders, so only a file row has one
+            ViewMode::GroupByExtension => self.get_selected_file_event().map(|e| (e.dir.clone(), false)),
+            ViewMode::GroupByFolder => {
+                let mut row_idx = 0;
+                for group in &self.folder_groups {
+                    let rows = 1 + if group.expanded { group.files.len() } else { 0 };
+                    if self.grouped_selected_index < row_idx + rows {
+                        return Some((group.path.clone(), false));
+                    }
+                    row_idx += rows;
+                }
+                None
+            }
+            ViewMode::TreeView => {
+                let node = self.tree_state.selected_node()?;
+                if node.is_dir {
+                    Some((node.path.clone(), true))
+                } else {
+                    node.path.parent().map(|p| (p.to_path_buf(), false))
+                }
+            }
+        }
+    }
+
+    /// Handle input when editing tags
+    fn handle_edit_tags_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter if !self.marked.is_empty() => {
+                let add: Vec<String> = self
+                    .input_buffer
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                if !add.is_empty() {
+                    let changed = self.store.update_tags_bulk(&self.marked_ids(), &add, &[])?;
+                    self.refresh_events()?;
+                    self.set_status(format!("Tagged {} marked files", changed));
+                }
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Enter => {
+                if let Some(event) = self.selected_event() {
+                    if let Some(id) = event.id {
+                        self.store.update_tags(id, &self.input_buffer)?;
+                        self.refresh_events()?;
+                        self.set_status("Tags updated".to_string());
+                    }
+                }
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+            }
             KeyCode::Backspace => {
                 self.input_buffer.pop();
             }
@@ -752,44 +1792,383 @@ impl App {
         Ok(())
     }
 
+    /// Handle input while typing a copy destination
+    fn handle_copy_to_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                self.input_mode = InputMode::Normal;
+                let dir = self.input_buffer.trim().to_string();
+                self.start_copy(&dir);
+            }
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Backspace => {
+                self.input_buffer.pop();
+            }
+            KeyCode::Char(c) => {
+                self.input_buffer.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// Start copying the selected file into `dir` (a leading `~/` is expanded)
+    ///
+    /// The copy runs on a worker thread so a large file doesn't freeze the
+    /// UI, and a file already at the destination is never overwritten.
+    fn start_copy(&mut self, dir: &str) {
+        let Some(source) = self.get_selected_file_event().cloned() else {
+            return;
+        };
+        if self.copy_task.is_some() {
+            self.set_status("A copy is already running".to_string());
+            return;
+        }
+        if dir.is_empty() {
+            self.set_status("No destination given".to_string());
+            return;
+        }
+
+        let dir = Config::expand_path(Path::new(dir));
+        if !dir.is_dir() {
+            self.set_status(format!("Not a directory: {}", dir.display()));
+            return;
+        }
+        let dir = dir.canonicalize().unwrap_or(dir);
+        let Some(name) = source.path.file_name() else {
+            return;
+        };
+        let dest = dir.join(name);
+        if dest.exists() {
+            self.set_status(format!("Already exists: {}", dest.display()));
+            return;
+        }
+        if !source.path.exists() {
+            self.set_status("File no longer exists".to_string());
+            return;
+        }
+
+        let (tx, done) = mpsc::channel();
+        let (from, to) = (source.path.clone(), dest.clone());
+        std::thread::spawn(move || {
+            let _ = tx.send(std::fs::copy(from, to));
+        });
+
+        self.set_status(format!("Copying {} to {}…", source.filename, dir.display()));
+        self.copy_task = Some(CopyTask { source, dest, done });
+    }
+
+    /// Report a finished copy
+    ///
+    /// A copy landing in a watched directory is recorded right away, with
+    /// the type, category and hash of the original.
+    pub fn poll_copy(&mut self) -> Result<()> {
+        let Some(task) = self.copy_task.take() else {
+            return Ok(());
+        };
+        let result = match task.done.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => {
+                self.copy_task = Some(task);
+                return Ok(());
+            }
+            Err(TryRecvError::Disconnected) => Err(std::io::Error::other("copy worker stopped")),
+        };
+
+        let bytes = match result {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.set_status(format!("Failed to copy {}: {}", task.source.filename, e));
+                return Ok(());
+            }
+        };
+
+        let watched = self.watched_dirs.iter().any(|dir| task.dest.starts_with(dir));
+        if watched {
+            let mut copy = FileEvent::from_path(task.dest.clone());
+            copy.file_type = task.source.file_type;
+            copy.category = task.source.category.clone();
+            copy.sha256 = task.source.sha256.clone();
+            copy.check_exec_flags();
+            self.store.insert_event(&copy)?;
+            self.refresh_keeping_selection()?;
+        }
+
+        self.set_status(format!(
+            "Copied {} ({}) to {}{}",
+            task.source.filename,
+            format_size(bytes),
+            task.dest.display(),
+            if watched { " and tracked it" } else { "" }
+        ));
+        Ok(())
+    }
+
+    /// Handle input while the file type chooser is open
+    fn handle_edit_type_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(chooser) = &mut self.type_chooser else {
+            self.input_mode = InputMode::Normal;
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => chooser.select_prev(),
+            KeyCode::Down | KeyCode::Char('j') => chooser.select_next(),
+            KeyCode::Enter => {
+                let (id, file_type) = (chooser.event_id, chooser.selected_type());
+                self.type_chooser = None;
+                self.input_mode = InputMode::Normal;
+                self.store.update_file_type(id, file_type)?;
+                self.refresh_events()?;
+                self.set_status(format!("Type set to {}", file_type.as_str()));
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.type_chooser = None;
+                self.input_mode = InputMode::Normal;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     /// Handle confirmation input
     fn handle_confirm_input(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => {
                 if let Some(action) = self.pending_action.take() {
                     match action {
-                        PendingAction::DeleteFile(id, path) => {
-                            // Delete from database
-                            self.store.delete_event(id)?;
-                            
-                            // Try to delete the actual file
-                            let path = std::path::Path::new(&path);
-                            if path.exists() {
-                                if let Err(e) = std::fs::remove_file(path) {
-                                    self.set_status(format!("Removed from ledger, but failed to delete file: {}", e));
-                                } else {
-                                    self.set_status("File deleted".to_string());
-                                }
-                            } else {
-                                self.set_status("Removed from ledger (file already gone)".to_string());
-                            }
-                            
-                            self.refresh_events()?;
+                        PendingAction::DeleteFile(id, path, ..) => {
+                            self.delete_file(id, &path, !self.trash_on_delete)?;
+                        }
+                        PendingAction::PermanentlyDeleteFile(id, path, ..) => {
+                            self.delete_file(id, &path, true)?;
+                        }
+                        PendingAction::DeleteMarked(ids, _) => {
+                            self.delete_marked(&ids, !self.trash_on_delete)?;
+                        }
+                        PendingAction::PermanentlyDeleteMarked(ids, _) => {
+                            self.delete_marked(&ids, true)?;
+                        }
+                        PendingAction::IgnoreDir(dir) => {
+                            self.ignore_dir_in_config(&dir);
                         }
                     }
                 }
                 self.input_mode = InputMode::Normal;
             }
             KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                self.pending_action = None;
+                let status = match self.pending_action.take() {
+                    Some(PendingAction::IgnoreDir(dir)) => {
+                        format!("Hiding {} for this session", dir.display())
+                    }
+                    _ => "Cancelled".to_string(),
+                };
                 self.input_mode = InputMode::Normal;
-                self.set_status("Cancelled".to_string());
+                self.set_status(status);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Put `text` on the system clipboard and report the outcome in the status bar
+    fn copy_to_clipboard(&mut self, text: String) {
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    self.set_status(format!("No clipboard available (headless or no display?): {}", e));
+                    return;
+                }
+            }
+        }
+
+        let result = self
+            .clipboard
+            .as_mut()
+            .map(|clipboard| clipboard.set_text(text.as_str()));
+        match result {
+            Some(Ok(())) => self.set_status(format!("Copied: {}", text)),
+            Some(Err(e)) => self.set_status(format!("Failed to copy to clipboard: {}", e)),
+            None => {}
+        }
+    }
+
+    /// Delete (or trash) the file, then remove its event from the ledger
+    ///
+    /// The ledger row is only removed once the file is gone, so a failed
+    /// delete leaves the event in place.
+    fn delete_file(&mut self, id: i64, path: &str, permanent: bool) -> Result<()> {
+        // Try to delete the actual file
+        let path = std::path::Path::new(path);
+        let status = if !path.exists() {
+            "Removed from ledger (file already gone)".to_string()
+        } else if permanent {
+            if let Err(e) = std::fs::remove_file(path) {
+                self.set_status(format!("Failed to delete file: {}", e));
+                return Ok(());
+            }
+            "File deleted".to_string()
+        } else {
+            if let Err(e) = trash::delete(path) {
+                self.set_status(format!("Failed to move file to trash: {}", e));
+                return Ok(());
+            }
+            "Moved to trash".to_string()
+        };
+
+        // Delete from database (or keep it as history)
+        self.store.remove_event(id, self.retain_deleted)?;
+        self.set_status(status);
+
+        self.refresh_events()
+    }
+
+    /// Delete (or trash) the given events' files and remove them from the ledger
+    fn delete_marked(&mut self, ids: &[i64], permanent: bool) -> Result<()> {
+        let mut removed = 0;
+        let mut failed = 0;
+
+        for &id in ids {
+            let Some(event) = self.store.get_event(id)? else {
+                continue;
+            };
+
+            if event.path.exists() {
+                let ok = if permanent {
+                    std::fs::remove_file(&event.path).is_ok()
+                } else {
+                    trash::delete(&event.path).is_ok()
+                };
+                if !ok {
+                    failed += 1;
+                    continue;
+                }
+            }
+
+            self.store.remove_event(id, self.retain_deleted)?;
+            removed += 1;
+        }
+
+        self.marked.clear();
+        let verb = if permanent { "Deleted" } else { "Moved to trash" };
+        if failed > 0 {
+            self.set_status(format!(
+                "{}: {} files ({} could not be removed from disk and were kept)",
+                verb, removed, failed
+            ));
+        } else {
+            self.set_status(format!("{}: {} files", verb, removed));
+        }
+
+        self.refresh_events()
+    }
+
+    /// Handle input while quick-finding a filename
+    fn handle_quick_find_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter | KeyCode::Esc => {
+                self.input_buffer.clear();
+                self.input_mode = InputMode::Normal;
+            }
+            // Jump to the next match after the current row
+            KeyCode::Tab => self.quick_find(1),
+            KeyCode::Backspace => {
+                self.input_buffer.pop();
+                self.quick_find(0);
+            }
+            KeyCode::Char(c) => {
+                self.input_buffer.push(c);
+                self.quick_find(0);
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle input while typing a date to jump to
+    fn handle_go_to_date_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter => {
+                self.input_mode = InputMode::Normal;
+                let input = self.input_buffer.trim().to_string();
+                match NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+                    Ok(date) => self.go_to_date(date)?,
+                    Err(_) => self.set_status(format!("Invalid date '{}'. Use YYYY-MM-DD", input)),
+                }
+            }
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Backspace => {
+                self.input_buffer.pop();
+            }
+            KeyCode::Char(c) => {
+                self.input_buffer.push(c);
             }
             _ => {}
         }
         Ok(())
     }
 
+    /// Select the first file on or before `date` (on or after it when sorted
+    /// oldest first) within the current filter, loading the page it's on
+    fn go_to_date(&mut self, date: NaiveDate) -> Result<()> {
+        if self.sort_key != SortKey::Time {
+            self.set_status("Sort by time (s) to jump to a date".to_string());
+            return Ok(());
+        }
+
+        // Newest first, the files of `date` start where the next day ends
+        let boundary = if self.sort_desc { date.succ_opt() } else { Some(date) };
+        let Some(boundary) = boundary.and_then(local_midnight) else {
+            self.set_status(format!("Can't jump to {}", date));
+            return Ok(());
+        };
+
+        let rank = self.store.rank_at_time(&self.filter, boundary)?;
+        if rank >= self.total_count {
+            let side = if self.sort_desc { "on or before" } else { "on or after" };
+            self.set_status(format!("No files {} {}", side, date));
+            return Ok(());
+        }
+
+        self.current_offset = rank / self.page_size * self.page_size;
+        self.selected_index = rank % self.page_size;
+        self.refresh_events()?;
+
+        let landed = self
+            .selected_event()
+            .map(|event| event.created_at.with_timezone(&Local).date_naive());
+        match landed {
+            Some(landed) if landed != date => {
+                self.set_status(format!("Nothing on {}, showing {}", date, landed))
+            }
+            _ => self.set_status(format!("Showing files from {}", date)),
+        }
+        Ok(())
+    }
+
+    /// Select the first loaded event at or after `selected_index + skip` whose
+    /// filename contains the quick-find text, wrapping around
+    fn quick_find(&mut self, skip: usize) {
+        let needle = self.input_buffer.to_lowercase();
+        if needle.is_empty() || self.events.is_empty() {
+            self.quick_find_missed = false;
+            return;
+        }
+
+        let len = self.events.len();
+        let found = (0..len)
+            .map(|i| (self.selected_index + skip + i) % len)
+            .find(|&i| self.events[i].filename.to_lowercase().contains(&needle));
+
+        self.quick_find_missed = found.is_none();
+        if let Some(index) = found {
+            self.selected_index = index;
+        }
+    }
+
     /// Move selection by delta (for flat view)
     fn move_selection(&mut self, delta: i32) {
         if self.events.is_empty() {
@@ -806,41 +2185,84 @@ impl App {
     }
     
     /// Move selection up (view-mode aware)
-    fn move_selection_up(&mut self) {
+    ///
+    /// With `wrap`, moving up from the first row goes to the last row of the
+    /// previous page, or of the last page when already on the first one.
+    fn move_selection_up(&mut self, wrap: bool) -> Result<()> {
         match self.view_mode {
-            ViewMode::Flat => self.move_selection(-1),
-            ViewMode::GroupByFolder => {
+            ViewMode::Flat => {
+                if wrap && self.selected_index == 0 && !self.events.is_empty() {
+                    if self.current_offset > 0 {
+                        self.prev_page()?;
+                    } else {
+                        self.last_page()?;
+                    }
+                    self.selected_index = self.events.len().saturating_sub(1);
+                } else {
+                    self.move_selection(-1);
+                }
+            }
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                 if self.grouped_selected_index > 0 {
                     self.grouped_selected_index -= 1;
+                } else if wrap {
+                    self.grouped_selected_index = self.count_grouped_rows().saturating_sub(1);
                 }
             }
             ViewMode::TreeView => {
-                self.tree_state.move_up();
+                if wrap && self.tree_state.selected_index == 0 {
+                    self.tree_state.selected_index = self.tree_state.flattened.len().saturating_sub(1);
+                } else {
+                    self.tree_state.move_up();
+                }
             }
         }
+        Ok(())
     }
     
     /// Move selection down (view-mode aware)
-    fn move_selection_down(&mut self) {
+    ///
+    /// With `wrap`, moving down from the last row goes to the first row of the
+    /// next page, or of the first page when already on the last one.
+    fn move_selection_down(&mut self, wrap: bool) -> Result<()> {
         match self.view_mode {
-            ViewMode::Flat => self.move_selection(1),
-            ViewMode::GroupByFolder => {
+            ViewMode::Flat => {
+                if wrap && self.selected_index + 1 >= self.events.len() && !self.events.is_empty() {
+                    if self.current_offset + self.page_size < self.total_count {
+                        self.next_page()?;
+                    } else {
+                        self.first_page()?;
+                    }
+                    self.selected_index = 0;
+                } else {
+                    self.move_selection(1);
+                }
+            }
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                 let max = self.count_grouped_rows().saturating_sub(1);
                 if self.grouped_selected_index < max {
                     self.grouped_selected_index += 1;
+                } else if wrap {
+                    self.grouped_selected_index = 0;
                 }
             }
             ViewMode::TreeView => {
-                self.tree_state.move_down();
+                if wrap && self.tree_state.selected_index + 1 >= self.tree_state.flattened.len() {
+                    self.tree_state.selected_index = 0;
+                    self.tree_state.scroll_offset = 0;
+                } else {
+                    self.tree_state.move_down();
+                }
             }
         }
+        Ok(())
     }
     
     /// Move selection by delta (view-mode aware)
     fn move_selection_by(&mut self, delta: i32) {
         match self.view_mode {
             ViewMode::Flat => self.move_selection(delta),
-            ViewMode::GroupByFolder => {
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                 let max = self.count_grouped_rows().saturating_sub(1);
                 if delta < 0 {
                     self.grouped_selected_index = self.grouped_selected_index.saturating_sub((-delta) as usize);
@@ -864,7 +2286,7 @@ impl App {
     fn move_to_start(&mut self) {
         match self.view_mode {
             ViewMode::Flat => self.selected_index = 0,
-            ViewMode::GroupByFolder => self.grouped_selected_index = 0,
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => self.grouped_selected_index = 0,
             ViewMode::TreeView => {
                 self.tree_state.selected_index = 0;
                 self.tree_state.scroll_offset = 0;
@@ -880,7 +2302,7 @@ impl App {
                     self.selected_index = self.events.len() - 1;
                 }
             }
-            ViewMode::GroupByFolder => {
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                 self.grouped_selected_index = self.count_grouped_rows().saturating_sub(1);
             }
             ViewMode::TreeView => {
@@ -894,15 +2316,15 @@ impl App {
         match self.view_mode {
             ViewMode::Flat => {
                 if self.selected_event().is_some() {
-                    self.view = View::Detail;
+                    self.open_detail();
                 }
             }
-            ViewMode::GroupByFolder => {
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                 // Toggle folder or view file details
                 if self.is_grouped_selection_on_folder() {
                     self.toggle_grouped_folder();
                 } else if self.get_selected_file_event().is_some() {
-                    self.view = View::Detail;
+                    self.open_detail();
                 }
             }
             ViewMode::TreeView => {
@@ -914,12 +2336,22 @@ impl App {
                         let nodes = self.tree_nodes.clone();
                         self.tree_state.toggle_selected(&nodes);
                     } else {
-                        self.view = View::Detail;
+                        self.open_detail();
                     }
                 }
             }
         }
     }
+
+    /// Switch to the detail view for the current selection
+    fn open_detail(&mut self) {
+        self.duplicate_count = self
+            .get_selected_file_event()
+            .and_then(|e| Some((e.id?, e.sha256.clone()?)))
+            .and_then(|(id, hash)| self.store.count_duplicates(&hash, id).ok())
+            .unwrap_or(0);
+        self.view = View::Detail;
+    }
     
     /// Toggle expand/collapse for currently selected folder in grouped view
     fn toggle_grouped_folder(&mut self) {
@@ -955,7 +2387,7 @@ impl App {
     fn get_selected_file_event(&self) -> Option<&FileEvent> {
         match self.view_mode {
             ViewMode::Flat => self.selected_event(),
-            ViewMode::GroupByFolder => {
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => {
                 let mut row_idx = 0;
                 for group in &self.folder_groups {
                     if row_idx == self.grouped_selected_index {
@@ -1015,10 +2447,14 @@ impl App {
                 InputOverlay::draw_search(self, frame, area);
             }
             InputMode::Filter => {
-                self.filter_overlay.draw(frame, area);
+                self.filter_overlay.draw(frame, area, &self.theme);
             }
             InputMode::Help => {
-                self.help_overlay.draw(frame, area);
+                self.help_overlay.draw(frame, area, &self.theme);
+            }
+            InputMode::EditTags if !self.marked.is_empty() => {
+                let title = format!("Tag {} Marked Files", self.marked.len());
+                InputOverlay::draw_edit(self, frame, area, &title, "Comma-separated tags to add");
             }
             InputMode::EditTags => {
                 InputOverlay::draw_edit(self, frame, area, "Edit Tags", "Comma-separated tags");
@@ -1026,10 +2462,33 @@ impl App {
             InputMode::EditNotes => {
                 InputOverlay::draw_edit(self, frame, area, "Edit Notes", "Enter note text");
             }
+            InputMode::CopyTo => {
+                InputOverlay::draw_edit(self, frame, area, "Copy To", "Destination directory (~/ allowed)");
+            }
             InputMode::Confirm => {
                 self.draw_confirm_dialog(frame, area);
             }
-            InputMode::Normal => {}
+            InputMode::Stats => {
+                if let Some(overlay) = &self.stats_overlay {
+                    overlay.draw(frame, area, &self.theme);
+                }
+            }
+            InputMode::EditType => {
+                if let Some(chooser) = &self.type_chooser {
+                    chooser.draw(frame, area, &self.theme);
+                }
+            }
+            InputMode::Diagnostics => {
+                let stats = self.stats_handle.as_ref().map(|counters| counters.snapshot());
+                DiagnosticsOverlay::draw(stats, frame, area, &self.theme);
+            }
+            InputMode::WatchPaths => {
+                if let Some(overlay) = &self.watch_paths_overlay {
+                    overlay.draw(frame, area, &self.theme);
+                }
+            }
+            InputMode::Messages => self.message_log.draw(frame, area, &self.theme),
+            InputMode::Normal | InputMode::QuickFind | InputMode::GoToDate => {}
         }
     }
 
@@ -1040,23 +2499,57 @@ impl App {
         } else {
             String::new()
         };
+
+        let watched = self.watched_dirs.len();
+        let watching = if self.configured_dirs > watched {
+            format!("{}/{}", watched, self.configured_dirs)
+        } else {
+            watched.to_string()
+        };
         
         let header_text = format!(
-            " 🦡 Ferret │ View: {} │ {}/{} files{} │ Watching {} dirs │ {}",
+            " 🦡 Ferret │ View: {} │ Sort: {} │ {}/{} files, {}{} │ Watching {} dirs │ {:.1}/s │ {}",
             self.view_mode.label(),
+            self.sort_label(),
             self.events.len(),
             self.total_count,
+            format_size(self.filtered_total_size),
             page_info,
-            self.watched_dirs,
+            watching,
+            self.event_rate(),
             self.filter.summary()
         );
 
-        let header = Paragraph::new(header_text)
-            .style(Style::default().fg(Color::Cyan).bold())
+        let mut spans = vec![Span::raw(header_text)];
+        if self.paused {
+            spans.push(Span::styled(
+                " PAUSED ",
+                Style::default().fg(Color::Black).bg(self.theme.status).bold(),
+            ));
+        }
+        let offline = self.offline_count();
+        if offline > 0 {
+            let noun = if offline == 1 { "path" } else { "paths" };
+            spans.push(Span::styled(
+                format!(" ⚠ {} {} offline ", offline, noun),
+                Style::default().fg(self.theme.executable).bold(),
+            ));
+        }
+        let over_quota = self.exceeded_quota_count();
+        if over_quota > 0 {
+            let noun = if over_quota == 1 { "quota" } else { "quotas" };
+            spans.push(Span::styled(
+                format!(" ⚠ {} {} exceeded ", over_quota, noun),
+                Style::default().fg(self.theme.executable).bold(),
+            ));
+        }
+
+        let header = Paragraph::new(Line::from(spans))
+            .style(Style::default().fg(self.theme.header).bold())
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::DarkGray)),
+                    .border_style(self.theme.border_style()),
             );
 
         frame.render_widget(header, area);
@@ -1074,20 +2567,40 @@ impl App {
                     } else {
                         ""
                     };
-                    format!(" Tab:view │ j/k:nav │ Enter:detail │ f:filter │ /:search │ ?:help{} │ q:quit ", page_hint)
+                    let marked_hint = if self.marked.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" {} marked │ d:delete │ t:tag │ Esc:clear │", self.marked.len())
+                    };
+                    format!("{} Tab:view │ j/k:nav │ Enter:detail │ f:filter │ /:search │ ?:help{} │ q:quit ", marked_hint, page_hint)
                 }
                 InputMode::Search => " Type to search │ Enter:apply │ Esc:cancel ".to_string(),
-                InputMode::Filter => " ↑↓:select │ ←→:adjust │ Space:toggle │ Enter:apply │ Esc:cancel ".to_string(),
-                InputMode::Help => " ↑↓:scroll │ q/Esc:close ".to_string(),
+                InputMode::Filter if self.filter_overlay.editing.is_some() => {
+                    " type a size (500K, 1.5G) │ Backspace:delete │ Enter:done ".to_string()
+                }
+                InputMode::Filter => " ↑↓:select │ ←→:adjust │ Space:toggle/edit │ Enter:apply │ Esc:cancel ".to_string(),
+                InputMode::Help | InputMode::WatchPaths | InputMode::Messages => {
+                    " ↑↓:scroll │ q/Esc:close ".to_string()
+                }
                 InputMode::EditTags | InputMode::EditNotes => " Type to edit │ Enter:save │ Esc:cancel ".to_string(),
+                InputMode::CopyTo => " Type a directory │ Enter:copy │ Esc:cancel ".to_string(),
                 InputMode::Confirm => " y:confirm │ n:cancel ".to_string(),
+                InputMode::Stats | InputMode::Diagnostics => " q/Esc:close ".to_string(),
+                InputMode::EditType => " ↑↓:select │ Enter:set type │ Esc:cancel ".to_string(),
+                InputMode::GoToDate => {
+                    format!(" Go to date (YYYY-MM-DD): {} │ Enter:jump │ Esc:cancel ", self.input_buffer)
+                }
+                InputMode::QuickFind => {
+                    let missed = if self.quick_find_missed { " (no match)" } else { "" };
+                    format!(" Find: {}{} │ Tab:next │ Enter/Esc:done ", self.input_buffer, missed)
+                }
             }
         };
 
         let style = if self.status_message.is_some() {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(self.theme.status)
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(self.theme.muted)
         };
 
         let footer = Paragraph::new(status).style(style);
@@ -1097,8 +2610,41 @@ impl App {
     /// Draw confirmation dialog
     fn draw_confirm_dialog(&self, frame: &mut Frame, area: Rect) {
         let message = match &self.pending_action {
-            Some(PendingAction::DeleteFile(_, path)) => {
-                format!("Delete file?\n\n{}\n\n(y)es / (n)o", path)
+            Some(PendingAction::DeleteFile(_, path, size, file_type)) if self.trash_on_delete => {
+                format!("Move {} to trash?\n\n{}\n\n(y)es / (n)o", describe_file(*size, *file_type), path)
+            }
+            Some(PendingAction::DeleteFile(_, path, size, file_type)) => {
+                format!("Delete {}?\n\n{}\n\n(y)es / (n)o", describe_file(*size, *file_type), path)
+            }
+            Some(PendingAction::PermanentlyDeleteFile(_, path, size, file_type)) => {
+                format!(
+                    "Permanently delete {}? This cannot be undone.\n\n{}\n\n(y)es / (n)o",
+                    describe_file(*size, *file_type),
+                    path
+                )
+            }
+            Some(PendingAction::DeleteMarked(ids, total)) if self.trash_on_delete => {
+                format!(
+                    "Move {} marked files ({}) to trash?\n\n(y)es / (n)o",
+                    ids.len(),
+                    format_size(*total)
+                )
+            }
+            Some(PendingAction::DeleteMarked(ids, total)) => {
+                format!("Delete {} marked files ({})?\n\n(y)es / (n)o", ids.len(), format_size(*total))
+            }
+            Some(PendingAction::PermanentlyDeleteMarked(ids, total)) => {
+                format!(
+                    "Permanently delete {} marked files ({})? This cannot be undone.\n\n(y)es / (n)o",
+                    ids.len(),
+                    format_size(*total)
+                )
+            }
+            Some(PendingAction::IgnoreDir(dir)) => {
+                format!(
+                    "Hidden for this session. Ignore new files here too?\n{}\n\nAdds it to ignore_patterns (rewrites config.toml)\n(y)es / (n)o",
+                    dir.display()
+                )
             }
             None => "Confirm?".to_string(),
         };
@@ -1107,8 +2653,8 @@ impl App {
         let dialog_width = 60.min(area.width - 4);
         let dialog_height = 7;
         let dialog_area = Rect::new(
-            (area.width - dialog_width) / 2,
-            (area.height - dialog_height) / 2,
+            area.x + (area.width - dialog_width) / 2,
+            area.y + (area.height - dialog_height) / 2,
             dialog_width,
             dialog_height,
         );
@@ -1129,57 +2675,152 @@ impl App {
     }
 }
 
+/// "4.10 GiB media file" for the delete confirmation, leaving out an unknown size
+fn describe_file(size: Option<u64>, file_type: FileType) -> String {
+    match size {
+        Some(size) => format!("{} {} file", format_size(size), file_type),
+        None => format!("{} file", file_type),
+    }
+}
+
+/// Group rows for the grouped views: by extension in that mode, else by folder
+fn group_events(view_mode: ViewMode, events: &[FileEvent]) -> Vec<FolderGroup> {
+    match view_mode {
+        ViewMode::GroupByExtension => FolderGroup::by_extension(events),
+        _ => FolderGroup::from_events(events),
+    }
+}
+
+/// Read the expanded directories saved by [`App::save_tree_state`], dropping
+/// any that no longer hold events
+///
+/// Returns `None` when nothing was saved, so the tree starts fully expanded.
+fn load_expanded_dirs(store: &Store, path: &Path) -> Option<HashSet<PathBuf>> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let saved: Vec<PathBuf> = match serde_json::from_str(&content) {
+        Ok(saved) => saved,
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable tree state {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    Some(
+        saved
+            .into_iter()
+            .filter(|dir| store.has_events_under(dir).unwrap_or(false))
+            .collect(),
+    )
+}
+
+/// What [`App::save_filter_state`] writes: the overlay choices and search
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct SavedFilter {
+    selection: FilterSelection,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    search: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    search_is_regex: bool,
+}
+
+/// Read the filter saved by [`App::save_filter_state`]
+///
+/// Returns `None` when nothing was saved, or the file is unreadable, so the
+/// TUI starts unfiltered.
+fn load_saved_filter(path: &Path) -> Option<SavedFilter> {
+    let content = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(saved) => Some(saved),
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable filter state {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Search `filter` for `query`, as a path regex or as plain text (an empty
+/// query matches everything)
+fn set_search(filter: &mut EventFilter, query: &str, is_regex: bool) {
+    filter.text_query = None;
+    filter.path_regex = None;
+    if !query.is_empty() {
+        if is_regex {
+            filter.path_regex = Some(query.to_string());
+        } else {
+            filter.text_query = Some(query.to_string());
+        }
+    }
+}
+
 /// Restore terminal to normal state - MUST be called on exit or panic
-fn restore_terminal() {
+///
+/// `alternate_screen` is false for an inline viewport, which never left
+/// the main screen.
+fn restore_terminal(alternate_screen: bool) {
     // Best effort - ignore errors during cleanup
     let _ = crossterm::terminal::disable_raw_mode();
-    let _ = crossterm::execute!(
-        std::io::stdout(),
-        crossterm::terminal::LeaveAlternateScreen,
-        crossterm::cursor::Show
-    );
+    let mut stdout = std::io::stdout();
+    let _ = crossterm::execute!(stdout, crossterm::event::DisableMouseCapture, crossterm::cursor::Show);
+    if alternate_screen {
+        let _ = crossterm::execute!(stdout, crossterm::terminal::LeaveAlternateScreen);
+    }
 }
 
 /// Install a panic hook that restores the terminal
-fn install_panic_hook() {
+fn install_panic_hook(alternate_screen: bool) {
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
-        restore_terminal();
+        restore_terminal(alternate_screen);
         original_hook(panic_info);
     }));
 }
 
 /// RAII guard that restores terminal on drop
-struct TerminalGuard;
+struct TerminalGuard {
+    alternate_screen: bool,
+}
 
 impl Drop for TerminalGuard {
     fn drop(&mut self) {
-        restore_terminal();
+        restore_terminal(self.alternate_screen);
     }
 }
 
 /// Run the TUI application
+///
+/// With `inline` set, the TUI is drawn in that many lines at the bottom of
+/// the terminal instead of the alternate screen, so scrollback is kept.
 pub fn run_tui(
     mut app: App,
     watcher_rx: Option<Receiver<WatcherMessage>>,
+    inline: Option<u16>,
 ) -> Result<()> {
+    let alternate_screen = inline.is_none();
+
     // Install panic hook FIRST before any terminal manipulation
-    install_panic_hook();
+    install_panic_hook(alternate_screen);
     
     // Setup terminal
     crossterm::terminal::enable_raw_mode()?;
     let mut stdout = std::io::stdout();
+    if alternate_screen {
+        crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+    }
     crossterm::execute!(
         stdout,
-        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableMouseCapture,
         crossterm::cursor::Hide
     )?;
     
     // RAII guard ensures cleanup even if we return early via ?
-    let _guard = TerminalGuard;
+    let _guard = TerminalGuard { alternate_screen };
 
     let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let viewport = match inline {
+        Some(lines) => Viewport::Inline(lines),
+        None => Viewport::Fullscreen,
+    };
+    let mut terminal = Terminal::with_options(backend, TerminalOptions { viewport })?;
     
     // Clear and reset terminal state completely
     terminal.clear()?;
@@ -1214,15 +2855,20 @@ pub fn run_tui(
         
         // Process batched refresh if needed
         let _ = app.process_batched_refresh();
+        let _ = app.process_auto_refresh();
+        let _ = app.poll_copy();
 
-        // Clear expired status messages
+        // Clear expired status messages and stale rate samples
         app.clear_expired_status();
+        app.prune_event_times();
 
         // Handle input with shorter poll for responsiveness
         let timeout = tick_rate.saturating_sub(last_tick.elapsed());
         if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                app.handle_key_event(key)?;
+            match event::read()? {
+                Event::Key(key) => app.handle_key_event(key)?,
+                Event::Mouse(mouse) => app.handle_mouse_event(mouse)?,
+                _ => {}
             }
         }
 
@@ -1236,6 +2882,737 @@ pub fn run_tui(
         }
     }
 
+    // Save while the terminal is still ours; failing to save shouldn't fail the exit
+    if let Err(e) = app.save_tree_state() {
+        tracing::warn!("Failed to save tree state: {}", e);
+    }
+    if let Err(e) = app.save_filter_state() {
+        tracing::warn!("Failed to save filter state: {}", e);
+    }
+
+    // Wipe the inline viewport and leave the cursor where it began, so the
+    // shell prompt continues below the existing scrollback
+    if !alternate_screen {
+        terminal.clear()?;
+        let top = terminal.get_frame().area().top();
+        terminal.set_cursor_position((0, top))?;
+    }
+
     // Guard will handle cleanup via Drop
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FileType;
+
+    fn app_with_events(count: usize) -> App {
+        let store = Store::in_memory().unwrap();
+        for i in 0..count {
+            let event = FileEvent::from_path(PathBuf::from(format!("/tmp/file{}.txt", i)));
+            store.insert_event(&event).unwrap();
+        }
+        App::new(store, &Config::default()).unwrap()
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+    }
+
+    #[test]
+    fn test_overlays_draw_inside_inline_viewport() {
+        use ratatui::backend::{Backend, TestBackend};
+
+        let mut backend = TestBackend::new(80, 40);
+        backend.set_cursor_position((0, 35)).unwrap();
+        let mut terminal =
+            Terminal::with_options(backend, TerminalOptions { viewport: Viewport::Inline(12) }).unwrap();
+
+        let mut app = app_with_events(3);
+        press(&mut app, KeyCode::Char('?'));
+        let mut area = Rect::default();
+        terminal
+            .draw(|f| {
+                area = f.area();
+                app.draw(f);
+            })
+            .unwrap();
+        assert_eq!(area, Rect::new(0, 28, 80, 12));
+
+        // Nothing is drawn above the viewport
+        let buffer = terminal.backend().buffer();
+        assert!((0..28).all(|y| (0..80).all(|x| buffer[(x, y)].symbol() == " ")));
+    }
+
+    #[test]
+    fn test_marks_survive_pagination_and_are_pruned() {
+        let mut app = app_with_events(Config::default().page_size + 5);
+        app.view_mode = ViewMode::Flat;
+
+        press(&mut app, KeyCode::Char(' '));
+        let marked_id = app.selected_event().unwrap().id.unwrap();
+        assert!(app.marked.contains(&marked_id));
+
+        app.next_page().unwrap();
+        assert_eq!(app.marked.len(), 1);
+
+        app.store.delete_event(marked_id).unwrap();
+        app.refresh_events().unwrap();
+        assert!(app.marked.is_empty());
+    }
+
+    #[test]
+    fn test_delete_confirmation_shows_size_and_type() {
+        let store = Store::in_memory().unwrap();
+        for (path, size) in [("/tmp/movie.mkv", 4_400_000_000), ("/tmp/notes.txt", 600_000_000)] {
+            let mut event = FileEvent::from_path(PathBuf::from(path));
+            event.size_bytes = Some(size);
+            store.insert_event(&event).unwrap();
+        }
+        let mut app = App::new(store, &Config::default()).unwrap();
+        app.view_mode = ViewMode::Flat;
+
+        let selected = app.selected_event().unwrap().clone();
+        press(&mut app, KeyCode::Char('D'));
+        match &app.pending_action {
+            Some(PendingAction::PermanentlyDeleteFile(_, _, size, file_type)) => {
+                assert_eq!(*size, selected.size_bytes);
+                assert_eq!(*file_type, selected.file_type);
+            }
+            other => panic!("unexpected pending action {:?}", other),
+        }
+        assert_eq!(describe_file(Some(4_400_000_000), FileType::Media), "4.10 GiB media file");
+        assert_eq!(describe_file(None, FileType::Document), "document file");
+        press(&mut app, KeyCode::Char('n'));
+
+        // Marked files report their count and combined size
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Char('d'));
+        match &app.pending_action {
+            Some(PendingAction::DeleteMarked(ids, total)) => {
+                assert_eq!(ids.len(), 2);
+                assert_eq!(*total, 5_000_000_000);
+            }
+            other => panic!("unexpected pending action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_event_rate_window() {
+        let mut app = app_with_events(0);
+        for _ in 0..10 {
+            app.handle_watcher_message(WatcherMessage::DeletedFile(PathBuf::from("/tmp/x")))
+                .unwrap();
+        }
+        app.handle_watcher_message(WatcherMessage::Started).unwrap();
+        assert_eq!(app.event_rate(), 2.0);
+
+        // Samples older than the window are pruned
+        app.event_times[0] -= EVENT_RATE_WINDOW * 2;
+        app.prune_event_times();
+        assert_eq!(app.event_times.len(), 9);
+    }
+
+    #[test]
+    fn test_bulk_tag_and_clear() {
+        let mut app = app_with_events(3);
+        app.view_mode = ViewMode::Flat;
+
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.marked.len(), 2);
+
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.input_mode, InputMode::EditTags);
+        for c in "keep".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        let tagged = app.events.iter().filter(|e| e.tags == "keep").count();
+        assert_eq!(tagged, 2);
+
+        // Esc clears marks instead of quitting
+        press(&mut app, KeyCode::Esc);
+        assert!(app.marked.is_empty());
+        assert_eq!(app.state, AppState::Running);
+    }
+
+    #[test]
+    fn test_tree_state_persists() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            database_path: Some(temp_dir.path().join("ledger.db")),
+            ..Config::default()
+        };
+        let store = Store::in_memory().unwrap();
+        for path in ["/data/docs/a.pdf", "/data/docs/old/b.pdf", "/data/pics/c.jpg"] {
+            store.insert_event(&FileEvent::from_path(PathBuf::from(path))).unwrap();
+        }
+
+        // Nothing saved yet: everything starts expanded
+        let mut app = App::new(store.clone(), &config).unwrap();
+        assert!(app.tree_state.expanded.contains(Path::new("/data/docs/old")));
+
+        app.tree_state.collapse(&PathBuf::from("/data/docs/old"));
+        app.tree_state.expand(&PathBuf::from("/data/gone"));
+        app.refresh_events().unwrap();
+        assert!(!app.tree_state.expanded.contains(Path::new("/data/docs/old")));
+        app.save_tree_state().unwrap();
+
+        let app = App::new(store, &config).unwrap();
+        assert!(!app.tree_state.expanded.contains(Path::new("/data/docs/old")));
+        assert!(!app.tree_state.expanded.contains(Path::new("/data/gone")));
+        assert!(app.tree_state.expanded.contains(Path::new("/data/pics")));
+    }
+
+    #[test]
+    fn test_auto_refresh_keeps_selection() {
+        let mut app = app_with_events(3);
+        app.auto_refresh = Some(Duration::ZERO);
+        press(&mut app, KeyCode::Down);
+        let selected = app.selected_event().unwrap().id;
+
+        // Rows written by another process, sorting above the selection
+        let mut newer = FileEvent::from_path(PathBuf::from("/tmp/newer.txt"));
+        newer.created_at += chrono::Duration::minutes(1);
+        app.store.insert_event(&newer).unwrap();
+
+        app.process_auto_refresh().unwrap();
+        assert_eq!(app.events.len(), 4);
+        assert_eq!(app.selected_index, 2);
+        assert_eq!(app.selected_event().unwrap().id, selected);
+
+        // Not while an overlay is open
+        app.store.insert_event(&FileEvent::from_path(PathBuf::from("/tmp/later.txt"))).unwrap();
+        press(&mut app, KeyCode::Char('?'));
+        app.process_auto_refresh().unwrap();
+        assert_eq!(app.events.len(), 4);
+
+        // Off unless configured
+        press(&mut app, KeyCode::Esc);
+        app.auto_refresh = None;
+        app.process_auto_refresh().unwrap();
+        assert_eq!(app.events.len(), 4);
+    }
+
+    #[test]
+    fn test_toggle_exact_sizes() {
+        let mut app = app_with_events(1);
+        press(&mut app, KeyCode::Char('B'));
+        assert_eq!(app.list_columns[1], ListColumn::Bytes);
+        press(&mut app, KeyCode::Char('B'));
+        assert_eq!(app.list_columns, ListColumn::defaults());
+
+        app.list_columns = vec![ListColumn::Path];
+        press(&mut app, KeyCode::Char('B'));
+        assert_eq!(app.list_columns, vec![ListColumn::Path]);
+    }
+
+    #[test]
+    fn test_watch_paths_overlay() {
+        let mut app = app_with_events(3);
+        app.set_watched_dirs(vec![PathBuf::from("/tmp"), PathBuf::from("/data")], 3);
+        app.set_offline_handle(Arc::new(Mutex::new(HashSet::from([PathBuf::from("/data")]))));
+
+        press(&mut app, KeyCode::Char('w'));
+        assert_eq!(app.input_mode, InputMode::WatchPaths);
+        let overlay = app.watch_paths_overlay.as_ref().unwrap();
+        let summary: Vec<_> = overlay
+            .entries
+            .iter()
+            .map(|entry| (entry.path.to_str().unwrap(), entry.offline, entry.event_count))
+            .collect();
+        assert_eq!(summary, vec![("/data", true, 0), ("/tmp", false, 3)]);
+        assert_eq!(overlay.skipped, 1);
+
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.watch_paths_overlay.is_none());
+    }
+
+    #[test]
+    fn test_quota_warning() {
+        let mut app = app_with_events(1);
+        assert_eq!(app.exceeded_quota_count(), 0);
+
+        let exceeded = Arc::new(Mutex::new(HashSet::new()));
+        app.set_quota_handle(exceeded.clone());
+        exceeded.lock().unwrap().insert("/tmp".to_string());
+        app.handle_watcher_message(WatcherMessage::Warning("Quota exceeded: /tmp".to_string()))
+            .unwrap();
+        assert_eq!(app.exceeded_quota_count(), 1);
+        assert_eq!(app.status_message.as_ref().unwrap().0, "Quota exceeded: /tmp");
+
+        // The badge goes away once the watcher finds the quota met again
+        exceeded.lock().unwrap().clear();
+        assert_eq!(app.exceeded_quota_count(), 0);
+    }
+
+    #[test]
+    fn test_filter_size_range() {
+        let mut app = app_with_events(1);
+        press(&mut app, KeyCode::Char('f'));
+        let min_row = app.filter_overlay.selected_types.len() + 3;
+        while app.filter_overlay.selected != min_row {
+            press(&mut app, KeyCode::Down);
+        }
+        for c in "10k".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Down);
+        for c in "1k".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+
+        // Minimum above maximum: the overlay stays open
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.input_mode, InputMode::Filter);
+        assert!(app.status_message.as_ref().unwrap().0.starts_with("Invalid size"));
+
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Backspace);
+        for c in "1m".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert_eq!((app.filter.min_size, app.filter.max_size), (Some(10 << 10), Some(1 << 20)));
+        assert_eq!(app.filter_selection.max_bytes, Some(1 << 20));
+    }
+
+    #[test]
+    fn test_filter_state_persists() {
+        use crate::tui::filters::TimePeriod;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            database_path: Some(temp_dir.path().join("ledger.db")),
+            ..Config::default()
+        };
+        let store = Store::in_memory().unwrap();
+        for path in ["/data/report.zip", "/data/photos.zip", "/data/report.pdf"] {
+            store.insert_event(&FileEvent::from_path(PathBuf::from(path))).unwrap();
+        }
+
+        let mut app = App::new(store.clone(), &config).unwrap();
+        assert_eq!(app.events.len(), 3);
+        press(&mut app, KeyCode::Char('f'));
+        let archive = FileType::all().iter().position(|&t| t == FileType::Archive).unwrap();
+        app.filter_overlay.selected_types[archive] = true;
+        app.filter_overlay.time_period = TimePeriod::Last24Hours;
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('/'));
+        for c in "report".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.events.len(), 1);
+        let first_since = app.filter.since.unwrap();
+        app.save_filter_state().unwrap();
+
+        // The period is re-derived from now rather than the saved time
+        std::thread::sleep(Duration::from_millis(10));
+        let mut app = App::new(store.clone(), &config).unwrap();
+        assert_eq!(app.filter.file_types, vec![FileType::Archive]);
+        assert!(app.filter.since.unwrap() > first_since);
+        assert_eq!(app.search_query, "report");
+        assert_eq!(app.events.len(), 1);
+
+        // Clearing is remembered too
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(app.events.len(), 3);
+        app.save_filter_state().unwrap();
+        let app = App::new(store, &config).unwrap();
+        assert_eq!(app.filter_selection, FilterSelection::default());
+        assert_eq!(app.events.len(), 3);
+    }
+
+    #[test]
+    fn test_go_to_date_pages_to_the_day() {
+        let store = Store::in_memory().unwrap();
+        let today = Local::now().date_naive();
+        let noon = |date: NaiveDate| local_midnight(date).unwrap() + chrono::Duration::hours(12);
+        // One file a day for 30 days, except none 12 days ago
+        for days_ago in (0..30).filter(|&d| d != 12) {
+            let mut event = FileEvent::from_path(PathBuf::from(format!("/tmp/day{}.txt", days_ago)));
+            event.created_at = noon(today - chrono::Duration::days(days_ago));
+            store.insert_event(&event).unwrap();
+        }
+        let config = Config {
+            page_size: 10,
+            ..Config::default()
+        };
+        let mut app = App::new(store, &config).unwrap();
+        app.view_mode = ViewMode::Flat;
+
+        let go_to = |app: &mut App, date: NaiveDate| {
+            press(app, KeyCode::Char(':'));
+            assert_eq!(app.input_mode, InputMode::GoToDate);
+            for c in date.format("%Y-%m-%d").to_string().chars() {
+                press(app, KeyCode::Char(c));
+            }
+            press(app, KeyCode::Enter);
+        };
+
+        go_to(&mut app, today - chrono::Duration::days(15));
+        assert_eq!(app.current_offset, 10);
+        assert_eq!(app.selected_event().unwrap().filename, "day15.txt");
+        assert_eq!(app.input_mode, InputMode::Normal);
+
+        // A day without files lands on the one before it
+        let empty_day = today - chrono::Duration::days(12);
+        go_to(&mut app, empty_day);
+        assert_eq!(app.selected_event().unwrap().filename, "day13.txt");
+        assert!(app.status_message.as_ref().unwrap().0.starts_with(&format!("Nothing on {}", empty_day)));
+
+        // Oldest first, it lands on the day after instead
+        app.sort_desc = false;
+        app.refresh_events().unwrap();
+        go_to(&mut app, empty_day);
+        assert_eq!(app.selected_event().unwrap().filename, "day11.txt");
+
+        go_to(&mut app, today + chrono::Duration::days(1));
+        assert_eq!(app.status_message.as_ref().unwrap().0, format!("No files on or after {}", today + chrono::Duration::days(1)));
+    }
+
+    #[test]
+    fn test_show_similar_from_detail() {
+        let store = Store::in_memory().unwrap();
+        for path in ["/dl/movie.part1.rar", "/dl/sub/movie.part2.rar", "/dl/movie.nfo", "/dl/other.rar"] {
+            store.insert_event(&FileEvent::from_path(PathBuf::from(path))).unwrap();
+        }
+        let config = Config {
+            ignore_patterns: Vec::new(),
+            ..Config::default()
+        };
+        let mut app = App::new(store, &config).unwrap();
+        app.view_mode = ViewMode::Flat;
+        app.selected_index = app.events.iter().position(|e| e.filename == "movie.part1.rar").unwrap();
+
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('m'));
+
+        assert_eq!(app.view, View::List);
+        assert_eq!(app.total_count, 2);
+        assert!(app.events.iter().all(|e| e.filename.starts_with("movie.part")));
+        assert_eq!(app.selected_event().unwrap().filename, "movie.part1.rar");
+        assert_eq!(
+            app.status_message.as_ref().unwrap().0,
+            "Showing 2 archive files named movie* (c clears)"
+        );
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(app.total_count, 4);
+    }
+
+    #[test]
+    fn test_show_siblings_from_detail() {
+        let store = Store::in_memory().unwrap();
+        for path in ["/data/docs/a.pdf", "/data/docs/b.pdf", "/data/pics/c.jpg"] {
+            store.insert_event(&FileEvent::from_path(PathBuf::from(path))).unwrap();
+        }
+        let config = Config {
+            ignore_patterns: Vec::new(),
+            ..Config::default()
+        };
+        let mut app = App::new(store, &config).unwrap();
+        app.view_mode = ViewMode::Flat;
+        let index = app.events.iter().position(|e| e.filename == "b.pdf").unwrap();
+        app.selected_index = index;
+
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, View::Detail);
+        press(&mut app, KeyCode::Char('g'));
+
+        assert_eq!(app.view, View::List);
+        assert_eq!(app.total_count, 2);
+        assert!(app.events.iter().all(|e| e.dir == Path::new("/data/docs")));
+        assert_eq!(app.selected_event().unwrap().filename, "b.pdf");
+        assert_eq!(app.status_message.as_ref().unwrap().0, "Showing 2 files in /data/docs");
+
+        // In the list, g still jumps to the start; c brings everything back
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.selected_index, 0);
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(app.total_count, 3);
+    }
+
+    #[test]
+    fn test_wrap_navigation_crosses_pages() {
+        let mut app = app_with_events(Config::default().page_size + 5);
+        app.view_mode = ViewMode::Flat;
+
+        // Off by default: Up on the first row stays put
+        press(&mut app, KeyCode::Up);
+        assert_eq!((app.current_offset, app.selected_index), (0, 0));
+
+        app.wrap_navigation = true;
+        press(&mut app, KeyCode::Char('G'));
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!((app.current_offset, app.selected_index), (5, 0));
+
+        // Past the end of the last page goes back to the first
+        press(&mut app, KeyCode::Char('G'));
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!((app.current_offset, app.selected_index), (0, 0));
+
+        // And back up from the very first row lands on the last entry
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.current_offset, 5);
+        assert_eq!(app.selected_index, Config::default().page_size - 1);
+    }
+
+    #[test]
+    fn test_quick_find_wraps_around() {
+        let mut app = app_with_events(12);
+        app.view_mode = ViewMode::Flat;
+        let matches: Vec<usize> = (0..app.events.len())
+            .filter(|&i| app.events[i].filename.starts_with("file1"))
+            .collect();
+        assert_eq!(matches.len(), 3);
+
+        press(&mut app, KeyCode::Char('\''));
+        assert_eq!(app.input_mode, InputMode::QuickFind);
+        for c in "FILE1".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(app.selected_index, matches[0]);
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.selected_index, matches[1]);
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.selected_index, matches[0]);
+
+        // No match keeps the selection where it was
+        press(&mut app, KeyCode::Char('z'));
+        assert!(app.quick_find_missed);
+        assert_eq!(app.selected_index, matches[0]);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.input_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_dir_stats_overlay() {
+        let mut app = app_with_events(3);
+        app.view_mode = ViewMode::GroupByFolder;
+
+        press(&mut app, KeyCode::Char('i'));
+        assert_eq!(app.input_mode, InputMode::Stats);
+        let overlay = app.stats_overlay.as_ref().unwrap();
+        assert!(overlay.title.starts_with("/tmp"));
+        assert_eq!(overlay.stats.total_count, 3);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.stats_overlay.is_none());
+    }
+
+    #[test]
+    fn test_group_by_extension_view() {
+        let store = Store::in_memory().unwrap();
+        for path in ["/dl/a.iso", "/dl/b.zip", "/other/c.ISO"] {
+            store.insert_event(&FileEvent::from_path(PathBuf::from(path))).unwrap();
+        }
+        let mut app = App::new(store, &Config::default()).unwrap();
+        app.view_mode = ViewMode::GroupByFolder;
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.view_mode, ViewMode::GroupByExtension);
+        let names: Vec<&str> = app.folder_groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec![".iso", ".zip"]);
+
+        // The first row is the ".iso" header, then its files from both folders
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.get_selected_file_event().unwrap().extension(), "iso");
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.input_mode, InputMode::Normal);
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.view_mode, ViewMode::TreeView);
+        assert_eq!(app.folder_groups.len(), 2);
+        assert!(app.folder_groups.iter().any(|g| g.path == Path::new("/other")));
+    }
+
+    #[test]
+    fn test_resize_page_keeps_selection() {
+        let mut app = app_with_events(250);
+        app.view_mode = ViewMode::Flat;
+        app.next_page().unwrap();
+        app.selected_index = 45;
+        let selected = app.selected_event().unwrap().id;
+
+        press(&mut app, KeyCode::Char('-'));
+        assert_eq!((app.page_size, app.current_offset, app.selected_index), (90, 90, 55));
+        assert_eq!(app.events.len(), 90);
+        assert_eq!(app.selected_event().unwrap().id, selected);
+
+        press(&mut app, KeyCode::Char('+'));
+        press(&mut app, KeyCode::Char('+'));
+        assert_eq!((app.page_size, app.current_offset, app.selected_index), (110, 110, 35));
+        assert_eq!(app.total_pages(), 3);
+        assert_eq!(app.selected_event().unwrap().id, selected);
+
+        app.page_size = 10;
+        press(&mut app, KeyCode::Char('-'));
+        assert_eq!(app.page_size, 10);
+    }
+
+    #[test]
+    fn test_change_file_type() {
+        let mut app = app_with_events(1);
+        app.view_mode = ViewMode::Flat;
+
+        // Starts on the current type; Esc leaves it alone
+        press(&mut app, KeyCode::Char('F'));
+        assert_eq!(app.input_mode, InputMode::EditType);
+        assert_eq!(app.type_chooser.as_ref().unwrap().selected_type(), FileType::Document);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert_eq!(app.selected_event().unwrap().file_type, FileType::Document);
+
+        press(&mut app, KeyCode::Char('F'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.type_chooser.is_none());
+        assert_eq!(app.selected_event().unwrap().file_type, FileType::Media);
+    }
+
+    #[test]
+    fn test_exclude_dir() {
+        let store = Store::in_memory().unwrap();
+        for path in ["/dl/junk/a.txt", "/dl/junk/b.txt", "/dl/keep.txt"] {
+            store.insert_event(&FileEvent::from_path(PathBuf::from(path))).unwrap();
+        }
+        let mut app = App::new(store, &Config::default()).unwrap();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        app.set_config_path(config_path.clone());
+        app.view_mode = ViewMode::GroupByFolder;
+        // Rows are folder headers followed by their files when expanded
+        let select_junk = |app: &mut App| {
+            app.grouped_selected_index = app
+                .folder_groups
+                .iter()
+                .take_while(|g| g.path != Path::new("/dl/junk"))
+                .map(|g| 1 + if g.expanded { g.files.len() } else { 0 })
+                .sum();
+        };
+        select_junk(&mut app);
+
+        // Declining keeps the folder hidden without touching the config
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.input_mode, InputMode::Confirm);
+        assert_eq!(app.total_count, 1);
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(!config_path.exists());
+
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(app.total_count, 3);
+
+        select_junk(&mut app);
+        press(&mut app, KeyCode::Char('x'));
+        press(&mut app, KeyCode::Char('y'));
+        let config = Config::load_from_file(&config_path).unwrap();
+        assert!(config.ignore_patterns.contains(&"/dl/junk/**".to_string()));
+        assert_eq!(app.events.len(), 1);
+    }
+
+    #[test]
+    fn test_copy_file_to_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("report.pdf");
+        let dest_dir = temp_dir.path().join("project");
+        std::fs::write(&source, "pdf contents").unwrap();
+        std::fs::create_dir(&dest_dir).unwrap();
+
+        let store = Store::in_memory().unwrap();
+        let mut event = FileEvent::from_path(source.clone());
+        event.sha256 = Some("abc123".to_string());
+        store.insert_event(&event).unwrap();
+        let mut app = App::new(store, &Config::default()).unwrap();
+        app.watched_dirs = vec![dest_dir.canonicalize().unwrap()];
+
+        let copy_to = |app: &mut App| {
+            press(app, KeyCode::Char('C'));
+            assert_eq!(app.input_mode, InputMode::CopyTo);
+            for c in dest_dir.to_string_lossy().chars() {
+                press(app, KeyCode::Char(c));
+            }
+            press(app, KeyCode::Enter);
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while app.copy_task.is_some() {
+                assert!(Instant::now() < deadline, "copy did not finish");
+                std::thread::sleep(Duration::from_millis(10));
+                app.poll_copy().unwrap();
+            }
+            app.status_message.clone().unwrap().0
+        };
+
+        let status = copy_to(&mut app);
+        assert!(status.starts_with("Copied report.pdf"), "{}", status);
+        assert!(status.ends_with("and tracked it"), "{}", status);
+        let dest = dest_dir.canonicalize().unwrap().join("report.pdf");
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "pdf contents");
+        let copy = app.store.get_event_by_path(&dest).unwrap().unwrap();
+        assert_eq!(copy.sha256.as_deref(), Some("abc123"));
+
+        // The copy is now listed first; select the original again
+        app.selected_index = app.events.iter().position(|e| e.path == source).unwrap();
+        let status = copy_to(&mut app);
+        assert!(status.starts_with("Already exists"), "{}", status);
+    }
+
+    #[test]
+    fn test_message_history() {
+        use crate::tui::messages::MESSAGE_HISTORY_LEN;
+
+        let mut app = app_with_events(1);
+        for i in 0..MESSAGE_HISTORY_LEN + 5 {
+            app.set_status(format!("message {}", i));
+        }
+        app.handle_watcher_message(WatcherMessage::DeletedFile(PathBuf::from("/tmp/gone.txt"))).unwrap();
+
+        let messages: Vec<&str> = app.message_log.newest_first().map(|(_, m)| m.as_str()).collect();
+        assert_eq!(messages.len(), MESSAGE_HISTORY_LEN);
+        assert_eq!(messages[0], "Gone from disk: /tmp/gone.txt");
+        assert_eq!(messages[1], format!("message {}", MESSAGE_HISTORY_LEN + 4));
+        assert_eq!(*messages.last().unwrap(), "message 6");
+
+        press(&mut app, KeyCode::Char('L'));
+        assert_eq!(app.input_mode, InputMode::Messages);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.message_log.scroll, 1);
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn test_open_with_missing_opener() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.md");
+        std::fs::write(&file, "# notes").unwrap();
+
+        let store = Store::in_memory().unwrap();
+        store.insert_event(&FileEvent::from_path(file)).unwrap();
+        let mut config = Config::default();
+        config.openers.insert("md".to_string(), "ferret-no-such-editor {path}".to_string());
+        let mut app = App::new(store, &config).unwrap();
+        app.view_mode = ViewMode::Flat;
+
+        press(&mut app, KeyCode::Char('o'));
+        let status = app.status_message.clone().unwrap().0;
+        assert!(status.starts_with("Failed to run ferret-no-such-editor"), "{}", status);
+    }
+}
@@ -24,7 +24,7 @@ impl DetailView {
                         Block::default()
                             .title(" Details ")
                             .borders(Borders::ALL)
-                            .border_style(Style::default().fg(Color::DarkGray)),
+                            .border_style(app.theme.border_style()),
                     );
                 frame.render_widget(empty, area);
                 return;
@@ -44,30 +44,47 @@ impl DetailView {
         let local_time = event.created_at.with_timezone(&Local);
         let utc_time = event.created_at;
 
-        let exists = event.path.exists();
+        let exists = !event.is_deleted();
         let exists_indicator = if exists { "✓" } else { "✗" };
         let exists_color = if exists { Color::Green } else { Color::Red };
+        let exists_text = match event.deleted_at {
+            Some(deleted_at) => format!(
+                " Deleted {}",
+                deleted_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S %Z")
+            ),
+            None => " File present".to_string(),
+        };
 
-        let info_lines = vec![
+        let mut info_lines = vec![
             Line::from(vec![
-                Span::styled("Path: ", Style::default().fg(Color::Yellow)),
+                Span::styled("Path: ", Style::default().fg(app.theme.label)),
                 Span::raw(event.path.to_string_lossy().to_string()),
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("Filename: ", Style::default().fg(Color::Yellow)),
+                Span::styled("Filename: ", Style::default().fg(app.theme.label)),
                 Span::raw(&event.filename),
             ]),
             Line::from(vec![
-                Span::styled("Directory: ", Style::default().fg(Color::Yellow)),
+                Span::styled("Directory: ", Style::default().fg(app.theme.label)),
                 Span::raw(event.dir.to_string_lossy().to_string()),
             ]),
+        ];
+
+        if let Some(url) = &event.origin_url {
+            info_lines.push(Line::from(vec![
+                Span::styled("From: ", Style::default().fg(app.theme.label)),
+                Span::raw(url.clone()),
+            ]));
+        }
+
+        info_lines.extend([
             Line::from(""),
             Line::from(vec![
-                Span::styled("Size: ", Style::default().fg(Color::Yellow)),
+                Span::styled("Size: ", Style::default().fg(app.theme.label)),
                 Span::styled(
                     event.size_display(),
-                    Style::default().fg(Color::Cyan),
+                    Style::default().fg(app.theme.header),
                 ),
                 Span::raw(format!(
                     " ({})",
@@ -79,33 +96,53 @@ impl DetailView {
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("Type: ", Style::default().fg(Color::Yellow)),
+                Span::styled("Type: ", Style::default().fg(app.theme.label)),
                 Span::styled(
                     event.file_type.as_str(),
-                    Self::type_style(event.file_type),
+                    Self::type_style(app, event.file_type),
+                ),
+                Span::styled(
+                    app.category_label(event).map(|label| format!("  ({})", label)).unwrap_or_default(),
+                    Style::default().fg(app.theme.muted),
                 ),
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("First Seen: ", Style::default().fg(Color::Yellow)),
+                Span::styled("First Seen: ", Style::default().fg(app.theme.label)),
                 Span::raw(local_time.format("%Y-%m-%d %H:%M:%S %Z").to_string()),
             ]),
             Line::from(vec![
-                Span::styled("            ", Style::default().fg(Color::Yellow)),
+                Span::styled("            ", Style::default().fg(app.theme.label)),
                 Span::styled(
                     format!("({})", utc_time.format("%Y-%m-%d %H:%M:%S UTC")),
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(app.theme.muted),
                 ),
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("Exists: ", Style::default().fg(Color::Yellow)),
+                Span::styled("Exists: ", Style::default().fg(app.theme.label)),
                 Span::styled(exists_indicator, Style::default().fg(exists_color)),
-                Span::raw(if exists { " File present" } else { " File missing" }),
+                Span::raw(exists_text),
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("Tags: ", Style::default().fg(Color::Yellow)),
+                Span::styled("SHA-256: ", Style::default().fg(app.theme.label)),
+                Span::raw(event.sha256.clone().unwrap_or_else(|| "(not hashed)".to_string())),
+            ]),
+            Line::from(vec![
+                Span::styled("Duplicates: ", Style::default().fg(app.theme.label)),
+                if app.duplicate_count > 0 {
+                    Span::styled(
+                        format!("{} duplicates", app.duplicate_count),
+                        Style::default().fg(Color::Magenta),
+                    )
+                } else {
+                    Span::raw("none")
+                },
+            ]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("Tags: ", Style::default().fg(app.theme.label)),
                 Span::raw(if event.tags.is_empty() {
                     "(none)".to_string()
                 } else {
@@ -114,14 +151,29 @@ impl DetailView {
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("Notes: ", Style::default().fg(Color::Yellow)),
+                Span::styled("Notes: ", Style::default().fg(app.theme.label)),
             ]),
             Line::from(vec![Span::raw(if event.notes.is_empty() {
                 "(none)".to_string()
             } else {
                 event.notes.clone()
             })]),
-        ];
+        ]);
+
+        // Runnable or downloaded executables get a warning above everything else
+        if event.is_flagged_executable() {
+            let reason = if event.quarantined { "quarantined" } else { "execute bit set" };
+            info_lines.splice(
+                0..0,
+                [
+                    Line::from(Span::styled(
+                        format!("⚠ Executable ({})", reason),
+                        Style::default().fg(app.theme.executable).bold(),
+                    )),
+                    Line::from(""),
+                ],
+            );
+        }
 
         let info = Paragraph::new(info_lines)
             .wrap(Wrap { trim: false })
@@ -129,7 +181,7 @@ impl DetailView {
                 Block::default()
                     .title(format!(" {} ", event.filename))
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Cyan)),
+                    .border_style(Style::default().fg(app.theme.header)),
             );
 
         frame.render_widget(info, info_area);
@@ -144,6 +196,10 @@ impl DetailView {
                 Span::styled(" O ", Style::default().fg(Color::Green).bold()),
                 Span::raw("Open folder"),
             ])),
+            ListItem::new(Line::from(vec![
+                Span::styled(" g ", Style::default().fg(Color::Green).bold()),
+                Span::raw("Files in this folder"),
+            ])),
             ListItem::new(Line::from("")),
             ListItem::new(Line::from(vec![
                 Span::styled(" t ", Style::default().fg(Color::Yellow).bold()),
@@ -153,11 +209,19 @@ impl DetailView {
                 Span::styled(" n ", Style::default().fg(Color::Yellow).bold()),
                 Span::raw("Edit notes"),
             ])),
+            ListItem::new(Line::from(vec![
+                Span::styled(" F ", Style::default().fg(Color::Yellow).bold()),
+                Span::raw("Change type"),
+            ])),
             ListItem::new(Line::from("")),
             ListItem::new(Line::from(vec![
                 Span::styled(" d ", Style::default().fg(Color::Red).bold()),
                 Span::raw("Delete file"),
             ])),
+            ListItem::new(Line::from(vec![
+                Span::styled(" D ", Style::default().fg(Color::Red).bold()),
+                Span::raw("Delete permanently"),
+            ])),
             ListItem::new(Line::from("")),
             ListItem::new(Line::from(vec![
                 Span::styled(" q ", Style::default().fg(Color::DarkGray).bold()),
@@ -173,22 +237,19 @@ impl DetailView {
             Block::default()
                 .title(" Actions ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::DarkGray)),
+                .border_style(app.theme.border_style()),
         );
 
         frame.render_widget(actions_list, actions_area);
     }
 
     /// Get style for file type
-    fn type_style(file_type: crate::models::FileType) -> Style {
-        use crate::models::FileType;
-        match file_type {
-            FileType::Executable => Style::default().fg(Color::Red).bold(),
-            FileType::Archive => Style::default().fg(Color::Magenta).bold(),
-            FileType::Document => Style::default().fg(Color::Blue).bold(),
-            FileType::Media => Style::default().fg(Color::Green).bold(),
-            FileType::Code => Style::default().fg(Color::Yellow).bold(),
-            FileType::Other => Style::default().fg(Color::Gray),
+    fn type_style(app: &App, file_type: crate::models::FileType) -> Style {
+        let style = app.theme.type_style(file_type);
+        if file_type == crate::models::FileType::Other {
+            style
+        } else {
+            style.bold()
         }
     }
 }
@@ -0,0 +1,56 @@
+//! Watcher diagnostics overlay component
+//!
+//! Shows what the watcher's processing thread did with the paths it was
+//! given, to explain why a file didn't show up.
+
+use super::theme::Theme;
+use crate::watcher::WatcherStats;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Watcher diagnostics overlay
+pub struct DiagnosticsOverlay;
+
+impl DiagnosticsOverlay {
+    /// Draw the overlay for `stats`, or a note when no watcher is running
+    pub fn draw(stats: Option<WatcherStats>, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let overlay_width = 50.min(area.width - 4);
+        let overlay_height = 10.min(area.height - 4);
+        let overlay_area = Rect::new(
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
+            overlay_width,
+            overlay_height,
+        );
+
+        // Clear the area behind the overlay
+        frame.render_widget(Clear, overlay_area);
+
+        let label = Style::default().fg(theme.label);
+        let lines = match stats {
+            Some(stats) => [
+                ("Received:           ", stats.received),
+                ("Ignored by pattern: ", stats.ignored_by_pattern),
+                ("Ignored by size:    ", stats.ignored_by_size),
+                ("Deduplicated:       ", stats.deduped),
+                ("Dropped (paused):   ", stats.dropped_paused),
+                ("Inserted:           ", stats.inserted),
+            ]
+            .into_iter()
+            .map(|(name, count)| Line::from(vec![Span::styled(name, label), Span::raw(count.to_string())]))
+            .collect(),
+            None => vec![Line::styled("No watcher running", Style::default().fg(theme.muted))],
+        };
+
+        let overlay = Paragraph::new(lines).block(
+            Block::default()
+                .title(" Watcher Diagnostics (q to close) ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.header)),
+        );
+
+        frame.render_widget(overlay, overlay_area);
+    }
+}
@@ -2,12 +2,16 @@
 //!
 //! Provides an interactive overlay for setting filter criteria.
 
+use super::theme::Theme;
+use crate::config::parse_size;
 use crate::models::{EventFilter, FileType};
+use anyhow::Result;
 use chrono::{Duration, Utc};
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Clear, List, ListItem},
 };
+use serde::{Deserialize, Serialize};
 
 /// Filter option types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,9 +21,18 @@ pub enum FilterOption {
     MinSize,
 }
 
-/// Time period options for filtering
+/// The size bounds that can be typed in
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeField {
+    Min,
+    Max,
+}
+
+/// Time period options for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TimePeriod {
+    #[default]
     All,
     LastHour,
     Last24Hours,
@@ -38,6 +51,28 @@ impl TimePeriod {
         }
     }
 
+    /// Label when used as an "older than" bound
+    pub fn as_older_than_str(&self) -> &'static str {
+        match self {
+            TimePeriod::All => "Any age",
+            TimePeriod::LastHour => "Older than 1 hour",
+            TimePeriod::Last24Hours => "Older than 24 hours",
+            TimePeriod::Last7Days => "Older than 7 days",
+            TimePeriod::Last30Days => "Older than 30 days",
+        }
+    }
+
+    /// How far back the period reaches (`None` for all time)
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            TimePeriod::All => None,
+            TimePeriod::LastHour => Some(Duration::hours(1)),
+            TimePeriod::Last24Hours => Some(Duration::hours(24)),
+            TimePeriod::Last7Days => Some(Duration::days(7)),
+            TimePeriod::Last30Days => Some(Duration::days(30)),
+        }
+    }
+
     pub fn all() -> &'static [TimePeriod] {
         &[
             TimePeriod::All,
@@ -69,7 +104,7 @@ impl TimePeriod {
     }
 }
 
-/// Size threshold options
+/// Minimum size presets, quick-fills for the minimum size field
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SizeThreshold {
     Any,
@@ -103,6 +138,32 @@ impl SizeThreshold {
         }
     }
 
+    /// The preset as typed into the minimum size field
+    pub fn as_input(&self) -> &'static str {
+        match self {
+            SizeThreshold::Any => "",
+            SizeThreshold::AtLeast1KB => "1K",
+            SizeThreshold::AtLeast100KB => "100K",
+            SizeThreshold::AtLeast1MB => "1M",
+            SizeThreshold::AtLeast10MB => "10M",
+            SizeThreshold::AtLeast100MB => "100M",
+        }
+    }
+
+    /// The preset for a minimum of `bytes`, if there is one
+    pub fn from_bytes(bytes: Option<u64>) -> Option<SizeThreshold> {
+        [
+            SizeThreshold::Any,
+            SizeThreshold::AtLeast1KB,
+            SizeThreshold::AtLeast100KB,
+            SizeThreshold::AtLeast1MB,
+            SizeThreshold::AtLeast10MB,
+            SizeThreshold::AtLeast100MB,
+        ]
+        .into_iter()
+        .find(|preset| preset.to_bytes() == bytes)
+    }
+
     pub fn next(&self) -> SizeThreshold {
         match self {
             SizeThreshold::Any => SizeThreshold::AtLeast1KB,
@@ -126,25 +187,84 @@ impl SizeThreshold {
     }
 }
 
+/// The choices made in the overlay, saved between sessions
+///
+/// Time periods are kept as periods rather than timestamps, so a restored
+/// "Last 24 hours" reaches back from the time it's restored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterSelection {
+    /// Selected file types
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<FileType>,
+    /// Names of the selected categories
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    pub since: TimePeriod,
+    pub older_than: TimePeriod,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+}
+
+/// Write `bytes` the way it would be typed, e.g. 1048576 as "1M"
+fn size_input(bytes: u64) -> String {
+    let units = [("T", 1u64 << 40), ("G", 1 << 30), ("M", 1 << 20), ("K", 1 << 10)];
+    units
+        .iter()
+        .find(|(_, unit)| bytes > 0 && bytes.is_multiple_of(*unit))
+        .map(|(name, unit)| format!("{}{}", bytes / unit, name))
+        .unwrap_or_else(|| bytes.to_string())
+}
+
+/// Parse a size field, empty meaning no bound
+fn parse_size_input(input: &str) -> Result<Option<u64>> {
+    if input.trim().is_empty() {
+        return Ok(None);
+    }
+    parse_size(input).map(Some)
+}
+
 /// Filter overlay state
 pub struct FilterOverlay {
     /// Currently selected option index
     pub selected: usize,
     /// Selected file types (toggle each)
     pub selected_types: Vec<bool>,
+    /// (name, label) of the `[[categories]]` entries offered below the types
+    pub categories: Vec<(String, String)>,
+    /// Selected categories (toggle each)
+    pub selected_categories: Vec<bool>,
     /// Selected time period
     pub time_period: TimePeriod,
-    /// Selected size threshold
-    pub size_threshold: SizeThreshold,
+    /// Selected "older than" period, giving a bounded window with `time_period`
+    pub older_than: TimePeriod,
+    /// Minimum size as typed, e.g. "500K" (empty = no minimum)
+    pub min_size_input: String,
+    /// Maximum size as typed (empty = no maximum)
+    pub max_size_input: String,
+    /// The size field being typed into, if any
+    pub editing: Option<SizeField>,
 }
 
 impl FilterOverlay {
     pub fn new() -> Self {
+        Self::with_categories(Vec::new())
+    }
+
+    /// Create an overlay that also offers user-defined categories
+    pub fn with_categories(categories: Vec<(String, String)>) -> Self {
         Self {
             selected: 0,
             selected_types: vec![false; FileType::all().len()],
+            selected_categories: vec![false; categories.len()],
+            categories,
             time_period: TimePeriod::All,
-            size_threshold: SizeThreshold::Any,
+            older_than: TimePeriod::All,
+            min_size_input: String::new(),
+            max_size_input: String::new(),
+            editing: None,
         }
     }
 
@@ -152,14 +272,138 @@ impl FilterOverlay {
     pub fn reset(&mut self) {
         self.selected = 0;
         self.selected_types = vec![false; FileType::all().len()];
+        self.selected_categories = vec![false; self.categories.len()];
         self.time_period = TimePeriod::All;
-        self.size_threshold = SizeThreshold::Any;
+        self.older_than = TimePeriod::All;
+        self.min_size_input.clear();
+        self.max_size_input.clear();
+        self.editing = None;
+    }
+
+    /// The current choices
+    ///
+    /// A size field that doesn't parse counts as empty; [`Self::size_bounds`]
+    /// reports why.
+    pub fn selection(&self) -> FilterSelection {
+        FilterSelection {
+            types: FileType::all()
+                .iter()
+                .zip(&self.selected_types)
+                .filter(|(_, &selected)| selected)
+                .map(|(&file_type, _)| file_type)
+                .collect(),
+            categories: self
+                .categories
+                .iter()
+                .zip(&self.selected_categories)
+                .filter(|(_, &selected)| selected)
+                .map(|((name, _), _)| name.clone())
+                .collect(),
+            since: self.time_period,
+            older_than: self.older_than,
+            min_bytes: parse_size_input(&self.min_size_input).ok().flatten(),
+            max_bytes: parse_size_input(&self.max_size_input).ok().flatten(),
+        }
+    }
+
+    /// Parse the size fields, rejecting unreadable sizes and a minimum
+    /// above the maximum
+    pub fn size_bounds(&self) -> Result<(Option<u64>, Option<u64>)> {
+        let min = parse_size_input(&self.min_size_input)?;
+        let max = parse_size_input(&self.max_size_input)?;
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                anyhow::bail!("Minimum size {} is above the maximum {}", self.min_size_input, self.max_size_input);
+            }
+        }
+        Ok((min, max))
+    }
+
+    /// Make `selection` the current choices, skipping categories that no
+    /// longer exist
+    pub fn restore(&mut self, selection: &FilterSelection) {
+        self.reset();
+        for (file_type, selected) in FileType::all().iter().zip(&mut self.selected_types) {
+            *selected = selection.types.contains(file_type);
+        }
+        for ((name, _), selected) in self.categories.iter().zip(&mut self.selected_categories) {
+            *selected = selection.categories.contains(name);
+        }
+        self.time_period = selection.since;
+        self.older_than = selection.older_than;
+        self.min_size_input = selection.min_bytes.map(size_input).unwrap_or_default();
+        self.max_size_input = selection.max_bytes.map(size_input).unwrap_or_default();
+    }
+
+    /// Index of the "since" time period option (after the types and categories)
+    fn time_index(&self) -> usize {
+        FileType::all().len() + self.categories.len()
     }
 
     /// Get total number of options
     fn total_options(&self) -> usize {
-        // File types + time period + size threshold
-        FileType::all().len() + 2
+        // File types + categories + since + older than + size preset + min + max
+        self.time_index() + 5
+    }
+
+    /// The size field on the highlighted row, if it is one
+    fn size_field(&self) -> Option<SizeField> {
+        let time_index = self.time_index();
+        if self.selected == time_index + 3 {
+            Some(SizeField::Min)
+        } else if self.selected == time_index + 4 {
+            Some(SizeField::Max)
+        } else {
+            None
+        }
+    }
+
+    /// The preset matching the minimum size field, if any
+    fn size_preset(&self) -> Option<SizeThreshold> {
+        parse_size_input(&self.min_size_input)
+            .ok()
+            .and_then(SizeThreshold::from_bytes)
+    }
+
+    /// Fill the minimum size field from a preset
+    fn apply_preset(&mut self, preset: SizeThreshold) {
+        self.min_size_input = preset.as_input().to_string();
+    }
+
+    /// Start typing into the highlighted size field, returning false if
+    /// the highlighted row isn't one
+    pub fn start_editing(&mut self) -> bool {
+        self.editing = self.size_field();
+        self.editing.is_some()
+    }
+
+    /// Stop typing into the size field
+    pub fn stop_editing(&mut self) {
+        self.editing = None;
+    }
+
+    /// The text of the size field being typed into
+    fn editing_input(&mut self) -> Option<&mut String> {
+        match self.editing? {
+            SizeField::Min => Some(&mut self.min_size_input),
+            SizeField::Max => Some(&mut self.max_size_input),
+        }
+    }
+
+    /// Type a character into the size field (digits, '.', units)
+    pub fn push_size_char(&mut self, c: char) {
+        if c.is_ascii_alphanumeric() || c == '.' {
+            if let Some(input) = self.editing_input() {
+                input.push(c);
+            }
+        }
+    }
+
+    /// Delete the last character of the size field
+    pub fn pop_size_char(&mut self) {
+        if let Some(input) = self.editing_input() {
+            input.pop();
+        }
     }
 
     /// Move to next option
@@ -183,19 +427,29 @@ impl FilterOverlay {
         if self.selected < type_count {
             // Toggle file type
             self.selected_types[self.selected] = !self.selected_types[self.selected];
+        } else if self.selected < self.time_index() {
+            // Toggle category
+            let i = self.selected - type_count;
+            self.selected_categories[i] = !self.selected_categories[i];
         }
     }
 
     /// Increase value for current selection
     pub fn increase_value(&mut self) {
-        let type_count = FileType::all().len();
+        let time_index = self.time_index();
         
-        if self.selected == type_count {
+        if self.selected == time_index {
             // Time period
             self.time_period = self.time_period.next();
-        } else if self.selected == type_count + 1 {
-            // Size threshold
-            self.size_threshold = self.size_threshold.next();
+        } else if self.selected == time_index + 1 {
+            // Older than
+            self.older_than = self.older_than.next();
+        } else if self.selected == time_index + 2 {
+            // Size preset
+            let preset = self.size_preset().map_or(SizeThreshold::AtLeast1KB, |p| p.next());
+            self.apply_preset(preset);
+        } else if self.selected > time_index + 2 {
+            // Size fields are typed into
         } else {
             // Toggle file type
             self.toggle_current();
@@ -204,14 +458,20 @@ impl FilterOverlay {
 
     /// Decrease value for current selection
     pub fn decrease_value(&mut self) {
-        let type_count = FileType::all().len();
+        let time_index = self.time_index();
         
-        if self.selected == type_count {
+        if self.selected == time_index {
             // Time period
             self.time_period = self.time_period.prev();
-        } else if self.selected == type_count + 1 {
-            // Size threshold
-            self.size_threshold = self.size_threshold.prev();
+        } else if self.selected == time_index + 1 {
+            // Older than
+            self.older_than = self.older_than.prev();
+        } else if self.selected == time_index + 2 {
+            // Size preset
+            let preset = self.size_preset().map_or(SizeThreshold::AtLeast100MB, |p| p.prev());
+            self.apply_preset(preset);
+        } else if self.selected > time_index + 2 {
+            // Size fields are typed into
         } else {
             // Toggle file type
             self.toggle_current();
@@ -221,54 +481,35 @@ impl FilterOverlay {
     /// Build an EventFilter from current selections
     pub fn build_filter(&self) -> EventFilter {
         let mut filter = EventFilter::new();
+        let selection = self.selection();
 
-        // Check if any file type is selected
-        let selected_type_indices: Vec<usize> = self
-            .selected_types
-            .iter()
-            .enumerate()
-            .filter(|(_, &selected)| selected)
-            .map(|(i, _)| i)
-            .collect();
-
-        // If exactly one type is selected, filter by it
-        if selected_type_indices.len() == 1 {
-            filter.file_type = Some(FileType::all()[selected_type_indices[0]]);
-        }
+        // Files of any selected type match
+        filter.file_types = selection.types;
 
-        // Time period
-        match self.time_period {
-            TimePeriod::All => {}
-            TimePeriod::LastHour => {
-                filter.since = Some(Utc::now() - Duration::hours(1));
-            }
-            TimePeriod::Last24Hours => {
-                filter.since = Some(Utc::now() - Duration::hours(24));
-            }
-            TimePeriod::Last7Days => {
-                filter.since = Some(Utc::now() - Duration::days(7));
-            }
-            TimePeriod::Last30Days => {
-                filter.since = Some(Utc::now() - Duration::days(30));
-            }
-        }
+        // Likewise for categories
+        filter.categories = selection.categories;
 
-        // Size threshold
-        if let Some(min_size) = self.size_threshold.to_bytes() {
-            filter.min_size = Some(min_size);
-        }
+        // Time window
+        let now = Utc::now();
+        filter.since = self.time_period.duration().map(|d| now - d);
+        filter.until = self.older_than.duration().map(|d| now - d);
+
+        // Size range
+        filter.min_size = selection.min_bytes;
+        filter.max_size = selection.max_bytes;
 
         filter
     }
 
     /// Draw the filter overlay
-    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+    pub fn draw(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
         // Calculate overlay size and position
         let overlay_width = 50.min(area.width - 4);
-        let overlay_height = (self.total_options() as u16 + 8).min(area.height - 4);
+        let category_rows = if self.categories.is_empty() { 0 } else { 2 };
+        let overlay_height = (self.total_options() as u16 + 8 + category_rows).min(area.height - 4);
         let overlay_area = Rect::new(
-            (area.width - overlay_width) / 2,
-            (area.height - overlay_height) / 2,
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
             overlay_width,
             overlay_height,
         );
@@ -281,8 +522,8 @@ impl FilterOverlay {
 
         // Section header for file types
         items.push(ListItem::new(Line::from(vec![
-            Span::styled("─ File Type ", Style::default().fg(Color::Yellow).bold()),
-            Span::styled("─".repeat(30), Style::default().fg(Color::DarkGray)),
+            Span::styled("─ File Type ", Style::default().fg(theme.label).bold()),
+            Span::styled("─".repeat(30), Style::default().fg(theme.muted)),
         ])));
 
         // File type options
@@ -290,7 +531,7 @@ impl FilterOverlay {
             let selected = self.selected_types[i];
             let checkbox = if selected { "[✓]" } else { "[ ]" };
             let style = if i == self.selected {
-                Style::default().bg(Color::DarkGray).fg(Color::White)
+                theme.selection()
             } else {
                 Style::default()
             };
@@ -299,68 +540,137 @@ impl FilterOverlay {
                 Span::styled(
                     format!(" {} ", checkbox),
                     if selected {
-                        Style::default().fg(Color::Green)
+                        Style::default().fg(theme.type_color(FileType::Media))
                     } else {
-                        Style::default().fg(Color::DarkGray)
+                        Style::default().fg(theme.muted)
                     },
                 ),
                 Span::styled(file_type.as_str(), style),
             ])));
         }
 
+        // Category options, if any are configured
+        let type_count = FileType::all().len();
+        if !self.categories.is_empty() {
+            items.push(ListItem::new(Line::from("")));
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled("─ Category ", Style::default().fg(theme.label).bold()),
+                Span::styled("─".repeat(31), Style::default().fg(theme.muted)),
+            ])));
+        }
+        for (i, (_, label)) in self.categories.iter().enumerate() {
+            let selected = self.selected_categories[i];
+            let checkbox = if selected { "[✓]" } else { "[ ]" };
+            let style = if type_count + i == self.selected {
+                theme.selection()
+            } else {
+                Style::default()
+            };
+
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(" {} ", checkbox),
+                    if selected {
+                        Style::default().fg(theme.type_color(FileType::Media))
+                    } else {
+                        Style::default().fg(theme.muted)
+                    },
+                ),
+                Span::styled(label.as_str(), style),
+            ])));
+        }
+
         // Section header for time
         items.push(ListItem::new(Line::from("")));
         items.push(ListItem::new(Line::from(vec![
-            Span::styled("─ Time Period ", Style::default().fg(Color::Yellow).bold()),
-            Span::styled("─".repeat(28), Style::default().fg(Color::DarkGray)),
+            Span::styled("─ Time Period ", Style::default().fg(theme.label).bold()),
+            Span::styled("─".repeat(28), Style::default().fg(theme.muted)),
         ])));
 
         // Time period option
-        let type_count = FileType::all().len();
-        let time_style = if self.selected == type_count {
-            Style::default().bg(Color::DarkGray).fg(Color::White)
+        let time_index = self.time_index();
+        let time_style = if self.selected == time_index {
+            theme.selection()
         } else {
             Style::default()
         };
         items.push(ListItem::new(Line::from(vec![
-            Span::styled(" ◄ ", Style::default().fg(Color::Cyan)),
+            Span::styled(" ◄ ", Style::default().fg(theme.header)),
             Span::styled(self.time_period.as_str(), time_style),
-            Span::styled(" ►", Style::default().fg(Color::Cyan)),
+            Span::styled(" ►", Style::default().fg(theme.header)),
+        ])));
+
+        // Older than option
+        let older_style = if self.selected == time_index + 1 {
+            theme.selection()
+        } else {
+            Style::default()
+        };
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(" ◄ ", Style::default().fg(theme.header)),
+            Span::styled(self.older_than.as_older_than_str(), older_style),
+            Span::styled(" ►", Style::default().fg(theme.header)),
         ])));
 
         // Section header for size
         items.push(ListItem::new(Line::from("")));
         items.push(ListItem::new(Line::from(vec![
-            Span::styled("─ Minimum Size ", Style::default().fg(Color::Yellow).bold()),
-            Span::styled("─".repeat(27), Style::default().fg(Color::DarkGray)),
+            Span::styled("─ Size ", Style::default().fg(theme.label).bold()),
+            Span::styled("─".repeat(35), Style::default().fg(theme.muted)),
         ])));
 
-        // Size threshold option
-        let size_style = if self.selected == type_count + 1 {
-            Style::default().bg(Color::DarkGray).fg(Color::White)
+        // Size preset option, filling in the minimum below
+        let size_style = if self.selected == time_index + 2 {
+            theme.selection()
         } else {
             Style::default()
         };
+        let preset = self.size_preset().map_or("Custom", |preset| preset.as_str());
         items.push(ListItem::new(Line::from(vec![
-            Span::styled(" ◄ ", Style::default().fg(Color::Cyan)),
-            Span::styled(self.size_threshold.as_str(), size_style),
-            Span::styled(" ►", Style::default().fg(Color::Cyan)),
+            Span::styled(" ◄ ", Style::default().fg(theme.header)),
+            Span::styled(preset, size_style),
+            Span::styled(" ►", Style::default().fg(theme.header)),
         ])));
 
+        // Min and max size fields
+        for (field, label, input) in [
+            (SizeField::Min, " Min: ", &self.min_size_input),
+            (SizeField::Max, " Max: ", &self.max_size_input),
+        ] {
+            let index = time_index + if field == SizeField::Min { 3 } else { 4 };
+            let editing = self.editing == Some(field);
+            let style = if index == self.selected {
+                theme.selection()
+            } else {
+                Style::default()
+            };
+            let text = match (editing, input.is_empty()) {
+                (true, _) => format!("{}▏", input),
+                (false, true) => "none".to_string(),
+                (false, false) => input.clone(),
+            };
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(label, Style::default().fg(theme.label)),
+                Span::styled(format!("[{:<12}]", text), style),
+            ])));
+        }
+
         // Instructions
+        let instructions = if self.editing.is_some() {
+            " Type a size like 500K or 1.5G  Enter:done"
+        } else {
+            " ↑↓:select  ←→:change  Space:toggle/edit  Enter:apply  Esc:cancel"
+        };
         items.push(ListItem::new(Line::from("")));
         items.push(ListItem::new(Line::from(vec![
-            Span::styled(
-                " ↑↓:select  ←→:change  Space:toggle  Enter:apply  Esc:cancel",
-                Style::default().fg(Color::DarkGray),
-            ),
+            Span::styled(instructions, Style::default().fg(theme.muted)),
         ])));
 
         let list = List::new(items).block(
             Block::default()
                 .title(" Filter ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(Style::default().fg(theme.header)),
         );
 
         frame.render_widget(list, overlay_area);
@@ -372,3 +682,130 @@ impl Default for FilterOverlay {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_window() {
+        let mut overlay = FilterOverlay::new();
+        overlay.selected = overlay.time_index();
+        overlay.increase_value(); // Last hour
+        overlay.decrease_value();
+        overlay.decrease_value(); // Last 30 days
+        overlay.next();
+        overlay.increase_value(); // Older than 1 hour
+
+        let filter = overlay.build_filter();
+        let since = filter.since.unwrap();
+        let until = filter.until.unwrap();
+        assert_eq!(until - since, Duration::days(30) - Duration::hours(1));
+
+        overlay.reset();
+        let filter = overlay.build_filter();
+        assert!(filter.since.is_none() && filter.until.is_none());
+    }
+
+    #[test]
+    fn test_several_types() {
+        let mut overlay = FilterOverlay::new();
+        let index = |ft| FileType::all().iter().position(|&t| t == ft).unwrap();
+        overlay.selected_types[index(FileType::Code)] = true;
+        overlay.selected_types[index(FileType::Archive)] = true;
+
+        let mut types = overlay.build_filter().file_types;
+        types.sort_by_key(|ft| ft.as_str());
+        assert_eq!(types, vec![FileType::Archive, FileType::Code]);
+
+        overlay.reset();
+        assert!(overlay.build_filter().file_types.is_empty());
+    }
+
+    #[test]
+    fn test_several_categories() {
+        let categories = vec![
+            ("ebook".to_string(), "Book".to_string()),
+            ("invoice".to_string(), "Invoice".to_string()),
+            ("photo".to_string(), "Photo".to_string()),
+        ];
+        let mut overlay = FilterOverlay::with_categories(categories);
+        overlay.selected_categories[0] = true;
+        overlay.selected_categories[2] = true;
+        assert_eq!(overlay.build_filter().categories, vec!["ebook", "photo"]);
+
+        overlay.reset();
+        assert!(overlay.build_filter().categories.is_empty());
+    }
+
+    #[test]
+    fn test_restore_selection() {
+        let categories = vec![("ebook".to_string(), "Book".to_string())];
+        let mut overlay = FilterOverlay::with_categories(categories.clone());
+        overlay.selected_types[0] = true;
+        overlay.selected_categories[0] = true;
+        overlay.time_period = TimePeriod::Last7Days;
+        overlay.min_size_input = "1M".to_string();
+        overlay.max_size_input = "1.5G".to_string();
+        let selection = overlay.selection();
+
+        let json = serde_json::to_string(&selection).unwrap();
+        let restored: FilterSelection = serde_json::from_str(&json).unwrap();
+        let mut fresh = FilterOverlay::with_categories(categories);
+        fresh.restore(&restored);
+        assert_eq!(fresh.selection(), selection);
+        assert_eq!(fresh.build_filter().min_size, Some(1024 * 1024));
+        assert_eq!(fresh.build_filter().max_size, Some(1536 << 20));
+        assert_eq!(fresh.max_size_input, "1536M");
+
+        // A category removed from the config is dropped
+        let mut without = FilterOverlay::new();
+        without.restore(&restored);
+        assert!(without.selection().categories.is_empty());
+        assert_eq!(without.selection().types, selection.types);
+    }
+
+    #[test]
+    fn test_size_fields() {
+        let mut overlay = FilterOverlay::new();
+        overlay.selected = overlay.time_index() + 2;
+        overlay.increase_value();
+        overlay.increase_value();
+        assert_eq!(overlay.min_size_input, "100K");
+
+        // Typed bounds replace the preset
+        overlay.next();
+        assert!(overlay.start_editing());
+        for _ in 0..4 {
+            overlay.pop_size_char();
+        }
+        for c in "2m".chars() {
+            overlay.push_size_char(c);
+        }
+        overlay.stop_editing();
+        assert_eq!(overlay.min_size_input, "2m");
+        assert_eq!(overlay.size_preset(), None);
+
+        overlay.next();
+        assert!(overlay.start_editing());
+        for c in "1 GB!".chars() {
+            overlay.push_size_char(c);
+        }
+        overlay.stop_editing();
+        assert_eq!(overlay.max_size_input, "1GB");
+        assert_eq!(overlay.size_bounds().unwrap(), (Some(2 << 20), Some(1_000_000_000)));
+
+        let filter = overlay.build_filter();
+        assert_eq!((filter.min_size, filter.max_size), (Some(2 << 20), Some(1_000_000_000)));
+
+        // Checked when applying
+        overlay.max_size_input = "1K".to_string();
+        assert!(overlay.size_bounds().is_err());
+        overlay.max_size_input = "lots".to_string();
+        assert!(overlay.size_bounds().is_err());
+
+        // Only the size rows are editable
+        overlay.selected = 0;
+        assert!(!overlay.start_editing());
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Displays keybinding help information.
 
+use super::theme::Theme;
+use crate::models::FileType;
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
@@ -27,13 +29,13 @@ impl HelpOverlay {
     }
 
     /// Draw the help overlay
-    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+    pub fn draw(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
         // Calculate overlay size and position
         let overlay_width = 60.min(area.width - 4);
         let overlay_height = 30.min(area.height - 4);
         let overlay_area = Rect::new(
-            (area.width - overlay_width) / 2,
-            (area.height - overlay_height) / 2,
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
             overlay_width,
             overlay_height,
         );
@@ -44,53 +46,79 @@ impl HelpOverlay {
         let help_text = vec![
             Line::from(Span::styled(
                 "🦡 Ferret - File Tracker",
-                Style::default().fg(Color::Cyan).bold(),
+                Style::default().fg(theme.header).bold(),
             )),
             Line::from(""),
             Line::from(Span::styled(
                 "View Modes",
-                Style::default().fg(Color::Yellow).bold(),
+                Style::default().fg(theme.label).bold(),
             )),
-            Line::from("  Tab        Switch view (Flat → Grouped → Tree)"),
+            Line::from("  Tab        Switch view (Flat → Grouped → By Ext → Tree)"),
             Line::from("  ←/h        Collapse dir / Back (Tree/Grouped)"),
             Line::from("  →/l        Expand dir / Enter (Tree/Grouped)"),
-            Line::from("  Space      Toggle expand/collapse"),
+            Line::from("  Space      Toggle expand/collapse (mark file in Flat)"),
             Line::from("  e          Expand all (Tree view)"),
             Line::from("  E          Collapse all (Tree view)"),
+            Line::from("  b          Toggle folder size bars (Tree view)"),
+            Line::from("  x          Hide folder, optionally ignore it in config"),
             Line::from(""),
             Line::from(Span::styled(
                 "Navigation",
-                Style::default().fg(Color::Yellow).bold(),
+                Style::default().fg(theme.label).bold(),
             )),
             Line::from("  ↑/k        Move selection up"),
-            Line::from("  ↓/j        Move selection down"),
+            Line::from("  ↓/j        Move selection down (wraps with wrap_navigation)"),
             Line::from("  PgUp/PgDn  Scroll by page"),
-            Line::from("  Home/g     Jump to start"),
+            Line::from("  +/-        Grow/shrink page size"),
+            Line::from("  Home/g     Jump to start (Details: files in same folder)"),
+            Line::from("  m          (Details) Files of the same type with a similar name"),
             Line::from("  End/G      Jump to end"),
             Line::from("  Enter      View details / Toggle folder"),
+            Line::from("  i          Stats for the selected folder"),
+            Line::from("  Mouse      Click to select, double-click for details"),
+            Line::from("  Wheel      Move selection"),
             Line::from(""),
             Line::from(Span::styled(
                 "Filtering & Search",
-                Style::default().fg(Color::Yellow).bold(),
+                Style::default().fg(theme.label).bold(),
             )),
-            Line::from("  /          Search by path"),
+            Line::from("  /          Search path, tags and notes"),
+            Line::from("  '          Jump to filename in page (Tab: next)"),
+            Line::from("  :          Go to date (YYYY-MM-DD)"),
+            Line::from("  Ctrl+R     (in search) Toggle regex path match"),
             Line::from("  f          Open filter menu"),
-            Line::from("  c          Clear all filters"),
+            Line::from("  c          Clear all filters and hidden folders"),
+            Line::from("  s          Cycle sort (time/size/name/type)"),
+            Line::from("  S          Toggle ascending/descending"),
+            Line::from("  T          Toggle relative/absolute times"),
+            Line::from("  B          Toggle rounded/exact byte sizes"),
             Line::from("  r          Refresh list"),
+            Line::from("  R          Reload config file"),
+            Line::from("  p          Pause/resume recording new files"),
+            Line::from("  w          Watched directories and their status"),
+            Line::from("  W          Watcher diagnostics (why files were skipped)"),
+            Line::from("  L          Message history (last 50 status messages)"),
             Line::from(""),
             Line::from(Span::styled(
                 "Actions",
-                Style::default().fg(Color::Yellow).bold(),
+                Style::default().fg(theme.label).bold(),
             )),
             Line::from("  o          Open file"),
             Line::from("  O          Open containing folder"),
+            Line::from("  y          Copy file path to clipboard"),
+            Line::from("  Y          Copy folder path to clipboard"),
+            Line::from("  C          Copy file to another directory"),
             Line::from("  t          Edit tags"),
             Line::from("  n          Edit notes"),
-            Line::from("  d          Delete file"),
+            Line::from("  F          Change file type"),
+            Line::from("  d          Delete file (to trash if enabled)"),
+            Line::from("  D          Delete file permanently"),
+            Line::from("  Esc        Clear marks"),
+            Line::from("             With marked files, t/d/D act on all of them"),
             Line::from(""),
             Line::from(Span::styled(
                 "General",
-                Style::default().fg(Color::Yellow).bold(),
+                Style::default().fg(theme.label).bold(),
             )),
             Line::from("  ?          Toggle this help"),
             Line::from("  q/Esc      Quit / Close overlay"),
@@ -98,36 +126,36 @@ impl HelpOverlay {
             Line::from(""),
             Line::from(Span::styled(
                 "File Types",
-                Style::default().fg(Color::Yellow).bold(),
+                Style::default().fg(theme.label).bold(),
             )),
             Line::from(vec![
-                Span::styled("  exec  ", Style::default().fg(Color::Red)),
+                Span::styled("  exec  ", theme.type_style(FileType::Executable)),
                 Span::raw("Executables (.exe, .sh, binaries)"),
             ]),
             Line::from(vec![
-                Span::styled("  arch  ", Style::default().fg(Color::Magenta)),
+                Span::styled("  arch  ", theme.type_style(FileType::Archive)),
                 Span::raw("Archives (.zip, .tar, .gz)"),
             ]),
             Line::from(vec![
-                Span::styled("  doc   ", Style::default().fg(Color::Blue)),
+                Span::styled("  doc   ", theme.type_style(FileType::Document)),
                 Span::raw("Documents (.pdf, .doc, .txt)"),
             ]),
             Line::from(vec![
-                Span::styled("  media ", Style::default().fg(Color::Green)),
+                Span::styled("  media ", theme.type_style(FileType::Media)),
                 Span::raw("Media (.jpg, .mp3, .mp4)"),
             ]),
             Line::from(vec![
-                Span::styled("  code  ", Style::default().fg(Color::Yellow)),
+                Span::styled("  code  ", theme.type_style(FileType::Code)),
                 Span::raw("Source code (.rs, .py, .js)"),
             ]),
             Line::from(vec![
-                Span::styled("  other ", Style::default().fg(Color::Gray)),
+                Span::styled("  other ", theme.type_style(FileType::Other)),
                 Span::raw("Other files"),
             ]),
             Line::from(""),
             Line::from(Span::styled(
                 "Tips",
-                Style::default().fg(Color::Yellow).bold(),
+                Style::default().fg(theme.label).bold(),
             )),
             Line::from("  • Use tags to organize files"),
             Line::from("  • Notes support any text"),
@@ -142,7 +170,7 @@ impl HelpOverlay {
                 Block::default()
                     .title(" Help (↑↓ to scroll, q to close) ")
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Cyan)),
+                    .border_style(Style::default().fg(theme.header)),
             );
 
         frame.render_widget(help, overlay_area);
@@ -14,11 +14,11 @@ pub struct InputOverlay;
 impl InputOverlay {
     /// Draw search input overlay
     pub fn draw_search(app: &App, frame: &mut Frame, area: Rect) {
-        let overlay_width = 50.min(area.width - 4);
+        let overlay_width = 60.min(area.width - 4);
         let overlay_height = 3;
         let overlay_area = Rect::new(
-            (area.width - overlay_width) / 2,
-            (area.height - overlay_height) / 2,
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
             overlay_width,
             overlay_height,
         );
@@ -26,13 +26,19 @@ impl InputOverlay {
         // Clear the area behind the overlay
         frame.render_widget(Clear, overlay_area);
 
+        let title = if app.search_is_regex {
+            " Regex search (Ctrl+R: substring, Enter: apply) "
+        } else {
+            " Search (Ctrl+R: regex, Enter: apply, Esc: cancel) "
+        };
+
         let input = Paragraph::new(format!("{}_", app.input_buffer))
             .style(Style::default().fg(Color::White))
             .block(
                 Block::default()
-                    .title(" Search (Enter to apply, Esc to cancel) ")
+                    .title(title)
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Yellow)),
+                    .border_style(Style::default().fg(app.theme.label)),
             );
 
         frame.render_widget(input, overlay_area);
@@ -43,8 +49,8 @@ impl InputOverlay {
         let overlay_width = 60.min(area.width - 4);
         let overlay_height = 5;
         let overlay_area = Rect::new(
-            (area.width - overlay_width) / 2,
-            (area.height - overlay_height) / 2,
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
             overlay_width,
             overlay_height,
         );
@@ -56,7 +62,7 @@ impl InputOverlay {
             Line::from(vec![
                 Span::styled(
                     format!("{}: ", hint),
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(app.theme.muted),
                 ),
             ]),
             Line::from(format!("{}_", app.input_buffer)),
@@ -68,7 +74,7 @@ impl InputOverlay {
                 Block::default()
                     .title(format!(" {} (Enter to save, Esc to cancel) ", title))
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Cyan)),
+                    .border_style(Style::default().fg(app.theme.header)),
             );
 
         frame.render_widget(input, overlay_area);
@@ -2,17 +2,54 @@
 //!
 //! Displays the main list of file events in a table format.
 
-use crate::models::FileType;
+use crate::models::{format_bytes_grouped, FileEvent, ListColumn};
 use crate::tui::app::App;
 use chrono::Local;
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table},
 };
+use std::ops::Range;
+
+/// Longest path shown before it's shortened
+const MAX_PATH_LEN: usize = 60;
 
 /// List view for displaying file events
 pub struct ListView;
 
+/// What part of each path to highlight: the search text or path filter
+enum Highlight {
+    None,
+    /// Substring matched ignoring ASCII case, like the SQL `LIKE` search
+    Substring(String),
+    /// A regex search
+    Regex(regex::Regex),
+}
+
+impl Highlight {
+    fn for_app(app: &App) -> Self {
+        if !app.search_query.is_empty() {
+            if app.search_is_regex {
+                return regex::Regex::new(&app.search_query).map_or(Highlight::None, Highlight::Regex);
+            }
+            return Highlight::Substring(app.search_query.clone());
+        }
+        match &app.filter.path_contains {
+            Some(path) if !path.is_empty() => Highlight::Substring(path.clone()),
+            _ => Highlight::None,
+        }
+    }
+
+    /// Byte range of the first match in `text`
+    fn find(&self, text: &str) -> Option<Range<usize>> {
+        match self {
+            Highlight::None => None,
+            Highlight::Substring(needle) => find_ignore_ascii_case(text, needle),
+            Highlight::Regex(regex) => regex.find(text).map(|m| m.range()).filter(|r| !r.is_empty()),
+        }
+    }
+}
+
 impl ListView {
     /// Draw the list view
     pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
@@ -24,6 +61,7 @@ impl ListView {
 
         let list_area = chunks[0];
         let scrollbar_area = chunks[1];
+        app.list_area = list_area;
 
         // Calculate visible rows
         let header_height = 1;
@@ -37,13 +75,19 @@ impl ListView {
             app.scroll_offset = app.selected_index - visible_rows + 1;
         }
 
-        // Create table headers
-        let header_cells = ["Time", "Size", "Type", "Path"]
-            .iter()
-            .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow).bold()));
+        // Create table headers from the configured columns, after the mark gutter
+        let header_cells = std::iter::once(Cell::from("")).chain(
+            app.list_columns
+                .iter()
+                .map(|c| {
+                    let title = Line::from(c.title()).alignment(Self::alignment(*c));
+                    Cell::from(title).style(Style::default().fg(app.theme.label).bold())
+                }),
+        );
         let header = Row::new(header_cells).height(1);
 
         // Create table rows
+        let highlight = Highlight::for_app(app);
         let rows: Vec<Row> = app
             .events
             .iter()
@@ -53,47 +97,35 @@ impl ListView {
             .map(|(idx, event)| {
                 let is_selected = idx == app.selected_index;
 
-                // Format time
-                let local_time = event.created_at.with_timezone(&Local);
-                let time_str = if local_time.date_naive() == Local::now().date_naive() {
-                    local_time.format("%H:%M:%S").to_string()
+                let row_style = if is_selected {
+                    app.theme.selection()
                 } else {
-                    local_time.format("%Y-%m-%d %H:%M").to_string()
+                    Style::default()
                 };
 
-                // Format size
-                let size_str = event.size_display();
-
-                // File type with color
-                let type_style = Self::type_style(event.file_type);
-                let type_cell = Cell::from(event.file_type.as_label()).style(type_style);
-
-                // Path (truncated)
-                let path_str = Self::truncate_path(&event.path.to_string_lossy(), 60);
-
-                let row_style = if is_selected {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                let is_marked = event.id.is_some_and(|id| app.marked.contains(&id));
+                let gutter = if is_marked {
+                    Cell::from("●").style(Style::default().fg(Color::Green))
                 } else {
-                    Style::default()
+                    Cell::from(" ")
                 };
 
-                Row::new(vec![
-                    Cell::from(time_str),
-                    Cell::from(size_str).style(Style::default().fg(Color::Cyan)),
-                    type_cell,
-                    Cell::from(path_str),
-                ])
-                .style(row_style)
+                let cells: Vec<Cell> = std::iter::once(gutter)
+                    .chain(
+                        app.list_columns
+                            .iter()
+                            .map(|column| Self::cell(app, event, *column, &highlight)),
+                    )
+                    .collect();
+
+                Row::new(cells).style(row_style)
             })
             .collect();
 
         // Column widths
-        let widths = [
-            Constraint::Length(17),  // Time
-            Constraint::Length(10),  // Size
-            Constraint::Length(6),   // Type
-            Constraint::Min(20),     // Path
-        ];
+        let widths: Vec<Constraint> = std::iter::once(Constraint::Length(1))
+            .chain(app.list_columns.iter().map(|c| Self::width(*c)))
+            .collect();
 
         let table = Table::new(rows, widths)
             .header(header)
@@ -101,7 +133,7 @@ impl ListView {
                 Block::default()
                     .title(format!(" Files ({}) ", app.events.len()))
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::DarkGray)),
+                    .border_style(app.theme.border_style()),
             )
             .row_highlight_style(Style::default().add_modifier(Modifier::BOLD));
 
@@ -120,15 +152,71 @@ impl ListView {
         }
     }
 
-    /// Get style for file type
-    fn type_style(file_type: FileType) -> Style {
-        match file_type {
-            FileType::Executable => Style::default().fg(Color::Red),
-            FileType::Archive => Style::default().fg(Color::Magenta),
-            FileType::Document => Style::default().fg(Color::Blue),
-            FileType::Media => Style::default().fg(Color::Green),
-            FileType::Code => Style::default().fg(Color::Yellow),
-            FileType::Other => Style::default().fg(Color::Gray),
+    /// Build the cell for one column of an event row
+    fn cell(app: &App, event: &FileEvent, column: ListColumn, highlight: &Highlight) -> Cell<'static> {
+        match column {
+            ListColumn::Time if app.relative_time => Cell::from(event.relative_time_display()),
+            ListColumn::Time => {
+                let local_time = event.created_at.with_timezone(&Local);
+                let time_str = if local_time.date_naive() == Local::now().date_naive() {
+                    local_time.format("%H:%M:%S").to_string()
+                } else {
+                    local_time.format("%Y-%m-%d %H:%M").to_string()
+                };
+                Cell::from(time_str)
+            }
+            ListColumn::Size => {
+                Cell::from(event.size_display()).style(Style::default().fg(app.theme.header))
+            }
+            ListColumn::Bytes => {
+                let bytes = event.size_bytes.map_or_else(|| "—".to_string(), format_bytes_grouped);
+                Cell::from(Line::from(bytes).alignment(Alignment::Right))
+                    .style(Style::default().fg(app.theme.header))
+            }
+            ListColumn::Type => {
+                let label = app.category_label(event).unwrap_or(event.file_type.as_label());
+                Cell::from(label.to_string()).style(app.theme.type_style(event.file_type))
+            }
+            ListColumn::Path => {
+                let path = event.path.to_string_lossy();
+                let Some(found) = highlight.find(&path) else {
+                    return Cell::from(Self::truncate_path(&path, MAX_PATH_LEN));
+                };
+                match Self::truncate_path_keeping(&path, MAX_PATH_LEN, found) {
+                    (text, Some(range)) => Cell::from(Line::from(vec![
+                        Span::raw(text[..range.start].to_string()),
+                        Span::styled(
+                            text[range.clone()].to_string(),
+                            Style::default().fg(Color::Black).bg(app.theme.status),
+                        ),
+                        Span::raw(text[range.end..].to_string()),
+                    ])),
+                    (text, None) => Cell::from(text),
+                }
+            }
+            ListColumn::Tags => {
+                Cell::from(event.tags.clone()).style(Style::default().fg(Color::Magenta))
+            }
+        }
+    }
+
+    /// Width constraint for a column
+    fn width(column: ListColumn) -> Constraint {
+        match column {
+            ListColumn::Time => Constraint::Length(17),
+            ListColumn::Size => Constraint::Length(10),
+            ListColumn::Bytes => Constraint::Length(16),
+            ListColumn::Type => Constraint::Length(6),
+            ListColumn::Path => Constraint::Min(20),
+            ListColumn::Tags => Constraint::Length(20),
+        }
+    }
+
+    /// Numbers line up on the right, everything else on the left
+    fn alignment(column: ListColumn) -> Alignment {
+        match column {
+            ListColumn::Bytes => Alignment::Right,
+            _ => Alignment::Left,
         }
     }
 
@@ -169,6 +257,60 @@ impl ListView {
             result
         }
     }
+
+    /// Truncate like [`Self::truncate_path`], but keep `keep` (a search
+    /// match) visible if it can be
+    ///
+    /// Returns the text and where `keep` is in it. A match in a directory
+    /// that would be cut off is shown as `...<dirs>.../filename`; if that
+    /// doesn't fit either, the plain truncation is returned without a range.
+    fn truncate_path_keeping(path: &str, max_len: usize, keep: Range<usize>) -> (String, Option<Range<usize>>) {
+        let truncated = Self::truncate_path(path, max_len);
+
+        // Every truncation keeps a suffix of the path behind a "..." or ".../"
+        let prefix = if truncated == path {
+            0
+        } else if truncated.starts_with(".../") {
+            4
+        } else {
+            3
+        };
+        let kept_from = path.len() - (truncated.len() - prefix);
+        if keep.start >= kept_from {
+            let shift = prefix + keep.start - kept_from;
+            return (truncated, Some(shift..shift + keep.len()));
+        }
+
+        // Show the part of the directory holding the match, then the filename
+        let filename_start = path.rfind('/').map_or(0, |i| i + 1);
+        let filename = &path[filename_start..];
+        // Room left after the leading "...", the ".../" and the filename
+        let available = max_len.saturating_sub(filename.len() + 7);
+        if keep.end >= filename_start || keep.len() > available {
+            return (truncated, None);
+        }
+        let mut start = keep.end.saturating_sub(available);
+        while !path.is_char_boundary(start) {
+            start += 1;
+        }
+
+        let lead = if start == 0 { "" } else { "..." };
+        let text = format!("{}{}.../{}", lead, &path[start..keep.end], filename);
+        let shift = lead.len() + keep.start - start;
+        (text, Some(shift..shift + keep.len()))
+    }
+}
+
+/// Byte range of the first occurrence of `needle` in `haystack`, ignoring ASCII case
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+        .map(|start| start..start + needle.len())
 }
 
 #[cfg(test)]
@@ -188,4 +330,35 @@ mod tests {
         assert!(truncated.len() <= 40);
         assert!(truncated.ends_with("file.txt"));
     }
+
+    #[test]
+    fn test_find_ignore_ascii_case() {
+        assert_eq!(find_ignore_ascii_case("/home/User/Report.PDF", "report"), Some(11..17));
+        assert_eq!(find_ignore_ascii_case("/home/user/file.txt", "zip"), None);
+        assert_eq!(find_ignore_ascii_case("/tmp/a", ""), None);
+    }
+
+    #[test]
+    fn test_truncate_path_keeping_match() {
+        let path = "/home/user/very/long/path/to/some/deeply/nested/directory/file.txt";
+
+        // Match in the part that survives truncation
+        let found = find_ignore_ascii_case(path, "FILE").unwrap();
+        let (text, range) = ListView::truncate_path_keeping(path, 40, found);
+        assert_eq!(text, ListView::truncate_path(path, 40));
+        assert_eq!(&text[range.unwrap()], "file");
+
+        // Match that plain truncation would cut off
+        let found = find_ignore_ascii_case(path, "long").unwrap();
+        let (text, range) = ListView::truncate_path_keeping(path, 40, found);
+        assert!(text.len() <= 40);
+        assert!(text.ends_with(".../file.txt"));
+        assert_eq!(&text[range.unwrap()], "long");
+
+        // Too long to show alongside the filename: plain truncation, no highlight
+        let found = 0..path.len() - "/file.txt".len();
+        let (text, range) = ListView::truncate_path_keeping(path, 40, found);
+        assert_eq!(text, ListView::truncate_path(path, 40));
+        assert_eq!(range, None);
+    }
 }
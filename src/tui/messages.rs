@@ -0,0 +1,84 @@
+//! Message history overlay component
+//!
+//! Status bar messages only stay up for a few seconds; this keeps the recent
+//! ones, along with what the watcher reported, so they can be read later.
+
+use super::theme::Theme;
+use chrono::{DateTime, Local};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use std::collections::VecDeque;
+
+/// How many messages are kept before the oldest are dropped
+pub const MESSAGE_HISTORY_LEN: usize = 50;
+
+/// Recent status and watcher messages, oldest first
+#[derive(Default)]
+pub struct MessageLog {
+    entries: VecDeque<(DateTime<Local>, String)>,
+    /// Current scroll position, in rows from the newest message
+    pub scroll: u16,
+}
+
+impl MessageLog {
+    /// Record `message`, dropping the oldest one when full
+    pub fn push(&mut self, message: String) {
+        if self.entries.len() == MESSAGE_HISTORY_LEN {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((Local::now(), message));
+    }
+
+    /// Messages from newest to oldest
+    pub fn newest_first(&self) -> impl Iterator<Item = &(DateTime<Local>, String)> {
+        self.entries.iter().rev()
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        let last = self.entries.len().saturating_sub(1) as u16;
+        self.scroll = (self.scroll + 1).min(last);
+    }
+
+    /// Draw the message history, newest at the top
+    pub fn draw(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let overlay_width = 80.min(area.width - 4);
+        let overlay_height = (self.entries.len().max(1) as u16 + 2).min(area.height - 4);
+        let overlay_area = Rect::new(
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
+            overlay_width,
+            overlay_height,
+        );
+
+        // Clear the area behind the overlay
+        frame.render_widget(Clear, overlay_area);
+
+        let mut lines: Vec<Line> = self
+            .newest_first()
+            .map(|(time, message)| {
+                Line::from(vec![
+                    Span::styled(time.format("%H:%M:%S  ").to_string(), Style::default().fg(theme.label)),
+                    Span::raw(message.clone()),
+                ])
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::styled("No messages yet", Style::default().fg(theme.muted)));
+        }
+
+        let overlay = Paragraph::new(lines).scroll((self.scroll, 0)).block(
+            Block::default()
+                .title(format!(" Messages (last {}, q to close) ", MESSAGE_HISTORY_LEN))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.header)),
+        );
+
+        frame.render_widget(overlay, overlay_area);
+    }
+}
@@ -5,10 +5,16 @@
 
 pub mod app;
 pub mod detail_view;
+pub mod diagnostics;
 pub mod filters;
 pub mod help;
 pub mod list_view;
+pub mod messages;
 pub mod input;
+pub mod stats_overlay;
+pub mod theme;
 pub mod tree_view;
+pub mod type_chooser;
+pub mod watch_paths;
 
 pub use app::App;
@@ -0,0 +1,85 @@
+//! Stats overlay component
+//!
+//! Summarizes an `EventStats`: file count, total size and type breakdown.
+
+use super::theme::Theme;
+use crate::models::{format_size, EventStats};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Stats overlay state
+pub struct StatsOverlay {
+    /// What the stats cover, shown as the title
+    pub title: String,
+    /// The stats to show
+    pub stats: EventStats,
+}
+
+impl StatsOverlay {
+    pub fn new(title: String, stats: EventStats) -> Self {
+        Self { title, stats }
+    }
+
+    /// Draw the stats overlay
+    pub fn draw(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let stats = &self.stats;
+
+        // Calculate overlay size and position
+        let overlay_width = 60.min(area.width - 4);
+        let overlay_height = (stats.by_type.len() as u16 + 11).min(area.height - 4);
+        let overlay_area = Rect::new(
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
+            overlay_width,
+            overlay_height,
+        );
+
+        // Clear the area behind the overlay
+        frame.render_widget(Clear, overlay_area);
+
+        let label = Style::default().fg(theme.label);
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("Files:      ", label),
+                Span::raw(stats.total_count.to_string()),
+            ]),
+            Line::from(vec![
+                Span::styled("Total size: ", label),
+                Span::raw(format_size(stats.total_size)),
+            ]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("Last 24h:   ", label),
+                Span::raw(format!("{} ({})", stats.count_24h, format_size(stats.size_24h))),
+            ]),
+            Line::from(vec![
+                Span::styled("Last 7d:    ", label),
+                Span::raw(format!("{} ({})", stats.count_7d, format_size(stats.size_7d))),
+            ]),
+            Line::from(vec![
+                Span::styled("Last 30d:   ", label),
+                Span::raw(format!("{} ({})", stats.count_30d, format_size(stats.size_30d))),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled("By Type", label.bold())),
+        ];
+
+        for (file_type, count, size) in &stats.by_type {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<6}", file_type.as_label()), theme.type_style(*file_type)),
+                Span::raw(format!("{:>6} files  {:>10}", count, format_size(*size))),
+            ]));
+        }
+
+        let overlay = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(" {} (q to close) ", self.title))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.header)),
+        );
+
+        frame.render_widget(overlay, overlay_area);
+    }
+}
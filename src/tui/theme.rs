@@ -0,0 +1,202 @@
+//! Colors used by the TUI
+//!
+//! A [`Theme`] maps semantic roles (header, selection, borders, each file
+//! type, ...) to colors. It starts from the "dark" or "light" preset and
+//! applies any per-role overrides from the `theme` config setting.
+
+use crate::config::{ThemeColors, ThemeConfig};
+use crate::models::FileType;
+use anyhow::Result;
+use ratatui::style::{Color, Style};
+use std::str::FromStr;
+
+/// Resolved TUI colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Header text and folder rows
+    pub header: Color,
+    /// Column titles, field labels and section headings
+    pub label: Color,
+    /// Text of the selected row
+    pub selection_fg: Color,
+    /// Background of the selected row
+    pub selection_bg: Color,
+    /// Panel borders
+    pub border: Color,
+    /// Status bar messages
+    pub status: Color,
+    /// Hints and secondary text
+    pub muted: Color,
+    /// Executable files
+    pub executable: Color,
+    /// Archives
+    pub archive: Color,
+    /// Documents
+    pub document: Color,
+    /// Images, audio and video
+    pub media: Color,
+    /// Source code
+    pub code: Color,
+    /// Everything else
+    pub other: Color,
+}
+
+impl Theme {
+    /// Preset for dark terminal backgrounds (the default)
+    pub fn dark() -> Self {
+        Self {
+            header: Color::Cyan,
+            label: Color::Yellow,
+            selection_fg: Color::White,
+            selection_bg: Color::DarkGray,
+            border: Color::DarkGray,
+            status: Color::Yellow,
+            muted: Color::DarkGray,
+            executable: Color::Red,
+            archive: Color::Magenta,
+            document: Color::Blue,
+            media: Color::Green,
+            code: Color::Yellow,
+            other: Color::Gray,
+        }
+    }
+
+    /// Preset for light terminal backgrounds
+    pub fn light() -> Self {
+        Self {
+            header: Color::Blue,
+            label: Color::Magenta,
+            selection_fg: Color::Black,
+            selection_bg: Color::Rgb(0xcc, 0xdd, 0xff),
+            border: Color::Gray,
+            status: Color::Rgb(0x99, 0x55, 0x00),
+            muted: Color::Rgb(0x70, 0x70, 0x70),
+            executable: Color::Red,
+            archive: Color::Magenta,
+            document: Color::Blue,
+            media: Color::Rgb(0x00, 0x80, 0x00),
+            code: Color::Rgb(0x99, 0x66, 0x00),
+            other: Color::DarkGray,
+        }
+    }
+
+    /// Look up a preset by name ("dark" or "light")
+    pub fn preset(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "dark" => Ok(Self::dark()),
+            "light" => Ok(Self::light()),
+            _ => anyhow::bail!("Unknown theme '{}'. Valid themes: dark, light", name),
+        }
+    }
+
+    /// Build the theme described by the `theme` config setting
+    pub fn from_config(config: &ThemeConfig) -> Result<Self> {
+        match config {
+            ThemeConfig::Preset(name) => Self::preset(name),
+            ThemeConfig::Custom(colors) => Self::with_overrides(colors),
+        }
+    }
+
+    /// Start from the chosen preset and apply each role that is set
+    fn with_overrides(colors: &ThemeColors) -> Result<Self> {
+        let mut theme = Self::preset(colors.preset.as_deref().unwrap_or("dark"))?;
+
+        let roles = [
+            ("header", &colors.header, &mut theme.header),
+            ("label", &colors.label, &mut theme.label),
+            ("selection_fg", &colors.selection_fg, &mut theme.selection_fg),
+            ("selection_bg", &colors.selection_bg, &mut theme.selection_bg),
+            ("border", &colors.border, &mut theme.border),
+            ("status", &colors.status, &mut theme.status),
+            ("muted", &colors.muted, &mut theme.muted),
+            ("executable", &colors.executable, &mut theme.executable),
+            ("archive", &colors.archive, &mut theme.archive),
+            ("document", &colors.document, &mut theme.document),
+            ("media", &colors.media, &mut theme.media),
+            ("code", &colors.code, &mut theme.code),
+            ("other", &colors.other, &mut theme.other),
+        ];
+        for (role, value, slot) in roles {
+            if let Some(name) = value {
+                *slot = Color::from_str(name)
+                    .map_err(|_| anyhow::anyhow!("Invalid color '{}' for theme.{}", name, role))?;
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Color for a file type
+    pub fn type_color(&self, file_type: FileType) -> Color {
+        match file_type {
+            FileType::Executable => self.executable,
+            FileType::Archive => self.archive,
+            FileType::Document => self.document,
+            FileType::Media => self.media,
+            FileType::Code => self.code,
+            FileType::Other => self.other,
+        }
+    }
+
+    /// Style for a file type
+    pub fn type_style(&self, file_type: FileType) -> Style {
+        Style::default().fg(self.type_color(file_type))
+    }
+
+    /// Style for the selected row
+    pub fn selection(&self) -> Style {
+        Style::default().bg(self.selection_bg).fg(self.selection_fg)
+    }
+
+    /// Style for panel borders
+    pub fn border_style(&self) -> Style {
+        Style::default().fg(self.border)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        let config: crate::config::Config = toml::from_str("theme = \"light\"").unwrap();
+        assert_eq!(Theme::from_config(&config.theme).unwrap(), Theme::light());
+
+        let config: crate::config::Config =
+            toml::from_str("[theme]\npreset = \"light\"\nborder = \"blue\"").unwrap();
+        let theme = Theme::from_config(&config.theme).unwrap();
+        assert_eq!(theme.border, Color::Blue);
+        assert_eq!(theme.header, Theme::light().header);
+
+        assert_eq!(Theme::from_config(&ThemeConfig::default()).unwrap(), Theme::dark());
+        assert!(Theme::preset("solarized").is_err());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let colors = ThemeColors {
+            preset: Some("light".to_string()),
+            header: Some("red".to_string()),
+            archive: Some("#336699".to_string()),
+            ..Default::default()
+        };
+        let theme = Theme::from_config(&ThemeConfig::Custom(Box::new(colors))).unwrap();
+        assert_eq!(theme.header, Color::Red);
+        assert_eq!(theme.type_color(FileType::Archive), Color::Rgb(0x33, 0x66, 0x99));
+        assert_eq!(theme.border, Theme::light().border);
+
+        let bad = ThemeColors {
+            border: Some("not-a-color".to_string()),
+            ..Default::default()
+        };
+        let err = Theme::from_config(&ThemeConfig::Custom(Box::new(bad))).unwrap_err();
+        assert!(err.to_string().contains("theme.border"));
+    }
+}
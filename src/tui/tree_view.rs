@@ -2,13 +2,16 @@
 //!
 //! Displays files in a nested directory hierarchy with expand/collapse.
 
-use crate::models::{FileType, FlattenedNode, FolderGroup, TreeNode, ViewMode};
+use crate::models::{format_size, FileType, FlattenedNode, FolderGroup, TreeNode, ViewMode};
 use crate::tui::app::App;
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table},
 };
 
+/// Widest a folder's size bar gets, in cells
+const SIZE_BAR_WIDTH: usize = 12;
+
 /// Tree view for displaying files in nested hierarchy
 pub struct TreeView;
 
@@ -17,7 +20,7 @@ impl TreeView {
     pub fn draw(app: &mut App, frame: &mut Frame, area: Rect) {
         match app.view_mode {
             ViewMode::Flat => Self::draw_flat(app, frame, area),
-            ViewMode::GroupByFolder => Self::draw_grouped(app, frame, area),
+            ViewMode::GroupByFolder | ViewMode::GroupByExtension => Self::draw_grouped(app, frame, area),
             ViewMode::TreeView => Self::draw_tree(app, frame, area),
         }
     }
@@ -75,6 +78,11 @@ impl TreeView {
         }
 
         let total_rows = display_rows.len();
+        let (group_icon, title) = if app.view_mode == ViewMode::GroupByExtension {
+            ("🏷", format!("By Extension ({} extensions)", app.folder_groups.len()))
+        } else {
+            ("📁", format!("Grouped View ({} folders)", app.folder_groups.len()))
+        };
 
         // Create table rows
         let rows: Vec<Row> = display_rows
@@ -85,7 +93,7 @@ impl TreeView {
             .map(|(idx, row)| {
                 let is_selected = idx == app.grouped_selected_index;
                 let style = if is_selected {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                    app.theme.selection()
                 } else {
                     Style::default()
                 };
@@ -95,14 +103,14 @@ impl TreeView {
                         let icon = if *expanded { "▼" } else { "▶" };
                         let size_str = format_size(*total_size);
                         Row::new(vec![
-                            Cell::from(format!("{} 📁 {} ({} files, {})", icon, name, file_count, size_str))
-                                .style(Style::default().fg(Color::Cyan).bold()),
+                            Cell::from(format!("{} {} {} ({} files, {})", icon, group_icon, name, file_count, size_str))
+                                .style(Style::default().fg(app.theme.header).bold()),
                         ]).style(style)
                     }
                     GroupedRow::File { filename, size_bytes, file_type, .. } => {
                         let icon = Self::file_icon(*file_type);
                         let size_str = size_bytes.map(format_size).unwrap_or_else(|| "?".to_string());
-                        let type_style = Self::type_style(*file_type);
+                        let type_style = app.theme.type_style(*file_type);
                         Row::new(vec![
                             Cell::from(format!("    {} {} ({})", icon, filename, size_str))
                                 .style(type_style),
@@ -115,9 +123,9 @@ impl TreeView {
         let table = Table::new(rows, [Constraint::Percentage(100)])
             .block(
                 Block::default()
-                    .title(format!(" Grouped View ({} folders) [Tab: switch view] ", app.folder_groups.len()))
+                    .title(format!(" {} [Tab: switch view] ", title))
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::DarkGray)),
+                    .border_style(app.theme.border_style()),
             );
 
         frame.render_widget(table, list_area);
@@ -155,6 +163,7 @@ impl TreeView {
         let flattened = &app.tree_state.flattened;
         let total_rows = flattened.len();
         let selected_idx = app.tree_state.get_selected_index();
+        let inner_width = (list_area.width as usize).saturating_sub(border_height);
 
         // Create table rows
         let rows: Vec<Row> = flattened
@@ -165,7 +174,7 @@ impl TreeView {
             .map(|(idx, node)| {
                 let is_selected = idx == selected_idx;
                 let style = if is_selected {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                    app.theme.selection()
                 } else {
                     Style::default()
                 };
@@ -197,14 +206,28 @@ impl TreeView {
                 let display = format!("{}{}{} {} {}", indent, expand_indicator, icon, node.name, info);
 
                 let cell_style = if node.is_dir {
-                    Style::default().fg(Color::Cyan)
+                    Style::default().fg(app.theme.header)
                 } else {
-                    Self::type_style(node.file_type.unwrap_or(FileType::Other))
+                    app.theme.type_style(node.file_type.unwrap_or(FileType::Other))
                 };
 
-                Row::new(vec![
-                    Cell::from(display).style(cell_style),
-                ]).style(style)
+                let mut spans = vec![Span::styled(display, cell_style)];
+
+                // Size relative to the largest sibling folder, in the space left after the name
+                if app.tree_size_bars && node.is_dir && node.sibling_max_size > 0 {
+                    let used = Line::from(spans.clone()).width();
+                    let width = inner_width.saturating_sub(used + 1).min(SIZE_BAR_WIDTH);
+                    if width >= 2 {
+                        let fraction = node.total_size as f64 / node.sibling_max_size as f64;
+                        spans.push(Span::raw(" ".repeat(inner_width - used - width)));
+                        spans.push(Span::styled(
+                            size_bar(fraction, width),
+                            Style::default().fg(app.theme.header),
+                        ));
+                    }
+                }
+
+                Row::new(vec![Cell::from(Line::from(spans))]).style(style)
             })
             .collect();
 
@@ -213,7 +236,7 @@ impl TreeView {
                 Block::default()
                     .title(format!(" Tree View ({} items) [Tab: switch, ←→: expand/collapse] ", total_rows))
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::DarkGray)),
+                    .border_style(app.theme.border_style()),
             );
 
         frame.render_widget(table, list_area);
@@ -267,18 +290,24 @@ impl TreeView {
             FileType::Other => "📎",
         }
     }
+}
 
-    /// Get style for file type
-    fn type_style(file_type: FileType) -> Style {
-        match file_type {
-            FileType::Executable => Style::default().fg(Color::Red),
-            FileType::Archive => Style::default().fg(Color::Magenta),
-            FileType::Document => Style::default().fg(Color::Blue),
-            FileType::Media => Style::default().fg(Color::Green),
-            FileType::Code => Style::default().fg(Color::Yellow),
-            FileType::Other => Style::default().fg(Color::Gray),
-        }
+/// A bar of block characters filling `fraction` of `width` cells, in
+/// eighths of a cell
+fn size_bar(fraction: f64, width: usize) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+    let eighths = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    let remainder = eighths % 8;
+    if remainder > 0 {
+        bar.push(PARTIAL[remainder]);
+    }
+    // Anything non-empty gets at least a sliver
+    if bar.is_empty() && fraction > 0.0 {
+        bar.push('▏');
     }
+    bar
 }
 
 /// Row type for grouped view
@@ -298,19 +327,16 @@ enum GroupedRow {
     },
 }
 
-/// Format file size in human-readable format
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.1}GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1}MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1}KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{}B", bytes)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_bar() {
+        assert_eq!(size_bar(1.0, 4), "████");
+        assert_eq!(size_bar(0.5, 4), "██");
+        assert_eq!(size_bar(10.0 / 32.0, 4), "█▎");
+        assert_eq!(size_bar(0.001, 4), "▏");
+        assert_eq!(size_bar(0.0, 4), "");
     }
 }
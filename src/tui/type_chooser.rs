@@ -0,0 +1,79 @@
+//! File type chooser component
+//!
+//! Lets the user correct the type recorded for a file when the extension
+//! guessed wrong.
+
+use super::theme::Theme;
+use crate::models::FileType;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// File type chooser state
+pub struct TypeChooser {
+    /// The event whose type is being changed
+    pub event_id: i64,
+    /// Index into `FileType::all()` of the highlighted type
+    pub selected: usize,
+}
+
+impl TypeChooser {
+    /// Open the chooser with the file's current type highlighted
+    pub fn new(event_id: i64, current: FileType) -> Self {
+        let selected = FileType::all().iter().position(|&t| t == current).unwrap_or(0);
+        Self { event_id, selected }
+    }
+
+    /// The highlighted type
+    pub fn selected_type(&self) -> FileType {
+        FileType::all()[self.selected]
+    }
+
+    /// Highlight the previous type
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Highlight the next type
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(FileType::all().len() - 1);
+    }
+
+    /// Draw the chooser
+    pub fn draw(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let types = FileType::all();
+        let overlay_width = 30.min(area.width - 4);
+        let overlay_height = (types.len() as u16 + 2).min(area.height - 4);
+        let overlay_area = Rect::new(
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
+            overlay_width,
+            overlay_height,
+        );
+
+        // Clear the area behind the overlay
+        frame.render_widget(Clear, overlay_area);
+
+        let lines: Vec<Line> = types
+            .iter()
+            .enumerate()
+            .map(|(i, &file_type)| {
+                if i == self.selected {
+                    Line::styled(format!(" ▸ {}", file_type.as_str()), theme.selection().bold())
+                } else {
+                    Line::styled(format!("   {}", file_type.as_str()), theme.type_style(file_type))
+                }
+            })
+            .collect();
+
+        let overlay = Paragraph::new(lines).block(
+            Block::default()
+                .title(" File Type ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.header)),
+        );
+
+        frame.render_widget(overlay, overlay_area);
+    }
+}
@@ -0,0 +1,100 @@
+//! Watch paths overlay component
+//!
+//! Lists each watched directory with whether it's currently online and how
+//! many events have been recorded under it.
+
+use super::theme::Theme;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use std::path::PathBuf;
+
+/// One watched directory as shown in the overlay
+pub struct WatchPathEntry {
+    pub path: PathBuf,
+    /// Found missing by the watcher's health check
+    pub offline: bool,
+    /// Events recorded in the directory and below it
+    pub event_count: u64,
+}
+
+/// Watch paths overlay state
+pub struct WatchPathsOverlay {
+    pub entries: Vec<WatchPathEntry>,
+    /// Configured paths that couldn't be watched at startup
+    pub skipped: usize,
+    /// Current scroll position
+    pub scroll: u16,
+}
+
+impl WatchPathsOverlay {
+    pub fn new(entries: Vec<WatchPathEntry>, skipped: usize) -> Self {
+        Self {
+            entries,
+            skipped,
+            scroll: 0,
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        let last = self.entries.len().saturating_sub(1) as u16;
+        self.scroll = (self.scroll + 1).min(last);
+    }
+
+    /// Draw the watch paths overlay
+    pub fn draw(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let extra_rows = if self.skipped > 0 { 2 } else { 0 };
+        let overlay_width = 70.min(area.width - 4);
+        let overlay_height = (self.entries.len().max(1) as u16 + 2 + extra_rows).min(area.height - 4);
+        let overlay_area = Rect::new(
+            area.x + (area.width - overlay_width) / 2,
+            area.y + (area.height - overlay_height) / 2,
+            overlay_width,
+            overlay_height,
+        );
+
+        // Clear the area behind the overlay
+        frame.render_widget(Clear, overlay_area);
+
+        let mut lines: Vec<Line> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let status = if entry.offline {
+                    Span::styled("offline ", Style::default().fg(theme.status).bold())
+                } else {
+                    Span::styled("online  ", Style::default().fg(theme.media))
+                };
+                Line::from(vec![
+                    status,
+                    Span::styled(format!("{:>7} files  ", entry.event_count), Style::default().fg(theme.label)),
+                    Span::raw(entry.path.display().to_string()),
+                ])
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::styled("No directories watched", Style::default().fg(theme.muted)));
+        }
+        if self.skipped > 0 {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(
+                format!("{} configured path(s) could not be watched (see the log)", self.skipped),
+                Style::default().fg(theme.muted),
+            ));
+        }
+
+        let overlay = Paragraph::new(lines).scroll((self.scroll, 0)).block(
+            Block::default()
+                .title(" Watched Directories (↑↓ to scroll, q to close) ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.header)),
+        );
+
+        frame.render_widget(overlay, overlay_area);
+    }
+}
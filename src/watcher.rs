@@ -13,70 +13,548 @@
 //!
 //! This ensures the notify callback never blocks and the UI thread never does disk I/O.
 
-use crate::config::Config;
-use crate::models::FileEvent;
+use crate::config::{Categories, Config, TypeRules, WatchRules};
+use crate::models::{FileEvent, FileType, TimestampSource};
+use crate::notifier::NewFileNotifier;
+use crate::quota::Quota;
+use crate::sink::{ChannelSink, EventSink, StoreSink, WebhookSink};
 use crate::store::Store;
 use anyhow::{Context, Result};
 use globset::GlobSet;
+use ignore::gitignore::Gitignore;
+use ignore::Match;
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Config as NotifyConfig, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use sha2::{Digest, Sha256};
+use lru::LruCache;
 use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicU64, Ordering}};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, trace, warn};
 
-/// Debounce window for coalescing rapid events on the same file
-const DEBOUNCE_WINDOW_MS: u64 = 300;
+/// Bytes read from the start of a file for content sniffing
+const SNIFF_BYTES: u64 = 8 * 1024;
 
-/// Maximum events to process per batch
-const MAX_BATCH_SIZE: usize = 500;
+/// How long after the debounce a new directory's files still count toward
+/// one extraction
+const EXTRACTION_WINDOW: Duration = Duration::from_secs(2);
+
+/// Times a growing file is re-checked before it's recorded anyway
+const MAX_STABILITY_CHECKS: u32 = 10;
+
+/// Least time between two `[[quota]]` checks while files keep changing
+const QUOTA_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Most threads classifying and hashing backfilled files at once
+const MAX_SCAN_WORKERS: usize = 8;
+
+/// Most paths each of the processing thread's caches remembers
+const PATH_CACHE_SIZE: usize = 10_000;
 
 /// Message types sent from the watcher to the main application
 #[derive(Debug, Clone)]
 pub enum WatcherMessage {
     /// A new file was detected
     NewFile(FileEvent),
-    /// A file was moved/renamed into a watched directory
+    /// A tracked file was moved or renamed within the watched directories
     MovedFile(FileEvent),
+    /// A tracked file was removed from disk
+    DeletedFile(PathBuf),
     /// An error occurred during watching
     Error(String),
+    /// Something needs attention but watching carries on, e.g. an exceeded quota
+    Warning(String),
+    /// A watch path that had gone offline exists again and is watched anew
+    PathRestored(PathBuf),
     /// The watcher started successfully
     Started,
     /// The watcher stopped
     Stopped,
 }
 
+/// Outcome of [`FileWatcher::watch_paths`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchSummary {
+    /// Paths now being watched
+    pub watched: usize,
+    /// Paths that were skipped or could not be watched
+    pub failed: usize,
+}
+
+/// What the processing thread has done with the paths it was given, for
+/// finding out why a file wasn't recorded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct WatcherStats {
+    /// File system events and scanned files received
+    pub received: u64,
+    /// Skipped by `ignore_patterns`, `.gitignore` or `max_depth`
+    pub ignored_by_pattern: u64,
+    /// Skipped for being smaller than `min_size_bytes`
+    pub ignored_by_size: u64,
+    /// Skipped because the path was already seen or is already tracked
+    pub deduped: u64,
+    /// Dropped while watching was paused
+    pub dropped_paused: u64,
+    /// Recorded as new files
+    pub inserted: u64,
+}
+
+impl WatcherStats {
+    /// One-line summary for logs
+    pub fn summary(&self) -> String {
+        format!(
+            "received {}, ignored by pattern {}, ignored by size {}, deduped {}, dropped while paused {}, inserted {}",
+            self.received,
+            self.ignored_by_pattern,
+            self.ignored_by_size,
+            self.deduped,
+            self.dropped_paused,
+            self.inserted
+        )
+    }
+}
+
+/// Counters behind [`WatcherStats`], updated by the processing thread
+#[derive(Debug, Default)]
+pub struct WatcherCounters {
+    received: AtomicU64,
+    ignored_by_pattern: AtomicU64,
+    ignored_by_size: AtomicU64,
+    deduped: AtomicU64,
+    dropped_paused: AtomicU64,
+    inserted: AtomicU64,
+}
+
+impl WatcherCounters {
+    /// Current values of the counters
+    pub fn snapshot(&self) -> WatcherStats {
+        WatcherStats {
+            received: self.received.load(Ordering::Relaxed),
+            ignored_by_pattern: self.ignored_by_pattern.load(Ordering::Relaxed),
+            ignored_by_size: self.ignored_by_size.load(Ordering::Relaxed),
+            deduped: self.deduped.load(Ordering::Relaxed),
+            dropped_paused: self.dropped_paused.load(Ordering::Relaxed),
+            inserted: self.inserted.load(Ordering::Relaxed),
+        }
+    }
+
+    fn add(counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Count files written to the database
+    pub fn count_inserted(&self, n: u64) {
+        Self::add(&self.inserted, n);
+    }
+}
+
 /// Internal message for raw events (no I/O performed yet)
 #[derive(Debug, Clone)]
 enum RawEvent {
     /// A potential file event with path and event kind
     File { path: PathBuf, kind: EventKind },
+    /// A rename where both the old and new path were observed
+    Rename { from: PathBuf, to: PathBuf },
+    /// Backfill files already present under a newly watched root
+    Scan(PathBuf),
     /// Shutdown signal
     Shutdown,
 }
 
+/// Settings the processing thread takes from the config
+#[derive(Clone)]
+struct ProcessorSettings {
+    /// Ignore patterns, minimum size and depth for paths without a `[[watch]]`
+    /// entry, shared with the watcher so a reload can replace them
+    default_rules: Arc<Mutex<Arc<WatchRules>>>,
+    /// Filters of each `[[watch]]` entry, keyed by canonical path
+    watch_rules: Arc<Mutex<HashMap<PathBuf, Arc<WatchRules>>>>,
+    /// Quiet period before a path's events are processed
+    debounce: Duration,
+    /// Most raw events collected per batch
+    max_batch_size: usize,
+    /// Type rules, categories and hashing for new files
+    classifier: Classifier,
+    /// Watch roots, shared with the watcher for depth calculation
+    watch_roots: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Desktop notifications for new files (None = disabled)
+    notifier: Option<NewFileNotifier>,
+    /// Where new files go, in order: the store, the UI, then external sinks
+    sinks: Vec<Arc<dyn EventSink>>,
+    /// Whether to skip paths ignored by `.gitignore` files
+    respect_gitignore: bool,
+    /// Whether empty files are skipped regardless of the minimum size
+    ignore_zero_byte: bool,
+    /// While set, new files are neither recorded nor reported
+    paused: Arc<AtomicBool>,
+    /// Record files reached through symlinks under their real path
+    follow_symlinks: bool,
+    /// The notify watcher, for watching directories created under a root
+    watcher: Arc<Mutex<RecommendedWatcher>>,
+    /// Files in one new directory above which it's summarized (0 = off)
+    extraction_threshold: usize,
+    /// Whether a summarized directory's files are left out
+    collapse_extractions: bool,
+    /// How long a new file's size must stay unchanged before it's recorded
+    stability_check: Option<Duration>,
+    /// How often watch roots are checked for still existing (None = never)
+    health_check: Option<Duration>,
+    /// Watch roots found missing by the health check, shared with the watcher
+    offline_roots: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Whether a root that comes back online is scanned for files added meanwhile
+    scan_existing: bool,
+    /// What happened to the paths processed, shared with the watcher
+    counters: Arc<WatcherCounters>,
+    /// `[[quota]]` limits, shared with the watcher so a reload can replace them
+    quotas: Arc<Mutex<Vec<Quota>>>,
+    /// Labels of the quotas currently exceeded, shared with the watcher
+    exceeded_quotas: Arc<Mutex<HashSet<String>>>,
+    /// Files Ferret writes itself, never recorded (see [`FileWatcher::own_files`]),
+    /// shared with the watcher so a reload can replace them
+    own_files: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl ProcessorSettings {
+    /// Filters for `path`: those of the deepest `[[watch]]` entry holding it,
+    /// or the top-level ones
+    fn rules_for(&self, path: &Path) -> Arc<WatchRules> {
+        self.watch_rules
+            .lock()
+            .ok()
+            .and_then(|rules| {
+                rules
+                    .iter()
+                    .filter(|(root, _)| path.starts_with(root))
+                    .max_by_key(|(root, _)| root.components().count())
+                    .map(|(_, rules)| rules.clone())
+            })
+            .unwrap_or_else(|| match self.default_rules.lock() {
+                Ok(rules) => rules.clone(),
+                Err(poisoned) => poisoned.into_inner().clone(),
+            })
+    }
+
+    /// Whether `path` is deeper below its watch root than `max_depth` allows
+    fn exceeds_max_depth(&self, path: &Path) -> bool {
+        let Some(max_depth) = self.rules_for(path).max_depth else {
+            return false;
+        };
+        let depth = self
+            .watch_roots
+            .lock()
+            .ok()
+            .and_then(|roots| depth_below_root(path, roots.iter()));
+        depth.is_some_and(|d| d > max_depth)
+    }
+
+    /// Whether every file directly inside `dir` would be below `max_depth`
+    fn contents_exceed_max_depth(&self, dir: &Path) -> bool {
+        let Some(max_depth) = self.rules_for(dir).max_depth else {
+            return false;
+        };
+        let depth = self
+            .watch_roots
+            .lock()
+            .ok()
+            .and_then(|roots| depth_below_root(dir, roots.iter()));
+        depth.is_some_and(|d| d >= max_depth)
+    }
+}
+
+/// Classification and hashing applied to every new file
+#[derive(Clone)]
+struct Classifier {
+    /// File type overrides from `[[rules]]`
+    type_rules: TypeRules,
+    /// User-defined categories from `[[categories]]`
+    categories: Categories,
+    /// Whether to sniff magic bytes when the extension gives no type
+    content_sniffing: bool,
+    /// Whether to hash new files
+    compute_hashes: bool,
+    /// Largest file to hash (0 = no limit)
+    max_hash_size: u64,
+    /// Which time new files are recorded with
+    timestamp_source: TimestampSource,
+}
+
+impl Classifier {
+    fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self {
+            type_rules: config.build_type_rules()?,
+            categories: config.build_categories()?,
+            content_sniffing: config.content_sniffing,
+            compute_hashes: config.compute_hashes,
+            max_hash_size: config.max_hash_size_bytes,
+            timestamp_source: config.timestamp_source()?,
+        })
+    }
+
+    /// Date the event from the configured `timestamp_source`
+    ///
+    /// Backfilled files date from their last modification when discovery
+    /// time is chosen, since they weren't just discovered. If the chosen
+    /// time can't be read, the event keeps its discovery time.
+    fn set_timestamp(&self, file_event: &mut FileEvent, backfill: bool) {
+        let source = match self.timestamp_source {
+            TimestampSource::Discovered if backfill => TimestampSource::Modified,
+            source => source,
+        };
+        if let Some(time) = file_event.path.metadata().ok().and_then(|m| source.time_of(&m)) {
+            file_event.created_at = time;
+        }
+    }
+
+    /// Build the event for a new file at `path`, fully classified
+    fn build(&self, path: PathBuf, backfill: bool) -> FileEvent {
+        let mut file_event = FileEvent::from_path(path);
+        self.classify(&mut file_event);
+        self.set_timestamp(&mut file_event, backfill);
+        self.hash(&mut file_event);
+        file_event
+    }
+
+    /// Build the events for backfilled `paths` on up to `MAX_SCAN_WORKERS`
+    /// threads, keeping their order
+    ///
+    /// Sniffing and hashing tens of thousands of existing files one at a
+    /// time would hold up the processing thread for a long while.
+    fn build_backfill(&self, paths: Vec<PathBuf>) -> Vec<FileEvent> {
+        let workers = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_SCAN_WORKERS)
+            .min(paths.len());
+        if workers <= 1 {
+            return paths.iter().filter_map(|path| self.build_existing(path)).collect();
+        }
+
+        let chunk_size = paths.len().div_ceil(workers);
+        thread::scope(|scope| {
+            let handles: Vec<_> = paths
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk.iter().filter_map(|path| self.build_existing(path)).collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        error!("Backfill worker failed, skipping its files");
+                        Vec::new()
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Build the event for an existing file, skipping it if classifying it
+    /// panics so one bad file can't stop the scan (or the processing thread)
+    fn build_existing(&self, path: &Path) -> Option<FileEvent> {
+        let built = panic::catch_unwind(AssertUnwindSafe(|| self.build(path.to_path_buf(), true)));
+        if built.is_err() {
+            error!("Skipping {}: classifying it failed", path.display());
+        }
+        built.ok()
+    }
+
+    /// Apply `[[rules]]` overrides, then content sniffing for unknown types,
+    /// flag runnable executables and look up the `[[categories]]` entry
+    fn classify(&self, file_event: &mut FileEvent) {
+        if let Some(file_type) = self.type_rules.classify(&file_event.path) {
+            file_event.file_type = file_type;
+        } else if self.content_sniffing && file_event.file_type == FileType::Other {
+            if let Some(file_type) = sniff_file_type(&file_event.path) {
+                file_event.file_type = file_type;
+            }
+        }
+        file_event.check_exec_flags();
+        file_event.check_origin();
+        file_event.category = self.categories.classify(&file_event.path).map(str::to_string);
+    }
+
+    /// Hash the file's contents if enabled (skipped for files over the size limit)
+    fn hash(&self, file_event: &mut FileEvent) {
+        if !self.compute_hashes {
+            return;
+        }
+        let path = &file_event.path;
+        let size = file_event.size_bytes.unwrap_or(0);
+        if self.max_hash_size == 0 || size <= self.max_hash_size {
+            match hash_file(path) {
+                Ok(hash) => file_event.sha256 = Some(hash),
+                Err(e) => debug!("Failed to hash {}: {}", path.display(), e),
+            }
+        } else {
+            trace!("Not hashing (too large): {} ({} bytes)", path.display(), size);
+        }
+    }
+}
+
+/// A bounded set of recently seen paths
+///
+/// Once full, adding a path forgets the one seen longest ago, rather than
+/// dropping everything at once, so what is remembered doesn't depend on
+/// how many other files came before.
+struct RecentPaths {
+    paths: LruCache<PathBuf, ()>,
+}
+
+impl RecentPaths {
+    fn new(capacity: usize) -> Self {
+        Self {
+            paths: LruCache::new(NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN)),
+        }
+    }
+
+    /// Remember `path` as the most recently seen
+    fn insert(&mut self, path: PathBuf) {
+        self.paths.put(path, ());
+    }
+
+    /// Whether `path` is remembered, marking it as seen again if so
+    fn contains(&mut self, path: &Path) -> bool {
+        self.paths.get(path).is_some()
+    }
+
+    /// Forget `path`, returning whether it was remembered
+    fn remove(&mut self, path: &Path) -> bool {
+        self.paths.pop(path).is_some()
+    }
+
+    /// Forget every remembered path under `dir`
+    fn remove_under(&mut self, dir: &Path) {
+        let under: Vec<PathBuf> = self.paths.iter().map(|(path, _)| path).filter(|path| path.starts_with(dir)).cloned().collect();
+        for path in under {
+            self.paths.pop(&path);
+        }
+    }
+}
+
+/// Compiled `.gitignore` matchers, cached per directory
+///
+/// Only used on the processing thread. A directory without a `.gitignore`
+/// is cached as `None` so it isn't checked again.
+#[derive(Default)]
+struct GitignoreCache {
+    matchers: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl GitignoreCache {
+    /// Whether any `.gitignore` from the file's directory up to the
+    /// repository root ignores `path`
+    ///
+    /// The deepest `.gitignore` with a matching rule decides, so a nested
+    /// `!pattern` can re-include a file ignored further up.
+    fn is_ignored(&mut self, path: &Path) -> bool {
+        for dir in path.ancestors().skip(1) {
+            if let Some(matcher) = self.matcher_for(dir) {
+                match matcher.matched_path_or_any_parents(path, false) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            // Don't look past the repository root
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        false
+    }
+
+    /// Forget the cached matcher for the directory holding a changed `.gitignore`
+    fn invalidate(&mut self, gitignore_path: &Path) {
+        if let Some(dir) = gitignore_path.parent() {
+            self.matchers.remove(dir);
+        }
+    }
+
+    /// Matcher for `dir`'s own `.gitignore`, compiled on first use
+    fn matcher_for(&mut self, dir: &Path) -> Option<&Gitignore> {
+        self.matchers
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let file = dir.join(".gitignore");
+                if !file.is_file() {
+                    return None;
+                }
+                let (matcher, err) = Gitignore::new(&file);
+                if let Some(e) = err {
+                    warn!("Problem reading {}: {}", file.display(), e);
+                }
+                Some(matcher)
+            })
+            .as_ref()
+    }
+}
+
+/// A directory created while watching, followed to detect extractions
+struct NewDir {
+    /// When the directory or its latest files were seen
+    last_seen: Instant,
+    /// Files recorded in it so far
+    files: usize,
+    /// Their combined size
+    size: u64,
+    /// The summary event, once more than `extraction_threshold` files arrived
+    summary: Option<FileEvent>,
+}
+
+/// A settled path ready for processing
+struct SettledPath {
+    path: PathBuf,
+    kind: EventKind,
+    /// Found by an initial scan rather than a notify event
+    backfill: bool,
+}
+
 /// File system watcher that monitors directories for new files
 pub struct FileWatcher {
-    /// The underlying notify watcher
-    watcher: RecommendedWatcher,
+    /// The underlying notify watcher, shared with the processing thread
+    watcher: Arc<Mutex<RecommendedWatcher>>,
     /// Sender for watcher messages (to UI)
     tx: Sender<WatcherMessage>,
     /// Paths currently being watched
     watched_paths: Arc<Mutex<HashSet<PathBuf>>>,
-    /// Glob matcher for ignored patterns
-    ignore_matcher: GlobSet,
-    /// Minimum file size to report
-    min_size: u64,
+    /// Top-level filters, shared with the processing thread
+    default_rules: Arc<Mutex<Arc<WatchRules>>>,
     /// Store reference for checking existing paths
     store: Option<Store>,
     /// Shutdown flag for processing thread
     shutdown: Arc<AtomicBool>,
+    /// Pause flag shared with the processing thread
+    paused: Arc<AtomicBool>,
+    /// Per-path filters from `[[watch]]`, shared with the processing thread
+    watch_rules: Arc<Mutex<HashMap<PathBuf, Arc<WatchRules>>>>,
     /// Handle to the processing thread
     processor_handle: Option<JoinHandle<()>>,
     /// Sender for raw events to processing thread
     raw_event_tx: Sender<RawEvent>,
+    /// Whether to backfill existing files when a path is first watched
+    scan_existing: bool,
+    /// Whether to also watch symlinked directories outside a watch root
+    follow_symlinks: bool,
+    /// Symlink targets watched on behalf of each watch root
+    link_targets: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Watch paths that currently don't exist, shared with the processing thread
+    offline_roots: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Processing counters, shared with the processing thread
+    counters: Arc<WatcherCounters>,
+    /// `[[quota]]` limits, shared with the processing thread
+    quotas: Arc<Mutex<Vec<Quota>>>,
+    /// Labels of the quotas currently exceeded, shared with the processing thread
+    exceeded_quotas: Arc<Mutex<HashSet<String>>>,
+    /// Files Ferret writes itself, shared with the processing thread
+    own_files: Arc<Mutex<HashSet<PathBuf>>>,
 }
 
 impl FileWatcher {
@@ -84,10 +562,16 @@ impl FileWatcher {
     pub fn new(config: &Config, store: Option<Store>) -> Result<(Self, Receiver<WatcherMessage>)> {
         let (tx, rx) = mpsc::channel();
         let (raw_event_tx, raw_event_rx) = mpsc::channel::<RawEvent>();
-        let ignore_matcher = config.build_ignore_matcher()?;
-        let min_size = config.min_size_bytes;
+        let default_rules = Arc::new(Mutex::new(Arc::new(config.default_watch_rules()?)));
         let watched_paths = Arc::new(Mutex::new(HashSet::new()));
         let shutdown = Arc::new(AtomicBool::new(false));
+        let paused = Arc::new(AtomicBool::new(false));
+        let offline_roots = Arc::new(Mutex::new(HashSet::new()));
+        let counters = Arc::new(WatcherCounters::default());
+        let watch_rules = Arc::new(Mutex::new(Self::shared_watch_rules(config)?));
+        let quotas = Arc::new(Mutex::new(config.build_quotas()?));
+        let exceeded_quotas = Arc::new(Mutex::new(HashSet::new()));
+        let own_files = Arc::new(Mutex::new(Self::own_files(config)));
         let debounce_ms = config.debounce_ms;
         
         // Clone for the notify callback (minimal - only sends raw paths)
@@ -98,10 +582,26 @@ impl FileWatcher {
             move |res: Result<Event, notify::Error>| {
                 match res {
                     Ok(event) => {
-                        // Only pass through create/modify events, filter out the rest immediately
+                        // Renames with both sides known update the existing row
+                        if let EventKind::Modify(ModifyKind::Name(RenameMode::Both)) = event.kind {
+                            if let [from, to] = &event.paths[..] {
+                                let _ = raw_tx_for_notify.send(RawEvent::Rename {
+                                    from: from.clone(),
+                                    to: to.clone(),
+                                });
+                                return;
+                            }
+                        }
+
+                        // Only pass through create/rename/remove events and writes,
+                        // filter out the rest (metadata, access) immediately
                         let dominated_by = matches!(
                             event.kind,
-                            EventKind::Create(_) | EventKind::Modify(notify::event::ModifyKind::Name(_))
+                            EventKind::Create(_)
+                                | EventKind::Modify(ModifyKind::Name(_))
+                                | EventKind::Modify(ModifyKind::Data(_))
+                                | EventKind::Modify(ModifyKind::Any)
+                                | EventKind::Remove(_)
                         );
                         
                         if dominated_by {
@@ -109,7 +609,7 @@ impl FileWatcher {
                                 // Send raw path - NO I/O here!
                                 let _ = raw_tx_for_notify.send(RawEvent::File { 
                                     path, 
-                                    kind: event.kind.clone() 
+                                    kind: event.kind 
                                 });
                             }
                         }
@@ -123,13 +623,46 @@ impl FileWatcher {
                 .with_poll_interval(Duration::from_millis(debounce_ms.max(100))),
         )
         .context("Failed to create file watcher")?;
+        let watcher = Arc::new(Mutex::new(watcher));
+
+        let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+        if let Some(store) = &store {
+            sinks.push(Arc::new(StoreSink::new(store.clone(), counters.clone())));
+        }
+        sinks.push(Arc::new(ChannelSink::new(tx.clone())));
+        if let Some(webhook) = WebhookSink::from_config(&config.webhook)? {
+            sinks.push(Arc::new(webhook));
+        }
 
         // Clone data for the processing thread
         let tx_for_processor = tx.clone();
         let store_for_processor = store.clone();
-        let ignore_matcher_for_processor = ignore_matcher.clone();
         let shutdown_for_processor = shutdown.clone();
-        let min_size_for_processor = min_size;
+        let settings_for_processor = ProcessorSettings {
+            default_rules: default_rules.clone(),
+            watch_rules: watch_rules.clone(),
+            debounce: Duration::from_millis(debounce_ms),
+            max_batch_size: config.max_batch_size.max(1),
+            classifier: Classifier::from_config(config)?,
+            watch_roots: watched_paths.clone(),
+            notifier: NewFileNotifier::from_config(&config.notifications),
+            sinks,
+            respect_gitignore: config.respect_gitignore,
+            ignore_zero_byte: config.ignore_zero_byte,
+            paused: paused.clone(),
+            follow_symlinks: config.follow_symlinks,
+            watcher: watcher.clone(),
+            extraction_threshold: config.extraction_threshold,
+            collapse_extractions: config.collapse_extractions,
+            stability_check: config.stability_check_ms.map(Duration::from_millis),
+            health_check: (config.health_check_ms > 0).then(|| Duration::from_millis(config.health_check_ms)),
+            offline_roots: offline_roots.clone(),
+            scan_existing: config.scan_existing,
+            counters: counters.clone(),
+            quotas: quotas.clone(),
+            exceeded_quotas: exceeded_quotas.clone(),
+            own_files: own_files.clone(),
+        };
 
         // Spawn dedicated processing thread for all I/O operations
         let processor_handle = thread::Builder::new()
@@ -139,8 +672,7 @@ impl FileWatcher {
                     raw_event_rx,
                     tx_for_processor,
                     store_for_processor,
-                    ignore_matcher_for_processor,
-                    min_size_for_processor,
+                    settings_for_processor,
                     shutdown_for_processor,
                 );
             })
@@ -150,12 +682,21 @@ impl FileWatcher {
             watcher,
             tx,
             watched_paths,
-            ignore_matcher,
-            min_size,
+            default_rules,
             store,
             shutdown,
+            paused,
+            watch_rules,
             processor_handle: Some(processor_handle),
             raw_event_tx,
+            scan_existing: config.scan_existing,
+            follow_symlinks: config.follow_symlinks,
+            link_targets: HashMap::new(),
+            offline_roots,
+            counters,
+            quotas,
+            exceeded_quotas,
+            own_files,
         };
 
         Ok((file_watcher, rx))
@@ -166,17 +707,36 @@ impl FileWatcher {
         raw_rx: Receiver<RawEvent>,
         tx: Sender<WatcherMessage>,
         store: Option<Store>,
-        ignore_matcher: GlobSet,
-        min_size: u64,
+        settings: ProcessorSettings,
         shutdown: Arc<AtomicBool>,
     ) {
         // Debounce map: path -> (last_seen_time, event_kind)
         let mut pending: HashMap<PathBuf, (Instant, EventKind)> = HashMap::new();
         
-        // Set of paths we've already processed (in-memory dedup for current session)
-        let mut processed_this_session: HashSet<PathBuf> = HashSet::new();
+        // Paths processed recently (in-memory dedup in front of the store,
+        // which decides for paths no longer remembered)
+        let mut processed_this_session = RecentPaths::new(PATH_CACHE_SIZE);
+
+        // Compiled .gitignore files, only filled when respect_gitignore is set
+        let mut gitignores = GitignoreCache::default();
+
+        // Directories created recently, only filled when extraction_threshold is set
+        let mut new_dirs: HashMap<PathBuf, NewDir> = HashMap::new();
+
+        // Size last sampled and checks so far for files waiting to stop growing
+        let mut unsettled: LruCache<PathBuf, (u64, u32)> =
+            LruCache::new(NonZeroUsize::new(PATH_CACHE_SIZE).unwrap_or(NonZeroUsize::MIN));
+
+        // Files skipped while still empty, picked up again once written to
+        let mut skipped_empty = RecentPaths::new(PATH_CACHE_SIZE);
         
-        let debounce_duration = Duration::from_millis(DEBOUNCE_WINDOW_MS);
+        let debounce_duration = settings.debounce;
+        let mut last_health_check = Instant::now();
+        let counters = &settings.counters;
+
+        // Quotas are checked at startup, then again whenever files changed
+        let mut quotas_stale = true;
+        let mut last_quota_check: Option<Instant> = None;
 
         loop {
             if shutdown.load(Ordering::Relaxed) {
@@ -185,15 +745,40 @@ impl FileWatcher {
 
             // Collect batch of raw events (non-blocking with timeout)
             let mut batch_count = 0;
+            let mut scan_roots = Vec::new();
+            let mut renames = Vec::new();
             loop {
                 match raw_rx.recv_timeout(Duration::from_millis(50)) {
                     Ok(RawEvent::File { path, kind }) => {
-                        pending.insert(path, (Instant::now(), kind));
                         batch_count += 1;
-                        if batch_count >= MAX_BATCH_SIZE {
+                        let is_write = matches!(kind, EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any));
+                        if !is_write {
+                            WatcherCounters::add(&counters.received, 1);
+                            pending.insert(path, (Instant::now(), kind));
+                        } else if let Some((time, _)) = pending.get_mut(&path) {
+                            // A write only pushes back a file that's waiting to
+                            // settle (keeping its Create), so it's recorded once
+                            // quiet for the whole window, with its final size
+                            *time = Instant::now();
+                        } else if skipped_empty.remove(&path) {
+                            WatcherCounters::add(&counters.received, 1);
+                            pending.insert(path, (Instant::now(), kind));
+                        }
+                        // Writes to any other file don't make it new
+                        if batch_count >= settings.max_batch_size {
                             break;
                         }
                     }
+                    Ok(RawEvent::Rename { from, to }) => {
+                        WatcherCounters::add(&counters.received, 1);
+                        // The one-sided events for these paths are superseded
+                        pending.remove(&from);
+                        pending.remove(&to);
+                        renames.push((from, to));
+                    }
+                    Ok(RawEvent::Scan(root)) => {
+                        scan_roots.push(root);
+                    }
                     Ok(RawEvent::Shutdown) => {
                         return;
                     }
@@ -202,23 +787,211 @@ impl FileWatcher {
                 }
             }
 
+            // Notice watch roots that vanished (e.g. an unmounted drive) or came back
+            if let Some(interval) = settings.health_check {
+                if last_health_check.elapsed() >= interval {
+                    last_health_check = Instant::now();
+                    let restored = Self::check_watch_roots(&settings, &tx);
+                    if settings.scan_existing {
+                        scan_roots.extend(restored);
+                    }
+                }
+            }
+
             // Process events that have "settled" (past debounce window)
             let now = Instant::now();
             let mut to_process = Vec::new();
             
             pending.retain(|path, (time, kind)| {
                 if now.duration_since(*time) >= debounce_duration {
-                    to_process.push((path.clone(), kind.clone()));
+                    to_process.push(SettledPath {
+                        path: path.clone(),
+                        kind: kind.clone(),
+                        backfill: false,
+                    });
                     false // Remove from pending
                 } else {
                     true // Keep in pending
                 }
             });
 
+            quotas_stale |= !to_process.is_empty() || !renames.is_empty() || !scan_roots.is_empty();
+
+            // Move tracked rows to their new path, keeping id, tags and notes
+            for (from, to) in renames {
+                processed_this_session.remove(&from);
+
+                // A renamed folder takes the files tracked inside it along
+                if to.is_dir() {
+                    processed_this_session.remove_under(&from);
+                    let Some(store) = &store else {
+                        continue;
+                    };
+                    match store.move_dir(&from, &to) {
+                        Ok(ids) => {
+                            debug!("Detected folder rename: {} -> {} ({} files)", from.display(), to.display(), ids.len());
+                            for event in ids.into_iter().filter_map(|id| store.get_event(id).ok().flatten()) {
+                                processed_this_session.insert(event.path.clone());
+                                if let Err(e) = tx.send(WatcherMessage::MovedFile(event)) {
+                                    error!("Failed to send watcher message: {}", e);
+                                }
+                            }
+                        }
+                        Err(e) => error!("Failed to move events in database: {}", e),
+                    }
+                    continue;
+                }
+
+                if !to.is_file() {
+                    continue;
+                }
+
+                // Moved somewhere we don't track: treat as a removal
+                if Self::should_ignore(&to, &settings.rules_for(&to).ignore_matcher)
+                    || settings.exceeds_max_depth(&to)
+                    || (settings.respect_gitignore && gitignores.is_ignored(&to))
+                {
+                    to_process.push(SettledPath {
+                        path: from,
+                        kind: EventKind::Remove(notify::event::RemoveKind::File),
+                        backfill: false,
+                    });
+                    continue;
+                }
+
+                let mut file_event = FileEvent::from_path(to.clone());
+                settings.classifier.classify(&mut file_event);
+
+                let moved = store.as_ref().and_then(|store| {
+                    match store.move_event(&from, &file_event) {
+                        Ok(Some(id)) => store.get_event(id).ok().flatten(),
+                        Ok(None) => None,
+                        Err(e) => {
+                            error!("Failed to move event in database: {}", e);
+                            None
+                        }
+                    }
+                });
+
+                match moved {
+                    Some(event) => {
+                        debug!("Detected rename: {} -> {}", from.display(), to.display());
+                        processed_this_session.insert(to);
+                        if let Err(e) = tx.send(WatcherMessage::MovedFile(event)) {
+                            error!("Failed to send watcher message: {}", e);
+                        }
+                    }
+                    // Source wasn't tracked: handle the destination like a plain move-in
+                    None => to_process.push(SettledPath {
+                        path: to,
+                        kind: EventKind::Modify(ModifyKind::Name(RenameMode::To)),
+                        backfill: false,
+                    }),
+                }
+            }
+
+            // Existing files under newly watched roots go through the same filters
+            for root in scan_roots {
+                let max_depth = settings.rules_for(&root).max_depth;
+                let files = scan_dir(&root, max_depth, settings.follow_symlinks);
+                debug!("Scanning {} existing files in {}", files.len(), root.display());
+                WatcherCounters::add(&counters.received, files.len() as u64);
+                to_process.extend(files.into_iter().map(|path| SettledPath {
+                    path,
+                    kind: EventKind::Create(notify::event::CreateKind::File),
+                    backfill: true,
+                }));
+            }
+
+            // Note new directories up front so their files can be grouped by
+            // folder whichever order they settle in
+            if settings.extraction_threshold > 0 {
+                let window = debounce_duration + EXTRACTION_WINDOW;
+                new_dirs.retain(|_, dir| now.duration_since(dir.last_seen) < window);
+
+                let mut created: Vec<&PathBuf> = to_process
+                    .iter()
+                    .filter(|s| !s.backfill && matches!(s.kind, EventKind::Create(_)) && s.path.is_dir())
+                    .map(|s| &s.path)
+                    .collect();
+                created.sort_by_key(|dir| dir.components().count());
+                for dir in created {
+                    if !new_dirs.keys().any(|root| dir.starts_with(root)) {
+                        new_dirs.insert(dir.clone(), NewDir {
+                            last_seen: now,
+                            files: 0,
+                            size: 0,
+                            summary: None,
+                        });
+                    }
+                }
+            }
+
+            // Files found in newly created directories, queued after this batch
+            let mut new_dir_files = Vec::new();
+
+            // Files whose size was still changing, checked again later
+            let mut still_growing = Vec::new();
+
+            // New files grouped by the new directory they landed in
+            let mut extracted: HashMap<PathBuf, Vec<(FileEvent, EventKind)>> = HashMap::new();
+
+            // Existing files that passed the filters, recorded in one batch
+            let mut backfilled = Vec::new();
+
             // Process settled events (THIS is where I/O happens)
-            for (path, kind) in to_process {
+            for SettledPath { mut path, kind, backfill } in to_process {
+                // Files reached through a symlinked directory are recorded under
+                // their real path, so each file gets one row however it's reached
+                if settings.follow_symlinks && !matches!(kind, EventKind::Remove(_)) {
+                    if let Ok(real) = path.canonicalize() {
+                        path = real;
+                    }
+                }
+
+                // The database, log and state files change all the time
+                if settings.own_files.lock().is_ok_and(|files| files.contains(&path)) {
+                    trace!("Ignoring path (written by Ferret): {}", path.display());
+                    continue;
+                }
+
+                // An edited .gitignore must be re-read next time it's needed
+                if path.file_name().is_some_and(|name| name == ".gitignore") {
+                    gitignores.invalidate(&path);
+                }
+
+                // Removals: mark the row as deleted and let the path be re-detected later
+                if matches!(kind, EventKind::Remove(_)) {
+                    if path.exists() {
+                        // Re-created before the debounce window settled
+                        continue;
+                    }
+                    processed_this_session.remove(&path);
+                    unsettled.pop(&path);
+
+                    let marked = match store {
+                        Some(ref store) => match store.mark_deleted(&path) {
+                            Ok(marked) => marked,
+                            Err(e) => {
+                                error!("Failed to mark event as deleted: {}", e);
+                                false
+                            }
+                        },
+                        None => true,
+                    };
+
+                    if marked {
+                        debug!("Detected deleted file: {}", path.display());
+                        if let Err(e) = tx.send(WatcherMessage::DeletedFile(path)) {
+                            error!("Failed to send watcher message: {}", e);
+                        }
+                    }
+                    continue;
+                }
+
                 // Skip if already processed this session
                 if processed_this_session.contains(&path) {
+                    WatcherCounters::add(&counters.deduped, 1);
                     continue;
                 }
 
@@ -229,116 +1002,446 @@ impl FileWatcher {
                 }
 
                 if path.is_dir() {
+                    if matches!(kind, EventKind::Create(_)) {
+                        new_dir_files.extend(Self::watch_new_dir(&settings, &path, &mut gitignores));
+                    }
+                    continue;
+                }
+
+                // Check depth below the watch root
+                if settings.exceeds_max_depth(&path) {
+                    trace!("Ignoring path (below max depth): {}", path.display());
+                    WatcherCounters::add(&counters.ignored_by_pattern, 1);
                     continue;
                 }
 
-                // Check ignore patterns
-                if Self::should_ignore(&path, &ignore_matcher) {
+                // Check ignore patterns (those of the path's `[[watch]]` entry, if any)
+                let rules = settings.rules_for(&path);
+                if Self::should_ignore(&path, &rules.ignore_matcher) {
                     trace!("Ignoring path (matches ignore pattern): {}", path.display());
+                    WatcherCounters::add(&counters.ignored_by_pattern, 1);
+                    continue;
+                }
+
+                // Check .gitignore files
+                if settings.respect_gitignore && gitignores.is_ignored(&path) {
+                    trace!("Ignoring path (ignored by .gitignore): {}", path.display());
+                    WatcherCounters::add(&counters.ignored_by_pattern, 1);
                     continue;
                 }
 
+                // Wait for files that are still being written to stop growing
+                if let (Some(_), false) = (settings.stability_check, backfill) {
+                    let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+                    match unsettled.get(&path).copied() {
+                        Some((last, _)) if last == size => {
+                            unsettled.pop(&path);
+                        }
+                        Some((_, checks)) if checks >= MAX_STABILITY_CHECKS => {
+                            debug!("Recording file that is still growing: {}", path.display());
+                            unsettled.pop(&path);
+                        }
+                        previous => {
+                            let checks = previous.map_or(0, |(_, checks)| checks + 1);
+                            trace!("Checking again later (size changing): {} ({} bytes)", path.display(), size);
+                            unsettled.put(path.clone(), (size, checks));
+                            still_growing.push((path, kind));
+                            continue;
+                        }
+                    }
+                }
+
                 // Check file size
                 if let Ok(metadata) = path.metadata() {
-                    if metadata.len() < min_size {
+                    if metadata.len() == 0 && settings.ignore_zero_byte {
+                        trace!("Ignoring path (empty): {}", path.display());
+                        WatcherCounters::add(&counters.ignored_by_size, 1);
+                        skipped_empty.insert(path);
+                        continue;
+                    }
+                    if metadata.len() < rules.min_size {
                         trace!("Ignoring path (too small): {} ({} bytes)", path.display(), metadata.len());
+                        WatcherCounters::add(&counters.ignored_by_size, 1);
                         continue;
                     }
                 }
 
-                // Check database for existing entry
+                // Check database for existing entry (deleted rows get revived below)
                 if let Some(ref store) = store {
-                    if let Ok(true) = store.path_exists(&path) {
+                    if let Ok(true) = store.path_is_live(&path) {
                         trace!("Ignoring path (already tracked): {}", path.display());
+                        WatcherCounters::add(&counters.deduped, 1);
                         processed_this_session.insert(path.clone());
                         continue;
                     }
                 }
 
+                // Paused: drop new files without recording them
+                if settings.paused.load(Ordering::Relaxed) {
+                    trace!("Ignoring path (watching paused): {}", path.display());
+                    WatcherCounters::add(&counters.dropped_paused, 1);
+                    continue;
+                }
+
+                // Existing files are classified together once the batch is filtered
+                if backfill {
+                    processed_this_session.insert(path.clone());
+                    backfilled.push(path);
+                    continue;
+                }
+
                 // Create file event
-                let file_event = FileEvent::from_path(path.clone());
-                
-                // INSERT INTO DATABASE HERE - not on UI thread!
-                // This is the key architectural fix: DB writes happen on the 
-                // processing thread, not the UI thread.
-                if let Some(ref store) = store {
-                    if let Err(e) = store.insert_event(&file_event) {
-                        error!("Failed to insert event into database: {}", e);
-                        // Continue anyway - we'll still notify the UI
-                    }
+                let file_event = settings.classifier.build(path.clone(), false);
+
+                // Files in a new directory wait until the whole batch is seen
+                if let Some(root) = new_dirs.keys().find(|root| path.starts_with(root)) {
+                    extracted.entry(root.clone()).or_default().push((file_event, kind));
+                    processed_this_session.insert(path);
+                    continue;
                 }
 
-                // Determine message type
-                let message = match kind {
-                    EventKind::Create(_) => WatcherMessage::NewFile(file_event),
-                    EventKind::Modify(notify::event::ModifyKind::Name(_)) => {
-                        WatcherMessage::MovedFile(file_event)
-                    }
-                    _ => WatcherMessage::NewFile(file_event),
+                Self::record(&settings, file_event, kind);
+                processed_this_session.insert(path);
+            }
+
+            if !backfilled.is_empty() {
+                let events = settings.classifier.build_backfill(backfilled);
+                Self::record_backfill(&settings, &events);
+            }
+
+            // Summarize directories that received more than extraction_threshold files
+            for (root, files) in extracted {
+                let Some(new_dir) = new_dirs.get_mut(&root) else {
+                    continue;
                 };
+                new_dir.last_seen = now;
+                new_dir.files += files.len();
+                new_dir.size += files.iter().filter_map(|(event, _)| event.size_bytes).sum::<u64>();
+
+                match new_dir.summary {
+                    // More files from an extraction already summarized: update its size
+                    Some(ref mut summary) => {
+                        summary.size_bytes = Some(new_dir.size);
+                        if let Some(ref store) = store {
+                            if let Err(e) = store.insert_event(summary) {
+                                error!("Failed to update extraction in database: {}", e);
+                            }
+                        }
+                    }
+                    None if new_dir.files > settings.extraction_threshold => {
+                        let mut summary = FileEvent::from_path(root.clone());
+                        summary.file_type = FileType::Archive;
+                        summary.size_bytes = Some(new_dir.size);
+                        debug!("Detected extraction of {} files: {}", new_dir.files, root.display());
+                        Self::record(
+                            &settings,
+                            summary.clone(),
+                            EventKind::Create(notify::event::CreateKind::Folder),
+                        );
+                        processed_this_session.insert(root);
+                        new_dir.summary = Some(summary);
+                    }
+                    None => {}
+                }
 
-                debug!("Detected new file: {}", path.display());
-                processed_this_session.insert(path);
-                
-                if let Err(e) = tx.send(message) {
-                    error!("Failed to send watcher message: {}", e);
+                if new_dir.summary.is_some() && settings.collapse_extractions {
+                    continue;
+                }
+                for (file_event, kind) in files {
+                    Self::record(&settings, file_event, kind);
                 }
             }
 
-            // Periodically trim the session cache if it gets too large
-            if processed_this_session.len() > 10000 {
-                processed_this_session.clear();
+            // Back in the queue, timed to settle one stability interval from now
+            if let Some(interval) = settings.stability_check {
+                let due = Instant::now() + interval.saturating_sub(debounce_duration);
+                for (path, kind) in still_growing {
+                    pending.entry(path).or_insert((due, kind));
+                }
             }
-        }
-    }
 
-    /// Start watching the configured paths
-    pub fn watch_paths(&mut self, paths: &[PathBuf]) -> Result<()> {
-        for path in paths {
-            self.watch_path(path)?;
+            // Debounce them like any other new file; paths notify also
+            // reported are deduplicated by the session cache and the store
+            for path in new_dir_files {
+                pending
+                    .entry(path)
+                    .or_insert((Instant::now(), EventKind::Create(notify::event::CreateKind::File)));
+            }
+
+            // Warn about quotas that the files recorded or removed pushed over
+            if let Some(ref store) = store {
+                if quotas_stale && last_quota_check.is_none_or(|t| t.elapsed() >= QUOTA_CHECK_INTERVAL) {
+                    quotas_stale = false;
+                    last_quota_check = Some(Instant::now());
+                    Self::check_quotas(&settings, store, &tx);
+                }
+            }
         }
-        
-        let _ = self.tx.send(WatcherMessage::Started);
-        info!("File watcher started, monitoring {} directories", paths.len());
-        
-        Ok(())
     }
 
-    /// Add a single path to watch
-    pub fn watch_path(&mut self, path: &Path) -> Result<()> {
-        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
-        
-        if !path.exists() {
-            warn!("Path does not exist, skipping: {}", path.display());
-            return Ok(());
+    /// Watch a directory created under a watch root and return the files
+    /// already inside it
+    ///
+    /// Not every platform's recursive watch picks up new subdirectories, and
+    /// files can land in a new directory before any watch covers it.
+    fn watch_new_dir(
+        settings: &ProcessorSettings,
+        dir: &Path,
+        gitignores: &mut GitignoreCache,
+    ) -> Vec<PathBuf> {
+        if Self::should_ignore(dir, &settings.rules_for(dir).ignore_matcher)
+            || settings.contents_exceed_max_depth(dir)
+            || (settings.respect_gitignore && gitignores.is_ignored(dir))
+        {
+            return Vec::new();
         }
 
-        if !path.is_dir() {
-            warn!("Path is not a directory, skipping: {}", path.display());
-            return Ok(());
+        match settings.watcher.lock() {
+            Ok(mut watcher) => {
+                if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
+                    warn!("Failed to watch new directory {}: {}", dir.display(), e);
+                }
+            }
+            Err(e) => error!("Lock error: {}", e),
         }
 
-        // Check if already watching
-        {
-            let mut watched = self.watched_paths.lock()
-                .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-            if watched.contains(&path) {
-                debug!("Already watching: {}", path.display());
-                return Ok(());
+        debug!("Watching new directory: {}", dir.display());
+        scan_dir(dir, None, settings.follow_symlinks)
+    }
+
+    /// Hand a new file to every sink and announce it
+    ///
+    /// A file renamed in from outside the watched directories is passed on
+    /// as moved rather than new.
+    fn record(settings: &ProcessorSettings, file_event: FileEvent, kind: EventKind) {
+        debug!("Detected new file: {}", file_event.path.display());
+
+        // The store sink comes first, so DB writes happen here on the
+        // processing thread, not the UI thread
+        let moved = matches!(kind, EventKind::Modify(ModifyKind::Name(_)));
+        for sink in &settings.sinks {
+            if moved {
+                sink.on_moved(&file_event);
+            } else {
+                sink.on_event(&file_event);
             }
-            watched.insert(path.clone());
         }
 
-        self.watcher
-            .watch(&path, RecursiveMode::Recursive)
-            .with_context(|| format!("Failed to watch path: {}", path.display()))?;
+        if let Some(notifier) = &settings.notifier {
+            notifier.on_new_file(&file_event);
+        }
+    }
 
-        info!("Now watching: {}", path.display());
-        Ok(())
+    /// Hand backfilled files to every sink at once
+    ///
+    /// The store writes them in a single transaction. They aren't new, so
+    /// nothing is announced.
+    fn record_backfill(settings: &ProcessorSettings, events: &[FileEvent]) {
+        debug!("Backfilled {} existing files", events.len());
+        for sink in &settings.sinks {
+            sink.on_batch(events);
+        }
     }
 
-    /// Stop watching a path
-    pub fn unwatch_path(&mut self, path: &Path) -> Result<()> {
+    /// Check that every watch root still exists, reporting roots that went
+    /// offline and re-watching those that are back
+    ///
+    /// Returns the roots that came back online.
+    fn check_watch_roots(settings: &ProcessorSettings, tx: &Sender<WatcherMessage>) -> Vec<PathBuf> {
+        let roots: Vec<PathBuf> = match settings.watch_roots.lock() {
+            Ok(roots) => roots.iter().cloned().collect(),
+            Err(_) => return Vec::new(),
+        };
+        let Ok(mut offline) = settings.offline_roots.lock() else {
+            return Vec::new();
+        };
+        offline.retain(|root| roots.contains(root));
+
+        let mut restored = Vec::new();
+        for root in roots {
+            let healthy = root.is_dir();
+            if !healthy && offline.insert(root.clone()) {
+                let message = format!("Watch path went offline: {}", root.display());
+                warn!("{}", message);
+                // The OS drops its watches with the directory; forget ours so
+                // the path can be watched again
+                if let Ok(mut watcher) = settings.watcher.lock() {
+                    let _ = watcher.unwatch(&root);
+                }
+                if let Err(e) = tx.send(WatcherMessage::Error(message)) {
+                    error!("Failed to send watcher message: {}", e);
+                }
+            } else if healthy && offline.contains(&root) {
+                let result = match settings.watcher.lock() {
+                    Ok(mut watcher) => watcher.watch(&root, RecursiveMode::Recursive),
+                    Err(_) => continue,
+                };
+                if let Err(e) = result {
+                    // Stays offline; tried again on the next check
+                    debug!("Failed to re-watch {}: {}", root.display(), e);
+                    continue;
+                }
+                offline.remove(&root);
+                debug!("Watch path is back online: {}", root.display());
+                if let Err(e) = tx.send(WatcherMessage::PathRestored(root.clone())) {
+                    error!("Failed to send watcher message: {}", e);
+                }
+                restored.push(root);
+            }
+        }
+        restored
+    }
+
+    /// Compare each quota with the current totals, warning about those
+    /// that just went over
+    ///
+    /// A quota is reported again only after it has dropped back below its
+    /// limit and gone over once more.
+    fn check_quotas(settings: &ProcessorSettings, store: &Store, tx: &Sender<WatcherMessage>) {
+        let quotas = match settings.quotas.lock() {
+            Ok(quotas) => quotas.clone(),
+            Err(_) => return,
+        };
+        let Ok(mut exceeded) = settings.exceeded_quotas.lock() else {
+            return;
+        };
+        let labels: HashSet<String> = quotas.iter().map(Quota::label).collect();
+        exceeded.retain(|label| labels.contains(label));
+
+        for quota in &quotas {
+            let usage = match quota.usage(store) {
+                Ok(usage) => usage,
+                Err(e) => {
+                    error!("Failed to check quota for {}: {}", quota.label(), e);
+                    continue;
+                }
+            };
+            if !usage.exceeded() {
+                exceeded.remove(&usage.label);
+            } else if exceeded.insert(usage.label.clone()) {
+                let message = format!("Quota exceeded: {}", usage.describe());
+                warn!("{}", message);
+                if let Err(e) = tx.send(WatcherMessage::Warning(message)) {
+                    error!("Failed to send watcher message: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Start watching the configured paths
+    ///
+    /// Paths that don't exist or hit the OS watch limit are skipped, and the
+    /// rest are still watched.
+    pub fn watch_paths(&mut self, paths: &[PathBuf]) -> Result<WatchSummary> {
+        let mut summary = WatchSummary::default();
+        for path in paths {
+            if self.watch_path(path)? {
+                summary.watched += 1;
+            } else {
+                summary.failed += 1;
+            }
+        }
+        
+        let _ = self.tx.send(WatcherMessage::Started);
+        info!(
+            "File watcher started, monitoring {} of {} directories",
+            summary.watched,
+            paths.len()
+        );
+        
+        Ok(summary)
+    }
+
+    /// Add a single path to watch
+    ///
+    /// Returns `false` if the path was skipped: it doesn't exist, isn't a
+    /// directory, or the OS watch limit was reached (reported as a
+    /// [`WatcherMessage::Error`]).
+    pub fn watch_path(&mut self, path: &Path) -> Result<bool> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        
+        if !path.exists() {
+            warn!("Path does not exist, skipping: {}", path.display());
+            return Ok(false);
+        }
+
+        if !path.is_dir() {
+            warn!("Path is not a directory, skipping: {}", path.display());
+            return Ok(false);
+        }
+
+        // Check if already watching
+        {
+            let mut watched = self.watched_paths.lock()
+                .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            if watched.contains(&path) {
+                debug!("Already watching: {}", path.display());
+                return Ok(true);
+            }
+            watched.insert(path.clone());
+        }
+
+        let result = self.notify_watcher()?.watch(&path, RecursiveMode::Recursive);
+        if let Err(e) = result {
+            if let Ok(mut watched) = self.watched_paths.lock() {
+                watched.remove(&path);
+            }
+            // Drop any subdirectory watches added before the failure
+            let _ = self.notify_watcher()?.unwatch(&path);
+
+            if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) {
+                let message = watch_limit_message(&path);
+                warn!("{}", message);
+                let _ = self.tx.send(WatcherMessage::Error(message));
+                return Ok(false);
+            }
+            return Err(e).with_context(|| format!("Failed to watch path: {}", path.display()));
+        }
+
+        if self.follow_symlinks {
+            self.watch_link_targets(&path);
+        }
+
+        // Backfill on the processing thread so startup isn't blocked
+        if self.scan_existing {
+            let _ = self.raw_event_tx.send(RawEvent::Scan(path.clone()));
+        }
+
+        info!("Now watching: {}", path.display());
+        Ok(true)
+    }
+
+    /// Watch directories that symlinks under `root` point to, if they lie
+    /// outside every watch root
+    fn watch_link_targets(&mut self, root: &Path) {
+        let roots = self.watched_paths();
+        let already_watched: HashSet<PathBuf> =
+            self.link_targets.values().flatten().cloned().collect();
+
+        let mut added = Vec::new();
+        for target in symlinked_dirs(root) {
+            if roots.iter().any(|r| target.starts_with(r)) || already_watched.contains(&target) {
+                continue;
+            }
+            let Ok(mut watcher) = self.watcher.lock() else {
+                return;
+            };
+            match watcher.watch(&target, RecursiveMode::Recursive) {
+                Ok(()) => {
+                    info!("Following symlink to: {}", target.display());
+                    added.push(target);
+                }
+                Err(e) => warn!("Failed to watch symlink target {}: {}", target.display(), e),
+            }
+        }
+
+        if !added.is_empty() {
+            self.link_targets.insert(root.to_path_buf(), added);
+        }
+    }
+
+    /// Stop watching a path
+    pub fn unwatch_path(&mut self, path: &Path) -> Result<()> {
         let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
         
         {
@@ -346,8 +1449,17 @@ impl FileWatcher {
                 .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
             watched.remove(&path);
         }
+        if let Ok(mut offline) = self.offline_roots.lock() {
+            offline.remove(&path);
+        }
 
-        self.watcher
+        let link_targets = self.link_targets.remove(&path).unwrap_or_default();
+        let mut watcher = self.notify_watcher()?;
+        for target in link_targets {
+            let _ = watcher.unwatch(&target);
+        }
+
+        watcher
             .unwatch(&path)
             .with_context(|| format!("Failed to unwatch path: {}", path.display()))?;
 
@@ -355,6 +1467,127 @@ impl FileWatcher {
         Ok(())
     }
 
+    /// Apply a changed config without restarting
+    ///
+    /// Watch paths no longer configured are unwatched and new ones watched,
+    /// and the processing thread switches to the new filters and quotas.
+    /// Returns the paths watched afterwards.
+    pub fn reload(&mut self, config: &Config) -> Result<Vec<PathBuf>> {
+        self.set_watch_rules(config)?;
+        self.set_quotas(config)?;
+        *self.own_files.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))? = Self::own_files(config);
+
+        let wanted: HashSet<PathBuf> = config
+            .expanded_watch_paths()
+            .into_iter()
+            .map(|p| p.canonicalize().unwrap_or(p))
+            .collect();
+
+        for path in self.watched_paths() {
+            if !wanted.contains(&path) {
+                self.unwatch_path(&path)?;
+            }
+        }
+        for path in &wanted {
+            // Already-watched paths are skipped by watch_path
+            self.watch_path(path)?;
+        }
+
+        Ok(self.watched_paths())
+    }
+
+    /// Replace the top-level and per-path filters with those of a reloaded config
+    pub fn set_watch_rules(&self, config: &Config) -> Result<()> {
+        let default_rules = Arc::new(config.default_watch_rules()?);
+        let rules = Self::shared_watch_rules(config)?;
+        *self.default_rules.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))? = default_rules;
+        *self.watch_rules.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))? = rules;
+        Ok(())
+    }
+
+    /// Replace the `[[quota]]` limits, e.g. after the config was reloaded
+    pub fn set_quotas(&self, config: &Config) -> Result<()> {
+        let quotas = config.build_quotas()?;
+        *self.quotas.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))? = quotas;
+        Ok(())
+    }
+
+    /// Files Ferret itself writes: the database with its `-wal`, `-shm` and
+    /// `-journal` files, the log file and the TUI state files
+    ///
+    /// They are skipped independently of `ignore_patterns`, so a database
+    /// kept inside a watched directory doesn't record its own writes. Paths
+    /// are resolved like the ones notify reports.
+    fn own_files(config: &Config) -> HashSet<PathBuf> {
+        let database = config.database_path();
+        let mut files: Vec<PathBuf> = ["", "-wal", "-shm", "-journal"]
+            .iter()
+            .map(|suffix| {
+                let mut name = database.clone().into_os_string();
+                name.push(suffix);
+                PathBuf::from(name)
+            })
+            .collect();
+        files.extend(config.log_file_path());
+        files.push(config.tree_state_path());
+        files.push(config.filter_state_path());
+
+        files
+            .into_iter()
+            .map(|path| {
+                let path = Config::expand_path(&path);
+                // The files may not exist yet, but their directory usually does
+                let real_dir = path.parent().and_then(|dir| dir.canonicalize().ok());
+                match (real_dir, path.file_name()) {
+                    (Some(dir), Some(name)) => dir.join(name),
+                    _ => path,
+                }
+            })
+            .collect()
+    }
+
+    /// Compile `[[watch]]` filters into the form shared with the processing thread
+    fn shared_watch_rules(config: &Config) -> Result<HashMap<PathBuf, Arc<WatchRules>>> {
+        Ok(config
+            .build_watch_rules()?
+            .into_iter()
+            .map(|(path, rules)| (path, Arc::new(rules)))
+            .collect())
+    }
+
+    /// Lock the notify watcher (the processing thread also adds watches)
+    fn notify_watcher(&self) -> Result<std::sync::MutexGuard<'_, RecommendedWatcher>> {
+        self.watcher.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))
+    }
+
+    /// Flag that pauses recording of new files while set
+    ///
+    /// Renames and deletions of already tracked files are still applied.
+    pub fn pause_handle(&self) -> Arc<AtomicBool> {
+        self.paused.clone()
+    }
+
+    /// Watch paths that currently don't exist, kept up to date by the
+    /// health check (see `health_check_ms`)
+    pub fn offline_handle(&self) -> Arc<Mutex<HashSet<PathBuf>>> {
+        self.offline_roots.clone()
+    }
+
+    /// Labels of the `[[quota]]` entries currently exceeded
+    pub fn quota_handle(&self) -> Arc<Mutex<HashSet<String>>> {
+        self.exceeded_quotas.clone()
+    }
+
+    /// What the processing thread has done with the paths it was given so far
+    pub fn stats(&self) -> WatcherStats {
+        self.counters.snapshot()
+    }
+
+    /// The processing counters, for showing live stats elsewhere
+    pub fn stats_handle(&self) -> Arc<WatcherCounters> {
+        self.counters.clone()
+    }
+
     /// Stop all watching and shut down processing thread
     pub fn stop(&mut self) -> Result<()> {
         // Signal shutdown to processing thread
@@ -372,8 +1605,13 @@ impl FileWatcher {
             watched.iter().cloned().collect()
         };
 
-        for path in paths {
-            let _ = self.watcher.unwatch(&path);
+        let link_targets: Vec<PathBuf> =
+            self.link_targets.drain().flat_map(|(_, targets)| targets).collect();
+        {
+            let mut watcher = self.notify_watcher()?;
+            for path in paths.into_iter().chain(link_targets) {
+                let _ = watcher.unwatch(&path);
+            }
         }
 
         {
@@ -415,102 +1653,226 @@ impl FileWatcher {
     }
 }
 
-impl Drop for FileWatcher {
-    fn drop(&mut self) {
-        // Ensure clean shutdown
-        self.shutdown.store(true, Ordering::Relaxed);
-        let _ = self.raw_event_tx.send(RawEvent::Shutdown);
-        if let Some(handle) = self.processor_handle.take() {
-            let _ = handle.join();
-        }
+/// Actionable explanation for hitting the OS file watch limit
+fn watch_limit_message(path: &Path) -> String {
+    if cfg!(target_os = "linux") {
+        format!(
+            "Not watching {}: inotify watch limit reached. Raise fs.inotify.max_user_watches \
+             (e.g. `sudo sysctl fs.inotify.max_user_watches=524288`)",
+            path.display()
+        )
+    } else {
+        format!(
+            "Not watching {}: the OS file watch limit was reached",
+            path.display()
+        )
     }
 }
 
-/// Builder for FileWatcher with a fluent API
-pub struct FileWatcherBuilder {
-    watch_paths: Vec<PathBuf>,
-    ignore_patterns: Vec<String>,
-    min_size: u64,
-    debounce_ms: u64,
-    store: Option<Store>,
+/// Depth of `path` below the closest watch root containing it
+///
+/// A file directly inside a root has depth 1. Returns `None` if no root
+/// contains the path.
+fn depth_below_root<'a>(path: &Path, roots: impl Iterator<Item = &'a PathBuf>) -> Option<usize> {
+    roots
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .map(|rel| rel.components().count())
+        .min()
 }
 
-impl FileWatcherBuilder {
-    /// Create a new builder
-    pub fn new() -> Self {
-        Self {
-            watch_paths: Vec::new(),
-            ignore_patterns: Vec::new(),
-            min_size: 0,
-            debounce_ms: 500,
-            store: None,
+/// Real paths of the directories that symlinks under `root` point to
+///
+/// Targets are searched for further symlinks too. Each directory is visited
+/// once, so symlink cycles terminate.
+fn symlinked_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut targets = Vec::new();
+    let mut visited: HashSet<PathBuf> = root.canonicalize().into_iter().collect();
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                stack.push(entry.path());
+            } else if file_type.is_symlink() {
+                let Ok(target) = entry.path().canonicalize() else { continue };
+                if target.is_dir() && visited.insert(target.clone()) {
+                    stack.push(target.clone());
+                    targets.push(target);
+                }
+            }
         }
     }
 
-    /// Add a path to watch
-    pub fn watch<P: AsRef<Path>>(mut self, path: P) -> Self {
-        self.watch_paths.push(path.as_ref().to_path_buf());
-        self
-    }
+    targets
+}
 
-    /// Add multiple paths to watch
-    pub fn watch_all<P: AsRef<Path>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
-        for path in paths {
-            self.watch_paths.push(path.as_ref().to_path_buf());
+/// List the files under `root`, descending at most `max_depth` levels
+///
+/// Symlinks are only followed if `follow_symlinks` is set; each real
+/// directory is then scanned once, so symlink cycles terminate.
+/// Unreadable directories are skipped.
+fn scan_dir(root: &Path, max_depth: Option<usize>, follow_symlinks: bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![(root.to_path_buf(), 1)];
+    let mut visited: HashSet<PathBuf> = root.canonicalize().into_iter().collect();
+
+    while let Some((dir, depth)) = stack.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("Failed to scan {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let Ok(mut file_type) = entry.file_type() else { continue };
+            if file_type.is_symlink() && follow_symlinks {
+                let Ok(metadata) = std::fs::metadata(entry.path()) else { continue };
+                file_type = metadata.file_type();
+            }
+
+            if file_type.is_dir() {
+                let real = entry.path().canonicalize().unwrap_or_else(|_| entry.path());
+                if max_depth.is_none_or(|max| depth < max) && visited.insert(real) {
+                    stack.push((entry.path(), depth + 1));
+                }
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
         }
-        self
     }
 
-    /// Add an ignore pattern
-    pub fn ignore(mut self, pattern: &str) -> Self {
-        self.ignore_patterns.push(pattern.to_string());
-        self
-    }
+    files
+}
 
-    /// Add multiple ignore patterns
-    pub fn ignore_all(mut self, patterns: impl IntoIterator<Item = String>) -> Self {
-        self.ignore_patterns.extend(patterns);
-        self
-    }
+/// Classify a file by reading its first few KB
+fn sniff_file_type(path: &Path) -> Option<FileType> {
+    let mut header = Vec::with_capacity(SNIFF_BYTES as usize);
+    std::fs::File::open(path)
+        .and_then(|f| f.take(SNIFF_BYTES).read_to_end(&mut header))
+        .ok()?;
+    FileType::from_content(&header)
+}
+
+/// Compute the SHA-256 of a file's contents as lowercase hex
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
 
-    /// Set minimum file size
-    pub fn min_size(mut self, size: u64) -> Self {
-        self.min_size = size;
-        self
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
     }
 
-    /// Set debounce delay
-    pub fn debounce_ms(mut self, ms: u64) -> Self {
-        self.debounce_ms = ms;
-        self
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// What [`Ingester::ingest`] did with a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestOutcome {
+    /// Recorded as a new event
+    Recorded,
+    /// Already in the ledger
+    AlreadyTracked,
+    /// Skipped by an ignore pattern, a `.gitignore`, the minimum size, for
+    /// being empty or for being a file Ferret writes itself
+    Ignored,
+    /// Doesn't exist or isn't a regular file
+    NotFound,
+}
+
+/// Records paths handed over by other tools (`ferret ingest`) with the same
+/// filters and classification as watched files, without watching anything
+pub struct Ingester {
+    /// Ignore patterns and minimum size for paths without a `[[watch]]` entry
+    default_rules: WatchRules,
+    /// Filters of each `[[watch]]` entry, keyed by canonical path
+    watch_rules: HashMap<PathBuf, WatchRules>,
+    /// Whether to skip paths ignored by `.gitignore` files
+    respect_gitignore: bool,
+    /// Whether empty files are skipped regardless of the minimum size
+    ignore_zero_byte: bool,
+    /// Files Ferret writes itself (see [`FileWatcher::own_files`])
+    own_files: HashSet<PathBuf>,
+    gitignores: GitignoreCache,
+    classifier: Classifier,
+}
+
+impl Ingester {
+    pub fn new(config: &Config) -> Result<Self> {
+        Ok(Self {
+            default_rules: config.default_watch_rules()?,
+            watch_rules: config.build_watch_rules()?,
+            respect_gitignore: config.respect_gitignore,
+            ignore_zero_byte: config.ignore_zero_byte,
+            own_files: FileWatcher::own_files(config),
+            gitignores: GitignoreCache::default(),
+            classifier: Classifier::from_config(config)?,
+        })
     }
 
-    /// Set store for path checking
-    pub fn with_store(mut self, store: Store) -> Self {
-        self.store = Some(store);
-        self
+    /// Filters for `path`: those of the deepest `[[watch]]` entry holding
+    /// it, or the top-level ones
+    fn rules_for(&self, path: &Path) -> &WatchRules {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.watch_rules
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .map_or(&self.default_rules, |(_, rules)| rules)
     }
 
-    /// Build the FileWatcher
-    pub fn build(self) -> Result<(FileWatcher, Receiver<WatcherMessage>)> {
-        let config = Config {
-            watch_paths: self.watch_paths.clone(),
-            ignore_patterns: self.ignore_patterns,
-            min_size_bytes: self.min_size,
-            debounce_ms: self.debounce_ms,
-            ..Config::default()
-        };
+    /// Check `path` against the filters and record it in `store`
+    ///
+    /// Relative paths are taken relative to the current directory.
+    pub fn ingest(&mut self, store: &Store, path: &Path) -> Result<IngestOutcome> {
+        let path = std::path::absolute(path)
+            .with_context(|| format!("Invalid path: {}", path.display()))?;
+        if !path.is_file() {
+            return Ok(IngestOutcome::NotFound);
+        }
 
-        let (mut watcher, rx) = FileWatcher::new(&config, self.store)?;
-        watcher.watch_paths(&self.watch_paths)?;
-        
-        Ok((watcher, rx))
+        let rules = self.rules_for(&path);
+        let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+        let real = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if self.own_files.contains(&real)
+            || FileWatcher::should_ignore(&path, &rules.ignore_matcher)
+            || size < rules.min_size
+            || (size == 0 && self.ignore_zero_byte)
+            || (self.respect_gitignore && self.gitignores.is_ignored(&path))
+        {
+            return Ok(IngestOutcome::Ignored);
+        }
+
+        if store.path_is_live(&path)? {
+            return Ok(IngestOutcome::AlreadyTracked);
+        }
+
+        let mut file_event = FileEvent::from_path(path);
+        self.classifier.classify(&mut file_event);
+        self.classifier.set_timestamp(&mut file_event, false);
+        self.classifier.hash(&mut file_event);
+        store.insert_event(&file_event)?;
+        Ok(IngestOutcome::Recorded)
     }
 }
 
-impl Default for FileWatcherBuilder {
-    fn default() -> Self {
-        Self::new()
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        // Ensure clean shutdown
+        self.shutdown.store(true, Ordering::Relaxed);
+        let _ = self.raw_event_tx.send(RawEvent::Shutdown);
+        if let Some(handle) = self.processor_handle.take() {
+            let _ = handle.join();
+        }
     }
 }
 
@@ -522,6 +1884,69 @@ mod tests {
     use std::io::Write;
     use std::time::Duration;
 
+    /// Sets up a watcher on a few paths with the other settings left at
+    /// their defaults
+    struct FileWatcherBuilder {
+        watch_paths: Vec<PathBuf>,
+        ignore_patterns: Vec<String>,
+        min_size: u64,
+        scan_existing: bool,
+        store: Option<Store>,
+    }
+
+    impl FileWatcherBuilder {
+        fn new() -> Self {
+            Self {
+                watch_paths: Vec::new(),
+                ignore_patterns: Vec::new(),
+                min_size: 0,
+                scan_existing: false,
+                store: None,
+            }
+        }
+
+        fn watch<P: AsRef<Path>>(mut self, path: P) -> Self {
+            self.watch_paths.push(path.as_ref().to_path_buf());
+            self
+        }
+
+        fn ignore(mut self, pattern: &str) -> Self {
+            self.ignore_patterns.push(pattern.to_string());
+            self
+        }
+
+        fn min_size(mut self, size: u64) -> Self {
+            self.min_size = size;
+            self
+        }
+
+        fn scan_existing(mut self, enabled: bool) -> Self {
+            self.scan_existing = enabled;
+            self
+        }
+
+        /// Record files in `store`, through the watcher's store sink
+        fn with_store(mut self, store: Store) -> Self {
+            self.store = Some(store);
+            self
+        }
+
+        fn build(self) -> Result<(FileWatcher, Receiver<WatcherMessage>)> {
+            let config = Config {
+                watch_paths: self.watch_paths.clone(),
+                ignore_patterns: self.ignore_patterns,
+                min_size_bytes: self.min_size,
+                debounce_ms: 300,
+                scan_existing: self.scan_existing,
+                ..Config::default()
+            };
+
+            let (mut watcher, rx) = FileWatcher::new(&config, self.store)?;
+            watcher.watch_paths(&self.watch_paths)?;
+            Ok((watcher, rx))
+        }
+    }
+
     #[test]
     fn test_watcher_creation() {
         let config = Config::default();
@@ -546,6 +1971,849 @@ mod tests {
             Path::new("/project/file.tmp"),
             &matcher
         ));
+        for junk in ["/photos/.DS_Store", "/photos/Thumbs.db", "/photos/desktop.ini"] {
+            assert!(FileWatcher::should_ignore(Path::new(junk), &matcher), "{}", junk);
+        }
+        assert!(!FileWatcher::should_ignore(
+            Path::new("/photos/holiday.jpg"),
+            &matcher
+        ));
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("hello.txt");
+        std::fs::write(&file_path, "hello").unwrap();
+
+        assert_eq!(
+            hash_file(&file_path).unwrap(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    fn test_gitignore_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let outer = temp_dir.path();
+        let repo = outer.join("repo");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(repo.join("sub")).unwrap();
+
+        // Above the repository root, so never consulted
+        std::fs::write(outer.join(".gitignore"), "*.txt\n").unwrap();
+        std::fs::write(repo.join(".gitignore"), "target/\n*.log\n").unwrap();
+        std::fs::write(repo.join("sub/.gitignore"), "!keep.log\n").unwrap();
+
+        let mut cache = GitignoreCache::default();
+        assert!(cache.is_ignored(&repo.join("target/debug/app")));
+        assert!(cache.is_ignored(&repo.join("build.log")));
+        assert!(cache.is_ignored(&repo.join("sub/other.log")));
+        assert!(!cache.is_ignored(&repo.join("sub/keep.log")));
+        assert!(!cache.is_ignored(&repo.join("notes.txt")));
+        assert!(!cache.is_ignored(&repo.join("main.rs")));
+
+        // Edits are picked up once the cached matcher is invalidated
+        std::fs::write(repo.join(".gitignore"), "*.rs\n").unwrap();
+        assert!(!cache.is_ignored(&repo.join("main.rs")));
+        cache.invalidate(&repo.join(".gitignore"));
+        assert!(cache.is_ignored(&repo.join("main.rs")));
+    }
+
+    #[test]
+    fn test_recent_paths_forget_oldest() {
+        let path = |i: usize| PathBuf::from(format!("/tmp/file{}.txt", i));
+        let mut recent = RecentPaths::new(3);
+        for i in 0..3 {
+            recent.insert(path(i));
+        }
+
+        // Seeing file0 again keeps it over file1 when the cache overflows
+        assert!(recent.contains(&path(0)));
+        recent.insert(path(3));
+        assert!(!recent.contains(&path(1)));
+        assert!(recent.contains(&path(0)));
+        assert!(recent.contains(&path(2)));
+
+        // A file deleted and re-created at the cap is remembered again, and
+        // the others stay remembered
+        assert!(recent.remove(&path(2)));
+        assert!(!recent.contains(&path(2)));
+        recent.insert(path(2));
+        recent.insert(path(4));
+        assert!(recent.contains(&path(2)));
+        assert!(recent.contains(&path(4)));
+        assert!(!recent.contains(&path(3)));
+    }
+
+    #[test]
+    fn test_depth_below_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        let nested = root.join("extracted").join("inner");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let top = root.join("top.zip");
+        let deep = nested.join("deep.txt");
+        File::create(&top).unwrap();
+        File::create(&deep).unwrap();
+
+        let roots = [root.clone()];
+        assert_eq!(depth_below_root(&top, roots.iter()), Some(1));
+        assert_eq!(depth_below_root(&deep, roots.iter()), Some(3));
+        assert_eq!(depth_below_root(Path::new("/elsewhere/file"), roots.iter()), None);
+
+        // The closest root wins when watch paths are nested
+        let roots = [root.clone(), root.join("extracted")];
+        assert_eq!(depth_below_root(&deep, roots.iter()), Some(2));
+    }
+
+    #[test]
+    fn test_scan_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        File::create(temp_dir.path().join("top.txt")).unwrap();
+        File::create(nested.join("deep.txt")).unwrap();
+
+        assert_eq!(scan_dir(temp_dir.path(), None, false).len(), 2);
+        assert_eq!(
+            scan_dir(temp_dir.path(), Some(1), false),
+            vec![temp_dir.path().join("top.txt")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        // watched/link -> other, and other/back -> watched forms a cycle
+        let temp_dir = TempDir::new().unwrap();
+        let watched = temp_dir.path().join("watched");
+        let other = temp_dir.path().join("other");
+        std::fs::create_dir_all(&watched).unwrap();
+        std::fs::create_dir_all(&other).unwrap();
+        symlink(&other, watched.join("link")).unwrap();
+        symlink(&watched, other.join("back")).unwrap();
+        std::fs::write(other.join("existing.txt"), "linked").unwrap();
+
+        assert!(scan_dir(&watched, None, false).is_empty());
+        assert_eq!(
+            scan_dir(&watched, None, true),
+            vec![watched.join("link").join("existing.txt")]
+        );
+        assert_eq!(symlinked_dirs(&watched), vec![other.canonicalize().unwrap()]);
+
+        // TempDir paths are hidden, so drop the default ignore patterns
+        let config = Config {
+            follow_symlinks: true,
+            scan_existing: true,
+            ignore_patterns: Vec::new(),
+            ..Config::default()
+        };
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, rx) = FileWatcher::new(&config, Some(store.clone())).unwrap();
+        watcher.watch_path(&watched).unwrap();
+
+        // Backfilled under its real path
+        let existing = other.canonicalize().unwrap().join("existing.txt");
+        let event = loop {
+            match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+                WatcherMessage::NewFile(event) => break event,
+                _ => continue,
+            }
+        };
+        assert_eq!(event.path, existing);
+
+        // New files in the target are picked up too
+        std::fs::write(other.join("new.txt"), "fresh").unwrap();
+        let event = loop {
+            match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+                WatcherMessage::NewFile(event) => break event,
+                _ => continue,
+            }
+        };
+        assert_eq!(event.filename, "new.txt");
+        assert_eq!(store.count_events().unwrap(), 2);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_scan_existing_backfills_with_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("old.txt");
+        std::fs::write(&file_path, "already here").unwrap();
+        let mtime: chrono::DateTime<chrono::Utc> =
+            file_path.metadata().unwrap().modified().unwrap().into();
+
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, rx) = FileWatcherBuilder::new()
+            .watch(temp_dir.path())
+            .scan_existing(true)
+            .with_store(store.clone())
+            .build()
+            .unwrap();
+
+        let event = loop {
+            match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+                WatcherMessage::NewFile(event) => break event,
+                _ => continue,
+            }
+        };
+        assert_eq!(event.filename, "old.txt");
+        assert_eq!(event.created_at, mtime);
+        assert_eq!(store.count_events().unwrap(), 1);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_ingest_uses_timestamp_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("copied.txt");
+        std::fs::write(&file_path, "copied").unwrap();
+        let file = std::fs::File::options().write(true).open(&file_path).unwrap();
+        let mtime = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        file.set_modified(mtime).unwrap();
+
+        let config = Config {
+            ignore_patterns: Vec::new(),
+            timestamp_source: "modified".to_string(),
+            ..Config::default()
+        };
+        let store = Store::in_memory().unwrap();
+        let mut ingester = Ingester::new(&config).unwrap();
+        assert_eq!(ingester.ingest(&store, &file_path).unwrap(), IngestOutcome::Recorded);
+
+        let event = store.get_event_by_path(&file_path).unwrap().unwrap();
+        assert_eq!(event.created_at, chrono::DateTime::<chrono::Utc>::from(mtime));
+    }
+
+    #[test]
+    fn test_ingest_skips_own_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            ignore_patterns: Vec::new(),
+            database_path: Some(temp_dir.path().join("ledger.db")),
+            log_file: Some(temp_dir.path().join("ferret.log")),
+            ..Config::default()
+        };
+        let store = Store::new(&config.database_path(), config.busy_timeout()).unwrap();
+        std::fs::write(temp_dir.path().join("ferret.log"), "log line").unwrap();
+        std::fs::write(temp_dir.path().join("report.pdf"), "pdf").unwrap();
+
+        let mut ingester = Ingester::new(&config).unwrap();
+        for own in ["ledger.db", "ferret.log"] {
+            let outcome = ingester.ingest(&store, &temp_dir.path().join(own)).unwrap();
+            assert_eq!(outcome, IngestOutcome::Ignored, "{}", own);
+        }
+        let outcome = ingester.ingest(&store, &temp_dir.path().join("report.pdf")).unwrap();
+        assert_eq!(outcome, IngestOutcome::Recorded);
+        assert_eq!(store.count_events().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_watch_paths_summary() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut watcher, _rx) = FileWatcher::new(&Config::default(), None).unwrap();
+
+        let summary = watcher
+            .watch_paths(&[temp_dir.path().to_path_buf(), temp_dir.path().join("missing")])
+            .unwrap();
+        assert_eq!(summary, WatchSummary { watched: 1, failed: 1 });
+        assert_eq!(watcher.watched_paths().len(), 1);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_stats_count_skipped_files() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("keep.txt"), "big enough").unwrap();
+        std::fs::write(temp_dir.path().join("download.part"), "big enough").unwrap();
+        std::fs::write(temp_dir.path().join("tiny.txt"), "x").unwrap();
+
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, _rx) = FileWatcherBuilder::new()
+            .watch(temp_dir.path())
+            .ignore("**/*.part")
+            .min_size(5)
+            .scan_existing(true)
+            .with_store(store)
+            .build()
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let handled = |stats: WatcherStats| stats.ignored_by_pattern + stats.ignored_by_size + stats.inserted;
+        while handled(watcher.stats()) < 3 {
+            assert!(Instant::now() < deadline, "scan was not processed: {:?}", watcher.stats());
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let stats = watcher.stats();
+        assert_eq!(stats.received, 3);
+        assert_eq!(stats.ignored_by_pattern, 1);
+        assert_eq!(stats.ignored_by_size, 1);
+        assert_eq!(stats.inserted, 1);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_own_database_is_not_tracked() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let config = Config {
+            watch_paths: vec![root.clone()],
+            ignore_patterns: Vec::new(),
+            database_path: Some(root.join("ledger.db")),
+            scan_existing: true,
+            ..Config::default()
+        };
+        let store = Store::new(&config.database_path(), config.busy_timeout()).unwrap();
+        let (mut watcher, rx) = FileWatcher::new(&config, Some(store.clone())).unwrap();
+        watcher.watch_paths(&config.expanded_watch_paths()).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        // The scan finds the database and its WAL in the watched dir, and
+        // recording a file writes to them
+        std::fs::write(root.join("report.pdf"), "pdf").unwrap();
+        let mut recorded = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(3);
+        while Instant::now() < deadline {
+            if let Ok(WatcherMessage::NewFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                recorded.push(event.filename);
+            }
+        }
+        assert_eq!(recorded, ["report.pdf"]);
+        let tracked = store.query_events(&crate::models::EventFilter::new()).unwrap();
+        assert_eq!(tracked.len(), 1);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_zero_byte_files_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("keep.txt"), "content").unwrap();
+        std::fs::write(temp_dir.path().join("empty.txt"), "").unwrap();
+
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, _rx) = FileWatcherBuilder::new()
+            .watch(temp_dir.path())
+            .scan_existing(true)
+            .with_store(store)
+            .build()
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while watcher.stats().ignored_by_size + watcher.stats().inserted < 2 {
+            assert!(Instant::now() < deadline, "scan was not processed: {:?}", watcher.stats());
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let stats = watcher.stats();
+        assert_eq!(stats.ignored_by_size, 1);
+        assert_eq!(stats.inserted, 1);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_writes_delay_recording_until_final_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let config = Config {
+            watch_paths: vec![root.clone()],
+            ignore_patterns: Vec::new(),
+            debounce_ms: 300,
+            ..Config::default()
+        };
+        let (mut watcher, rx) = FileWatcher::new(&config, None).unwrap();
+        watcher.watch_paths(&config.expanded_watch_paths()).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        // Created empty, then appended to for longer than the debounce window
+        let growing = root.join("download.bin");
+        let mut file = File::create(&growing).unwrap();
+        for _ in 0..5 {
+            std::thread::sleep(Duration::from_millis(150));
+            file.write_all(&[0u8; 1000]).unwrap();
+            file.flush().unwrap();
+        }
+        drop(file);
+
+        // Left empty past the window (skipped), then written
+        let late = root.join("late.txt");
+        File::create(&late).unwrap();
+        std::thread::sleep(Duration::from_millis(800));
+        std::fs::write(&late, "written later").unwrap();
+
+        let mut sizes = HashMap::new();
+        let deadline = Instant::now() + Duration::from_secs(3);
+        while Instant::now() < deadline && sizes.len() < 2 {
+            if let Ok(WatcherMessage::NewFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                assert!(sizes.insert(event.filename, event.size_bytes).is_none(), "recorded twice");
+            }
+        }
+        assert_eq!(sizes.get("download.bin"), Some(&Some(5000)));
+        assert_eq!(sizes.get("late.txt"), Some(&Some(13)));
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_paused_watcher_skips_new_files() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("old.txt"), "already here").unwrap();
+
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, rx) = FileWatcherBuilder::new()
+            .with_store(store.clone())
+            .scan_existing(true)
+            .build()
+            .unwrap();
+        watcher.pause_handle().store(true, Ordering::Relaxed);
+        watcher.watch_path(temp_dir.path()).unwrap();
+
+        std::thread::sleep(Duration::from_millis(1000));
+        assert!(!rx.try_iter().any(|msg| matches!(msg, WatcherMessage::NewFile(_))));
+        assert_eq!(store.count_events().unwrap(), 0);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_watch_entries_use_their_own_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let downloads = root.join("Downloads");
+        let camera = root.join("Camera");
+        std::fs::create_dir(&downloads).unwrap();
+        std::fs::create_dir(&camera).unwrap();
+
+        let config = Config {
+            watch_paths: vec![downloads.clone()],
+            ignore_patterns: vec!["**/*.part".to_string()],
+            watch: vec![crate::config::WatchConfig {
+                path: camera.clone(),
+                ignore_patterns: Some(Vec::new()),
+                min_size_bytes: Some(10),
+                max_depth: None,
+            }],
+            ..Config::default()
+        };
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, rx) = FileWatcher::new(&config, Some(store.clone())).unwrap();
+        watcher.watch_paths(&config.expanded_watch_paths()).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        std::fs::write(downloads.join("movie.part"), "partial download").unwrap();
+        std::fs::write(downloads.join("tiny.txt"), "x").unwrap();
+        std::fs::write(camera.join("IMG_0001.part"), "partial image").unwrap();
+        std::fs::write(camera.join("thumb.jpg"), "x").unwrap();
+
+        let mut seen = HashSet::new();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            if let Ok(WatcherMessage::NewFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                seen.insert(event.filename);
+            }
+        }
+
+        let expected: HashSet<String> =
+            ["tiny.txt", "IMG_0001.part"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(seen, expected);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_reload_applies_watch_paths_and_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let first = root.join("first");
+        let second = root.join("second");
+        std::fs::create_dir(&first).unwrap();
+        std::fs::create_dir(&second).unwrap();
+
+        let config = Config {
+            watch_paths: vec![first.clone()],
+            ..Config::default()
+        };
+        let (mut watcher, rx) = FileWatcher::new(&config, None).unwrap();
+        watcher.watch_paths(&config.expanded_watch_paths()).unwrap();
+        assert_eq!(watcher.watched_paths(), vec![first.clone()]);
+
+        let reloaded = Config {
+            watch_paths: vec![second.clone()],
+            ignore_patterns: vec!["**/*.log".to_string()],
+            log_file: Some(second.join("activity.txt")),
+            ..Config::default()
+        };
+        let watched = watcher.reload(&reloaded).unwrap();
+        assert_eq!(watched, vec![second.clone()]);
+        assert_eq!(watcher.watched_paths(), vec![second.clone()]);
+        std::thread::sleep(Duration::from_millis(100));
+
+        std::fs::write(first.join("old.txt"), "unwatched").unwrap();
+        std::fs::write(second.join("debug.log"), "ignored").unwrap();
+        std::fs::write(second.join("activity.txt"), "the reloaded log file").unwrap();
+        std::fs::write(second.join("new.txt"), "tracked").unwrap();
+
+        let mut seen = HashSet::new();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            if let Ok(WatcherMessage::NewFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                seen.insert(event.filename);
+            }
+        }
+        assert_eq!(seen, HashSet::from(["new.txt".to_string()]));
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_files_in_new_subdirectories_are_tracked() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, rx) = FileWatcherBuilder::new()
+            .watch(&root)
+            .with_store(store.clone())
+            .build()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        // Written straight away, before any watch on the new directories can settle
+        let nested = root.join("incoming").join("2024").join("june");
+        std::fs::create_dir_all(&nested).unwrap();
+        let file = nested.join("photo.jpg");
+        std::fs::write(&file, "jpeg").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut seen = false;
+        while !seen && Instant::now() < deadline {
+            if let Ok(WatcherMessage::NewFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                seen = event.path == file;
+            }
+        }
+        assert!(seen, "file in new subdirectory was not reported");
+        assert!(store.get_event_by_path(&file).unwrap().is_some());
+
+        // Later files in the new directory are picked up by its watch
+        let later = nested.join("later.jpg");
+        std::fs::write(&later, "jpeg").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut seen = false;
+        while !seen && Instant::now() < deadline {
+            if let Ok(WatcherMessage::NewFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                seen = event.path == later;
+            }
+        }
+        assert!(seen, "later file in new subdirectory was not reported");
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_extraction_is_summarized() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        let config = Config {
+            watch_paths: vec![root.clone()],
+            ignore_patterns: Vec::new(),
+            extraction_threshold: 3,
+            collapse_extractions: true,
+            ..Config::default()
+        };
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, rx) = FileWatcher::new(&config, Some(store.clone())).unwrap();
+        watcher.watch_paths(&config.watch_paths).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let extracted = root.join("photos");
+        std::fs::create_dir_all(extracted.join("raw")).unwrap();
+        for i in 0..5 {
+            std::fs::write(extracted.join(format!("img{}.jpg", i)), "jpeg").unwrap();
+        }
+        std::fs::write(extracted.join("raw").join("img.cr2"), "raw").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut summary = None;
+        while summary.is_none() && Instant::now() < deadline {
+            if let Ok(WatcherMessage::NewFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                assert_eq!(event.path, extracted, "individual file reported: {}", event.path.display());
+                summary = Some(event);
+            }
+        }
+        let summary = summary.expect("extraction was not summarized");
+        assert_eq!(summary.file_type, FileType::Archive);
+        assert_eq!(summary.size_bytes, Some(5 * 4 + 3));
+
+        assert!(store.get_event_by_path(&extracted).unwrap().is_some());
+        assert!(store.get_event_by_path(&extracted.join("img0.jpg")).unwrap().is_none());
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_growing_file_is_recorded_once_stable() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        let config = Config {
+            watch_paths: vec![root.clone()],
+            ignore_patterns: Vec::new(),
+            debounce_ms: 50,
+            stability_check_ms: Some(300),
+            ..Config::default()
+        };
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, rx) = FileWatcher::new(&config, Some(store.clone())).unwrap();
+        watcher.watch_paths(&config.watch_paths).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        // A download written in place, a chunk every 100ms
+        let path = root.join("installer.bin");
+        let mut file = File::create(&path).unwrap();
+        for _ in 0..10 {
+            file.write_all(&[0; 1024]).unwrap();
+            file.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        drop(file);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut recorded = None;
+        while recorded.is_none() && Instant::now() < deadline {
+            if let Ok(WatcherMessage::NewFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                recorded = Some(event);
+            }
+        }
+        let recorded = recorded.expect("file was not recorded");
+        assert_eq!(recorded.size_bytes, Some(10 * 1024));
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_vanished_watch_path_is_reattached() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap().join("drive");
+        std::fs::create_dir(&root).unwrap();
+
+        let config = Config {
+            watch_paths: vec![root.clone()],
+            ignore_patterns: Vec::new(),
+            debounce_ms: 50,
+            health_check_ms: 100,
+            ..Config::default()
+        };
+        let (mut watcher, rx) = FileWatcher::new(&config, None).unwrap();
+        watcher.watch_paths(&config.watch_paths).unwrap();
+        let offline = watcher.offline_handle();
+
+        let wait_for = |matches: &dyn Fn(&WatcherMessage) -> bool| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while Instant::now() < deadline {
+                if let Ok(msg) = rx.recv_timeout(Duration::from_millis(100)) {
+                    if matches(&msg) {
+                        return msg;
+                    }
+                }
+            }
+            panic!("expected watcher message did not arrive");
+        };
+
+        // "Unmount" the drive
+        std::fs::remove_dir_all(&root).unwrap();
+        let msg = wait_for(&|msg| matches!(msg, WatcherMessage::Error(_)));
+        assert!(matches!(msg, WatcherMessage::Error(e) if e.contains("offline")));
+        assert!(offline.lock().unwrap().contains(&root));
+
+        // Back again: watched anew, and new files are seen
+        std::fs::create_dir(&root).unwrap();
+        let msg = wait_for(&|msg| matches!(msg, WatcherMessage::PathRestored(_)));
+        assert!(matches!(msg, WatcherMessage::PathRestored(path) if path == root));
+        assert!(offline.lock().unwrap().is_empty());
+
+        std::fs::write(root.join("after.txt"), "new").unwrap();
+        let msg = wait_for(&|msg| matches!(msg, WatcherMessage::NewFile(_)));
+        assert!(matches!(msg, WatcherMessage::NewFile(event) if event.filename == "after.txt"));
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_exceeded_quota_warns_once() {
+        use crate::config::QuotaConfig;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let config = Config {
+            watch_paths: vec![root.clone()],
+            ignore_patterns: Vec::new(),
+            debounce_ms: 50,
+            quota: vec![QuotaConfig {
+                dir: root.display().to_string(),
+                file_type: None,
+                max_bytes: None,
+                max_count: Some(1),
+            }],
+            ..Config::default()
+        };
+        let (mut watcher, rx) = FileWatcher::new(&config, Some(Store::in_memory().unwrap())).unwrap();
+        watcher.watch_paths(&config.watch_paths).unwrap();
+        let exceeded = watcher.quota_handle();
+        std::thread::sleep(Duration::from_millis(100));
+
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(root.join(name), "data").unwrap();
+        }
+
+        let next_warning = |wait: Duration| {
+            let deadline = Instant::now() + wait;
+            while Instant::now() < deadline {
+                if let Ok(WatcherMessage::Warning(message)) = rx.recv_timeout(Duration::from_millis(100)) {
+                    return Some(message);
+                }
+            }
+            None
+        };
+
+        let warning = next_warning(Duration::from_secs(5)).expect("no quota warning");
+        assert!(warning.contains("Quota exceeded"));
+        assert!(exceeded.lock().unwrap().contains(&root.display().to_string()));
+
+        // Still over, but not reported again
+        std::fs::write(root.join("d.txt"), "data").unwrap();
+        assert_eq!(next_warning(QUOTA_CHECK_INTERVAL * 2), None);
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_rename_keeps_existing_row() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_path = temp_dir.path().canonicalize().unwrap().join("report.pdf");
+        std::fs::write(&old_path, "report").unwrap();
+
+        let store = Store::in_memory().unwrap();
+        let id = store.insert_event(&FileEvent::from_path(old_path.clone())).unwrap();
+        store.update_tags(id, "work").unwrap();
+
+        let (mut watcher, rx) = FileWatcherBuilder::new()
+            .watch(temp_dir.path())
+            .with_store(store.clone())
+            .build()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let new_dir = old_path.with_file_name("archive");
+        std::fs::create_dir(&new_dir).unwrap();
+        // Give the recursive watch time to pick up the new directory
+        std::thread::sleep(Duration::from_millis(500));
+        std::fs::rename(&old_path, new_dir.join("report.pdf")).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if let Ok(WatcherMessage::MovedFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                assert_eq!(event.id, Some(id));
+                assert_eq!(event.path, new_dir.join("report.pdf"));
+                assert_eq!(event.tags, "work");
+                break;
+            }
+        }
+
+        // Best effort on platforms without paired rename events
+        if cfg!(target_os = "linux") {
+            let moved = store.get_event(id).unwrap().unwrap();
+            assert_eq!(moved.path, new_dir.join("report.pdf"));
+            assert_eq!(store.count_events().unwrap(), 1);
+        }
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_folder_rename_moves_rows_inside() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_dir = temp_dir.path().canonicalize().unwrap().join("photos");
+        std::fs::create_dir_all(old_dir.join("2024")).unwrap();
+        let old_path = old_dir.join("2024").join("beach.jpg");
+        std::fs::write(&old_path, "beach").unwrap();
+
+        let store = Store::in_memory().unwrap();
+        let id = store.insert_event(&FileEvent::from_path(old_path.clone())).unwrap();
+        store.update_tags(id, "trip").unwrap();
+
+        let (mut watcher, rx) = FileWatcherBuilder::new()
+            .watch(temp_dir.path())
+            .with_store(store.clone())
+            .build()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let new_dir = old_dir.with_file_name("pictures");
+        std::fs::rename(&old_dir, &new_dir).unwrap();
+        let new_path = new_dir.join("2024").join("beach.jpg");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if let Ok(WatcherMessage::MovedFile(event)) = rx.recv_timeout(Duration::from_millis(100)) {
+                assert_eq!(event.id, Some(id));
+                assert_eq!(event.path, new_path);
+                assert_eq!(event.tags, "trip");
+                break;
+            }
+        }
+
+        // Best effort on platforms without paired rename events
+        if cfg!(target_os = "linux") {
+            let moved = store.get_event(id).unwrap().unwrap();
+            assert_eq!(moved.path, new_path);
+            assert_eq!(moved.dir, new_dir.join("2024"));
+            assert_eq!(store.count_events().unwrap(), 1);
+        }
+
+        watcher.stop().unwrap();
+    }
+
+    #[test]
+    fn test_file_moved_in_is_reported_as_moved() {
+        let watched = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let source = outside.path().join("invoice.pdf");
+        std::fs::write(&source, "invoice").unwrap();
+
+        let store = Store::in_memory().unwrap();
+        let (mut watcher, rx) = FileWatcherBuilder::new()
+            .watch(watched.path())
+            .with_store(store.clone())
+            .build()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        // Only the destination side of the rename is seen
+        let target = watched.path().canonicalize().unwrap().join("invoice.pdf");
+        std::fs::rename(&source, &target).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut message = None;
+        while Instant::now() < deadline && message.is_none() {
+            message = rx.recv_timeout(Duration::from_millis(100)).ok().filter(|msg| {
+                matches!(msg, WatcherMessage::NewFile(_) | WatcherMessage::MovedFile(_))
+            });
+        }
+        if cfg!(target_os = "linux") {
+            assert!(matches!(message, Some(WatcherMessage::MovedFile(event)) if event.path == target));
+        }
+        assert!(store.get_event_by_path(&target).unwrap().is_some());
+
+        watcher.stop().unwrap();
     }
 
     #[test]